@@ -0,0 +1,423 @@
+//! C API for tantivy.
+//!
+//! This crate exposes a small subset of tantivy through a C ABI, so that
+//! an index can be searched from applications that are not written in Rust:
+//! opening an index, parsing and running a query, and retrieving the
+//! matching documents as JSON.
+//!
+//! The matching C declarations are in `tantivy.h`.
+//!
+//! # Errors
+//!
+//! Functions returning a pointer return `NULL` on failure. The message
+//! of the last error of the calling thread can then be read with
+//! `tantivy_last_error`.
+//!
+//! Panics never unwind into the caller: they are caught, and reported
+//! as errors.
+//!
+//! # Memory
+//!
+//! Every object returned by the API has to be released with the
+//! matching `tantivy_*_free` function.
+#![warn(missing_docs)]
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::FieldType;
+use tantivy::{DocAddress, Index, IndexReader, LeasedItem, Score, Searcher};
+
+thread_local! {
+    // A `const` initializer would require Rust 1.59.
+    #[allow(clippy::missing_const_for_thread_local)]
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error<E: fmt::Display>(error: E) {
+    let message = error.to_string().replace('\0', " ");
+    let message = CString::new(message).expect("Nul bytes were removed.");
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, returning `default` if it panics.
+///
+/// Unwinding across an `extern "C"` function is undefined behavior,
+/// so every function of the API runs its body through this function.
+fn catch_panic<T, F: FnOnce() -> T>(default: T, f: F) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(format!("Panicked: {}", message));
+            default
+        }
+    }
+}
+
+/// Reads a C string given by the caller.
+unsafe fn read_str<'a>(s: *const c_char, name: &str) -> Option<&'a str> {
+    if s.is_null() {
+        set_last_error(format!("`{}` is NULL.", name));
+        return None;
+    }
+    match CStr::from_ptr(s).to_str() {
+        Ok(s) => Some(s),
+        Err(_) => {
+            set_last_error(format!("`{}` is not valid UTF-8.", name));
+            None
+        }
+    }
+}
+
+/// Reads search results given by the caller.
+unsafe fn read_results<'a>(
+    results: *const TantivySearchResults,
+) -> Option<&'a TantivySearchResults> {
+    if results.is_null() {
+        set_last_error("`results` is NULL.");
+        return None;
+    }
+    Some(&*results)
+}
+
+/// An index opened for search.
+pub struct TantivyIndex {
+    index: Index,
+    reader: IndexReader,
+}
+
+/// The hits of a search, together with the searcher they were obtained from.
+pub struct TantivySearchResults {
+    searcher: LeasedItem<Searcher>,
+    hits: Vec<(Score, DocAddress)>,
+}
+
+impl TantivySearchResults {
+    fn hit(&self, hit_ord: usize) -> Option<(Score, DocAddress)> {
+        let hit = self.hits.get(hit_ord).cloned();
+        if hit.is_none() {
+            set_last_error(format!(
+                "Hit {} is out of bounds ({} hits).",
+                hit_ord,
+                self.hits.len()
+            ));
+        }
+        hit
+    }
+}
+
+/// Returns the message of the last error that occurred in the calling thread,
+/// or `NULL` if no error occurred.
+///
+/// The returned string is owned by the library. It remains valid until the
+/// next failing call in the same thread.
+#[no_mangle]
+pub extern "C" fn tantivy_last_error() -> *const c_char {
+    catch_panic(ptr::null(), || {
+        LAST_ERROR.with(|last_error| {
+            last_error
+                .borrow()
+                .as_ref()
+                .map(|message| message.as_ptr())
+                .unwrap_or_else(ptr::null)
+        })
+    })
+}
+
+/// Opens the index stored in the directory `path`.
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+/// `path` has to be a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_open(path: *const c_char) -> *mut TantivyIndex {
+    catch_panic(ptr::null_mut(), || {
+        let path = match read_str(path, "path") {
+            Some(path) => path,
+            None => return ptr::null_mut(),
+        };
+        let index_and_reader = Index::open_in_dir(path).and_then(|index| {
+            let reader = index.reader()?;
+            Ok(TantivyIndex { index, reader })
+        });
+        match index_and_reader {
+            Ok(index) => Box::into_raw(Box::new(index)),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Releases an index returned by `tantivy_index_open`.
+///
+/// # Safety
+/// `index` has to be `NULL` or a pointer returned by `tantivy_index_open`
+/// that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_index_free(index: *mut TantivyIndex) {
+    catch_panic((), || {
+        if !index.is_null() {
+            drop(Box::from_raw(index));
+        }
+    })
+}
+
+/// Parses `query` with tantivy's query language and returns its `limit` best hits.
+///
+/// Terms that do not target a specific field are searched in all of the
+/// indexed text fields of the schema.
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+/// `index` has to be a valid pointer returned by `tantivy_index_open`, and
+/// `query` a valid nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_search(
+    index: *const TantivyIndex,
+    query: *const c_char,
+    limit: usize,
+) -> *mut TantivySearchResults {
+    catch_panic(ptr::null_mut(), || {
+        if index.is_null() {
+            set_last_error("`index` is NULL.");
+            return ptr::null_mut();
+        }
+        let index = &*index;
+        let query = match read_str(query, "query") {
+            Some(query) => query,
+            None => return ptr::null_mut(),
+        };
+        if limit == 0 {
+            set_last_error("`limit` has to be strictly positive.");
+            return ptr::null_mut();
+        }
+        let schema = index.index.schema();
+        let default_fields = schema
+            .fields()
+            .filter(|(_, field_entry)| match field_entry.field_type() {
+                FieldType::Str(text_options) => text_options.get_indexing_options().is_some(),
+                _ => false,
+            })
+            .map(|(field, _)| field)
+            .collect();
+        let query = match QueryParser::for_index(&index.index, default_fields).parse_query(query) {
+            Ok(query) => query,
+            Err(err) => {
+                set_last_error(format!("Failed to parse the query: {:?}", err));
+                return ptr::null_mut();
+            }
+        };
+        let searcher = index.reader.searcher();
+        match searcher.search(&query, &TopDocs::with_limit(limit)) {
+            Ok(hits) => Box::into_raw(Box::new(TantivySearchResults { searcher, hits })),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Returns the number of hits, or 0 if `results` is `NULL`.
+///
+/// # Safety
+/// `results` has to be `NULL` or a valid pointer returned by `tantivy_search`.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_search_results_len(results: *const TantivySearchResults) -> usize {
+    catch_panic(0, || match read_results(results) {
+        Some(results) => results.hits.len(),
+        None => 0,
+    })
+}
+
+/// Returns the score of the hit `hit_ord`, or `NaN` if it is out of bounds
+/// or if `results` is `NULL`.
+///
+/// Hits are sorted by decreasing score.
+///
+/// # Safety
+/// `results` has to be `NULL` or a valid pointer returned by `tantivy_search`.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_search_results_score(
+    results: *const TantivySearchResults,
+    hit_ord: usize,
+) -> f32 {
+    catch_panic(f32::NAN, || {
+        read_results(results)
+            .and_then(|results| results.hit(hit_ord))
+            .map(|(score, _)| score)
+            .unwrap_or(f32::NAN)
+    })
+}
+
+/// Returns the stored fields of the document of the hit `hit_ord`,
+/// as a JSON object keyed by field name.
+///
+/// Returns `NULL` on failure. The string has to be released
+/// with `tantivy_string_free`.
+///
+/// # Safety
+/// `results` has to be `NULL` or a valid pointer returned by `tantivy_search`.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_search_results_doc_json(
+    results: *const TantivySearchResults,
+    hit_ord: usize,
+) -> *mut c_char {
+    catch_panic(ptr::null_mut(), || {
+        let results = match read_results(results) {
+            Some(results) => results,
+            None => return ptr::null_mut(),
+        };
+        let doc_address = match results.hit(hit_ord) {
+            Some((_, doc_address)) => doc_address,
+            None => return ptr::null_mut(),
+        };
+        match results.searcher.doc(doc_address) {
+            Ok(doc) => {
+                let doc_json = results.searcher.schema().to_json(&doc);
+                CString::new(doc_json)
+                    .expect("JSON strings do not contain nul bytes.")
+                    .into_raw()
+            }
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Releases search results returned by `tantivy_search`.
+///
+/// # Safety
+/// `results` has to be `NULL` or a pointer returned by `tantivy_search`
+/// that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_search_results_free(results: *mut TantivySearchResults) {
+    catch_panic((), || {
+        if !results.is_null() {
+            drop(Box::from_raw(results));
+        }
+    })
+}
+
+/// Releases a string returned by the library.
+///
+/// # Safety
+/// `s` has to be `NULL` or a string returned by `tantivy_search_results_doc_json`
+/// that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tantivy_string_free(s: *mut c_char) {
+    catch_panic((), || {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::doc;
+    use tantivy::schema::{Schema, STORED, STRING, TEXT};
+
+    fn last_error() -> String {
+        let last_error = tantivy_last_error();
+        assert!(!last_error.is_null());
+        unsafe { CStr::from_ptr(last_error) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_capi_search() -> tantivy::Result<()> {
+        let tempdir = tempfile::TempDir::new()?;
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let index = Index::create_in_dir(tempdir.path(), schema_builder.build())?;
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+        index_writer.add_document(doc!(title => "The Old Man and the Sea", id => "a"));
+        index_writer.add_document(doc!(title => "Of Mice and Men", id => "b"));
+        index_writer.add_document(doc!(title => "The Sea, The Sea", id => "c"));
+        index_writer.commit()?;
+
+        let path = CString::new(tempdir.path().to_str().unwrap()).unwrap();
+        unsafe {
+            let index = tantivy_index_open(path.as_ptr());
+            assert!(!index.is_null());
+
+            let query = CString::new("sea").unwrap();
+            let results = tantivy_search(index, query.as_ptr(), 10);
+            assert!(!results.is_null());
+            assert_eq!(tantivy_search_results_len(results), 2);
+            assert!(tantivy_search_results_score(results, 0) > 0.0);
+            assert!(tantivy_search_results_score(results, 2).is_nan());
+            let doc_json = tantivy_search_results_doc_json(results, 0);
+            assert_eq!(
+                CStr::from_ptr(doc_json).to_str().unwrap(),
+                r#"{"id":["c"],"title":["The Sea, The Sea"]}"#
+            );
+            tantivy_string_free(doc_json);
+            assert!(tantivy_search_results_doc_json(results, 2).is_null());
+            assert!(last_error().contains("out of bounds"));
+            tantivy_search_results_free(results);
+
+            let query = CString::new("id:b").unwrap();
+            let results = tantivy_search(index, query.as_ptr(), 10);
+            assert_eq!(tantivy_search_results_len(results), 1);
+            tantivy_search_results_free(results);
+
+            let query = CString::new("unknown_field:sea").unwrap();
+            assert!(tantivy_search(index, query.as_ptr(), 10).is_null());
+            assert!(last_error().starts_with("Failed to parse the query"));
+
+            tantivy_index_free(index);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_capi_null_results() {
+        unsafe {
+            assert_eq!(tantivy_search_results_len(ptr::null()), 0);
+            assert_eq!(last_error(), "`results` is NULL.");
+            assert!(tantivy_search_results_score(ptr::null(), 0).is_nan());
+            assert!(tantivy_search_results_doc_json(ptr::null(), 0).is_null());
+            tantivy_search_results_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_capi_catch_panic() {
+        assert_eq!(catch_panic(1, || panic!("boom")), 1);
+        assert_eq!(last_error(), "Panicked: boom");
+        assert_eq!(catch_panic(1, || 2), 2);
+    }
+
+    #[test]
+    fn test_capi_open_errors() {
+        unsafe {
+            assert!(tantivy_index_open(ptr::null()).is_null());
+            assert_eq!(last_error(), "`path` is NULL.");
+            let path = CString::new("/does/not/exist").unwrap();
+            assert!(tantivy_index_open(path.as_ptr()).is_null());
+            assert!(!last_error().is_empty());
+        }
+    }
+}
@@ -0,0 +1,49 @@
+/* C API for the tantivy search engine library.
+ *
+ * Functions returning a pointer return NULL on failure. The message of the
+ * last error of the calling thread is then returned by tantivy_last_error().
+ * Panics are caught, and reported as errors.
+ *
+ * Every object returned by the API has to be released with the matching
+ * tantivy_*_free function.
+ */
+#ifndef TANTIVY_H
+#define TANTIVY_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct TantivyIndex TantivyIndex;
+typedef struct TantivySearchResults TantivySearchResults;
+
+/* Message of the last error of the calling thread, or NULL.
+ * Owned by the library, valid until the next failing call in the same thread. */
+const char *tantivy_last_error(void);
+
+/* Opens the index stored in the directory `path`. */
+TantivyIndex *tantivy_index_open(const char *path);
+void tantivy_index_free(TantivyIndex *index);
+
+/* Parses `query` and returns its `limit` best hits, sorted by decreasing score.
+ * Terms that do not target a specific field are searched in all of the
+ * indexed text fields. */
+TantivySearchResults *tantivy_search(const TantivyIndex *index, const char *query, size_t limit);
+/* Number of hits, or 0 if `results` is NULL. */
+size_t tantivy_search_results_len(const TantivySearchResults *results);
+/* Score of a hit, or NaN if `hit_ord` is out of bounds or `results` is NULL. */
+float tantivy_search_results_score(const TantivySearchResults *results, size_t hit_ord);
+/* Stored fields of the document of a hit, as a JSON object keyed by field name.
+ * Has to be released with tantivy_string_free. */
+char *tantivy_search_results_doc_json(const TantivySearchResults *results, size_t hit_ord);
+void tantivy_search_results_free(TantivySearchResults *results);
+
+void tantivy_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TANTIVY_H */
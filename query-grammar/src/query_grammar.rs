@@ -2,9 +2,11 @@ use super::user_input_ast::{UserInputAST, UserInputBound, UserInputLeaf, UserInp
 use crate::Occur;
 use combine::error::StringStreamError;
 use combine::parser::char::{char, digit, letter, space, spaces, string};
+use combine::parser::range::recognize;
 use combine::parser::Parser;
 use combine::{
-    attempt, choice, eof, many, many1, one_of, optional, parser, satisfy, skip_many1, value,
+    attempt, choice, eof, many, many1, one_of, optional, parser, satisfy, skip_count_min_max,
+    skip_many1, value,
 };
 
 fn field<'a>() -> impl Parser<&'a str, Output = String> {
@@ -22,10 +24,10 @@ fn word<'a>() -> impl Parser<&'a str, Output = String> {
     (
         satisfy(|c: char| {
             !c.is_whitespace()
-                && !['-', '^', '`', ':', '{', '}', '"', '[', ']', '(', ')'].contains(&c)
+                && !['-', '^', '`', ':', '{', '}', '"', '[', ']', '(', ')', '~'].contains(&c)
         }),
         many(satisfy(|c: char| {
-            !c.is_whitespace() && ![':', '^', '{', '}', '"', '[', ']', '(', ')'].contains(&c)
+            !c.is_whitespace() && ![':', '^', '{', '}', '"', '[', ']', '(', ')', '~'].contains(&c)
         })),
     )
         .map(|(s1, s2): (char, String)| format!("{}{}", s1, s2))
@@ -35,13 +37,46 @@ fn word<'a>() -> impl Parser<&'a str, Output = String> {
         })
 }
 
+/// Function that parses an RFC 3339 date time out of a Stream
+/// Supports date times like:
+/// 2002-10-02T15:00:00Z, 2002-10-02T15:00:00.05Z, 2002-10-02T15:00:00+02:00
+///
+/// Only the shape of the date time is checked here: whether it is
+/// an actual date is checked by the query parser.
+fn date_time<'a>() -> impl Parser<&'a str, Output = String> {
+    let two_digits = || skip_count_min_max(2, 2, digit());
+    let time_offset = one_of("Zz".chars()).map(|_| ()).or((
+        one_of("+-".chars()),
+        two_digits(),
+        char(':'),
+        two_digits(),
+    )
+        .map(|_| ()));
+    recognize((
+        skip_count_min_max(4, 4, digit()),
+        char('-'),
+        two_digits(),
+        char('-'),
+        two_digits(),
+        one_of("Tt".chars()),
+        two_digits(),
+        char(':'),
+        two_digits(),
+        char(':'),
+        two_digits(),
+        optional((char('.'), skip_many1(digit()))),
+        time_offset,
+    ))
+    .map(|date_time: &str| date_time.to_string())
+}
+
 fn term_val<'a>() -> impl Parser<&'a str, Output = String> {
     let phrase = char('"').with(many1(satisfy(|c| c != '"'))).skip(char('"'));
     phrase.or(word())
 }
 
 fn term_query<'a>() -> impl Parser<&'a str, Output = UserInputLiteral> {
-    let term_val_with_field = negative_number().or(term_val());
+    let term_val_with_field = attempt(date_time()).or(negative_number()).or(term_val());
     (field(), term_val_with_field).map(|(field_name, phrase)| UserInputLiteral {
         field_name: Some(field_name),
         phrase,
@@ -49,15 +84,63 @@ fn term_query<'a>() -> impl Parser<&'a str, Output = UserInputLiteral> {
 }
 
 fn literal<'a>() -> impl Parser<&'a str, Output = UserInputLeaf> {
-    let term_default_field = term_val().map(|phrase| UserInputLiteral {
-        field_name: None,
-        phrase,
-    });
+    let term_default_field = attempt(date_time())
+        .or(term_val())
+        .map(|phrase| UserInputLiteral {
+            field_name: None,
+            phrase,
+        });
     attempt(term_query())
         .or(term_default_field)
         .map(UserInputLeaf::from)
 }
 
+/// Function that parses a prefix term out of a Stream
+/// Supports prefix terms like:
+/// rust*, title:rust*
+fn prefix_term<'a>() -> impl Parser<&'a str, Output = UserInputLeaf> {
+    (optional(attempt(field())), word()).and_then(|(field, word): (Option<String>, String)| {
+        match word.strip_suffix('*') {
+            Some(prefix) if !prefix.is_empty() && !prefix.contains('*') => {
+                Ok(UserInputLeaf::Prefix {
+                    field,
+                    prefix: prefix.to_string(),
+                })
+            }
+            _ => Err(StringStreamError::UnexpectedParse),
+        }
+    })
+}
+
+/// Function that parses a fuzzy term out of a Stream
+/// Supports fuzzy terms like:
+/// roam~, roam~1, title:roam~2
+///
+/// The Levenshtein distance defaults to 2 when it is omitted.
+fn fuzzy_term<'a>() -> impl Parser<&'a str, Output = UserInputLeaf> {
+    (
+        optional(attempt(field())),
+        word(),
+        char('~'),
+        optional(many1(digit())),
+    )
+        .and_then(
+            |(field, term, _, distance_opt): (Option<String>, String, char, Option<String>)| {
+                let distance = match distance_opt {
+                    Some(distance) => distance
+                        .parse::<u8>()
+                        .map_err(|_| StringStreamError::UnexpectedParse),
+                    None => Ok(2),
+                };
+                distance.map(|distance| UserInputLeaf::Fuzzy {
+                    field,
+                    term,
+                    distance,
+                })
+            },
+        )
+}
+
 fn negative_number<'a>() -> impl Parser<&'a str, Output = String> {
     (
         char('-'),
@@ -81,9 +164,11 @@ fn spaces1<'a>() -> impl Parser<&'a str, Output = ()> {
 /// Supports ranges like:
 /// [5 TO 10], {5 TO 10}, [* TO 10], [10 TO *], {10 TO *], >5, <=10
 /// [a TO *], [a TO c], [abc TO bcd}
+/// [2002-10-02T15:00:00Z TO 2002-10-03T15:00:00Z}, >=2002-10-02T15:00:00Z
 fn range<'a>() -> impl Parser<&'a str, Output = UserInputLeaf> {
     let range_term_val = || {
-        word()
+        attempt(date_time())
+            .or(word())
             .or(negative_number())
             .or(char('*').with(value("*".to_string())))
     };
@@ -166,6 +251,8 @@ fn leaf<'a>() -> impl Parser<&'a str, Output = UserInputAST> {
                 string("NOT").skip(spaces1()).with(leaf()).map(negate),
             ))
             .or(attempt(range().map(UserInputAST::from)))
+            .or(attempt(prefix_term().map(UserInputAST::from)))
+            .or(attempt(fuzzy_term().map(UserInputAST::from)))
             .or(literal().map(UserInputAST::from))
             .parse_stream(input)
             .into_result()
@@ -458,6 +545,85 @@ mod test {
         assert_eq!(res4, expected_weight);
     }
 
+    #[test]
+    fn test_date_time_parser() {
+        for date_time in &[
+            "2002-10-02T15:00:00Z",
+            "2002-10-02t15:00:00.05z",
+            "2002-10-02T15:00:00+02:00",
+            "2002-10-02T15:00:00.123-07:30",
+        ] {
+            assert_eq!(
+                super::date_time().parse(*date_time),
+                Ok((date_time.to_string(), ""))
+            );
+        }
+        for not_date_time in &[
+            "2002-10-02",
+            "2002-10-02T15:00Z",
+            "2002-10-02T15:00:00",
+            "02-10-02T15:00:00Z",
+        ] {
+            assert!(super::date_time().parse(*not_date_time).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_date_time_ranges() {
+        test_parse_query_to_ast_helper(
+            "date:[2002-10-02T15:00:00Z TO 2002-10-03T15:00:00.5+02:00}",
+            "date:[\"2002-10-02T15:00:00Z\" TO \"2002-10-03T15:00:00.5+02:00\"}",
+        );
+        test_parse_query_to_ast_helper(
+            "date:>=2002-10-02T15:00:00Z",
+            "date:[\"2002-10-02T15:00:00Z\" TO \"*\"}",
+        );
+        test_parse_query_to_ast_helper(
+            "date:{* TO 2002-10-02T15:00:00Z]",
+            "date:{\"*\" TO \"2002-10-02T15:00:00Z\"]",
+        );
+        test_parse_query_to_ast_helper(
+            "date:2002-10-02T15:00:00Z",
+            "date:\"2002-10-02T15:00:00Z\"",
+        );
+        test_parse_query_to_ast_helper("2002-10-02T15:00:00Z", "\"2002-10-02T15:00:00Z\"");
+    }
+
+    #[test]
+    fn test_prefix_term_parser() {
+        let expected = UserInputLeaf::Prefix {
+            field: Some("title".to_string()),
+            prefix: "rust".to_string(),
+        };
+        assert_eq!(prefix_term().parse("title:rust*").unwrap().0, expected);
+        assert!(prefix_term().parse("rust").is_err());
+        assert!(prefix_term().parse("ru*st*").is_err());
+        test_parse_query_to_ast_helper("rust*", "\"rust\"*");
+        test_parse_query_to_ast_helper("title:rust* -go", "(*title:\"rust\"* -\"go\")");
+        test_parse_query_to_ast_helper("rust*^2", "(\"rust\"*)^2");
+        test_parse_query_to_ast_helper("ru*st", "\"ru*st\"");
+    }
+
+    #[test]
+    fn test_fuzzy_term_parser() {
+        let expected = UserInputLeaf::Fuzzy {
+            field: Some("title".to_string()),
+            term: "roam".to_string(),
+            distance: 1,
+        };
+        assert_eq!(fuzzy_term().parse("title:roam~1").unwrap().0, expected);
+        test_parse_query_to_ast_helper("roam~", "\"roam\"~2");
+        test_parse_query_to_ast_helper("title:roam~1", "title:\"roam\"~1");
+        test_parse_query_to_ast_helper(
+            "+title:roam~1 -body:foam",
+            "(+title:\"roam\"~1 -body:\"foam\")",
+        );
+        test_parse_query_to_ast_helper("roam~1^2", "(\"roam\"~1)^2");
+        test_parse_query_to_ast_helper("roam~10", "\"roam\"~10");
+        test_is_parse_err("roam~1000");
+        test_is_parse_err("\"roam around\"~1");
+    }
+
     #[test]
     fn test_parse_query_to_triming_spaces() {
         test_parse_query_to_ast_helper("   abc", "\"abc\"");
@@ -12,6 +12,15 @@ pub enum UserInputLeaf {
         lower: UserInputBound,
         upper: UserInputBound,
     },
+    Fuzzy {
+        field: Option<String>,
+        term: String,
+        distance: u8,
+    },
+    Prefix {
+        field: Option<String>,
+        prefix: String,
+    },
 }
 
 impl Debug for UserInputLeaf {
@@ -32,6 +41,25 @@ impl Debug for UserInputLeaf {
                 Ok(())
             }
             UserInputLeaf::All => write!(formatter, "*"),
+            UserInputLeaf::Fuzzy {
+                ref field,
+                ref term,
+                distance,
+            } => {
+                if let Some(ref field) = field {
+                    write!(formatter, "{}:", field)?;
+                }
+                write!(formatter, "\"{}\"~{}", term, distance)
+            }
+            UserInputLeaf::Prefix {
+                ref field,
+                ref prefix,
+            } => {
+                if let Some(ref field) = field {
+                    write!(formatter, "{}:", field)?;
+                }
+                write!(formatter, "\"{}\"*", prefix)
+            }
         }
     }
 }
@@ -25,7 +25,7 @@ impl<'a> Eq for Hit<'a> {}
 
 impl<'a> PartialEq<Hit<'a>> for Hit<'a> {
     fn eq(&self, other: &Hit<'_>) -> bool {
-        self.count == other.count
+        self.count == other.count && self.facet == other.facet
     }
 }
 
@@ -35,9 +35,14 @@ impl<'a> PartialOrd<Hit<'a>> for Hit<'a> {
     }
 }
 
+// The greatest `Hit` is the worst one: it has the lowest count, and
+// ties are broken in favor of the lowest facet.
 impl<'a> Ord for Hit<'a> {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.count.cmp(&self.count)
+        other
+            .count
+            .cmp(&self.count)
+            .then_with(|| self.facet.cmp(other.facet))
     }
 }
 
@@ -227,6 +232,11 @@ impl FacetCollector {
         }
     }
 
+    /// Returns the facet field of the collector.
+    pub(crate) fn field(&self) -> Field {
+        self.field
+    }
+
     /// Adds a facet that we want to record counts
     ///
     /// Adding facet `Facet::from("/country")` for instance,
@@ -398,6 +408,8 @@ impl<'a> Iterator for FacetChildIterator<'a> {
 }
 
 impl FacetCounts {
+    /// Returns an iterator over the counts of the children of the given facet,
+    /// in the lexicographical order of the facets.
     pub fn get<T>(&self, facet_from: T) -> FacetChildIterator<'_>
     where
         Facet: From<T>,
@@ -417,6 +429,10 @@ impl FacetCounts {
         FacetChildIterator { underlying }
     }
 
+    /// Returns the `k` children of the given facet with the highest counts,
+    /// sorted by decreasing count.
+    ///
+    /// Facets with the same count are sorted in lexicographical order.
     pub fn top_k<T>(&self, facet: T, k: usize) -> Vec<(&Facet, u64)>
     where
         Facet: From<T>,
@@ -653,6 +669,39 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_facet_collector_topk_tie_break() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let facet_field = schema_builder.add_facet_field("facet");
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for (facet, count) in &[
+            ("/category/electronics/phone", 3),
+            ("/category/electronics/laptop", 2),
+            ("/category/electronics/camera", 2),
+            ("/category/electronics/tv", 2),
+            ("/category/books", 1),
+        ] {
+            for _ in 0..*count {
+                index_writer.add_document(doc!(facet_field => Facet::from(*facet)));
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let mut facet_collector = FacetCollector::for_field(facet_field);
+        facet_collector.add_facet("/category/electronics");
+        let counts: FacetCounts = searcher.search(&AllQuery, &facet_collector)?;
+        assert_eq!(
+            counts.top_k("/category/electronics", 3),
+            vec![
+                (&Facet::from("/category/electronics/phone"), 3),
+                (&Facet::from("/category/electronics/camera"), 2),
+                (&Facet::from("/category/electronics/laptop"), 2),
+            ]
+        );
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
@@ -0,0 +1,254 @@
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::{value_from_u64, FastFieldReader};
+use crate::query::Weight;
+use crate::schema::{Cardinality, Field, FieldType, Type, Value};
+use crate::{DocAddress, DocId, Score, SegmentLocalId, SegmentReader, TantivyError};
+use std::collections::HashMap;
+
+/// Wraps a top collector, and attaches the values of some
+/// single-valued fast fields to each of its hits.
+///
+/// Values are read while the segment is being collected, so that
+/// the hits do not have to be fetched from the doc store.
+pub(crate) struct FastFieldValuesCollector<TCollector> {
+    collector: TCollector,
+    fields: Vec<Field>,
+}
+
+impl<TCollector> FastFieldValuesCollector<TCollector> {
+    pub fn new(collector: TCollector, fields: Vec<Field>) -> FastFieldValuesCollector<TCollector> {
+        FastFieldValuesCollector { collector, fields }
+    }
+
+    fn fast_field_readers(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Vec<(Type, FastFieldReader<u64>)>> {
+        let schema = segment_reader.schema();
+        self.fields
+            .iter()
+            .map(|&field| {
+                let field_entry = schema.get_field_entry(field);
+                let cardinality = match field_entry.field_type() {
+                    FieldType::U64(options)
+                    | FieldType::I64(options)
+                    | FieldType::F64(options)
+                    | FieldType::Date(options) => options.get_fastfield_cardinality(),
+                    _ => None,
+                };
+                if cardinality != Some(Cardinality::SingleValue) {
+                    return Err(TantivyError::SchemaError(format!(
+                        "Field {:?} is not a single-valued numerical fast field.",
+                        field_entry.name()
+                    )));
+                }
+                let fast_field_reader = segment_reader
+                    .fast_fields()
+                    .u64_lenient(field)
+                    .ok_or_else(|| {
+                        TantivyError::SchemaError(format!(
+                            "Field {:?} is not a fast field.",
+                            field_entry.name()
+                        ))
+                    })?;
+                Ok((field_entry.field_type().value_type(), fast_field_reader))
+            })
+            .collect()
+    }
+}
+
+fn add_values<TScore>(
+    hits: Vec<(TScore, DocAddress)>,
+    fast_field_readers: &[(Type, FastFieldReader<u64>)],
+) -> Vec<(TScore, DocAddress, Vec<Value>)> {
+    hits.into_iter()
+        .map(|(score, doc_address)| {
+            let DocAddress(_, doc) = doc_address;
+            let values = fast_field_readers
+                .iter()
+                .map(|(value_type, reader)| value_from_u64(*value_type, reader.get(doc)))
+                .collect();
+            (score, doc_address, values)
+        })
+        .collect()
+}
+
+impl<TCollector, TScore> Collector for FastFieldValuesCollector<TCollector>
+where
+    TCollector: Collector<Fruit = Vec<(TScore, DocAddress)>>,
+    TCollector::Child: SegmentCollector<Fruit = Vec<(TScore, DocAddress)>>,
+    TScore: 'static + Send + Sync,
+{
+    type Fruit = Vec<(TScore, DocAddress, Vec<Value>)>;
+
+    type Child = FastFieldValuesSegmentCollector<TCollector::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let fast_field_readers = self.fast_field_readers(segment_reader)?;
+        let segment_collector = self
+            .collector
+            .for_segment(segment_local_id, segment_reader)?;
+        Ok(FastFieldValuesSegmentCollector {
+            segment_collector,
+            fast_field_readers,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(TScore, DocAddress, Vec<Value>)>>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut values_by_address: HashMap<DocAddress, Vec<Value>> = HashMap::new();
+        let segment_hits = segment_fruits
+            .into_iter()
+            .map(|segment_fruit| {
+                segment_fruit
+                    .into_iter()
+                    .map(|(score, doc_address, values)| {
+                        values_by_address.insert(doc_address, values);
+                        (score, doc_address)
+                    })
+                    .collect()
+            })
+            .collect();
+        let hits = self.collector.merge_fruits(segment_hits)?;
+        Ok(hits
+            .into_iter()
+            .map(|(score, doc_address)| {
+                let values = values_by_address.remove(&doc_address).unwrap_or_default();
+                (score, doc_address, values)
+            })
+            .collect())
+    }
+
+    // Delegates to the wrapped collector, in order to preserve
+    // its optimized implementation (e.g. block-max pruning for `TopDocs`).
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Vec<(TScore, DocAddress, Vec<Value>)>> {
+        let fast_field_readers = self.fast_field_readers(segment_reader)?;
+        let hits = self
+            .collector
+            .collect_segment(weight, segment_ord, segment_reader)?;
+        Ok(add_values(hits, &fast_field_readers))
+    }
+}
+
+/// Segment collector associated to `TopDocs::with_fast_field_values`.
+pub struct FastFieldValuesSegmentCollector<TSegmentCollector> {
+    segment_collector: TSegmentCollector,
+    fast_field_readers: Vec<(Type, FastFieldReader<u64>)>,
+}
+
+impl<TSegmentCollector, TScore> SegmentCollector
+    for FastFieldValuesSegmentCollector<TSegmentCollector>
+where
+    TSegmentCollector: SegmentCollector<Fruit = Vec<(TScore, DocAddress)>>,
+    TScore: 'static + Send + Sync,
+{
+    type Fruit = Vec<(TScore, DocAddress, Vec<Value>)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.segment_collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let hits = self.segment_collector.harvest();
+        add_values(hits, &self.fast_field_readers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FastFieldValuesCollector;
+    use crate::collector::{Collector, SegmentCollector, TopDocs};
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Cardinality, IntOptions, Schema, Value, FAST, STORED, TEXT};
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_top_docs_with_fast_field_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let rank = schema_builder.add_i64_field("rank", FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea", price => 9.5f64, rank => -1i64));
+        index_writer.add_document(doc!(title => "sea sea", price => 3.0f64, rank => 2i64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "sea sea sea", price => 1.0f64));
+        index_writer.add_document(doc!(title => "mountain", price => 7.0f64, rank => 5i64));
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(title, "mountain"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("sea")?;
+        let top_docs = TopDocs::with_limit(2).with_fast_field_values(vec![price, rank]);
+        let hits = searcher.search(&query, &top_docs)?;
+        let doc_values: Vec<(DocAddress, Vec<Value>)> = hits
+            .into_iter()
+            .map(|(_score, doc_address, values)| (doc_address, values))
+            .collect();
+        assert_eq!(
+            doc_values,
+            vec![
+                (DocAddress(1, 0), vec![Value::F64(1.0), Value::I64(0)]),
+                (DocAddress(0, 1), vec![Value::F64(3.0), Value::I64(2)]),
+            ]
+        );
+
+        // The default `collect`/`harvest` path returns the same values.
+        let collector = FastFieldValuesCollector::new(TopDocs::with_limit(2), vec![price, rank]);
+        let mut segment_collector = collector.for_segment(0, searcher.segment_reader(0))?;
+        segment_collector.collect(0, 1.0);
+        let fruit = segment_collector.harvest();
+        assert_eq!(fruit.len(), 1);
+        assert_eq!(fruit[0].2, vec![Value::F64(9.5), Value::I64(-1)]);
+
+        let all_docs = searcher.search(
+            &AllQuery,
+            &TopDocs::with_limit(10).with_fast_field_values(Vec::new()),
+        )?;
+        assert_eq!(all_docs.len(), 3);
+        assert!(all_docs.iter().all(|(_, _, values)| values.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_with_fast_field_values_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let not_fast = schema_builder.add_u64_field("not_fast", STORED);
+        let multivalued = schema_builder.add_u64_field(
+            "multivalued",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea", not_fast => 1u64, multivalued => 2u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        for &field in &[title, not_fast, multivalued] {
+            let top_docs = TopDocs::with_limit(10).with_fast_field_values(vec![field]);
+            assert!(matches!(
+                searcher.search(&AllQuery, &top_docs),
+                Err(crate::TantivyError::SchemaError(_))
+            ));
+        }
+        Ok(())
+    }
+}
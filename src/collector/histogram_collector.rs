@@ -0,0 +1,443 @@
+use crate::collector::{Collector, Count, SegmentCollector};
+use crate::fastfield::FastFieldReader;
+use crate::schema::{Field, Type};
+use crate::{DocId, Score, SegmentLocalId, SegmentReader, TantivyError};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Maximum number of buckets of an aggregation, unless set otherwise.
+pub(crate) const DEFAULT_MAX_BUCKETS: usize = 65_536;
+
+pub(crate) fn too_many_buckets(max_buckets: usize) -> TantivyError {
+    TantivyError::InvalidArgument(format!(
+        "The aggregation exceeds the maximum number of buckets ({}).",
+        max_buckets
+    ))
+}
+
+/// A bucket of the histogram computed by the `HistogramCollector`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistogramBucket<TFruit> {
+    from: f64,
+    to: f64,
+    fruit: TFruit,
+}
+
+impl<TFruit> HistogramBucket<TFruit> {
+    /// Lower bound of the bucket (inclusive).
+    pub fn from(&self) -> f64 {
+        self.from
+    }
+
+    /// Upper bound of the bucket (exclusive).
+    pub fn to(&self) -> f64 {
+        self.to
+    }
+
+    /// Fruit of the sub-aggregation, computed over the documents of the bucket.
+    pub fn fruit(&self) -> &TFruit {
+        &self.fruit
+    }
+
+    /// Consumes the bucket and returns the fruit of the sub-aggregation.
+    pub fn into_fruit(self) -> TFruit {
+        self.fruit
+    }
+}
+
+/// `HistogramCollector` groups the matching documents into buckets of fixed width,
+/// according to the value of a `u64`, `i64`, `f64` or date fast field.
+///
+/// The bucket of a value `val` starts at `offset + k * interval`, where `k` is the
+/// largest integer such that this bound is lower or equal to `val`. Date values are
+/// bucketed according to their timestamp, expressed in seconds.
+///
+/// By default, the documents of each bucket are counted, but any collector
+/// can be run on the documents of each bucket with `.sub_aggregation(...)`:
+/// a `StatsCollector`, a tuple of collectors, a `TermsCollector` or another
+/// `HistogramCollector`.
+///
+/// Only non-empty buckets are returned, in increasing order.
+/// Searching fails if the matching documents fall in more than `max_buckets`
+/// buckets (65,536 by default).
+///
+/// ```rust
+/// use tantivy::collector::{HistogramCollector, StatsCollector};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// let mut schema_builder = Schema::builder();
+/// let price = schema_builder.add_f64_field("price", FAST);
+/// let rating = schema_builder.add_u64_field("rating", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer(3_000_000).unwrap();
+/// index_writer.add_document(doc!(price => 2.5f64, rating => 4u64));
+/// index_writer.add_document(doc!(price => 7.0f64, rating => 2u64));
+/// index_writer.add_document(doc!(price => 12.0f64, rating => 5u64));
+/// index_writer.add_document(doc!(price => 18.5f64, rating => 3u64));
+/// assert!(index_writer.commit().is_ok());
+///
+/// let searcher = index.reader().unwrap().searcher();
+/// let histogram = HistogramCollector::for_field(price, 10.0);
+/// let buckets = searcher.search(&AllQuery, &histogram).unwrap();
+/// assert_eq!(buckets.len(), 2);
+/// assert_eq!(buckets[0].from(), 0.0);
+/// assert_eq!(*buckets[0].fruit(), 2);
+/// assert_eq!(buckets[1].from(), 10.0);
+/// assert_eq!(*buckets[1].fruit(), 2);
+///
+/// // Average rating per price range.
+/// let histogram = HistogramCollector::for_field(price, 10.0)
+///     .sub_aggregation(StatsCollector::for_field(rating));
+/// let buckets = searcher.search(&AllQuery, &histogram).unwrap();
+/// assert_eq!(buckets[0].fruit().as_ref().unwrap().mean(), 3.0);
+/// assert_eq!(buckets[1].fruit().as_ref().unwrap().mean(), 4.0);
+/// ```
+pub struct HistogramCollector<TCollector = Count> {
+    field: Field,
+    interval: f64,
+    offset: f64,
+    max_buckets: usize,
+    sub_collector: Arc<TCollector>,
+}
+
+impl HistogramCollector<Count> {
+    /// Creates a `HistogramCollector` counting the documents in buckets of width `interval`
+    /// of the given fast field.
+    pub fn for_field(field: Field, interval: f64) -> HistogramCollector<Count> {
+        HistogramCollector {
+            field,
+            interval,
+            offset: 0.0,
+            max_buckets: DEFAULT_MAX_BUCKETS,
+            sub_collector: Arc::new(Count),
+        }
+    }
+}
+
+impl<TCollector> HistogramCollector<TCollector> {
+    /// Shifts the bounds of the buckets by `offset`.
+    pub fn with_offset(mut self, offset: f64) -> HistogramCollector<TCollector> {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the maximum number of buckets the aggregation may collect before failing.
+    pub fn with_max_buckets(mut self, max_buckets: usize) -> HistogramCollector<TCollector> {
+        self.max_buckets = max_buckets;
+        self
+    }
+
+    /// Runs `sub_collector` on the documents of each bucket,
+    /// instead of counting them.
+    pub fn sub_aggregation<TSubCollector: Collector>(
+        self,
+        sub_collector: TSubCollector,
+    ) -> HistogramCollector<TSubCollector> {
+        HistogramCollector {
+            field: self.field,
+            interval: self.interval,
+            offset: self.offset,
+            max_buckets: self.max_buckets,
+            sub_collector: Arc::new(sub_collector),
+        }
+    }
+}
+
+impl<TCollector> Collector for HistogramCollector<TCollector>
+where
+    TCollector: Collector + 'static,
+{
+    type Fruit = Vec<HistogramBucket<TCollector::Fruit>>;
+
+    type Child = HistogramSegmentCollector<TCollector>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<HistogramSegmentCollector<TCollector>> {
+        if !(self.interval.is_finite() && self.interval > 0.0) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The interval of a histogram has to be a positive number, got {}.",
+                self.interval
+            )));
+        }
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let fast_fields = segment_reader.fast_fields();
+        let fast_field_reader = match field_entry.field_type().value_type() {
+            Type::U64 => fast_fields
+                .u64(self.field)
+                .map(HistogramFastFieldReader::U64),
+            Type::I64 => fast_fields
+                .i64(self.field)
+                .map(HistogramFastFieldReader::I64),
+            Type::F64 => fast_fields
+                .f64(self.field)
+                .map(HistogramFastFieldReader::F64),
+            Type::Date => fast_fields
+                .date(self.field)
+                .map(HistogramFastFieldReader::Date),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64, i64, f64 or date fast field.",
+                field_entry.name()
+            ))
+        })?;
+        // Validates that the sub-collector can be run on this segment.
+        self.sub_collector
+            .for_segment(segment_local_id, segment_reader)?;
+        Ok(HistogramSegmentCollector {
+            segment_local_id,
+            segment_reader: segment_reader.clone(),
+            fast_field_reader,
+            interval: self.interval,
+            offset: self.offset,
+            max_buckets: self.max_buckets,
+            sub_collector: self.sub_collector.clone(),
+            buckets: BTreeMap::new(),
+            error: None,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.sub_collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<HistogramSegmentFruit<TCollector>>,
+    ) -> crate::Result<Vec<HistogramBucket<TCollector::Fruit>>> {
+        let mut bucket_fruits: BTreeMap<i64, Vec<SubFruit<TCollector>>> = BTreeMap::new();
+        for segment_fruit in segment_fruits {
+            for (bucket, fruit) in segment_fruit? {
+                bucket_fruits.entry(bucket).or_default().push(fruit);
+            }
+        }
+        if bucket_fruits.len() > self.max_buckets {
+            return Err(too_many_buckets(self.max_buckets));
+        }
+        bucket_fruits
+            .into_iter()
+            .map(|(bucket, fruits)| {
+                let from = self.offset + bucket as f64 * self.interval;
+                Ok(HistogramBucket {
+                    from,
+                    to: from + self.interval,
+                    fruit: self.sub_collector.merge_fruits(fruits)?,
+                })
+            })
+            .collect()
+    }
+}
+
+type SubFruit<TCollector> = <<TCollector as Collector>::Child as SegmentCollector>::Fruit;
+
+/// Fruit of the `HistogramSegmentCollector`: the fruits of the sub-collector for each bucket.
+pub type HistogramSegmentFruit<TCollector> = crate::Result<BTreeMap<i64, SubFruit<TCollector>>>;
+
+enum HistogramFastFieldReader {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    F64(FastFieldReader<f64>),
+    Date(FastFieldReader<crate::DateTime>),
+}
+
+impl HistogramFastFieldReader {
+    fn get_f64(&self, doc: DocId) -> f64 {
+        match self {
+            HistogramFastFieldReader::U64(reader) => reader.get(doc) as f64,
+            HistogramFastFieldReader::I64(reader) => reader.get(doc) as f64,
+            HistogramFastFieldReader::F64(reader) => reader.get(doc),
+            HistogramFastFieldReader::Date(reader) => reader.get(doc).timestamp() as f64,
+        }
+    }
+}
+
+/// Segment collector associated to the `HistogramCollector`.
+pub struct HistogramSegmentCollector<TCollector: Collector> {
+    segment_local_id: SegmentLocalId,
+    segment_reader: SegmentReader,
+    fast_field_reader: HistogramFastFieldReader,
+    interval: f64,
+    offset: f64,
+    max_buckets: usize,
+    sub_collector: Arc<TCollector>,
+    buckets: BTreeMap<i64, TCollector::Child>,
+    error: Option<TantivyError>,
+}
+
+impl<TCollector> SegmentCollector for HistogramSegmentCollector<TCollector>
+where
+    TCollector: Collector + 'static,
+{
+    type Fruit = HistogramSegmentFruit<TCollector>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.error.is_some() {
+            return;
+        }
+        let val = self.fast_field_reader.get_f64(doc);
+        if val.is_nan() {
+            return;
+        }
+        let bucket = ((val - self.offset) / self.interval).floor() as i64;
+        if !self.buckets.contains_key(&bucket) {
+            if self.buckets.len() >= self.max_buckets {
+                self.error = Some(too_many_buckets(self.max_buckets));
+                return;
+            }
+            match self
+                .sub_collector
+                .for_segment(self.segment_local_id, &self.segment_reader)
+            {
+                Ok(bucket_collector) => {
+                    self.buckets.insert(bucket, bucket_collector);
+                }
+                Err(error) => {
+                    self.error = Some(error);
+                    return;
+                }
+            }
+        }
+        if let Some(bucket_collector) = self.buckets.get_mut(&bucket) {
+            bucket_collector.collect(doc, score);
+        }
+    }
+
+    fn harvest(self) -> HistogramSegmentFruit<TCollector> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        Ok(self
+            .buckets
+            .into_iter()
+            .map(|(bucket, bucket_collector)| (bucket, bucket_collector.harvest()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HistogramCollector;
+    use crate::collector::{Count, StatsCollector};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, TEXT};
+    use crate::{DateTime, Index, Term};
+    use chrono::Utc;
+
+    #[test]
+    fn test_histogram_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let temperature = schema_builder.add_i64_field("temperature", FAST);
+        let humidity = schema_builder.add_f64_field("humidity", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a", temperature=>-7i64, humidity=>0.25f64));
+        index_writer.add_document(doc!(text=>"a", temperature=>3i64, humidity=>0.5f64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text=>"b", temperature=>8i64, humidity=>0.75f64));
+        index_writer.add_document(doc!(text=>"a", temperature=>24i64, humidity=>0.5f64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let buckets =
+            searcher.search(&AllQuery, &HistogramCollector::for_field(temperature, 10.0))?;
+        let counts: Vec<(f64, f64, usize)> = buckets
+            .iter()
+            .map(|bucket| (bucket.from(), bucket.to(), *bucket.fruit()))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(-10.0, 0.0, 1), (0.0, 10.0, 2), (20.0, 30.0, 1)]
+        );
+
+        let histogram = HistogramCollector::for_field(temperature, 10.0).with_offset(5.0);
+        let buckets = searcher.search(&AllQuery, &histogram)?;
+        let froms: Vec<f64> = buckets.iter().map(|bucket| bucket.from()).collect();
+        assert_eq!(froms, vec![-15.0, -5.0, 5.0, 15.0]);
+
+        let query = TermQuery::new(Term::from_field_text(text, "a"), IndexRecordOption::Basic);
+        let histogram = HistogramCollector::for_field(humidity, 0.5).sub_aggregation((
+            Count,
+            HistogramCollector::for_field(temperature, 10.0)
+                .sub_aggregation(StatsCollector::for_field(humidity)),
+        ));
+        let buckets = searcher.search(&query, &histogram)?;
+        assert_eq!(buckets.len(), 2);
+        let (count, temperature_buckets) = buckets[1].fruit();
+        assert_eq!(buckets[1].from(), 0.5);
+        assert_eq!(*count, 2);
+        assert_eq!(temperature_buckets.len(), 2);
+        let stats = temperature_buckets[1].fruit().as_ref().unwrap();
+        assert_eq!(temperature_buckets[1].from(), 20.0);
+        assert_eq!(stats.count(), 1);
+        assert_eq!(stats.max(), 0.5f64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_collector_date() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let date = schema_builder.add_date_field("date", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for date_str in &[
+            "2020-11-02T10:00:00+00:00",
+            "2020-11-02T23:00:00+00:00",
+            "2020-11-04T01:00:00+00:00",
+        ] {
+            let datetime: DateTime = chrono::DateTime::parse_from_rfc3339(date_str)
+                .unwrap()
+                .with_timezone(&Utc);
+            index_writer.add_document(doc!(date=>datetime));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let buckets = searcher.search(&AllQuery, &HistogramCollector::for_field(date, 86_400.0))?;
+        let counts: Vec<(i64, usize)> = buckets
+            .iter()
+            .map(|bucket| (bucket.from() as i64, *bucket.fruit()))
+            .collect();
+        assert_eq!(counts, vec![(1_604_275_200, 2), (1_604_448_000, 1)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_collector_errors() {
+        let mut schema_builder = Schema::builder();
+        let not_fast = schema_builder.add_u64_field("not_fast", INDEXED);
+        let fast = schema_builder.add_u64_field("fast", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests().unwrap();
+        index_writer.add_document(doc!(not_fast=>1u64, fast=>1u64));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert!(searcher
+            .search(&AllQuery, &HistogramCollector::for_field(not_fast, 1.0))
+            .is_err());
+        assert!(searcher
+            .search(&AllQuery, &HistogramCollector::for_field(fast, 0.0))
+            .is_err());
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &HistogramCollector::for_field(fast, 1.0).with_max_buckets(0)
+            )
+            .is_err());
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &HistogramCollector::for_field(fast, 1.0)
+                    .sub_aggregation(StatsCollector::for_field(not_fast))
+            )
+            .is_err());
+    }
+}
@@ -107,8 +107,13 @@ pub use self::custom_score_top_collector::{CustomScorer, CustomSegmentScorer};
 mod tweak_score_top_collector;
 pub use self::tweak_score_top_collector::{ScoreSegmentTweaker, ScoreTweaker};
 
+mod score_expression;
+pub use self::score_expression::{ScoreExpression, ScoreExpressionSegmentTweaker};
+
+mod fast_field_values_collector;
+
 mod facet_collector;
-pub use self::facet_collector::FacetCollector;
+pub use self::facet_collector::{FacetCollector, FacetCounts};
 use crate::query::Weight;
 
 mod docset_collector;
@@ -117,6 +122,25 @@ pub use self::docset_collector::DocSetCollector;
 mod filter_collector_wrapper;
 pub use self::filter_collector_wrapper::FilterCollector;
 
+mod stats_collector;
+pub use self::stats_collector::{Stats, StatsCollector};
+
+mod significant_terms_collector;
+pub use self::significant_terms_collector::{SignificantTerm, SignificantTermsCollector};
+
+mod value_count_collector;
+pub use self::value_count_collector::ValueCountCollector;
+
+mod histogram_collector;
+pub use self::histogram_collector::{
+    HistogramBucket, HistogramCollector, HistogramSegmentCollector, HistogramSegmentFruit,
+};
+
+mod terms_collector;
+pub use self::terms_collector::{
+    TermsBucket, TermsCollector, TermsSegmentCollector, TermsSegmentFruit,
+};
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast_rs::Downcast {}
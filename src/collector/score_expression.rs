@@ -0,0 +1,561 @@
+use crate::collector::{ScoreSegmentTweaker, ScoreTweaker};
+use crate::common::{u64_to_f64, u64_to_i64};
+use crate::fastfield::FastFieldReader;
+use crate::schema::{Cardinality, Field, FieldType, Schema, Type};
+use crate::{DocId, Score, SegmentReader, TantivyError};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Maximum depth of the parsed expression, protecting the parser and the
+/// evaluation from stack overflows.
+const MAX_DEPTH: usize = 128;
+
+/// Maximum number of arguments of a function.
+const MAX_NUM_ARGS: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Log,
+    Log10,
+    Sqrt,
+    Exp,
+    Abs,
+    Min,
+    Max,
+    Pow,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        match name {
+            "log" => Some(Function::Log),
+            "log10" => Some(Function::Log10),
+            "sqrt" => Some(Function::Sqrt),
+            "exp" => Some(Function::Exp),
+            "abs" => Some(Function::Abs),
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "pow" => Some(Function::Pow),
+            _ => None,
+        }
+    }
+
+    fn num_args(self) -> usize {
+        match self {
+            Function::Log | Function::Log10 | Function::Sqrt | Function::Exp | Function::Abs => 1,
+            Function::Min | Function::Max | Function::Pow => 2,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match self {
+            Function::Log => args[0].ln(),
+            Function::Log10 => args[0].log10(),
+            Function::Sqrt => args[0].sqrt(),
+            Function::Exp => args[0].exp(),
+            Function::Abs => args[0].abs(),
+            Function::Min => args[0].min(args[1]),
+            Function::Max => args[0].max(args[1]),
+            Function::Pow => args[0].powf(args[1]),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Const(f64),
+    Score,
+    // Ordinal of the field in `ScoreExpression::fields`.
+    Field(usize),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, score: f64, field_values: &mut dyn FnMut(usize) -> f64) -> f64 {
+        match self {
+            Expr::Const(val) => *val,
+            Expr::Score => score,
+            Expr::Field(field_ord) => field_values(*field_ord),
+            Expr::Neg(expr) => -expr.eval(score, field_values),
+            Expr::Add(left, right) => {
+                left.eval(score, field_values) + right.eval(score, field_values)
+            }
+            Expr::Sub(left, right) => {
+                left.eval(score, field_values) - right.eval(score, field_values)
+            }
+            Expr::Mul(left, right) => {
+                left.eval(score, field_values) * right.eval(score, field_values)
+            }
+            Expr::Div(left, right) => {
+                left.eval(score, field_values) / right.eval(score, field_values)
+            }
+            Expr::Call(function, args) => {
+                // Avoids allocating for every document.
+                let mut arg_vals = [0f64; MAX_NUM_ARGS];
+                for (arg_val, arg) in arg_vals.iter_mut().zip(args) {
+                    *arg_val = arg.eval(score, field_values);
+                }
+                function.apply(&arg_vals[..args.len()])
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(expression: &str) -> crate::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars: Peekable<Chars> = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut num = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                num.push(c);
+                chars.next();
+            }
+            let val = num
+                .parse()
+                .map_err(|_| invalid_expression(format!("invalid number {:?}", num)))?;
+            tokens.push(Token::Num(val));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+        } else if "+-*/(),".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            return Err(invalid_expression(format!("unexpected character {:?}", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+fn invalid_expression(msg: String) -> TantivyError {
+    TantivyError::InvalidArgument(format!("Invalid score expression: {}.", msg))
+}
+
+struct Parser<'a> {
+    schema: &'a Schema,
+    tokens: Vec<Token>,
+    pos: usize,
+    fields: Vec<(Field, Type)>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn consume_op(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn check_depth(depth: usize) -> crate::Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_expression(
+                "the expression is nested too deeply".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn expect_op(&mut self, op: char) -> crate::Result<()> {
+        if self.consume_op(op) {
+            Ok(())
+        } else {
+            Err(invalid_expression(format!("expected {:?}", op)))
+        }
+    }
+
+    // `depth` is an upper bound of the depth of the parsed expression.
+    // Chained operators nest their left operand, so they count as well.
+
+    // expr := term (('+' | '-') term)*
+    fn parse_expr(&mut self, mut depth: usize) -> crate::Result<Expr> {
+        Self::check_depth(depth)?;
+        let mut expr = self.parse_term(depth + 1)?;
+        loop {
+            if self.consume_op('+') {
+                depth += 1;
+                Self::check_depth(depth)?;
+                expr = Expr::Add(Box::new(expr), Box::new(self.parse_term(depth + 1)?));
+            } else if self.consume_op('-') {
+                depth += 1;
+                Self::check_depth(depth)?;
+                expr = Expr::Sub(Box::new(expr), Box::new(self.parse_term(depth + 1)?));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn parse_term(&mut self, mut depth: usize) -> crate::Result<Expr> {
+        Self::check_depth(depth)?;
+        let mut expr = self.parse_factor(depth + 1)?;
+        loop {
+            if self.consume_op('*') {
+                depth += 1;
+                Self::check_depth(depth)?;
+                expr = Expr::Mul(Box::new(expr), Box::new(self.parse_factor(depth + 1)?));
+            } else if self.consume_op('/') {
+                depth += 1;
+                Self::check_depth(depth)?;
+                expr = Expr::Div(Box::new(expr), Box::new(self.parse_factor(depth + 1)?));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    // factor := '-' factor | number | '_score' | field | function '(' args ')' | '(' expr ')'
+    fn parse_factor(&mut self, depth: usize) -> crate::Result<Expr> {
+        Self::check_depth(depth)?;
+        match self.next() {
+            Some(Token::Op('-')) => Ok(Expr::Neg(Box::new(self.parse_factor(depth + 1)?))),
+            Some(Token::Op('(')) => {
+                let expr = self.parse_expr(depth + 1)?;
+                self.expect_op(')')?;
+                Ok(expr)
+            }
+            Some(Token::Num(val)) => Ok(Expr::Const(val)),
+            Some(Token::Ident(ident)) => {
+                if ident == "_score" {
+                    return Ok(Expr::Score);
+                }
+                if self.consume_op('(') {
+                    let function = Function::from_name(&ident).ok_or_else(|| {
+                        invalid_expression(format!("unknown function {:?}", ident))
+                    })?;
+                    let mut args = vec![self.parse_expr(depth + 1)?];
+                    while self.consume_op(',') {
+                        args.push(self.parse_expr(depth + 1)?);
+                    }
+                    self.expect_op(')')?;
+                    if args.len() != function.num_args() {
+                        return Err(invalid_expression(format!(
+                            "{:?} expects {} argument(s)",
+                            ident,
+                            function.num_args()
+                        )));
+                    }
+                    return Ok(Expr::Call(function, args));
+                }
+                self.field(&ident)
+            }
+            Some(token) => Err(invalid_expression(format!("unexpected {:?}", token))),
+            None => Err(invalid_expression(
+                "unexpected end of expression".to_string(),
+            )),
+        }
+    }
+
+    fn field(&mut self, field_name: &str) -> crate::Result<Expr> {
+        let field = self
+            .schema
+            .get_field(field_name)
+            .ok_or_else(|| TantivyError::SchemaError(format!("Unknown field {:?}.", field_name)))?;
+        let field_type = self.schema.get_field_entry(field).field_type();
+        let cardinality = match field_type {
+            FieldType::U64(options)
+            | FieldType::I64(options)
+            | FieldType::F64(options)
+            | FieldType::Date(options) => options.get_fastfield_cardinality(),
+            _ => None,
+        };
+        if cardinality != Some(Cardinality::SingleValue) {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a single-valued numerical fast field.",
+                field_name
+            )));
+        }
+        let field_ord = match self.fields.iter().position(|&(f, _)| f == field) {
+            Some(field_ord) => field_ord,
+            None => {
+                self.fields.push((field, field_type.value_type()));
+                self.fields.len() - 1
+            }
+        };
+        Ok(Expr::Field(field_ord))
+    }
+}
+
+/// `ScoreExpression` computes the score of the documents from a formula
+/// referencing the original score and fast field values.
+///
+/// The formula is given as a string, making it possible to tweak a ranking
+/// without recompiling a custom collector.
+/// It is used with [`TopDocs::tweak_score`](./struct.TopDocs.html#method.tweak_score).
+///
+/// The expression can contain:
+/// - numbers,
+/// - `_score`, the score computed by the query,
+/// - the name of single-valued numerical fast fields (`u64`, `i64`, `f64`,
+///   or `date`, as a timestamp in seconds). The value of a document
+///   without any value is 0.
+/// - the operators `+`, `-`, `*`, `/`, and parentheses,
+/// - the functions `log` (natural logarithm), `log10`, `sqrt`, `exp`, `abs`, `min(a, b)`,
+///   `max(a, b)` and `pow(a, b)`.
+///
+/// The expression is evaluated with `f64` precision. Documents for which it
+/// is not a number (e.g. `sqrt(-1)`) get a score of `f32::NEG_INFINITY`, so that
+/// they are ranked last.
+///
+/// ```rust
+/// use tantivy::collector::{ScoreExpression, TopDocs};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let popularity = schema_builder.add_u64_field("popularity", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(title => "The Sea", popularity => 1u64));
+/// index_writer.add_document(doc!(title => "The Sea Wolf", popularity => 1000u64));
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("sea")?;
+/// let expression = ScoreExpression::parse(&index.schema(), "_score * log(1 + popularity)")?;
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(2).tweak_score(expression))?;
+/// assert_eq!(top_docs[0].1, DocAddress(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScoreExpression {
+    expr: Expr,
+    fields: Vec<(Field, Type)>,
+}
+
+impl ScoreExpression {
+    /// Parses a score expression.
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if the expression is not valid or is nested
+    /// too deeply, and a
+    /// `SchemaError` if it references a field that is not a single-valued numerical fast field.
+    pub fn parse(schema: &Schema, expression: &str) -> crate::Result<ScoreExpression> {
+        let mut parser = Parser {
+            schema,
+            tokens: tokenize(expression)?,
+            pos: 0,
+            fields: Vec::new(),
+        };
+        let expr = parser.parse_expr(0)?;
+        if let Some(token) = parser.peek() {
+            return Err(invalid_expression(format!("unexpected {:?}", token)));
+        }
+        Ok(ScoreExpression {
+            expr,
+            fields: parser.fields,
+        })
+    }
+
+    /// Evaluates the expression, given the score of a document
+    /// and the values of the fields it references.
+    fn eval(&self, score: Score, field_values: &mut dyn FnMut(usize) -> f64) -> Score {
+        let val = self.expr.eval(f64::from(score), field_values);
+        // NaN scores cannot be ordered, and would break the ranking of `TopDocs`.
+        if val.is_nan() {
+            return Score::NEG_INFINITY;
+        }
+        val as Score
+    }
+}
+
+impl ScoreTweaker<Score> for ScoreExpression {
+    type Child = ScoreExpressionSegmentTweaker;
+
+    fn segment_tweaker(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<ScoreExpressionSegmentTweaker> {
+        let fast_fields = segment_reader.fast_fields();
+        let fast_field_readers = self
+            .fields
+            .iter()
+            .map(|&(field, value_type)| {
+                let fast_field_reader = fast_fields.u64_lenient(field).ok_or_else(|| {
+                    TantivyError::SchemaError(format!("Field {:?} is not a fast field.", field))
+                })?;
+                Ok((fast_field_reader, value_type))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(ScoreExpressionSegmentTweaker {
+            expression: self.clone(),
+            fast_field_readers,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct ScoreExpressionSegmentTweaker {
+    expression: ScoreExpression,
+    fast_field_readers: Vec<(FastFieldReader<u64>, Type)>,
+}
+
+impl ScoreSegmentTweaker<Score> for ScoreExpressionSegmentTweaker {
+    fn score(&mut self, doc: DocId, score: Score) -> Score {
+        let fast_field_readers = &self.fast_field_readers;
+        self.expression.eval(score, &mut |field_ord| {
+            let (fast_field_reader, value_type) = &fast_field_readers[field_ord];
+            let val = fast_field_reader.get(doc);
+            match value_type {
+                Type::I64 | Type::Date => u64_to_i64(val) as f64,
+                Type::F64 => u64_to_f64(val),
+                _ => val as f64,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScoreExpression;
+    use crate::collector::{ScoreSegmentTweaker, ScoreTweaker, TopDocs};
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, INDEXED, TEXT};
+    use crate::{DocAddress, Index, TantivyError};
+
+    fn test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let popularity = schema_builder.add_u64_field("popularity", FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let ratio = schema_builder.add_f64_field("ratio", FAST);
+        schema_builder.add_u64_field("not_fast", INDEXED);
+        schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(popularity => 3u64, delta => -2i64, ratio => 0.5f64));
+        index_writer.add_document(doc!(popularity => 9u64, delta => 4i64, ratio => 0.25f64));
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn eval(index: &Index, expression: &str, score: f32) -> crate::Result<Vec<f32>> {
+        let expression = ScoreExpression::parse(&index.schema(), expression)?;
+        let searcher = index.reader()?.searcher();
+        let mut segment_tweaker = expression.segment_tweaker(searcher.segment_reader(0))?;
+        Ok((0..2)
+            .map(|doc| segment_tweaker.score(doc, score))
+            .collect())
+    }
+
+    #[test]
+    fn test_score_expression_eval() -> crate::Result<()> {
+        let index = test_index()?;
+        assert_eq!(eval(&index, "1 + 2 * 3", 0.0)?, vec![7.0, 7.0]);
+        assert_eq!(eval(&index, "(1 + 2) * -_score", 2.0)?, vec![-6.0, -6.0]);
+        assert_eq!(eval(&index, "10 - 4 - 3", 0.0)?, vec![3.0, 3.0]);
+        assert_eq!(eval(&index, "popularity / 3", 0.0)?, vec![1.0, 3.0]);
+        assert_eq!(eval(&index, "delta * ratio", 0.0)?, vec![-1.0, 1.0]);
+        assert_eq!(
+            eval(&index, "max(delta, 0) + pow(2, 3) + sqrt(popularity)", 0.0)?,
+            vec![9.732_051, 15.0]
+        );
+        assert_eq!(
+            eval(&index, "abs(min(delta, popularity)) + log(exp(1.5))", 0.0)?,
+            vec![3.5, 5.5]
+        );
+        assert_eq!(eval(&index, "log10(1000)", 0.0)?, vec![3.0, 3.0]);
+        assert_eq!(
+            eval(&index, "sqrt(delta)", 0.0)?,
+            vec![f32::NEG_INFINITY, 2.0]
+        );
+        assert_eq!(
+            eval(&index, &vec!["1"; 100].join(" + "), 0.0)?,
+            vec![100.0, 100.0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_expression_errors() -> crate::Result<()> {
+        let schema = test_index()?.schema();
+        for expression in &[
+            "",
+            "1 +",
+            "(1 + 2",
+            "1 2",
+            "1 $ 2",
+            "1..2",
+            "unknown(1)",
+            "pow(1)",
+            "log(1, 2)",
+            &"(".repeat(1_000),
+            &"-".repeat(1_000),
+            &vec!["1"; 1_000].join(" + "),
+        ] {
+            assert!(
+                matches!(
+                    ScoreExpression::parse(&schema, expression),
+                    Err(TantivyError::InvalidArgument(_))
+                ),
+                "{}",
+                expression
+            );
+        }
+        for expression in &["unknown_field", "not_fast + 1", "log(text)"] {
+            assert!(
+                matches!(
+                    ScoreExpression::parse(&schema, expression),
+                    Err(TantivyError::SchemaError(_))
+                ),
+                "{}",
+                expression
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_expression_tweak_score() -> crate::Result<()> {
+        let index = test_index()?;
+        let searcher = index.reader()?.searcher();
+        let expression = ScoreExpression::parse(&index.schema(), "_score - popularity")?;
+        let top_docs =
+            searcher.search(&AllQuery, &TopDocs::with_limit(2).tweak_score(expression))?;
+        assert_eq!(
+            top_docs,
+            vec![(-2.0, DocAddress(0, 0)), (-8.0, DocAddress(0, 1))]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+use crate::collector::{Collector, SegmentCollector};
+use crate::common::BitSet;
+use crate::core::InvertedIndexReader;
+use crate::schema::{Field, IndexRecordOption, Term};
+use crate::{DocId, Score, SegmentLocalId, SegmentReader};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A term that is over-represented in the documents matching a query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignificantTerm {
+    text: String,
+    score: f64,
+    foreground_count: u64,
+    background_count: u64,
+}
+
+impl SignificantTerm {
+    /// Text of the term.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// JLH score of the term.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// Number of documents matching the query that contain the term.
+    pub fn foreground_count(&self) -> u64 {
+        self.foreground_count
+    }
+
+    /// Number of documents of the index that contain the term.
+    pub fn background_count(&self) -> u64 {
+        self.background_count
+    }
+}
+
+/// `SignificantTermsCollector` returns the terms of a text field that are
+/// statistically over-represented in the documents matching the query,
+/// compared to the whole index.
+///
+/// Terms are scored with the JLH score: if `fg` (resp. `bg`) is the proportion
+/// of matching documents (resp. of documents of the index) containing the term,
+/// the score is `(fg - bg) * fg / bg`, for terms such that `fg > bg`.
+///
+/// The collector visits the postings of all of the terms of the field, so it
+/// can be slow on large indexes.
+/// Background counts include deleted documents, just like `Searcher::doc_freq`.
+///
+/// ```rust
+/// use tantivy::collector::SignificantTermsCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(body => "flu fever cough"));
+/// index_writer.add_document(doc!(body => "flu fever"));
+/// index_writer.add_document(doc!(body => "broken leg"));
+/// index_writer.add_document(doc!(body => "broken arm fever"));
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let query = QueryParser::for_index(&index, vec![body]).parse_query("flu")?;
+/// let significant_terms = searcher.search(&query, &SignificantTermsCollector::for_field(body, 2))?;
+/// assert_eq!(significant_terms[0].text(), "flu");
+/// assert_eq!(significant_terms[1].text(), "cough");
+/// # Ok(())
+/// # }
+/// ```
+pub struct SignificantTermsCollector {
+    field: Field,
+    limit: usize,
+}
+
+impl SignificantTermsCollector {
+    /// Creates a collector returning the `limit` most significant terms
+    /// of the given field.
+    pub fn for_field(field: Field, limit: usize) -> SignificantTermsCollector {
+        SignificantTermsCollector { field, limit }
+    }
+}
+
+pub struct SegmentTermCounts {
+    inverted_index: Arc<InvertedIndexReader>,
+    foreground_total: u64,
+    background_total: u64,
+    // term -> foreground count
+    foreground_counts: HashMap<String, u64>,
+}
+
+impl Collector for SignificantTermsCollector {
+    type Fruit = Vec<SignificantTerm>;
+    type Child = SignificantTermsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<SignificantTermsSegmentCollector> {
+        Ok(SignificantTermsSegmentCollector {
+            inverted_index: segment_reader.inverted_index(self.field)?,
+            foreground_docs: BitSet::with_max_value(segment_reader.max_doc()),
+            foreground_total: 0,
+            background_total: u64::from(segment_reader.max_doc()),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<crate::Result<SegmentTermCounts>>,
+    ) -> crate::Result<Vec<SignificantTerm>> {
+        let segment_fruits = segment_fruits
+            .into_iter()
+            .collect::<crate::Result<Vec<SegmentTermCounts>>>()?;
+        let mut foreground_total = 0u64;
+        let mut background_total = 0u64;
+        let mut foreground_counts: HashMap<String, u64> = HashMap::new();
+        for segment_fruit in &segment_fruits {
+            foreground_total += segment_fruit.foreground_total;
+            background_total += segment_fruit.background_total;
+            for (text, foreground_count) in &segment_fruit.foreground_counts {
+                *foreground_counts.entry(text.clone()).or_insert(0) += foreground_count;
+            }
+        }
+        if foreground_total == 0 {
+            return Ok(Vec::new());
+        }
+        let mut significant_terms: Vec<SignificantTerm> = Vec::new();
+        for (text, foreground_count) in foreground_counts {
+            // The background count is computed over all of the segments,
+            // including those without any matching document, like `Searcher::doc_freq`.
+            let term = Term::from_field_text(self.field, &text);
+            let mut background_count = 0u64;
+            for segment_fruit in &segment_fruits {
+                background_count += u64::from(segment_fruit.inverted_index.doc_freq(&term)?);
+            }
+            let foreground_ratio = foreground_count as f64 / foreground_total as f64;
+            let background_ratio = background_count as f64 / background_total as f64;
+            if foreground_ratio <= background_ratio {
+                continue;
+            }
+            let score = (foreground_ratio - background_ratio) * foreground_ratio / background_ratio;
+            significant_terms.push(SignificantTerm {
+                text,
+                score,
+                foreground_count,
+                background_count,
+            });
+        }
+        significant_terms.sort_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.text.cmp(&right.text))
+        });
+        significant_terms.truncate(self.limit);
+        Ok(significant_terms)
+    }
+}
+
+pub struct SignificantTermsSegmentCollector {
+    inverted_index: Arc<InvertedIndexReader>,
+    foreground_docs: BitSet,
+    foreground_total: u64,
+    background_total: u64,
+}
+
+impl SignificantTermsSegmentCollector {
+    /// Counts the matching documents containing each term of the segment.
+    fn foreground_counts(&self) -> crate::Result<HashMap<String, u64>> {
+        let mut foreground_counts = HashMap::new();
+        if self.foreground_total == 0 {
+            return Ok(foreground_counts);
+        }
+        let mut term_stream = self.inverted_index.terms().stream()?;
+        while let Some((term_bytes, term_info)) = term_stream.next() {
+            let text = match std::str::from_utf8(term_bytes) {
+                Ok(text) => text,
+                Err(_) => continue,
+            };
+            let mut block_postings = self
+                .inverted_index
+                .read_block_postings_from_terminfo(term_info, IndexRecordOption::Basic)?;
+            let mut foreground_count = 0u64;
+            loop {
+                let docs = block_postings.docs();
+                if docs.is_empty() {
+                    break;
+                }
+                foreground_count += docs
+                    .iter()
+                    .filter(|&&doc| self.foreground_docs.contains(doc))
+                    .count() as u64;
+                block_postings.advance();
+            }
+            if foreground_count > 0 {
+                foreground_counts.insert(text.to_string(), foreground_count);
+            }
+        }
+        Ok(foreground_counts)
+    }
+}
+
+impl SegmentCollector for SignificantTermsSegmentCollector {
+    type Fruit = crate::Result<SegmentTermCounts>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        self.foreground_docs.insert(doc);
+        self.foreground_total += 1;
+    }
+
+    fn harvest(self) -> crate::Result<SegmentTermCounts> {
+        let foreground_counts = self.foreground_counts()?;
+        Ok(SegmentTermCounts {
+            inverted_index: self.inverted_index,
+            foreground_total: self.foreground_total,
+            background_total: self.background_total,
+            foreground_counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignificantTermsCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_significant_terms_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"rust fast safe"));
+        index_writer.add_document(doc!(text_field=>"rust safe"));
+        index_writer.add_document(doc!(text_field=>"python fast"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"python slow"));
+        index_writer.add_document(doc!(text_field=>"java fast"));
+        index_writer.add_document(doc!(text_field=>"rust borrow"));
+        index_writer.commit()?;
+        // This segment has no matching document, but still counts in the background.
+        index_writer.add_document(doc!(text_field=>"python safe"));
+        index_writer.add_document(doc!(text_field=>"java slow"));
+        index_writer.add_document(doc!(text_field=>"go fast"));
+        index_writer.add_document(doc!(text_field=>"go slow"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "rust"),
+            IndexRecordOption::Basic,
+        );
+        let significant_terms = searcher.search(
+            &query,
+            &SignificantTermsCollector::for_field(text_field, 10),
+        )?;
+        let texts: Vec<&str> = significant_terms.iter().map(|term| term.text()).collect();
+        // "fast" appears in 1 of the 3 matching docs, and in 4 of the 10 docs:
+        // it is not over-represented.
+        assert_eq!(texts, vec!["rust", "safe", "borrow"]);
+        let rust = &significant_terms[0];
+        assert_eq!(rust.foreground_count(), 3);
+        assert_eq!(rust.background_count(), 3);
+        crate::assert_nearly_equals!(rust.score() as f32, 7.0 / 3.0);
+        let safe = &significant_terms[1];
+        assert_eq!(safe.foreground_count(), 2);
+        assert_eq!(safe.background_count(), 3);
+        let top_1 =
+            searcher.search(&query, &SignificantTermsCollector::for_field(text_field, 1))?;
+        assert_eq!(top_1.len(), 1);
+        // Nothing is over-represented when all of the documents match.
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &SignificantTermsCollector::for_field(text_field, 10)
+            )?
+            .is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::FastFieldReader;
+use crate::schema::{Field, Type};
+use crate::{DocId, Score, SegmentLocalId, SegmentReader, TantivyError};
+
+/// Statistics over the values of a numerical fast field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Stats {
+    fn with_value(val: f64) -> Stats {
+        Stats {
+            count: 1,
+            sum: val,
+            min: val,
+            max: val,
+        }
+    }
+
+    fn add(&mut self, val: f64) {
+        self.count += 1;
+        self.sum += val;
+        self.min = self.min.min(val);
+        self.max = self.max.max(val);
+    }
+
+    fn merge(&mut self, other: &Stats) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Number of documents that were collected.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Sum of the values.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Smallest value.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Largest value.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Average of the values.
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+/// `StatsCollector` computes the count, sum, min, max and mean
+/// of a `u64`, `i64` or `f64` fast field over the matching documents.
+///
+/// Values are converted to `f64`. Large `u64` and `i64` values may therefore
+/// lose precision.
+///
+/// The collector returns `None` if no document matched the query.
+///
+/// ```rust
+/// use tantivy::collector::StatsCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// let mut schema_builder = Schema::builder();
+/// let price = schema_builder.add_f64_field("price", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer(3_000_000).unwrap();
+/// index_writer.add_document(doc!(price => 2.5f64));
+/// index_writer.add_document(doc!(price => 10.0f64));
+/// index_writer.add_document(doc!(price => -0.5f64));
+/// assert!(index_writer.commit().is_ok());
+///
+/// let searcher = index.reader().unwrap().searcher();
+/// let stats = searcher.search(&AllQuery, &StatsCollector::for_field(price)).unwrap().unwrap();
+/// assert_eq!(stats.count(), 3);
+/// assert_eq!(stats.min(), -0.5f64);
+/// assert_eq!(stats.max(), 10.0f64);
+/// assert_eq!(stats.mean(), 4.0f64);
+/// ```
+pub struct StatsCollector {
+    field: Field,
+}
+
+impl StatsCollector {
+    /// Creates a `StatsCollector` computing the statistics of the given fast field.
+    pub fn for_field(field: Field) -> StatsCollector {
+        StatsCollector { field }
+    }
+}
+
+impl Collector for StatsCollector {
+    type Fruit = Option<Stats>;
+
+    type Child = StatsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<StatsSegmentCollector> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let fast_fields = segment_reader.fast_fields();
+        let fast_field_reader = match field_entry.field_type().value_type() {
+            Type::U64 => fast_fields.u64(self.field).map(NumericFastFieldReader::U64),
+            Type::I64 => fast_fields.i64(self.field).map(NumericFastFieldReader::I64),
+            Type::F64 => fast_fields.f64(self.field).map(NumericFastFieldReader::F64),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64, i64 or f64 fast field.",
+                field_entry.name()
+            ))
+        })?;
+        Ok(StatsSegmentCollector {
+            fast_field_reader,
+            stats: None,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_stats: Vec<Option<Stats>>) -> crate::Result<Option<Stats>> {
+        let mut merged_stats: Option<Stats> = None;
+        for stats in segment_stats.into_iter().flatten() {
+            match merged_stats.as_mut() {
+                Some(merged_stats) => merged_stats.merge(&stats),
+                None => merged_stats = Some(stats),
+            }
+        }
+        Ok(merged_stats)
+    }
+}
+
+enum NumericFastFieldReader {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    F64(FastFieldReader<f64>),
+}
+
+impl NumericFastFieldReader {
+    fn get_f64(&self, doc: DocId) -> f64 {
+        match self {
+            NumericFastFieldReader::U64(reader) => reader.get(doc) as f64,
+            NumericFastFieldReader::I64(reader) => reader.get(doc) as f64,
+            NumericFastFieldReader::F64(reader) => reader.get(doc),
+        }
+    }
+}
+
+/// Segment collector associated to the `StatsCollector`.
+pub struct StatsSegmentCollector {
+    fast_field_reader: NumericFastFieldReader,
+    stats: Option<Stats>,
+}
+
+impl SegmentCollector for StatsSegmentCollector {
+    type Fruit = Option<Stats>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let val = self.fast_field_reader.get_f64(doc);
+        match self.stats.as_mut() {
+            Some(stats) => stats.add(val),
+            None => self.stats = Some(Stats::with_value(val)),
+        }
+    }
+
+    fn harvest(self) -> Option<Stats> {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatsCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, TEXT};
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_stats_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let altitude = schema_builder.add_f64_field("altitude", FAST);
+        let count = schema_builder.add_i64_field("count", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a", altitude=>-1.5f64, count=>-3i64));
+        index_writer.add_document(doc!(text=>"a", altitude=>4.0f64, count=>1i64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text=>"b", altitude=>3.5f64, count=>5i64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let altitude_stats = searcher
+            .search(&AllQuery, &StatsCollector::for_field(altitude))?
+            .unwrap();
+        assert_eq!(altitude_stats.count(), 3);
+        assert_eq!(altitude_stats.sum(), 6.0f64);
+        assert_eq!(altitude_stats.min(), -1.5f64);
+        assert_eq!(altitude_stats.max(), 4.0f64);
+        assert_eq!(altitude_stats.mean(), 2.0f64);
+
+        let query = TermQuery::new(Term::from_field_text(text, "a"), IndexRecordOption::Basic);
+        let count_stats = searcher
+            .search(&query, &StatsCollector::for_field(count))?
+            .unwrap();
+        assert_eq!(count_stats.count(), 2);
+        assert_eq!(count_stats.min(), -3.0f64);
+        assert_eq!(count_stats.max(), 1.0f64);
+
+        let no_match_query =
+            TermQuery::new(Term::from_field_text(text, "c"), IndexRecordOption::Basic);
+        assert!(searcher
+            .search(&no_match_query, &StatsCollector::for_field(count))?
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats_collector_requires_numerical_fast_field() {
+        let mut schema_builder = Schema::builder();
+        let not_fast = schema_builder.add_u64_field("not_fast", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests().unwrap();
+        index_writer.add_document(doc!(not_fast=>1u64));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert!(searcher
+            .search(&AllQuery, &StatsCollector::for_field(not_fast))
+            .is_err());
+    }
+}
@@ -0,0 +1,433 @@
+use crate::collector::histogram_collector::{too_many_buckets, DEFAULT_MAX_BUCKETS};
+use crate::collector::{Collector, Count, SegmentCollector};
+use crate::fastfield::{value_from_u64, FastFieldReader, MultiValueIntFastFieldReader};
+use crate::schema::{Cardinality, Field, FieldType, Type, Value};
+use crate::{DocId, Score, SegmentLocalId, SegmentReader, TantivyError};
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::Arc;
+
+/// A bucket of the terms aggregation computed by the `TermsCollector`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermsBucket<TFruit> {
+    value: Value,
+    doc_count: u64,
+    fruit: TFruit,
+}
+
+impl<TFruit> TermsBucket<TFruit> {
+    /// Value of the fast field shared by the documents of the bucket.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Number of documents in the bucket.
+    pub fn doc_count(&self) -> u64 {
+        self.doc_count
+    }
+
+    /// Fruit of the sub-aggregation, computed over the documents of the bucket.
+    pub fn fruit(&self) -> &TFruit {
+        &self.fruit
+    }
+
+    /// Consumes the bucket and returns the fruit of the sub-aggregation.
+    pub fn into_fruit(self) -> TFruit {
+        self.fruit
+    }
+}
+
+/// `TermsCollector` groups the matching documents according to the values
+/// of a `u64`, `i64`, `f64` or date fast field, with one bucket per value.
+///
+/// The field can be single-valued, optional or multivalued. A document with several
+/// values belongs to the bucket of each of them. The documents without any value in an
+/// optional or multivalued field do not belong to any bucket. A single-valued fast field
+/// cannot tell a missing value from `0`, so its documents without a value fall in the
+/// bucket of `0`: fields that may be missing should rather be declared with
+/// `Cardinality::Optional`.
+///
+/// By default, the documents of each bucket are counted, but any collector
+/// can be run on the documents of each bucket with `.sub_aggregation(...)`:
+/// a `StatsCollector`, a tuple of collectors, a `HistogramCollector` or another
+/// `TermsCollector`.
+///
+/// Buckets are returned by decreasing number of documents, then by increasing value.
+/// Searching fails if the matching documents have more than `max_buckets` distinct
+/// values (65,536 by default).
+///
+/// ```rust
+/// use tantivy::collector::{StatsCollector, TermsCollector};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, Value, FAST};
+/// use tantivy::{doc, Index};
+///
+/// let mut schema_builder = Schema::builder();
+/// let category = schema_builder.add_u64_field("category", FAST);
+/// let price = schema_builder.add_f64_field("price", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer(3_000_000).unwrap();
+/// index_writer.add_document(doc!(category => 1u64, price => 2.5f64));
+/// index_writer.add_document(doc!(category => 2u64, price => 7.0f64));
+/// index_writer.add_document(doc!(category => 2u64, price => 12.0f64));
+/// assert!(index_writer.commit().is_ok());
+///
+/// let searcher = index.reader().unwrap().searcher();
+/// let buckets = searcher.search(&AllQuery, &TermsCollector::for_field(category)).unwrap();
+/// assert_eq!(buckets.len(), 2);
+/// assert_eq!(buckets[0].value(), &Value::U64(2));
+/// assert_eq!(buckets[0].doc_count(), 2);
+///
+/// // Average price per category.
+/// let terms = TermsCollector::for_field(category)
+///     .sub_aggregation(StatsCollector::for_field(price));
+/// let buckets = searcher.search(&AllQuery, &terms).unwrap();
+/// assert_eq!(buckets[0].fruit().as_ref().unwrap().mean(), 9.5);
+/// assert_eq!(buckets[1].fruit().as_ref().unwrap().mean(), 2.5);
+/// ```
+pub struct TermsCollector<TCollector = Count> {
+    field: Field,
+    limit: Option<usize>,
+    max_buckets: usize,
+    sub_collector: Arc<TCollector>,
+}
+
+impl TermsCollector<Count> {
+    /// Creates a `TermsCollector` counting the documents for each value
+    /// of the given fast field.
+    pub fn for_field(field: Field) -> TermsCollector<Count> {
+        TermsCollector {
+            field,
+            limit: None,
+            max_buckets: DEFAULT_MAX_BUCKETS,
+            sub_collector: Arc::new(Count),
+        }
+    }
+}
+
+impl<TCollector> TermsCollector<TCollector> {
+    /// Only returns the `limit` buckets with the most documents.
+    pub fn with_limit(mut self, limit: usize) -> TermsCollector<TCollector> {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets the maximum number of buckets, that is of distinct values,
+    /// the aggregation may collect before failing.
+    pub fn with_max_buckets(mut self, max_buckets: usize) -> TermsCollector<TCollector> {
+        self.max_buckets = max_buckets;
+        self
+    }
+
+    /// Runs `sub_collector` on the documents of each bucket,
+    /// instead of counting them.
+    pub fn sub_aggregation<TSubCollector: Collector>(
+        self,
+        sub_collector: TSubCollector,
+    ) -> TermsCollector<TSubCollector> {
+        TermsCollector {
+            field: self.field,
+            limit: self.limit,
+            max_buckets: self.max_buckets,
+            sub_collector: Arc::new(sub_collector),
+        }
+    }
+}
+
+impl<TCollector> Collector for TermsCollector<TCollector>
+where
+    TCollector: Collector + 'static,
+{
+    type Fruit = Vec<TermsBucket<TCollector::Fruit>>;
+
+    type Child = TermsSegmentCollector<TCollector>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<TermsSegmentCollector<TCollector>> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let cardinality = match field_entry.field_type() {
+            FieldType::U64(options)
+            | FieldType::I64(options)
+            | FieldType::F64(options)
+            | FieldType::Date(options) => options.get_fastfield_cardinality(),
+            _ => None,
+        };
+        let fast_fields = segment_reader.fast_fields();
+        let fast_field_reader = match cardinality {
+            Some(Cardinality::SingleValue) => fast_fields
+                .u64_lenient(self.field)
+                .map(TermsFastFieldReader::SingleValue),
+            // Optional fast fields are stored as multivalued fast fields.
+            Some(Cardinality::MultiValues) | Some(Cardinality::Optional) => fast_fields
+                .u64s_lenient(self.field)
+                .map(TermsFastFieldReader::MultiValues),
+            None => None,
+        }
+        .ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64, i64, f64 or date fast field.",
+                field_entry.name()
+            ))
+        })?;
+        // Validates that the sub-collector can be run on this segment.
+        self.sub_collector
+            .for_segment(segment_local_id, segment_reader)?;
+        Ok(TermsSegmentCollector {
+            segment_local_id,
+            segment_reader: segment_reader.clone(),
+            fast_field_reader,
+            value_type: field_entry.field_type().value_type(),
+            max_buckets: self.max_buckets,
+            sub_collector: self.sub_collector.clone(),
+            vals: Vec::new(),
+            buckets: BTreeMap::new(),
+            error: None,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.sub_collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<TermsSegmentFruit<TCollector>>,
+    ) -> crate::Result<Vec<TermsBucket<TCollector::Fruit>>> {
+        let mut merged_buckets: BTreeMap<u64, (Value, u64, Vec<SubFruit<TCollector>>)> =
+            BTreeMap::new();
+        for segment_fruit in segment_fruits {
+            for (val, (value, doc_count, fruit)) in segment_fruit? {
+                let bucket = merged_buckets
+                    .entry(val)
+                    .or_insert_with(|| (value, 0, Vec::new()));
+                bucket.1 += doc_count;
+                bucket.2.push(fruit);
+            }
+        }
+        if merged_buckets.len() > self.max_buckets {
+            return Err(too_many_buckets(self.max_buckets));
+        }
+        // The sort is stable: buckets with the same number of documents
+        // remain sorted by value.
+        let mut buckets: Vec<_> = merged_buckets.into_iter().collect();
+        buckets.sort_by(|(_, (_, left_count, _)), (_, (_, right_count, _))| {
+            right_count.cmp(left_count)
+        });
+        if let Some(limit) = self.limit {
+            buckets.truncate(limit);
+        }
+        buckets
+            .into_iter()
+            .map(|(_, (value, doc_count, fruits))| {
+                Ok(TermsBucket {
+                    value,
+                    doc_count,
+                    fruit: self.sub_collector.merge_fruits(fruits)?,
+                })
+            })
+            .collect()
+    }
+}
+
+type SubFruit<TCollector> = <<TCollector as Collector>::Child as SegmentCollector>::Fruit;
+
+/// Fruit of the `TermsSegmentCollector`: the value, the number of documents and the fruit
+/// of the sub-collector for each bucket, keyed by the `u64` representation of the value.
+pub type TermsSegmentFruit<TCollector> =
+    crate::Result<BTreeMap<u64, (Value, u64, SubFruit<TCollector>)>>;
+
+enum TermsFastFieldReader {
+    SingleValue(FastFieldReader<u64>),
+    MultiValues(MultiValueIntFastFieldReader<u64>),
+}
+
+/// Segment collector associated to the `TermsCollector`.
+pub struct TermsSegmentCollector<TCollector: Collector> {
+    segment_local_id: SegmentLocalId,
+    segment_reader: SegmentReader,
+    fast_field_reader: TermsFastFieldReader,
+    value_type: Type,
+    max_buckets: usize,
+    sub_collector: Arc<TCollector>,
+    vals: Vec<u64>,
+    buckets: BTreeMap<u64, (u64, TCollector::Child)>,
+    error: Option<TantivyError>,
+}
+
+impl<TCollector: Collector> TermsSegmentCollector<TCollector> {
+    fn collect_val(&mut self, val: u64, doc: DocId, score: Score) -> crate::Result<()> {
+        if !self.buckets.contains_key(&val) {
+            if self.buckets.len() >= self.max_buckets {
+                return Err(too_many_buckets(self.max_buckets));
+            }
+            let bucket_collector = self
+                .sub_collector
+                .for_segment(self.segment_local_id, &self.segment_reader)?;
+            self.buckets.insert(val, (0, bucket_collector));
+        }
+        if let Some((doc_count, bucket_collector)) = self.buckets.get_mut(&val) {
+            *doc_count += 1;
+            bucket_collector.collect(doc, score);
+        }
+        Ok(())
+    }
+}
+
+impl<TCollector> SegmentCollector for TermsSegmentCollector<TCollector>
+where
+    TCollector: Collector + 'static,
+{
+    type Fruit = TermsSegmentFruit<TCollector>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.error.is_some() {
+            return;
+        }
+        let mut vals = mem::take(&mut self.vals);
+        match &self.fast_field_reader {
+            TermsFastFieldReader::SingleValue(reader) => {
+                vals.clear();
+                vals.push(reader.get(doc));
+            }
+            TermsFastFieldReader::MultiValues(reader) => {
+                reader.get_vals(doc, &mut vals);
+                vals.sort_unstable();
+                vals.dedup();
+            }
+        }
+        for &val in &vals {
+            if let Err(error) = self.collect_val(val, doc, score) {
+                self.error = Some(error);
+                break;
+            }
+        }
+        self.vals = vals;
+    }
+
+    fn harvest(self) -> TermsSegmentFruit<TCollector> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let value_type = self.value_type;
+        Ok(self
+            .buckets
+            .into_iter()
+            .map(|(val, (doc_count, bucket_collector))| {
+                let value = value_from_u64(value_type, val);
+                (val, (value, doc_count, bucket_collector.harvest()))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TermsCollector;
+    use crate::collector::{Count, HistogramCollector};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{Cardinality, IndexRecordOption, IntOptions, Schema, Value};
+    use crate::schema::{FAST, INDEXED, TEXT};
+    use crate::{Index, TantivyError, Term};
+
+    #[test]
+    fn test_terms_collector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let temperature = schema_builder.add_i64_field("temperature", FAST);
+        let tags = schema_builder.add_u64_field(
+            "tags",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a", temperature=>-7i64, tags=>2u64, tags=>2u64));
+        index_writer.add_document(doc!(text=>"a", temperature=>3i64, tags=>2u64, tags=>5u64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text=>"b", temperature=>3i64, tags=>5u64));
+        index_writer.add_document(doc!(text=>"a", temperature=>24i64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let buckets = searcher.search(&AllQuery, &TermsCollector::for_field(temperature))?;
+        let counts: Vec<(Value, u64)> = buckets
+            .iter()
+            .map(|bucket| (bucket.value().clone(), bucket.doc_count()))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![(Value::I64(3), 2), (Value::I64(-7), 1), (Value::I64(24), 1)]
+        );
+        let buckets = searcher.search(
+            &AllQuery,
+            &TermsCollector::for_field(temperature).with_limit(2),
+        )?;
+        assert_eq!(buckets.len(), 2);
+
+        // Documents are counted once per distinct value, and not at all without values.
+        let query = TermQuery::new(Term::from_field_text(text, "a"), IndexRecordOption::Basic);
+        let terms = TermsCollector::for_field(tags)
+            .sub_aggregation((Count, HistogramCollector::for_field(temperature, 10.0)));
+        let buckets = searcher.search(&query, &terms)?;
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].value(), &Value::U64(2));
+        assert_eq!(buckets[0].doc_count(), 2);
+        let (count, temperature_buckets) = buckets[0].fruit();
+        assert_eq!(*count, 2);
+        assert_eq!(temperature_buckets.len(), 2);
+        assert_eq!(buckets[1].value(), &Value::U64(5));
+        assert_eq!(buckets[1].doc_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_terms_collector_max_buckets() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let not_fast = schema_builder.add_u64_field("not_fast", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id=>1u64, not_fast=>1u64));
+        index_writer.add_document(doc!(id=>2u64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id=>3u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        // Exceeded within a segment.
+        assert!(matches!(
+            searcher.search(
+                &AllQuery,
+                &TermsCollector::for_field(id).with_max_buckets(1)
+            ),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        // Exceeded once the segments are merged.
+        assert!(matches!(
+            searcher.search(
+                &AllQuery,
+                &TermsCollector::for_field(id).with_max_buckets(2)
+            ),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            searcher
+                .search(
+                    &AllQuery,
+                    &TermsCollector::for_field(id).with_max_buckets(3)
+                )?
+                .len(),
+            3
+        );
+        assert!(matches!(
+            searcher.search(&AllQuery, &TermsCollector::for_field(not_fast)),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -1,12 +1,15 @@
 use super::Collector;
+use crate::collector::fast_field_values_collector::FastFieldValuesCollector;
 use crate::collector::top_collector::{ComparableDoc, TopCollector};
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
     CustomScorer, CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
 };
-use crate::fastfield::FastFieldReader;
+use crate::common;
+use crate::fastfield::{FastFieldReader, MultiValueIntFastFieldReader};
 use crate::query::Weight;
-use crate::schema::Field;
+use crate::schema::geo_point::MISSING_POINT_VAL;
+use crate::schema::{Field, FieldType, GeoPoint, Value};
 use crate::DocAddress;
 use crate::DocId;
 use crate::Score;
@@ -23,6 +26,7 @@ struct FastFieldConvertCollector<
 > {
     pub collector: TCollector,
     pub field: Field,
+    pub ascending: bool,
     pub fast_value: std::marker::PhantomData<TFastValue>,
 }
 
@@ -72,12 +76,54 @@ where
         let raw_result = self.collector.merge_fruits(segment_fruits)?;
         let transformed_result = raw_result
             .into_iter()
-            .map(|(score, doc_address)| (TFastValue::from_u64(score), doc_address))
+            .map(|(score, doc_address)| {
+                let val = if self.ascending {
+                    u64::MAX - score
+                } else {
+                    score
+                };
+                (TFastValue::from_u64(val), doc_address)
+            })
             .collect::<Vec<_>>();
         Ok(transformed_result)
     }
 }
 
+/// Collector returning the documents closest to a geo point, along with
+/// their distance in meters.
+struct GeoDistanceCollector {
+    collector: CustomScoreTopCollector<ScorerByGeoDistance, u64>,
+}
+
+impl Collector for GeoDistanceCollector {
+    type Fruit = Vec<(f64, DocAddress)>;
+
+    type Child = <CustomScoreTopCollector<ScorerByGeoDistance, u64> as Collector>::Child;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        self.collector.for_segment(segment_local_id, segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let raw_result = self.collector.merge_fruits(segment_fruits)?;
+        Ok(raw_result
+            .into_iter()
+            .map(|(score, doc_address)| (common::u64_to_f64(u64::MAX - score), doc_address))
+            .collect())
+    }
+}
+
 /// The `TopDocs` collector keeps track of the top `K` documents
 /// sorted by their score.
 ///
@@ -130,16 +176,24 @@ impl fmt::Debug for TopDocs {
 
 struct ScorerByFastFieldReader {
     ff_reader: FastFieldReader<u64>,
+    ascending: bool,
 }
 
 impl CustomSegmentScorer<u64> for ScorerByFastFieldReader {
     fn score(&mut self, doc: DocId) -> u64 {
-        self.ff_reader.get(doc)
+        let val = self.ff_reader.get(doc);
+        if self.ascending {
+            // Reversing the u64 representation makes the smallest values the best ones.
+            u64::MAX - val
+        } else {
+            val
+        }
     }
 }
 
 struct ScorerByField {
     field: Field,
+    ascending: bool,
 }
 
 impl CustomScorer<u64> for ScorerByField {
@@ -155,7 +209,81 @@ impl CustomScorer<u64> for ScorerByField {
                     self.field
                 ))
             })?;
-        Ok(ScorerByFastFieldReader { ff_reader })
+        Ok(ScorerByFastFieldReader {
+            ff_reader,
+            ascending: self.ascending,
+        })
+    }
+}
+
+enum GeoPointsReader {
+    SingleValue(FastFieldReader<u64>),
+    // Optional fast fields are stored as multivalued fast fields.
+    MultiValues(MultiValueIntFastFieldReader<u64>),
+}
+
+struct ScorerByGeoDistanceReader {
+    points_reader: GeoPointsReader,
+    origin: GeoPoint,
+    vals: Vec<u64>,
+}
+
+impl CustomSegmentScorer<u64> for ScorerByGeoDistanceReader {
+    fn score(&mut self, doc: DocId) -> u64 {
+        let origin = self.origin;
+        let distance_to = |val: u64| GeoPoint::from_u64(val).distance(&origin);
+        // The documents without a point are infinitely far.
+        let distance = match &self.points_reader {
+            GeoPointsReader::SingleValue(reader) => match reader.get(doc) {
+                MISSING_POINT_VAL => f64::INFINITY,
+                val => distance_to(val),
+            },
+            GeoPointsReader::MultiValues(reader) => {
+                reader.get_vals(doc, &mut self.vals);
+                self.vals
+                    .iter()
+                    .map(|&val| distance_to(val))
+                    .fold(f64::INFINITY, f64::min)
+            }
+        };
+        // The closest documents get the highest score.
+        u64::MAX - common::f64_to_u64(distance)
+    }
+}
+
+struct ScorerByGeoDistance {
+    field: Field,
+    origin: GeoPoint,
+}
+
+impl CustomScorer<u64> for ScorerByGeoDistance {
+    type Child = ScorerByGeoDistanceReader;
+
+    fn segment_scorer(&self, segment_reader: &SegmentReader) -> crate::Result<Self::Child> {
+        let field_entry = segment_reader.schema().get_field_entry(self.field);
+        let fast_fields = segment_reader.fast_fields();
+        let points_reader = match field_entry.field_type() {
+            FieldType::U64(_) => fast_fields
+                .u64(self.field)
+                .map(GeoPointsReader::SingleValue)
+                .or_else(|| {
+                    fast_fields
+                        .u64s(self.field)
+                        .map(GeoPointsReader::MultiValues)
+                }),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64 fast field.",
+                field_entry.name()
+            ))
+        })?;
+        Ok(ScorerByGeoDistanceReader {
+            points_reader,
+            origin: self.origin,
+            vals: Vec::new(),
+        })
     }
 }
 
@@ -285,7 +413,11 @@ impl TopDocs {
         self,
         field: Field,
     ) -> impl Collector<Fruit = Vec<(u64, DocAddress)>> {
-        CustomScoreTopCollector::new(ScorerByField { field }, self.0.into_tscore())
+        let scorer_by_field = ScorerByField {
+            field,
+            ascending: false,
+        };
+        CustomScoreTopCollector::new(scorer_by_field, self.0.into_tscore())
     }
 
     /// Set top-K to rank documents by a given fast field.
@@ -368,10 +500,109 @@ impl TopDocs {
         FastFieldConvertCollector {
             collector: u64_collector,
             field: fast_field,
+            ascending: false,
+            fast_value: PhantomData,
+        }
+    }
+
+    /// Set top-K to rank documents by a given fast field, in ascending order.
+    ///
+    /// This is the same as [.order_by_fast_field(...)](#method.order_by_fast_field),
+    /// except that the documents with the smallest values come first.
+    /// In case of a tie, documents are sorted by ascending `DocAddress`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{Schema, FAST};
+    /// use tantivy::{doc, DocAddress, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let price = schema_builder.add_f64_field("price", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+    /// index_writer.add_document(doc!(price => 12.5f64));
+    /// index_writer.add_document(doc!(price => -3.0f64));
+    /// index_writer.add_document(doc!(price => 7.0f64));
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let cheapest = TopDocs::with_limit(2).order_by_fast_field_asc(price);
+    /// let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &cheapest)?;
+    /// assert_eq!(top_docs, vec![(-3.0, DocAddress(0, 1)), (7.0, DocAddress(0, 2))]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn order_by_fast_field_asc<TFastValue>(
+        self,
+        fast_field: Field,
+    ) -> impl Collector<Fruit = Vec<(TFastValue, DocAddress)>>
+    where
+        TFastValue: FastValue + 'static,
+    {
+        let scorer_by_field = ScorerByField {
+            field: fast_field,
+            ascending: true,
+        };
+        FastFieldConvertCollector {
+            collector: CustomScoreTopCollector::new(scorer_by_field, self.0.into_tscore()),
+            field: fast_field,
+            ascending: true,
             fast_value: PhantomData,
         }
     }
 
+    /// Set top-K to rank documents by their distance to a geo point, closest first.
+    ///
+    /// The field has to be a `u64` fast field, in which the points are indexed
+    /// as described in [`GeoPoint`](../schema/struct.GeoPoint.html).
+    /// The documents are returned along with their distance to `origin`, in meters.
+    /// The distance of a document with several points is the one of its closest point.
+    /// The documents without a point come last, with an infinite distance.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{GeoPoint, Schema, FAST};
+    /// use tantivy::{doc, DocAddress, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let location = schema_builder.add_u64_field("location", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+    /// index_writer.add_document(doc!(location => GeoPoint::new(40.7128, -74.0060)?));
+    /// index_writer.add_document(doc!(location => GeoPoint::new(51.5074, -0.1278)?));
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let paris = GeoPoint::new(48.8566, 2.3522)?;
+    /// let closest = TopDocs::with_limit(1).order_by_geo_distance(location, paris);
+    /// let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &closest)?;
+    /// assert_eq!(top_docs[0].1, DocAddress(0, 1));
+    /// assert!((top_docs[0].0 - 343_500.0).abs() < 1_000.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn order_by_geo_distance(
+        self,
+        geo_field: Field,
+        origin: GeoPoint,
+    ) -> impl Collector<Fruit = Vec<(f64, DocAddress)>> {
+        let scorer_by_geo_distance = ScorerByGeoDistance {
+            field: geo_field,
+            origin,
+        };
+        GeoDistanceCollector {
+            collector: CustomScoreTopCollector::new(scorer_by_geo_distance, self.0.into_tscore()),
+        }
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to tweak or replace
@@ -465,8 +696,18 @@ impl TopDocs {
     ///      searcher.search(&query, &top_docs_by_custom_score).unwrap();
     /// ```
     ///
+    /// # Sorting by several keys
+    ///
+    /// The tweaked score only needs to implement `PartialOrd`. Returning a tuple,
+    /// e.g. `(original_score, date, std::cmp::Reverse(id))`, sorts the documents by
+    /// several keys, the first one being the most significant.
+    /// Keys wrapped in `std::cmp::Reverse` are sorted in ascending order.
+    ///
+    /// Documents that are tied on all of the keys are sorted by ascending `DocAddress`.
+    ///
     /// # See also
-    /// [custom_score(...)](#method.custom_score).
+    /// [custom_score(...)](#method.custom_score), and
+    /// [`ScoreExpression`](./struct.ScoreExpression.html) to tweak the score with a formula.
     pub fn tweak_score<TScore, TScoreSegmentTweaker, TScoreTweaker>(
         self,
         score_tweaker: TScoreTweaker,
@@ -592,6 +833,51 @@ impl TopDocs {
     {
         CustomScoreTopCollector::new(custom_score, self.0.into_tscore())
     }
+
+    /// Returns the values of some fast fields alongside each of the top hits.
+    ///
+    /// The values are read from the fast fields while the segments are
+    /// collected, in the order of `fields`. This avoids fetching the hits
+    /// from the doc store when only a few numerical values are needed
+    /// to display them.
+    ///
+    /// All of the fields have to be single-valued numerical fast fields
+    /// (`u64`, `i64`, `f64` or `Date`), otherwise a `SchemaError` is returned
+    /// at the moment of search.
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::QueryParser;
+    /// use tantivy::schema::{Schema, Value, FAST, TEXT};
+    /// use tantivy::{doc, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let price = schema_builder.add_f64_field("price", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    ///
+    /// let mut index_writer = index.writer(3_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Old Man and the Sea", price => 12.5f64));
+    /// index_writer.add_document(doc!(title => "Of Mice and Men", price => 8.0f64));
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let query = QueryParser::for_index(&index, vec![title]).parse_query("sea")?;
+    /// let top_docs = TopDocs::with_limit(10).with_fast_field_values(vec![price]);
+    /// let hits = searcher.search(&query, &top_docs)?;
+    /// assert_eq!(hits.len(), 1);
+    /// let (_score, _doc_address, values) = &hits[0];
+    /// assert_eq!(values, &vec![Value::F64(12.5)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_fast_field_values(
+        self,
+        fields: Vec<Field>,
+    ) -> impl Collector<Fruit = Vec<(Score, DocAddress, Vec<Value>)>> {
+        FastFieldValuesCollector::new(self, fields)
+    }
 }
 
 impl Collector for TopDocs {
@@ -698,11 +984,12 @@ mod tests {
     use super::TopDocs;
     use crate::collector::Collector;
     use crate::query::{AllQuery, Query, QueryParser};
-    use crate::schema::{Field, Schema, FAST, STORED, TEXT};
+    use crate::schema::{Cardinality, Field, GeoPoint, IntOptions, Schema};
+    use crate::schema::{FAST, INDEXED, STORED, TEXT};
     use crate::Index;
     use crate::IndexWriter;
     use crate::Score;
-    use crate::{DocAddress, DocId, SegmentReader};
+    use crate::{DocAddress, DocId, SegmentReader, Term};
 
     fn make_index() -> Index {
         let mut schema_builder = Schema::builder();
@@ -906,6 +1193,113 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_top_field_collector_ascending() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let altitude = schema_builder.add_i64_field("altitude", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(altitude => 40i64));
+        index_writer.add_document(doc!(altitude => -5i64));
+        index_writer.add_document(doc!(altitude => 40i64));
+        index_writer.add_document(doc!(altitude => 12i64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let top_collector = TopDocs::with_limit(4).order_by_fast_field_asc(altitude);
+        let top_docs: Vec<(i64, DocAddress)> = searcher.search(&AllQuery, &top_collector)?;
+        assert_eq!(
+            &top_docs[..],
+            &[
+                (-5, DocAddress(0, 1)),
+                (12, DocAddress(0, 3)),
+                (40, DocAddress(0, 0)),
+                (40, DocAddress(0, 2)),
+            ]
+        );
+
+        let top_collector = TopDocs::with_limit(2)
+            .and_offset(2)
+            .order_by_fast_field_asc(altitude);
+        let top_docs: Vec<(i64, DocAddress)> = searcher.search(&AllQuery, &top_collector)?;
+        assert_eq!(
+            &top_docs[..],
+            &[(40, DocAddress(0, 0)), (40, DocAddress(0, 2))]
+        );
+
+        let top_collector = TopDocs::with_limit(1).order_by_fast_field_asc::<u64>(altitude);
+        assert!(searcher.search(&AllQuery, &top_collector).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_order_by_geo_distance() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field("location", FAST);
+        let altitude = schema_builder.add_i64_field("altitude", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let point = |lat: f64, lon: f64| GeoPoint::new(lat, lon).unwrap();
+        index_writer.add_document(doc!(location => point(35.6762, 139.6503)));
+        index_writer.add_document(doc!(location => point(34.6937, 135.5023)));
+        index_writer.add_document(doc!(location => point(-33.8688, 151.2093)));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let kyoto = point(35.0116, 135.7681);
+        let top_collector = TopDocs::with_limit(3).order_by_geo_distance(location, kyoto);
+        let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &top_collector)?;
+        let doc_addresses: Vec<DocAddress> = top_docs.iter().map(|(_, addr)| *addr).collect();
+        assert_eq!(
+            doc_addresses,
+            vec![DocAddress(0, 1), DocAddress(0, 0), DocAddress(0, 2)]
+        );
+        let (distance, _) = top_docs[0];
+        assert!((distance - point(34.6937, 135.5023).distance(&kyoto)).abs() < 0.01);
+        assert!(top_docs[1].0 < top_docs[2].0);
+
+        let top_collector = TopDocs::with_limit(1).order_by_geo_distance(altitude, kyoto);
+        assert!(searcher.search(&AllQuery, &top_collector).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_order_by_geo_distance_missing_points() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field("location", FAST);
+        let optional_location = schema_builder.add_u64_field(
+            "optional_location",
+            IntOptions::default().set_fast(Cardinality::Optional),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let point = |lat: f64, lon: f64| GeoPoint::new(lat, lon).unwrap();
+        index_writer.add_document(doc!());
+        index_writer.add_document(doc!(
+            location => point(-33.8688, 151.2093),
+            optional_location => point(-33.8688, 151.2093)
+        ));
+        // The point whose cells are all zeros is not taken for a missing point.
+        index_writer.add_document(doc!(
+            location => point(-90.0, -180.0),
+            optional_location => point(-90.0, -180.0)
+        ));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        // Closer to (-90, -180) than to Sydney.
+        let origin = point(-85.0, -170.0);
+        for &field in &[location, optional_location] {
+            let top_collector = TopDocs::with_limit(3).order_by_geo_distance(field, origin);
+            let top_docs: Vec<(f64, DocAddress)> = searcher.search(&AllQuery, &top_collector)?;
+            assert_eq!(top_docs[0].1, DocAddress(0, 2));
+            assert!(top_docs[0].0 < 600_000.0);
+            assert_eq!(top_docs[1].1, DocAddress(0, 1));
+            assert_eq!(top_docs[2], (f64::INFINITY, DocAddress(0, 0)));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_top_field_collector_i64() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -1019,6 +1413,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_top_collector_skips_deleted_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+        for id in 0u64..300u64 {
+            let text = if id % 3 == 0 { "a b" } else { "a" };
+            index_writer.add_document(doc!(text_field=>text, id_field=>id));
+        }
+        index_writer.commit()?;
+        for id in 0u64..250u64 {
+            index_writer.delete_term(Term::from_field_u64(id_field, id));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        for (query_str, expected_num_docs) in &[("a", 50), ("a b", 50), ("b", 16)] {
+            let query = query_parser.parse_query(query_str)?;
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(100))?;
+            assert_eq!(top_docs.len(), *expected_num_docs);
+            for (_score, DocAddress(segment_ord, doc)) in top_docs {
+                assert!(!searcher.segment_reader(segment_ord).is_deleted(doc));
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_tweak_score_top_collector_with_offset() {
         let index = make_index();
@@ -1041,6 +1464,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tweak_score_top_collector_multiple_keys() -> crate::Result<()> {
+        use std::cmp::Reverse;
+        let mut schema_builder = Schema::builder();
+        let category_field = schema_builder.add_u64_field("category", FAST);
+        let id_field = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+        index_writer.add_document(doc!(category_field=>1u64, id_field=>5u64));
+        index_writer.add_document(doc!(category_field=>2u64, id_field=>3u64));
+        index_writer.add_document(doc!(category_field=>1u64, id_field=>2u64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(category_field=>2u64, id_field=>1u64));
+        index_writer.add_document(doc!(category_field=>1u64, id_field=>4u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let collector =
+            TopDocs::with_limit(4).tweak_score(move |segment_reader: &SegmentReader| {
+                let category_reader = segment_reader.fast_fields().u64(category_field).unwrap();
+                let id_reader = segment_reader.fast_fields().u64(id_field).unwrap();
+                move |doc: DocId, _original_score: Score| {
+                    (category_reader.get(doc), Reverse(id_reader.get(doc)))
+                }
+            });
+        let top_docs = searcher.search(&AllQuery, &collector)?;
+        let sort_keys: Vec<(u64, u64)> = top_docs
+            .iter()
+            .map(|&((category, Reverse(id)), _)| (category, id))
+            .collect();
+        assert_eq!(sort_keys, vec![(2, 1), (2, 3), (1, 2), (1, 4)]);
+        Ok(())
+    }
+
     #[test]
     fn test_custom_score_top_collector_with_offset() {
         let index = make_index();
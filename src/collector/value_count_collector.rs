@@ -0,0 +1,188 @@
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::{FastFieldReader, MultiValueIntFastFieldReader};
+use crate::schema::Field;
+use crate::{DocId, Score, SegmentLocalId, SegmentReader, TantivyError};
+use std::collections::BTreeMap;
+
+/// `ValueCountCollector` counts the number of matching documents
+/// for each value of a `u64` fast field.
+///
+/// The field can be single-valued or multivalued, which makes it possible
+/// to facet over tags or category ids encoded as `u64`.
+/// A document associated with the same value several times is
+/// only counted once for this value.
+///
+/// ```rust
+/// use tantivy::collector::ValueCountCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Cardinality, IntOptions, Schema};
+/// use tantivy::{doc, Index};
+///
+/// let mut schema_builder = Schema::builder();
+/// let category_ids = schema_builder.add_u64_field(
+///     "category_ids",
+///     IntOptions::default().set_fast(Cardinality::MultiValues),
+/// );
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer(3_000_000).unwrap();
+/// index_writer.add_document(doc!(category_ids => 1u64, category_ids => 3u64));
+/// index_writer.add_document(doc!(category_ids => 3u64));
+/// index_writer.add_document(doc!());
+/// assert!(index_writer.commit().is_ok());
+///
+/// let searcher = index.reader().unwrap().searcher();
+/// let counts = searcher.search(&AllQuery, &ValueCountCollector::for_field(category_ids)).unwrap();
+/// assert_eq!(counts.into_iter().collect::<Vec<_>>(), vec![(1u64, 1u64), (3u64, 2u64)]);
+/// ```
+pub struct ValueCountCollector {
+    field: Field,
+}
+
+impl ValueCountCollector {
+    /// Creates a `ValueCountCollector` counting the values of the given fast field.
+    pub fn for_field(field: Field) -> ValueCountCollector {
+        ValueCountCollector { field }
+    }
+}
+
+impl Collector for ValueCountCollector {
+    type Fruit = BTreeMap<u64, u64>;
+
+    type Child = ValueCountSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<ValueCountSegmentCollector> {
+        let fast_fields = segment_reader.fast_fields();
+        let fast_field_reader = if let Some(reader) = fast_fields.u64(self.field) {
+            U64FastFieldReader::SingleValue(reader)
+        } else if let Some(reader) = fast_fields.u64s(self.field) {
+            U64FastFieldReader::MultiValues(reader)
+        } else {
+            let field_name = segment_reader.schema().get_field_name(self.field);
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a u64 fast field.",
+                field_name
+            )));
+        };
+        Ok(ValueCountSegmentCollector {
+            fast_field_reader,
+            vals: Vec::new(),
+            counts: BTreeMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_counts: Vec<BTreeMap<u64, u64>>,
+    ) -> crate::Result<BTreeMap<u64, u64>> {
+        let mut merged_counts = BTreeMap::new();
+        for counts in segment_counts {
+            for (val, count) in counts {
+                *merged_counts.entry(val).or_insert(0u64) += count;
+            }
+        }
+        Ok(merged_counts)
+    }
+}
+
+enum U64FastFieldReader {
+    SingleValue(FastFieldReader<u64>),
+    MultiValues(MultiValueIntFastFieldReader<u64>),
+}
+
+/// Segment collector associated to the `ValueCountCollector`.
+pub struct ValueCountSegmentCollector {
+    fast_field_reader: U64FastFieldReader,
+    vals: Vec<u64>,
+    counts: BTreeMap<u64, u64>,
+}
+
+impl SegmentCollector for ValueCountSegmentCollector {
+    type Fruit = BTreeMap<u64, u64>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        match &self.fast_field_reader {
+            U64FastFieldReader::SingleValue(reader) => {
+                *self.counts.entry(reader.get(doc)).or_insert(0u64) += 1;
+            }
+            U64FastFieldReader::MultiValues(reader) => {
+                reader.get_vals(doc, &mut self.vals);
+                self.vals.sort_unstable();
+                self.vals.dedup();
+                for &val in &self.vals {
+                    *self.counts.entry(val).or_insert(0u64) += 1;
+                }
+            }
+        }
+    }
+
+    fn harvest(self) -> BTreeMap<u64, u64> {
+        self.counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueCountCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{Cardinality, IndexRecordOption, IntOptions, Schema, FAST, TEXT};
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_value_count_collector_multivalued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let tags = schema_builder.add_u64_field(
+            "tags",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a", tags=>2u64, tags=>7u64, tags=>2u64));
+        index_writer.add_document(doc!(text=>"a", tags=>7u64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text=>"b", tags=>5u64));
+        index_writer.add_document(doc!(text=>"a"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let counts = searcher.search(&AllQuery, &ValueCountCollector::for_field(tags))?;
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            vec![(2, 1), (5, 1), (7, 2)]
+        );
+        let query = TermQuery::new(Term::from_field_text(text, "a"), IndexRecordOption::Basic);
+        let counts = searcher.search(&query, &ValueCountCollector::for_field(tags))?;
+        assert_eq!(counts.into_iter().collect::<Vec<_>>(), vec![(2, 1), (7, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_count_collector_single_valued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_u64_field("category", FAST);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category=>3u64));
+        index_writer.add_document(doc!(category=>1u64));
+        index_writer.add_document(doc!(category=>3u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let counts = searcher.search(&AllQuery, &ValueCountCollector::for_field(category))?;
+        assert_eq!(counts.into_iter().collect::<Vec<_>>(), vec![(1, 1), (3, 2)]);
+        assert!(searcher
+            .search(&AllQuery, &ValueCountCollector::for_field(text))
+            .is_err());
+        Ok(())
+    }
+}
@@ -81,6 +81,20 @@ impl BitUnpacker {
         }
     }
 
+    pub fn num_bits(&self) -> u8 {
+        self.num_bits as u8
+    }
+
+    /// Returns true iff the data is long enough to decode `num_vals` values.
+    pub fn holds_num_vals(&self, num_vals: u64) -> bool {
+        if self.num_bits == 0 || num_vals == 0 {
+            return true;
+        }
+        // Values are read as 8 bytes words.
+        let last_addr = ((num_vals - 1) * self.num_bits) >> 3;
+        last_addr + 8 <= self.data.len() as u64
+    }
+
     pub fn get(&self, idx: u64) -> u64 {
         if self.num_bits == 0 {
             return 0u64;
@@ -99,6 +113,75 @@ impl BitUnpacker {
         let val_shifted = (val_unshifted_unmasked >> bit_shift) as u64;
         val_shifted & mask
     }
+
+    /// Decodes the values with the indexes going from
+    /// `start` to `start + output.len()` into `output`.
+    ///
+    /// If AVX2 instructions are available on the running CPU, the decoding
+    /// loop is compiled with them. Byte-aligned bit widths (8, 16, 32 and 64 bits)
+    /// are then decoded several values at a time.
+    pub fn get_range(&self, start: u64, output: &mut [u64]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                // Safe, as the running CPU supports AVX2.
+                unsafe { self.get_range_avx2(start, output) };
+                return;
+            }
+        }
+        self.get_range_scalar(start, output);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    unsafe fn get_range_avx2(&self, start: u64, output: &mut [u64]) {
+        self.get_range_scalar(start, output);
+    }
+
+    // Inlined in `get_range_avx2`, so that it gets vectorized with AVX2.
+    #[inline(always)]
+    fn get_range_scalar(&self, start: u64, output: &mut [u64]) {
+        let data: &[u8] = self.data.as_slice();
+        let start = start as usize;
+        let len = output.len();
+        match self.num_bits {
+            0 => output.iter_mut().for_each(|out| *out = 0u64),
+            8 => {
+                for (out, &byte) in output.iter_mut().zip(&data[start..start + len]) {
+                    *out = u64::from(byte);
+                }
+            }
+            16 => {
+                let bytes = &data[start * 2..(start + len) * 2];
+                for (out, chunk) in output.iter_mut().zip(bytes.chunks_exact(2)) {
+                    *out = u64::from(LittleEndian::read_u16(chunk));
+                }
+            }
+            32 => {
+                let bytes = &data[start * 4..(start + len) * 4];
+                for (out, chunk) in output.iter_mut().zip(bytes.chunks_exact(4)) {
+                    *out = u64::from(LittleEndian::read_u32(chunk));
+                }
+            }
+            64 => {
+                let bytes = &data[start * 8..(start + len) * 8];
+                for (out, chunk) in output.iter_mut().zip(bytes.chunks_exact(8)) {
+                    *out = LittleEndian::read_u64(chunk);
+                }
+            }
+            num_bits => {
+                let mask = self.mask;
+                let mut addr_in_bits = start as u64 * num_bits;
+                for out in output.iter_mut() {
+                    let addr = (addr_in_bits >> 3) as usize;
+                    let bit_shift = addr_in_bits & 7;
+                    let val_unshifted_unmasked = LittleEndian::read_u64(&data[addr..addr + 8]);
+                    *out = (val_unshifted_unmasked >> bit_shift) & mask;
+                    addr_in_bits += num_bits;
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +212,33 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_bitpacker_get_range() {
+        for &num_bits in &[0u8, 1, 3, 8, 13, 16, 32, 56] {
+            let (bitunpacker, vals) = create_fastfield_bitpacker(1_000, num_bits);
+            for &(start, len) in &[(0usize, 1_000usize), (3, 500), (999, 1), (17, 0)] {
+                let mut output = vec![u64::MAX; len];
+                bitunpacker.get_range(start as u64, &mut output);
+                assert_eq!(&output[..], &vals[start..start + len]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitpacker_get_range_64_bits() {
+        let vals: Vec<u64> = (0u64..100).map(|i| i.wrapping_mul(u64::MAX / 7)).collect();
+        let mut data = Vec::new();
+        let mut bitpacker = BitPacker::new();
+        for &val in &vals {
+            bitpacker.write(val, 64, &mut data).unwrap();
+        }
+        bitpacker.close(&mut data).unwrap();
+        let bitunpacker = BitUnpacker::new(OwnedBytes::new(data), 64);
+        let mut output = vec![0u64; 98];
+        bitunpacker.get_range(2, &mut output);
+        assert_eq!(&output[..], &vals[2..]);
+    }
+
     #[test]
     fn test_bitpacker() {
         test_bitpacker_util(10, 3);
@@ -30,6 +30,18 @@ impl Executor {
         Ok(Executor::ThreadPool(pool))
     }
 
+    /// Runs `task` in the background.
+    ///
+    /// The `ThreadPool` variant runs the task in its thread pool. As the
+    /// `SingleThread` variant has no thread of its own, it runs the task
+    /// in rayon's global thread pool.
+    pub(crate) fn spawn<F: FnOnce() + Send + 'static>(&self, task: F) {
+        match self {
+            Executor::SingleThread => rayon::spawn(task),
+            Executor::ThreadPool(pool) => pool.spawn(task),
+        }
+    }
+
     /// Perform a map in the thread pool.
     ///
     /// Regardless of the executor (`SingleThread` or `ThreadPool`), panics in the task
@@ -116,6 +128,18 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn test_spawn() {
+        for executor in &[
+            Executor::single_thread(),
+            Executor::multi_thread(2, "search-test").unwrap(),
+        ] {
+            let (sender, receiver) = crossbeam::channel::bounded(1);
+            executor.spawn(move || sender.send(3).unwrap());
+            assert_eq!(receiver.recv().unwrap(), 3);
+        }
+    }
+
     #[test]
     fn test_map_singlethread() {
         let result: Vec<usize> = Executor::single_thread()
@@ -0,0 +1,192 @@
+use crate::core::{Searcher, SegmentReader};
+use crate::fastfield::{
+    value_from_u64, BytesFastFieldReader, FastFieldReader, MultiValueIntFastFieldReader,
+};
+use crate::schema::{Cardinality, Document, Field, FieldType, FieldValue, Schema, Type};
+use crate::DocId;
+use std::io::Write;
+
+/// Returns the fast fields that are not stored, and the type of their values.
+fn unstored_fast_fields(schema: &Schema) -> Vec<(Field, Type, Option<Cardinality>)> {
+    schema
+        .fields()
+        .filter(|(_, field_entry)| !field_entry.is_stored())
+        .filter_map(|(field, field_entry)| match field_entry.field_type() {
+            FieldType::U64(options)
+            | FieldType::I64(options)
+            | FieldType::F64(options)
+            | FieldType::Date(options) => options.get_fastfield_cardinality().map(|cardinality| {
+                (
+                    field,
+                    field_entry.field_type().value_type(),
+                    Some(cardinality),
+                )
+            }),
+            FieldType::Bytes(options) if options.is_fast() => Some((field, Type::Bytes, None)),
+            _ => None,
+        })
+        .collect()
+}
+
+enum FastFieldValues {
+    Single(FastFieldReader<u64>),
+    Multi(MultiValueIntFastFieldReader<u64>),
+    Bytes(BytesFastFieldReader),
+}
+
+/// Adds the values of the unstored fast fields of a segment to its documents.
+struct SegmentFastFieldValues {
+    readers: Vec<(Field, Type, FastFieldValues)>,
+    vals: Vec<u64>,
+}
+
+impl SegmentFastFieldValues {
+    fn open(
+        segment_reader: &SegmentReader,
+        fields: &[(Field, Type, Option<Cardinality>)],
+    ) -> SegmentFastFieldValues {
+        let fast_fields = segment_reader.fast_fields();
+        let readers = fields
+            .iter()
+            .filter_map(|&(field, value_type, cardinality)| {
+                let reader = match cardinality {
+                    Some(Cardinality::SingleValue) => {
+                        FastFieldValues::Single(fast_fields.u64_lenient(field)?)
+                    }
+                    // Optional fast fields are stored as multivalued fast fields.
+                    Some(Cardinality::MultiValues) | Some(Cardinality::Optional) => {
+                        FastFieldValues::Multi(fast_fields.u64s_lenient(field)?)
+                    }
+                    None => FastFieldValues::Bytes(fast_fields.bytes(field)?),
+                };
+                Some((field, value_type, reader))
+            })
+            .collect();
+        SegmentFastFieldValues {
+            readers,
+            vals: Vec::new(),
+        }
+    }
+
+    fn add_values(&mut self, doc_id: DocId, doc: &mut Document) {
+        for (field, value_type, reader) in &self.readers {
+            match reader {
+                FastFieldValues::Single(reader) => {
+                    doc.add(FieldValue::new(
+                        *field,
+                        value_from_u64(*value_type, reader.get(doc_id)),
+                    ));
+                }
+                FastFieldValues::Multi(reader) => {
+                    reader.get_vals(doc_id, &mut self.vals);
+                    for &val in &self.vals {
+                        doc.add(FieldValue::new(*field, value_from_u64(*value_type, val)));
+                    }
+                }
+                FastFieldValues::Bytes(reader) => {
+                    let bytes = reader.get_bytes(doc_id);
+                    if !bytes.is_empty() {
+                        doc.add_bytes(*field, bytes);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Writes the alive documents of the searcher as JSON Lines.
+///
+/// See `Searcher::export_jsonl`.
+pub(crate) fn export_jsonl<W: Write>(
+    searcher: &Searcher,
+    mut wrt: W,
+    with_fast_fields: bool,
+) -> crate::Result<u64> {
+    let schema = searcher.schema();
+    let fast_fields = if with_fast_fields {
+        unstored_fast_fields(schema)
+    } else {
+        Vec::new()
+    };
+    let mut num_docs = 0u64;
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        let store_reader = searcher.store_reader(segment_ord as u32)?;
+        let mut fast_field_values = SegmentFastFieldValues::open(segment_reader, &fast_fields);
+        for doc_id in segment_reader.doc_ids_alive() {
+            let mut doc = store_reader.get(doc_id)?;
+            fast_field_values.add_values(doc_id, &mut doc);
+            writeln!(wrt, "{}", schema.to_json(&doc))?;
+            num_docs += 1;
+        }
+    }
+    wrt.flush()?;
+    Ok(num_docs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{
+        BytesOptions, Cardinality, IntOptions, Schema, FAST, STORED, STRING, TEXT,
+    };
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_export_jsonl() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let id = schema_builder.add_text_field("id", STRING);
+        let price = schema_builder.add_f64_field("price", FAST | STORED);
+        let popularity = schema_builder.add_u64_field("popularity", FAST);
+        let tags = schema_builder.add_i64_field(
+            "tags",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let payload = schema_builder.add_bytes_field("payload", BytesOptions::default().set_fast());
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "first",
+            id => "a",
+            price => 1.5f64,
+            popularity => 3u64,
+            tags => -1i64,
+            tags => 2i64,
+            payload => vec![1u8, 2u8],
+        ));
+        index_writer.add_document(doc!(title => "deleted", id => "b"));
+        index_writer.add_document(doc!(title => "second", id => "c", price => 2.5f64));
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(id, "b"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let mut buffer = Vec::new();
+        assert_eq!(searcher.export_jsonl(&mut buffer, false)?, 2);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"price\":[1.5],\"title\":[\"first\"]}\n{\"price\":[2.5],\"title\":[\"second\"]}\n"
+        );
+
+        let mut buffer = Vec::new();
+        assert_eq!(searcher.export_jsonl(&mut buffer, true)?, 2);
+        let lines: Vec<&str> = std::str::from_utf8(&buffer).unwrap().lines().collect();
+        assert_eq!(
+            lines[1],
+            "{\"popularity\":[0],\"price\":[2.5],\"title\":[\"second\"]}"
+        );
+        let doc = schema.parse_document(lines[0]).unwrap();
+        assert_eq!(doc.get_first(popularity).unwrap().u64_value(), Some(3));
+        let tag_values: Vec<i64> = doc
+            .get_all(tags)
+            .flat_map(|value| value.i64_value())
+            .collect();
+        assert_eq!(tag_values, vec![-1, 2]);
+        assert_eq!(
+            doc.get_first(payload).unwrap().bytes_value(),
+            Some(&[1u8, 2u8][..])
+        );
+        assert_eq!(doc.get_all(price).count(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::core::InvertedIndexReader;
+use crate::termdict::{TermDictionary, TermMerger, TermStreamerBuilder};
+use std::io;
+use std::ops::Bound;
+use std::sync::Arc;
+
+/// The terms of a given field, across all of the segments of a `Searcher`.
+///
+/// See `Searcher::field_terms`.
+pub struct FieldTerms {
+    inverted_indexes: Vec<Arc<InvertedIndexReader>>,
+}
+
+impl FieldTerms {
+    pub(crate) fn new(inverted_indexes: Vec<Arc<InvertedIndexReader>>) -> FieldTerms {
+        FieldTerms { inverted_indexes }
+    }
+
+    fn stream_with<'a, F>(&'a self, term_range: F) -> io::Result<FieldTermStreamer<'a>>
+    where
+        F: Fn(&'a TermDictionary) -> TermStreamerBuilder<'a>,
+    {
+        let streamers = self
+            .inverted_indexes
+            .iter()
+            .map(|inverted_index| term_range(inverted_index.terms()).into_stream())
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(FieldTermStreamer {
+            term_merger: TermMerger::new(streamers),
+        })
+    }
+
+    /// Returns a stream over all of the terms of the field, in the
+    /// lexicographical order of their bytes.
+    pub fn stream(&self) -> io::Result<FieldTermStreamer<'_>> {
+        self.stream_with(TermDictionary::range)
+    }
+
+    /// Returns a stream over the terms of the field starting with the given prefix.
+    pub fn prefix_stream<K: AsRef<[u8]>>(&self, prefix: K) -> io::Result<FieldTermStreamer<'_>> {
+        self.stream_with(|term_dict| term_dict.prefix_range(prefix.as_ref()))
+    }
+
+    /// Returns a stream over the terms of the field within the given bounds.
+    pub fn range_stream(
+        &self,
+        lower_bound: Bound<&[u8]>,
+        upper_bound: Bound<&[u8]>,
+    ) -> io::Result<FieldTermStreamer<'_>> {
+        self.stream_with(|term_dict| {
+            let builder = term_dict.range();
+            let builder = match lower_bound {
+                Bound::Included(term_bytes) => builder.ge(term_bytes),
+                Bound::Excluded(term_bytes) => builder.gt(term_bytes),
+                Bound::Unbounded => builder,
+            };
+            match upper_bound {
+                Bound::Included(term_bytes) => builder.le(term_bytes),
+                Bound::Excluded(term_bytes) => builder.lt(term_bytes),
+                Bound::Unbounded => builder,
+            }
+        })
+    }
+}
+
+/// Stream over the terms of a field, across all of the segments of a `Searcher`.
+pub struct FieldTermStreamer<'a> {
+    term_merger: TermMerger<'a>,
+}
+
+impl<'a> FieldTermStreamer<'a> {
+    /// Advances the stream to the next term.
+    /// Returns false if there are no more terms.
+    pub fn advance(&mut self) -> bool {
+        self.term_merger.advance()
+    }
+
+    /// Returns the bytes of the current term.
+    ///
+    /// Calling `.key()` before the first call to `.advance()` is incorrect.
+    pub fn key(&self) -> &[u8] {
+        self.term_merger.key()
+    }
+
+    /// Returns the number of documents containing the current term,
+    /// summed over all of the segments.
+    ///
+    /// Just like `Searcher::doc_freq`, deleted documents are taken in account.
+    pub fn doc_freq(&self) -> u64 {
+        self.term_merger
+            .current_kvs()
+            .iter()
+            .map(|heap_item| u64::from(heap_item.streamer.value().doc_freq))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldTermStreamer;
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+    use std::ops::Bound;
+
+    fn collect_terms(mut stream: FieldTermStreamer<'_>) -> Vec<(String, u64)> {
+        let mut terms = Vec::new();
+        while stream.advance() {
+            let term = String::from_utf8(stream.key().to_vec()).unwrap();
+            terms.push((term, stream.doc_freq()));
+        }
+        terms
+    }
+
+    #[test]
+    fn test_field_terms() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let other_field = schema_builder.add_text_field("other", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"abc abd", other_field=>"zzz"));
+        index_writer.add_document(doc!(text_field=>"abd b"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"abd c"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let field_terms = searcher.field_terms(text_field)?;
+        assert_eq!(
+            collect_terms(field_terms.stream()?),
+            vec![
+                ("abc".to_string(), 1),
+                ("abd".to_string(), 3),
+                ("b".to_string(), 1),
+                ("c".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            collect_terms(field_terms.prefix_stream("ab")?),
+            vec![("abc".to_string(), 1), ("abd".to_string(), 3)]
+        );
+        assert_eq!(
+            collect_terms(
+                field_terms.range_stream(Bound::Excluded(b"abc"), Bound::Included(b"b"))?
+            ),
+            vec![("abd".to_string(), 3), ("b".to_string(), 1)]
+        );
+        let other_terms = searcher.field_terms(other_field)?;
+        assert_eq!(
+            collect_terms(other_terms.stream()?),
+            vec![("zzz".to_string(), 1)]
+        );
+        Ok(())
+    }
+}
@@ -1,20 +1,28 @@
 use super::segment::Segment;
+use crate::core::replication::install_files;
+use crate::core::segment_reader::ExpirationCache;
+use crate::core::validation::validate_segment;
 use crate::core::Executor;
 use crate::core::IndexMeta;
+use crate::core::IndexSettings;
+use crate::core::ReplicationSnapshot;
 use crate::core::SegmentId;
 use crate::core::SegmentMeta;
 use crate::core::SegmentMetaInventory;
 use crate::core::META_FILEPATH;
-use crate::directory::error::OpenReadError;
+use crate::core::{commit_point_filepath, commit_point_generation};
+use crate::core::{SegmentError, ValidationReport};
+use crate::directory::error::{Incompatibility, OpenReadError};
 use crate::directory::ManagedDirectory;
 #[cfg(feature = "mmap")]
 use crate::directory::MmapDirectory;
 use crate::directory::INDEX_WRITER_LOCK;
-use crate::directory::{Directory, RAMDirectory};
+use crate::directory::{Directory, RAMDirectory, ReadOnlyDirectory};
+use crate::directory::{WatchCallback, WatchHandle};
 use crate::error::DataCorruption;
 use crate::error::TantivyError;
 use crate::indexer::index_writer::HEAP_SIZE_MIN;
-use crate::indexer::segment_updater::save_new_metas;
+use crate::indexer::segment_updater::{save_metas, save_new_metas};
 use crate::reader::IndexReader;
 use crate::reader::IndexReaderBuilder;
 use crate::schema::Field;
@@ -25,7 +33,6 @@ use crate::IndexWriter;
 use std::collections::HashSet;
 use std::fmt;
 
-#[cfg(feature = "mmap")]
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -36,24 +43,80 @@ fn load_metas(
 ) -> crate::Result<IndexMeta> {
     let meta_data = directory.atomic_read(&META_FILEPATH)?;
     let meta_string = String::from_utf8_lossy(&meta_data);
-    IndexMeta::deserialize(&meta_string, &inventory)
-        .map_err(|e| {
-            DataCorruption::new(
-                META_FILEPATH.to_path_buf(),
-                format!("Meta file cannot be deserialized. {:?}.", e),
-            )
-        })
-        .map_err(From::from)
+    let index_meta = IndexMeta::deserialize(&meta_string, inventory).map_err(|e| {
+        DataCorruption::new(
+            META_FILEPATH.to_path_buf(),
+            format!("Meta file cannot be deserialized. {:?}.", e),
+        )
+    })?;
+    check_index_format_version(&index_meta)?;
+    Ok(index_meta)
+}
+
+/// Refuses to open an index written with a more recent index format
+/// than the one supported by this version of tantivy.
+fn check_index_format_version(index_meta: &IndexMeta) -> crate::Result<()> {
+    if let Some(index_version) = index_meta.version.as_ref() {
+        if index_version.index_format_version() > crate::INDEX_FORMAT_VERSION {
+            return Err(TantivyError::IncompatibleIndex(
+                Incompatibility::IndexMismatch {
+                    library_version: crate::version().clone(),
+                    index_version: index_version.clone(),
+                },
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `new_schema` only appends new fields to `schema`.
+fn check_schema_evolution(schema: &Schema, new_schema: &Schema) -> crate::Result<()> {
+    let new_fields: Vec<_> = new_schema.fields().collect();
+    for (field, field_entry) in schema.fields() {
+        match new_fields.get(field.field_id() as usize) {
+            Some((_, new_field_entry)) if *new_field_entry == field_entry => {}
+            Some((_, new_field_entry)) if new_field_entry.name() == field_entry.name() => {
+                return Err(TantivyError::SchemaError(format!(
+                    "The type or the options of the field {:?} cannot be changed.",
+                    field_entry.name()
+                )));
+            }
+            _ => {
+                return Err(TantivyError::SchemaError(format!(
+                    "The field {:?} is missing, or not at the same position, in the new schema.",
+                    field_entry.name()
+                )));
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Search Index
+///
+/// Cloning an `Index` is cheap. The clones share the directory, the tokenizer
+/// registry, the search executor and the segment meta inventory:
+/// a tokenizer registered on a clone is visible from all of the others.
+/// Replacing the search executor with `set_multithread_executor` however only
+/// affects the `Index` on which it is called.
+///
+/// Each clone has its own copy of the schema: `update_schema` only updates the
+/// schema of the `Index` on which it is called. The `IndexWriter`s of the other
+/// clones fail to commit until the index is opened again.
+///
+/// Reading the index (`reader`, `searchable_segments`, ...) can be done from any
+/// number of clones at the same time. Writing the index is exclusive: among all of
+/// the clones, and all of the processes accessing the same directory, only one
+/// `IndexWriter` can exist at a time.
 #[derive(Clone)]
 pub struct Index {
     directory: ManagedDirectory,
     schema: Schema,
+    settings: IndexSettings,
     executor: Arc<Executor>,
     tokenizers: TokenizerManager,
     inventory: SegmentMetaInventory,
+    expiration_cache: ExpirationCache,
 }
 
 impl Index {
@@ -81,6 +144,15 @@ impl Index {
         Ok(())
     }
 
+    /// Replace the default single thread search executor pool
+    /// by an executor shared with other indexes.
+    ///
+    /// This makes it possible for several indexes to search
+    /// their segments in the same thread pool.
+    pub fn set_shared_multithread_executor(&mut self, shared_executor: Arc<Executor>) {
+        self.executor = shared_executor;
+    }
+
     /// Replace the default single thread search executor pool
     /// by a thread pool with a given number of threads.
     pub fn set_default_multithread_executor(&mut self) -> crate::Result<()> {
@@ -100,7 +172,7 @@ impl Index {
     /// Creates a new index in a given filepath.
     /// The index will use the `MMapDirectory`.
     ///
-    /// If a previous index was in this directory, then its meta file will be destroyed.
+    /// If a previous index was in this directory, it returns an `IndexAlreadyExists` error.
     #[cfg(feature = "mmap")]
     pub fn create_in_dir<P: AsRef<Path>>(
         directory_path: P,
@@ -113,6 +185,20 @@ impl Index {
         Index::create(mmap_directory, schema)
     }
 
+    /// Opens an index in a given filepath, or creates it if it does not exist.
+    /// The index will use the `MMapDirectory`.
+    ///
+    /// If an index exists but its schema does not match `schema`, a `SchemaError`
+    /// is returned.
+    #[cfg(feature = "mmap")]
+    pub fn open_or_create_in_dir<P: AsRef<Path>>(
+        directory_path: P,
+        schema: Schema,
+    ) -> crate::Result<Index> {
+        let mmap_directory = MmapDirectory::open(directory_path)?;
+        Index::open_or_create(mmap_directory, schema)
+    }
+
     /// Opens or creates a new index in the provided directory
     pub fn open_or_create<Dir: Directory>(dir: Dir, schema: Schema) -> crate::Result<Index> {
         if !Index::exists(&dir)? {
@@ -146,16 +232,35 @@ impl Index {
     ///
     /// If a directory previously existed, it will be erased.
     pub fn create<Dir: Directory>(dir: Dir, schema: Schema) -> crate::Result<Index> {
+        Index::create_with_settings(dir, schema, IndexSettings::default())
+    }
+
+    /// Creates a new index given an implementation of the trait `Directory`,
+    /// and the `IndexSettings` of the index.
+    ///
+    /// The settings are persisted with the index: they cannot be changed afterwards.
+    ///
+    /// If a directory previously existed, it will be erased.
+    pub fn create_with_settings<Dir: Directory>(
+        dir: Dir,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> crate::Result<Index> {
+        settings.check_schema(&schema)?;
         let directory = ManagedDirectory::wrap(dir)?;
-        Index::from_directory(directory, schema)
+        Index::from_directory(directory, schema, settings)
     }
 
     /// Create a new index from a directory.
     ///
     /// This will overwrite existing meta.json
-    fn from_directory(directory: ManagedDirectory, schema: Schema) -> crate::Result<Index> {
-        save_new_metas(schema.clone(), &directory)?;
-        let metas = IndexMeta::with_schema(schema);
+    fn from_directory(
+        directory: ManagedDirectory,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> crate::Result<Index> {
+        save_new_metas(schema.clone(), settings.clone(), &directory)?;
+        let metas = IndexMeta::with_schema_and_settings(schema, settings);
         Index::create_from_metas(directory, &metas, SegmentMetaInventory::default())
     }
 
@@ -169,17 +274,33 @@ impl Index {
         Ok(Index {
             directory,
             schema,
+            settings: metas.index_settings.clone(),
             tokenizers: TokenizerManager::default(),
             executor: Arc::new(Executor::single_thread()),
             inventory,
+            expiration_cache: ExpirationCache::default(),
         })
     }
 
+    /// Returns the settings of the index.
+    pub fn settings(&self) -> &IndexSettings {
+        &self.settings
+    }
+
+    pub(crate) fn expiration_cache(&self) -> &ExpirationCache {
+        &self.expiration_cache
+    }
+
     /// Accessor for the tokenizer manager.
     pub fn tokenizers(&self) -> &TokenizerManager {
         &self.tokenizers
     }
 
+    /// Replaces the tokenizer manager of the index.
+    pub(crate) fn set_tokenizers(&mut self, tokenizers: TokenizerManager) {
+        self.tokenizers = tokenizers;
+    }
+
     /// Helper to access the tokenizer associated to a specific field.
     pub fn tokenizer_for_field(&self, field: Field) -> crate::Result<TextAnalyzer> {
         let field_entry = self.schema.get_field_entry(field);
@@ -238,8 +359,13 @@ impl Index {
     /// As long as the `SegmentMeta` lives, the files associated with the
     /// `SegmentMeta` are guaranteed to not be garbage collected, regardless of
     /// whether the segment is recorded as part of the index or not.
+    ///
+    /// The `SegmentMeta` records the number of fields of the current schema:
+    /// the segment is expected to be written with this schema.
     pub fn new_segment_meta(&self, segment_id: SegmentId, max_doc: u32) -> SegmentMeta {
-        self.inventory.new_segment_meta(segment_id, max_doc)
+        self.inventory
+            .new_segment_meta(segment_id, max_doc)
+            .with_num_fields(self.schema.fields().count() as u32)
     }
 
     /// Open the index using the provided directory
@@ -250,11 +376,79 @@ impl Index {
         Index::create_from_metas(directory, &metas, inventory)
     }
 
+    /// Open the index using the provided directory, in read-only mode.
+    ///
+    /// Neither the index nor the resulting `IndexReader` will ever write
+    /// to the directory: no lock files are created, and attempting to
+    /// create an `IndexWriter` returns an error.
+    ///
+    /// This is suitable to serve an index from a read-only filesystem.
+    /// See [`ReadOnlyDirectory`](./directory/struct.ReadOnlyDirectory.html).
+    pub fn open_readonly<D: Directory>(directory: D) -> crate::Result<Index> {
+        Index::open(ReadOnlyDirectory::wrap(directory))
+    }
+
     /// Reads the index meta file from the directory.
     pub fn load_metas(&self) -> crate::Result<IndexMeta> {
         load_metas(self.directory(), &self.inventory)
     }
 
+    /// Returns the generations of the commit points available in the index,
+    /// in increasing order.
+    ///
+    /// Commit points are only kept if `IndexWriter::set_num_commit_points_to_keep`
+    /// was set.
+    pub fn list_commit_points(&self) -> Vec<u64> {
+        let mut generations: Vec<u64> = self
+            .directory
+            .list_managed_files()
+            .iter()
+            .filter_map(|path| commit_point_generation(path))
+            .collect();
+        generations.sort_unstable();
+        generations
+    }
+
+    /// Reads the `IndexMeta` of the commit point with the given generation.
+    ///
+    /// See `Index::list_commit_points`.
+    pub fn load_commit_point(&self, generation: u64) -> crate::Result<IndexMeta> {
+        let commit_point_path = commit_point_filepath(generation);
+        let meta_data = self.directory.atomic_read(&commit_point_path)?;
+        let meta_string = String::from_utf8_lossy(&meta_data);
+        let index_meta = IndexMeta::deserialize(&meta_string, &self.inventory).map_err(|e| {
+            DataCorruption::new(
+                commit_point_path,
+                format!("Commit point cannot be deserialized. {:?}.", e),
+            )
+        })?;
+        check_index_format_version(&index_meta)?;
+        Ok(index_meta)
+    }
+
+    /// Registers a callback that is called with the new `IndexMeta`
+    /// every time a new commit is detected.
+    ///
+    /// The commit may have been made by an `IndexWriter` living in a different
+    /// process, which makes it possible for long-lived readers to react to
+    /// new commits.
+    ///
+    /// The callback is registered for the lifetime of the returned `WatchHandle`.
+    /// See [`Directory::watch`](./directory/trait.Directory.html#tymethod.watch).
+    pub fn on_commit<F>(&self, callback: F) -> crate::Result<WatchHandle>
+    where
+        F: Fn(&IndexMeta) + Sync + Send + 'static,
+    {
+        let index = self.clone();
+        self.directory
+            .watch(WatchCallback::new(move || match index.load_metas() {
+                Ok(index_meta) => callback(&index_meta),
+                Err(err) => {
+                    error!("Failed to load metas after commit was detected. {:?}", err);
+                }
+            }))
+    }
+
     /// Open a new index writer. Attempts to acquire a lockfile.
     ///
     /// The lockfile should be deleted on drop, but it is possible
@@ -339,6 +533,41 @@ impl Index {
         self.schema.clone()
     }
 
+    /// Replaces the schema of the index with `schema`, in which new fields
+    /// were appended to the fields of the current schema.
+    ///
+    /// The existing segments are not reindexed: they simply do not have any value
+    /// for the new fields. Their documents do not match any term of the new fields,
+    /// and are read as having the value `0` in the new single-valued fast fields.
+    ///
+    /// `meta.json` is atomically replaced. The `IndexReader`s and the other clones of
+    /// this `Index` keep on using the previous schema: they need to be created again.
+    /// Until then, committing from the other clones fails with a `SchemaError`.
+    ///
+    /// # Errors
+    /// The fields of the current schema have to be kept in `schema`, with the same
+    /// name, the same type and the same options, in the same order: if they are not,
+    /// a `SchemaError` is returned.
+    /// The schema cannot be updated while an `IndexWriter` is alive: if the index lock
+    /// is already taken, a `LockFailure` error is returned.
+    pub fn update_schema(&mut self, schema: Schema) -> crate::Result<()> {
+        let _directory_lock = self
+            .directory
+            .acquire_lock(&INDEX_WRITER_LOCK)
+            .map_err(|err| {
+                TantivyError::LockFailure(
+                    err,
+                    Some("Failed to acquire index lock to update the schema.".to_string()),
+                )
+            })?;
+        let mut metas = self.load_metas()?;
+        check_schema_evolution(&metas.schema, &schema)?;
+        metas.schema = schema.clone();
+        save_metas(&metas, &self.directory)?;
+        self.schema = schema;
+        Ok(())
+    }
+
     /// Returns the list of segments that are searchable
     pub fn searchable_segments(&self) -> crate::Result<Vec<Segment>> {
         Ok(self
@@ -355,9 +584,7 @@ impl Index {
 
     /// Creates a new segment.
     pub fn new_segment(&self) -> Segment {
-        let segment_meta = self
-            .inventory
-            .new_segment_meta(SegmentId::generate_random(), 0);
+        let segment_meta = self.new_segment_meta(SegmentId::generate_random(), 0);
         self.segment(segment_meta)
     }
 
@@ -386,10 +613,179 @@ impl Index {
             .collect())
     }
 
+    /// Returns a `ReplicationSnapshot` of the last commit, listing the files
+    /// a replica needs to fetch to reproduce it.
+    ///
+    /// The files of the commit are protected from garbage collection
+    /// for as long as the snapshot is alive.
+    pub fn replication_snapshot(&self) -> crate::Result<ReplicationSnapshot> {
+        let meta = self.load_metas()?;
+        Ok(ReplicationSnapshot::new(self.clone(), meta))
+    }
+
+    /// Installs a commit received from a primary index on this replica index.
+    ///
+    /// `meta_json` is the serialized `IndexMeta` of the commit,
+    /// as returned by `ReplicationSnapshot::meta_json`.
+    /// `fetch_file` is only called for the segment files that are missing
+    /// in this index, and should return their content, as returned by
+    /// `ReplicationSnapshot::read_file`.
+    /// Files left incomplete by an installation that failed are detected by their
+    /// checksum, and fetched again.
+    ///
+    /// All of the files are written before `meta.json` is atomically replaced, so that
+    /// readers switch from the previous commit to the new one at once.
+    /// The files that are not used anymore are then garbage collected.
+    ///
+    /// # Errors
+    /// The replica cannot have an `IndexWriter` of its own: if the index lock is
+    /// already taken, a `LockFailure` error is returned.
+    /// The schema of the commit must be the same as the schema of this index.
+    pub fn install_replication_snapshot<F>(
+        &self,
+        meta_json: &[u8],
+        fetch_file: F,
+    ) -> crate::Result<()>
+    where
+        F: FnMut(&Path) -> crate::Result<Vec<u8>>,
+    {
+        let _directory_lock = self
+            .directory
+            .acquire_lock(&INDEX_WRITER_LOCK)
+            .map_err(|err| {
+                TantivyError::LockFailure(
+                    err,
+                    Some("Failed to acquire index lock to install the snapshot.".to_string()),
+                )
+            })?;
+        let meta_string = String::from_utf8_lossy(meta_json);
+        let meta = IndexMeta::deserialize(&meta_string, &self.inventory).map_err(|e| {
+            TantivyError::InvalidArgument(format!("Invalid snapshot meta. {:?}.", e))
+        })?;
+        check_index_format_version(&meta)?;
+        if meta.schema != self.schema() {
+            return Err(TantivyError::SchemaError(
+                "The schema of the snapshot does not match the schema of the index.".to_string(),
+            ));
+        }
+        install_files(&self.directory, &meta, meta_json, fetch_file)?;
+        let mut directory = self.directory.clone();
+        let inventory = &self.inventory;
+        directory.garbage_collect(|| {
+            let mut files: HashSet<PathBuf> = inventory
+                .all()
+                .into_iter()
+                .flat_map(|segment_meta| segment_meta.list_files())
+                .collect();
+            files.insert(META_FILEPATH.to_path_buf());
+            files
+        })?;
+        Ok(())
+    }
+
+    /// Copies the files of the last commit to `dest_directory`, producing
+    /// a consistent backup of the index, that can be opened with `Index::open`.
+    ///
+    /// Indexing does not need to be paused: the files of the commit are protected
+    /// from garbage collection until the copy is over, and the commits
+    /// happening in the meantime are not part of the backup.
+    ///
+    /// Files are copied through the `Directory` API. They are never hard-linked.
+    ///
+    /// Returns the `IndexMeta` of the commit that was copied.
+    ///
+    /// # Errors
+    /// If `dest_directory` already contains an index, returns an `IndexAlreadyExists` error.
+    pub fn snapshot<D: Directory>(&self, dest_directory: D) -> crate::Result<IndexMeta> {
+        if Index::exists(&dest_directory)? {
+            return Err(TantivyError::IndexAlreadyExists);
+        }
+        let snapshot = self.replication_snapshot()?;
+        let dest_directory = ManagedDirectory::wrap(dest_directory)?;
+        install_files(
+            &dest_directory,
+            snapshot.meta(),
+            &snapshot.meta_json()?,
+            |path| Ok(snapshot.read_file(path)?.as_slice().to_vec()),
+        )?;
+        Ok(snapshot.meta().clone())
+    }
+
     /// Returns the set of corrupted files
     pub fn validate_checksum(&self) -> crate::Result<HashSet<PathBuf>> {
         self.directory.list_damaged().map_err(Into::into)
     }
+
+    /// Checks the searchable segments of the index.
+    ///
+    /// The checksums of the segment files are verified, and the number of documents
+    /// of the postings, the field norms, the fast fields and the doc store are
+    /// checked against the number of documents of the segment.
+    ///
+    /// This reads the entire index: it is meant to be run after a crash,
+    /// or as a periodic health check.
+    pub fn validate(&self) -> crate::Result<ValidationReport> {
+        let mut report = ValidationReport::default();
+        for segment in self.searchable_segments()? {
+            for message in validate_segment(&segment) {
+                report.segment_errors.push(SegmentError {
+                    segment_id: segment.id(),
+                    message,
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    /// Validates the index, and quarantines the segments in which an inconsistency
+    /// was found, so that the rest of the index stays searchable.
+    ///
+    /// The quarantined segments are removed from `meta.json`, and their documents
+    /// are not searchable anymore. Their files are copied to files prefixed by
+    /// `.quarantine.`, which are not subject to garbage collection.
+    ///
+    /// Returns the report of the validation that preceded the repair.
+    ///
+    /// # Errors
+    /// The index cannot be repaired while an `IndexWriter` is alive: if the index lock
+    /// is already taken, a `LockFailure` error is returned.
+    pub fn repair(&self) -> crate::Result<ValidationReport> {
+        let _directory_lock = self
+            .directory
+            .acquire_lock(&INDEX_WRITER_LOCK)
+            .map_err(|err| {
+                TantivyError::LockFailure(
+                    err,
+                    Some("Failed to acquire index lock to repair the index.".to_string()),
+                )
+            })?;
+        let report = self.validate()?;
+        let damaged_segments = report.damaged_segments();
+        if damaged_segments.is_empty() {
+            return Ok(report);
+        }
+        let mut metas = self.load_metas()?;
+        for segment_meta in &metas.segments {
+            if !damaged_segments.contains(&segment_meta.id()) {
+                continue;
+            }
+            for path in segment_meta.list_files() {
+                // The files are copied as they are, footer included.
+                // Missing files are left out of the quarantine.
+                if let Ok(data) = self.directory.atomic_read(&path) {
+                    let quarantined_path = format!(".quarantine.{}", path.display());
+                    self.directory
+                        .atomic_write(Path::new(&quarantined_path), &data)?;
+                }
+            }
+            warn!("Segment {:?} was quarantined.", segment_meta.id());
+        }
+        metas
+            .segments
+            .retain(|segment_meta| !damaged_segments.contains(&segment_meta.id()));
+        save_metas(&metas, &self.directory)?;
+        Ok(report)
+    }
 }
 
 impl fmt::Debug for Index {
@@ -400,12 +796,18 @@ impl fmt::Debug for Index {
 
 #[cfg(test)]
 mod tests {
+    use crate::collector::TopDocs;
     use crate::directory::{RAMDirectory, WatchCallback};
+    use crate::query::TermQuery;
     use crate::schema::Field;
-    use crate::schema::{Schema, INDEXED, TEXT};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING, TEXT};
     use crate::IndexReader;
     use crate::ReloadPolicy;
-    use crate::{Directory, Index};
+    use crate::SegmentReader;
+    use crate::Term;
+    use crate::{Directory, Executor, Index, IndexSettings};
+    use futures::executor::block_on;
+    use std::sync::Arc;
 
     #[test]
     fn test_indexer_for_field() {
@@ -421,6 +823,274 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_index_clone_shares_resources() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_clone = index.clone();
+
+        index_clone
+            .tokenizers()
+            .register("custom", crate::tokenizer::RawTokenizer);
+        assert!(index.tokenizers().get("custom").is_some());
+
+        let mut index_writer = index.writer_for_tests()?;
+        assert!(matches!(
+            index_clone.writer_for_tests(),
+            Err(crate::TantivyError::LockFailure(..))
+        ));
+        index_writer.add_document(doc!(body => "hello"));
+        index_writer.commit()?;
+        assert_eq!(index_clone.reader()?.searcher().num_docs(), 1);
+
+        index_clone.set_multithread_executor(2)?;
+        assert!(matches!(
+            index_clone.search_executor(),
+            Executor::ThreadPool(_)
+        ));
+        assert!(matches!(index.search_executor(), Executor::SingleThread));
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_settings() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", crate::schema::STORED);
+        let schema = schema_builder.build();
+        let directory = RAMDirectory::create();
+        let settings = IndexSettings {
+            docstore_blocksize: 100,
+            ..IndexSettings::default()
+        };
+        let index = Index::create_with_settings(directory.clone(), schema, settings.clone())?;
+        assert_eq!(index.settings(), &settings);
+        let mut index_writer = index.writer_for_tests()?;
+        for _ in 0..10 {
+            index_writer.add_document(doc!(body => "a".repeat(60)));
+        }
+        index_writer.commit()?;
+
+        let index = Index::open(directory)?;
+        assert_eq!(index.settings(), &settings);
+        assert_eq!(index.load_metas()?.index_settings, settings);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.store_reader(0)?.block_checkpoints().count(), 5);
+        assert_eq!(
+            Index::create_in_ram(Schema::builder().build()).settings(),
+            &IndexSettings::default()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_sstable_term_dictionary() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::query::{FuzzyTermQuery, QueryParser, TermQuery};
+        use crate::termdict::TermDictionaryType;
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_with_settings(
+            RAMDirectory::create(),
+            schema_builder.build(),
+            IndexSettings {
+                term_dictionary_type: TermDictionaryType::SSTable,
+                ..IndexSettings::default()
+            },
+        )?;
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..300 {
+            index_writer.add_document(doc!(body => format!("apple term{} banana", i)));
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(body => "apricot"));
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let query_parser = QueryParser::for_index(&index, vec![body]);
+        let check_searches = |reader: &IndexReader| -> crate::Result<()> {
+            reader.reload()?;
+            let searcher = reader.searcher();
+            let term_query = TermQuery::new(
+                Term::from_field_text(body, "term150"),
+                IndexRecordOption::Basic,
+            );
+            assert_eq!(searcher.search(&term_query, &Count)?, 1);
+            let fuzzy_query = FuzzyTermQuery::new(Term::from_field_text(body, "aple"), 1, true);
+            assert_eq!(searcher.search(&fuzzy_query, &Count)?, 300);
+            let prefix_query = query_parser.parse_query("ap*")?;
+            assert_eq!(searcher.search(&prefix_query, &Count)?, 301);
+            Ok(())
+        };
+        check_searches(&reader)?;
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        block_on(index_writer.merge(&segment_ids))?;
+        index_writer.wait_merging_threads()?;
+        check_searches(&reader)?;
+        assert_eq!(reader.searcher().segment_readers().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_update_schema() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let directory = RAMDirectory::create();
+        let mut index = Index::create(directory.clone(), schema_builder.build())?;
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(title => "sea"));
+            index_writer.commit()?;
+        }
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let rank = schema_builder.add_u64_field("rank", FAST);
+        let new_schema = schema_builder.build();
+        index.update_schema(new_schema.clone())?;
+        assert!(index.schema() == new_schema);
+        assert!(Index::open(directory)?.schema() == new_schema);
+
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea", tag => "new", rank => 3u64));
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let tag_query = TermQuery::new(
+            Term::from_field_text(tag, "new"),
+            IndexRecordOption::WithFreqs,
+        );
+        assert_eq!(
+            searcher.search(&tag_query, &TopDocs::with_limit(10))?.len(),
+            1
+        );
+        let mut ranks: Vec<u64> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.fast_fields().u64(rank).unwrap().get(0))
+            .collect();
+        ranks.sort();
+        assert_eq!(ranks, vec![0, 3]);
+
+        // Old and new segments can be merged together.
+        let segment_ids = index.searchable_segment_ids()?;
+        block_on(index_writer.merge(&segment_ids))?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(
+            searcher.search(&tag_query, &TopDocs::with_limit(10))?.len(),
+            1
+        );
+        let rank_reader = searcher.segment_reader(0).fast_fields().u64(rank).unwrap();
+        let mut ranks = vec![rank_reader.get(0), rank_reader.get(1)];
+        ranks.sort();
+        assert_eq!(ranks, vec![0, 3]);
+
+        // The schema cannot be updated while an `IndexWriter` is alive.
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("tag", STRING);
+        schema_builder.add_u64_field("rank", FAST);
+        schema_builder.add_u64_field("other", FAST);
+        let other_schema = schema_builder.build();
+        assert!(matches!(
+            index.update_schema(other_schema.clone()),
+            Err(crate::TantivyError::LockFailure(..))
+        ));
+        drop(index_writer);
+        index.update_schema(other_schema)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_update_schema_other_clone_cannot_commit() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let directory = RAMDirectory::create();
+        let mut index = Index::create(directory.clone(), schema_builder.build())?;
+        let index_clone = index.clone();
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("rank", FAST);
+        let new_schema = schema_builder.build();
+        index.update_schema(new_schema.clone())?;
+
+        let mut index_writer = index_clone.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea"));
+        assert!(matches!(
+            index_writer.commit(),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        assert!(Index::open(directory)?.schema() == new_schema);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_missing_fast_field_data() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea"));
+        index_writer.commit()?;
+        drop(index_writer);
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("rank", FAST);
+        index.update_schema(schema_builder.build())?;
+
+        let segment_meta = index.searchable_segment_metas()?[0].clone();
+        assert_eq!(segment_meta.num_fields(), Some(1));
+        assert!(SegmentReader::open(&index.segment(segment_meta.clone())).is_ok());
+
+        // The fast field data of `rank` is missing from a segment written with `rank`:
+        // the segment is corrupted.
+        let corrupted_segment = index.segment(segment_meta.with_num_fields(2));
+        assert!(matches!(
+            SegmentReader::open(&corrupted_segment),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_update_schema_validation() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("rank", FAST);
+        let mut index = Index::create_in_ram(schema_builder.build());
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_i64_field("rank", FAST);
+        assert!(matches!(
+            index.update_schema(schema_builder.build()),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("rank", FAST);
+        schema_builder.add_text_field("title", TEXT);
+        assert!(matches!(
+            index.update_schema(schema_builder.build()),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        assert!(matches!(
+            index.update_schema(schema_builder.build()),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        assert_eq!(index.schema().fields().count(), 2);
+        Ok(())
+    }
+
     #[test]
     fn test_index_exists() {
         let directory = RAMDirectory::create();
@@ -466,6 +1136,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_open_index_with_newer_format_fails() {
+        let directory = RAMDirectory::create();
+        let index = Index::create(directory.clone(), throw_away_schema()).unwrap();
+        let meta_json = serde_json::to_string(&index.load_metas().unwrap())
+            .unwrap()
+            .replace(
+                &format!("\"index_format_version\":{}", crate::INDEX_FORMAT_VERSION),
+                &format!(
+                    "\"index_format_version\":{}",
+                    crate::INDEX_FORMAT_VERSION + 1
+                ),
+            );
+        directory
+            .atomic_write(&crate::core::META_FILEPATH, meta_json.as_bytes())
+            .unwrap();
+        assert!(matches!(
+            Index::open(directory),
+            Err(crate::TantivyError::IncompatibleIndex(_))
+        ));
+    }
+
     fn throw_away_schema() -> Schema {
         let mut schema_builder = Schema::builder();
         let _ = schema_builder.add_u64_field("num_likes", INDEXED);
@@ -486,6 +1178,133 @@ mod tests {
         test_index_on_commit_reload_policy_aux(field, &index, &reader);
     }
 
+    #[test]
+    fn test_index_default_reload_policy() {
+        assert_eq!(ReloadPolicy::default(), ReloadPolicy::OnCommit);
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let index = Index::create_in_ram(schema);
+        let reader = index.reader().unwrap();
+        assert_eq!(reader.searcher().num_docs(), 0);
+        test_index_on_commit_reload_policy_aux(field, &index, &reader);
+    }
+
+    #[test]
+    fn test_index_shared_multithread_executor() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::query::AllQuery;
+        let executor = Arc::new(Executor::multi_thread(2, "test-shared-search-")?);
+        let mut indexes = Vec::new();
+        for num_docs in 1u64..3u64 {
+            let schema = throw_away_schema();
+            let field = schema.get_field("num_likes").unwrap();
+            let mut index = Index::create_in_ram(schema);
+            index.set_shared_multithread_executor(executor.clone());
+            let mut writer = index.writer_for_tests()?;
+            for val in 0..num_docs {
+                writer.add_document(doc!(field=>val));
+                writer.commit()?;
+            }
+            indexes.push(index);
+        }
+        assert!(std::ptr::eq(
+            indexes[0].search_executor(),
+            indexes[1].search_executor()
+        ));
+        for (ord, index) in indexes.iter().enumerate() {
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.search(&AllQuery, &Count)?, ord + 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_reader_search_executor() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::query::AllQuery;
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let index = Index::create_in_ram(schema);
+        let mut writer = index.writer_for_tests()?;
+        writer.add_document(doc!(field=>1u64));
+        writer.commit()?;
+        writer.add_document(doc!(field=>2u64));
+        writer.commit()?;
+        let executor = Arc::new(Executor::multi_thread(2, "test-reader-search-")?);
+        let reader = index
+            .reader_builder()
+            .search_executor(executor.clone())
+            .try_into()?;
+        let searcher = reader.searcher();
+        assert!(std::ptr::eq(
+            searcher.index().search_executor(),
+            executor.as_ref()
+        ));
+        assert!(!std::ptr::eq(index.search_executor(), executor.as_ref()));
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_reader_warmer() -> crate::Result<()> {
+        use crate::Searcher;
+        use crate::TantivyError;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let index = Index::create_in_ram(schema);
+        let num_docs_warmed = Arc::new(AtomicUsize::new(0));
+        let num_docs_warmed_clone = num_docs_warmed.clone();
+        let num_searchers_warmed = Arc::new(AtomicUsize::new(0));
+        let num_searchers_warmed_clone = num_searchers_warmed.clone();
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .num_searchers(3)
+            .warmer(move |searcher: &Searcher| {
+                num_docs_warmed_clone.store(searcher.num_docs() as usize, Ordering::SeqCst);
+                num_searchers_warmed_clone.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .try_into()?;
+        assert_eq!(num_docs_warmed.load(Ordering::SeqCst), 0);
+        // Each of the searchers is warmed.
+        assert_eq!(num_searchers_warmed.load(Ordering::SeqCst), 3);
+        let mut writer = index.writer_for_tests()?;
+        writer.add_document(doc!(field=>1u64));
+        writer.commit()?;
+        reader.reload()?;
+        assert_eq!(num_docs_warmed.load(Ordering::SeqCst), 1);
+        assert_eq!(num_searchers_warmed.load(Ordering::SeqCst), 6);
+        assert_eq!(reader.searcher().num_docs(), 1);
+
+        let failing_warmer = |_: &Searcher| -> crate::Result<()> {
+            Err(TantivyError::InvalidArgument("warming failed".to_string()))
+        };
+        assert!(index
+            .reader_builder()
+            .warmer(failing_warmer)
+            .try_into()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_on_commit_callback() -> crate::Result<()> {
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let index = Index::create_in_ram(schema);
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        let _handle = index.on_commit(move |index_meta| {
+            let _ = sender.send(index_meta.opstamp);
+        })?;
+        let mut writer = index.writer_for_tests()?;
+        writer.add_document(doc!(field=>1u64));
+        let opstamp = writer.commit()?;
+        assert_eq!(receiver.recv().unwrap(), opstamp);
+        Ok(())
+    }
+
     #[cfg(feature = "mmap")]
     mod mmap_specific {
 
@@ -494,6 +1313,24 @@ mod tests {
         use std::path::PathBuf;
         use tempfile::TempDir;
 
+        #[test]
+        fn test_open_or_create_in_dir() -> crate::Result<()> {
+            let tempdir = TempDir::new().unwrap();
+            assert!(Index::open_in_dir(tempdir.path()).is_err());
+            let index = Index::open_or_create_in_dir(tempdir.path(), throw_away_schema())?;
+            assert!(index.schema() == throw_away_schema());
+            assert!(Index::open_or_create_in_dir(tempdir.path(), throw_away_schema()).is_ok());
+            assert!(matches!(
+                Index::create_in_dir(tempdir.path(), throw_away_schema()),
+                Err(crate::TantivyError::IndexAlreadyExists)
+            ));
+            assert!(matches!(
+                Index::open_or_create_in_dir(tempdir.path(), Schema::builder().build()),
+                Err(crate::TantivyError::SchemaError(_))
+            ));
+            Ok(())
+        }
+
         #[test]
         fn test_index_on_commit_reload_policy_mmap() {
             let schema = throw_away_schema();
@@ -624,4 +1461,137 @@ mod tests {
             mem_right_after_commit
         );
     }
+
+    #[test]
+    fn test_replication_snapshot() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let primary = Index::create_in_ram(schema.clone());
+        let replica = Index::create_in_ram(schema);
+        let replica_reader = replica
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer = primary.writer_for_tests()?;
+        let replicate = || -> crate::Result<usize> {
+            let snapshot = primary.replication_snapshot()?;
+            let mut num_fetched_files = 0;
+            replica.install_replication_snapshot(&snapshot.meta_json()?, |path| {
+                num_fetched_files += 1;
+                Ok(snapshot.read_file(path)?.as_slice().to_vec())
+            })?;
+            Ok(num_fetched_files)
+        };
+        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.add_document(doc!(text_field=>"c"));
+        index_writer.commit()?;
+        let num_files_first_segment = replicate()?;
+        assert!(num_files_first_segment > 0);
+        replica_reader.reload()?;
+        assert_eq!(replica_reader.searcher().num_docs(), 2);
+
+        // Only the files of the new segment are fetched.
+        index_writer.add_document(doc!(text_field=>"b"));
+        index_writer.commit()?;
+        assert_eq!(replicate()?, num_files_first_segment);
+        replica_reader.reload()?;
+        assert_eq!(replica_reader.searcher().num_docs(), 3);
+
+        // Nothing to fetch if the replica is already up to date.
+        assert_eq!(replicate()?, 0);
+
+        // Deleting a document only requires fetching the delete file.
+        index_writer.delete_term(Term::from_field_text(text_field, "a"));
+        index_writer.commit()?;
+        assert_eq!(replicate()?, 1);
+        replica_reader.reload()?;
+        assert_eq!(replica_reader.searcher().num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_replication_snapshot_rewrites_partial_files() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let primary = Index::create_in_ram(schema.clone());
+        let replica = Index::create_in_ram(schema);
+        let mut index_writer = primary.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.commit()?;
+        let snapshot = primary.replication_snapshot()?;
+        let partial_file = snapshot.files().next().unwrap().to_path_buf();
+        let data = snapshot.read_file(&partial_file)?;
+        // Simulates an installation that failed while writing the file.
+        replica
+            .directory()
+            .atomic_write(&partial_file, &data.as_slice()[..data.len() / 2])?;
+        let mut fetched_files = Vec::new();
+        replica.install_replication_snapshot(&snapshot.meta_json()?, |path| {
+            fetched_files.push(path.to_path_buf());
+            Ok(snapshot.read_file(path)?.as_slice().to_vec())
+        })?;
+        assert!(fetched_files.contains(&partial_file));
+        assert!(replica.validate_checksum()?.is_empty());
+        let searcher = replica.reader()?.searcher();
+        assert_eq!(
+            searcher.doc_freq(&Term::from_field_text(text_field, "a"))?,
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_replication_snapshot_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let primary = Index::create_in_ram(schema_builder.build());
+        let snapshot = primary.replication_snapshot()?;
+        assert!(snapshot
+            .read_file(std::path::Path::new("meta.json"))
+            .is_err());
+        let meta_json = snapshot.meta_json()?;
+
+        let mut other_schema_builder = Schema::builder();
+        other_schema_builder.add_text_field("title", TEXT);
+        let other_index = Index::create_in_ram(other_schema_builder.build());
+        assert!(matches!(
+            other_index.install_replication_snapshot(&meta_json, |_| Ok(Vec::new())),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+
+        let replica = Index::create_in_ram(primary.schema());
+        let _index_writer = replica.writer_for_tests()?;
+        assert!(matches!(
+            replica.install_replication_snapshot(&meta_json, |_| Ok(Vec::new())),
+            Err(crate::TantivyError::LockFailure(_, _))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_snapshot() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"b"));
+
+        let backup_directory = RAMDirectory::create();
+        let backup_meta = index.snapshot(backup_directory.clone())?;
+        assert_eq!(backup_meta.segments.len(), 1);
+        index_writer.commit()?;
+
+        let backup_index = Index::open(backup_directory.clone())?;
+        assert!(backup_index.schema() == index.schema());
+        assert_eq!(backup_index.reader()?.searcher().num_docs(), 1);
+        assert!(matches!(
+            index.snapshot(backup_directory),
+            Err(crate::TantivyError::IndexAlreadyExists)
+        ));
+        Ok(())
+    }
 }
@@ -1,10 +1,13 @@
 use super::SegmentComponent;
 use crate::core::SegmentId;
-use crate::schema::Schema;
+use crate::schema::{Cardinality, Field, FieldType, Schema};
+use crate::store::DEFAULT_BLOCK_SIZE;
+use crate::termdict::TermDictionaryType;
 use crate::Opstamp;
+use crate::Version;
 use census::{Inventory, TrackedObject};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 
@@ -14,6 +17,85 @@ struct DeleteMeta {
     opstamp: Opstamp,
 }
 
+/// Settings of an index, chosen when the index is created.
+///
+/// They are persisted in the `meta.json` file, and apply to all of the
+/// segments written by the index, including the segments resulting from a merge.
+///
+/// See `Index::create_with_settings`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSettings {
+    /// Size of the uncompressed blocks of the doc store, in bytes.
+    ///
+    /// Larger blocks compress better, but retrieving a stored document
+    /// requires to decompress its entire block.
+    #[serde(default = "default_docstore_blocksize")]
+    pub docstore_blocksize: usize,
+    /// Name of the date field holding the expiration date of the documents.
+    ///
+    /// The field has to be a single-valued fast date field.
+    /// Once their expiration date is past, documents are excluded from search
+    /// like deleted documents, and they are physically removed when their segment
+    /// is merged.
+    ///
+    /// Expiration is evaluated when the `IndexReader` is reloaded, not when a query
+    /// is executed: a searcher keeps returning the documents that expired after it was
+    /// acquired. The expiration dates of a segment are only scanned again once the
+    /// earliest of its pending expiration dates is past.
+    ///
+    /// Documents without an expiration date never expire. Since the fast field holds
+    /// the Unix epoch for them, a document expiring at the Unix epoch never expires either.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_field: Option<String>,
+    /// Data structure of the term dictionaries of the new segments.
+    ///
+    /// Segments written with a different term dictionary type remain readable,
+    /// and are converted when they are merged.
+    #[serde(default)]
+    pub term_dictionary_type: TermDictionaryType,
+}
+
+fn default_docstore_blocksize() -> usize {
+    DEFAULT_BLOCK_SIZE
+}
+
+impl Default for IndexSettings {
+    fn default() -> IndexSettings {
+        IndexSettings {
+            docstore_blocksize: default_docstore_blocksize(),
+            expiration_field: None,
+            term_dictionary_type: TermDictionaryType::default(),
+        }
+    }
+}
+
+impl IndexSettings {
+    /// Checks that the settings are consistent with the schema of the index.
+    pub(crate) fn check_schema(&self, schema: &Schema) -> crate::Result<()> {
+        if let Some(field_name) = self.expiration_field.as_ref() {
+            let field = schema.get_field(field_name).ok_or_else(|| {
+                crate::TantivyError::SchemaError(format!(
+                    "The expiration field {:?} does not exist.",
+                    field_name
+                ))
+            })?;
+            let is_single_valued_fast_date = match schema.get_field_entry(field).field_type() {
+                FieldType::Date(options) => {
+                    options.get_fastfield_cardinality() == Some(Cardinality::SingleValue)
+                }
+                _ => false,
+            };
+            if !is_single_valued_fast_date {
+                return Err(crate::TantivyError::SchemaError(format!(
+                    "The expiration field {:?} has to be a single-valued fast date field.",
+                    field_name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct SegmentMetaInventory {
     inventory: Inventory<InnerSegmentMeta>,
@@ -34,6 +116,8 @@ impl SegmentMetaInventory {
             segment_id,
             max_doc,
             deletes: None,
+            attributes: BTreeMap::new(),
+            num_fields: None,
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
@@ -160,6 +244,56 @@ impl SegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc,
             deletes: None,
+            attributes: inner_meta.attributes.clone(),
+            num_fields: inner_meta.num_fields,
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the user attributes of the segment.
+    ///
+    /// See `IndexWriter::set_segment_attributes`.
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.tracked.attributes
+    }
+
+    /// Replaces the user attributes of the segment.
+    pub(crate) fn with_attributes(self, attributes: BTreeMap<String, String>) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            attributes,
+            num_fields: inner_meta.num_fields,
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the number of fields of the schema the segment was written with,
+    /// or `None` if the segment meta does not record it.
+    pub(crate) fn num_fields(&self) -> Option<u32> {
+        self.tracked.num_fields
+    }
+
+    /// Returns true iff `field` belongs to the schema the segment was written with.
+    ///
+    /// The fields appended to the schema afterwards by `Index::update_schema` do not.
+    /// Segment metas that do not record their number of fields are assumed to contain
+    /// all of the fields.
+    pub(crate) fn contains_field(&self, field: Field) -> bool {
+        self.num_fields()
+            .map(|num_fields| field.field_id() < num_fields)
+            .unwrap_or(true)
+    }
+
+    /// Records the number of fields of the schema the segment is written with.
+    pub(crate) fn with_num_fields(self, num_fields: u32) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            attributes: inner_meta.attributes.clone(),
+            num_fields: Some(num_fields),
         });
         SegmentMeta { tracked }
     }
@@ -174,6 +308,8 @@ impl SegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc: inner_meta.max_doc,
             deletes: Some(delete_meta),
+            attributes: inner_meta.attributes.clone(),
+            num_fields: inner_meta.num_fields,
         });
         SegmentMeta { tracked }
     }
@@ -184,6 +320,26 @@ struct InnerSegmentMeta {
     segment_id: SegmentId,
     max_doc: u32,
     deletes: Option<DeleteMeta>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    num_fields: Option<u32>,
+}
+
+/// Returns the user attributes shared by all of the given segments,
+/// with the same value.
+///
+/// These are the attributes of the segment resulting from their merge.
+pub(crate) fn common_attributes(segment_metas: &[SegmentMeta]) -> BTreeMap<String, String> {
+    let mut segment_metas_it = segment_metas.iter();
+    let mut attributes = match segment_metas_it.next() {
+        Some(segment_meta) => segment_meta.attributes().clone(),
+        None => return BTreeMap::new(),
+    };
+    for segment_meta in segment_metas_it {
+        attributes.retain(|key, value| segment_meta.attributes().get(key) == Some(value));
+    }
+    attributes
 }
 
 impl InnerSegmentMeta {
@@ -201,6 +357,7 @@ impl InnerSegmentMeta {
 /// * the searchable segments,
 /// * the index `docstamp`
 /// * the schema
+/// * the version of tantivy that wrote it
 ///
 #[derive(Clone, Serialize)]
 pub struct IndexMeta {
@@ -208,8 +365,20 @@ pub struct IndexMeta {
     pub segments: Vec<SegmentMeta>,
     /// Index `Schema`
     pub schema: Schema,
+    /// Settings of the index.
+    pub index_settings: IndexSettings,
     /// Opstamp associated to the last `commit` operation.
     pub opstamp: Opstamp,
+    /// Generation of the commit point.
+    ///
+    /// It is incremented by one every time the `meta.json` file is written,
+    /// that is on every commit and after every merge.
+    pub generation: u64,
+    /// Number of commit points kept in the index.
+    ///
+    /// See `IndexWriter::set_num_commit_points_to_keep`.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub num_commit_points_to_keep: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     /// Payload associated to the last commit.
     ///
@@ -217,15 +386,33 @@ pub struct IndexMeta {
     /// to help identify this commit.
     /// This payload is entirely unused by tantivy.
     pub payload: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Version of tantivy, and index format, that wrote this `IndexMeta`.
+    ///
+    /// Indexes created before the version was recorded in the `meta.json` file
+    /// have no version.
+    pub version: Option<Version>,
+}
+
+fn is_zero(val: &usize) -> bool {
+    *val == 0
 }
 
 #[derive(Deserialize)]
 struct UntrackedIndexMeta {
     pub segments: Vec<InnerSegmentMeta>,
     pub schema: Schema,
+    #[serde(default)]
+    pub index_settings: IndexSettings,
     pub opstamp: Opstamp,
+    #[serde(default)]
+    pub generation: u64,
+    #[serde(default)]
+    pub num_commit_points_to_keep: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<String>,
+    #[serde(default)]
+    pub version: Option<Version>,
 }
 
 impl UntrackedIndexMeta {
@@ -237,8 +424,12 @@ impl UntrackedIndexMeta {
                 .map(|inner_seg_meta| inner_seg_meta.track(inventory))
                 .collect::<Vec<SegmentMeta>>(),
             schema: self.schema,
+            index_settings: self.index_settings,
             opstamp: self.opstamp,
+            generation: self.generation,
+            num_commit_points_to_keep: self.num_commit_points_to_keep,
             payload: self.payload,
+            version: self.version,
         }
     }
 }
@@ -250,14 +441,31 @@ impl IndexMeta {
     /// This new index does not contains any segments.
     /// Opstamp will the value `0u64`.
     pub fn with_schema(schema: Schema) -> IndexMeta {
+        IndexMeta::with_schema_and_settings(schema, IndexSettings::default())
+    }
+
+    /// Create an `IndexMeta` object representing a brand new `Index`
+    /// with the given schema and settings.
+    pub fn with_schema_and_settings(schema: Schema, index_settings: IndexSettings) -> IndexMeta {
         IndexMeta {
             segments: vec![],
             schema,
+            index_settings,
             opstamp: 0u64,
+            generation: 0u64,
+            num_commit_points_to_keep: 0,
             payload: None,
+            version: Some(crate::version().clone()),
         }
     }
 
+    /// Returns the index format version of this `IndexMeta`, if it is known.
+    pub fn index_format_version(&self) -> Option<u32> {
+        self.version
+            .as_ref()
+            .map(|version| version.index_format_version)
+    }
+
     pub(crate) fn deserialize(
         meta_json: &str,
         inventory: &SegmentMetaInventory,
@@ -281,7 +489,7 @@ impl fmt::Debug for IndexMeta {
 #[cfg(test)]
 mod tests {
 
-    use super::IndexMeta;
+    use super::{IndexMeta, IndexSettings, SegmentMetaInventory};
     use crate::schema::{Schema, TEXT};
     use serde_json;
 
@@ -295,13 +503,40 @@ mod tests {
         let index_metas = IndexMeta {
             segments: Vec::new(),
             schema,
+            index_settings: IndexSettings::default(),
             opstamp: 0u64,
+            generation: 0u64,
+            num_commit_points_to_keep: 0,
             payload: None,
+            version: None,
         };
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
         assert_eq!(
             json,
-            r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"opstamp":0}"#
+            r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"index_settings":{"docstore_blocksize":16384,"term_dictionary_type":"fst"},"opstamp":0,"generation":0}"#
+        );
+    }
+
+    #[test]
+    fn test_serialize_deserialize_metas_version() {
+        let index_metas = IndexMeta::with_schema(Schema::builder().build());
+        let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
+        let deserialized = IndexMeta::deserialize(&json, &SegmentMetaInventory::default()).unwrap();
+        assert_eq!(deserialized.version.as_ref(), Some(crate::version()));
+        assert_eq!(
+            deserialized.index_format_version(),
+            Some(crate::INDEX_FORMAT_VERSION)
         );
     }
+
+    #[test]
+    fn test_deserialize_metas_without_version() {
+        let json = r#"{"segments":[],"schema":[],"opstamp":0}"#;
+        let index_metas = IndexMeta::deserialize(json, &SegmentMetaInventory::default()).unwrap();
+        assert!(index_metas.version.is_none());
+        assert_eq!(index_metas.generation, 0);
+        assert_eq!(index_metas.num_commit_points_to_keep, 0);
+        assert_eq!(index_metas.index_settings, IndexSettings::default());
+        assert!(index_metas.index_format_version().is_none());
+    }
 }
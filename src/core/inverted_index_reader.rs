@@ -65,6 +65,14 @@ impl InvertedIndexReader {
         }
     }
 
+    /// Returns the indexing options of the field.
+    ///
+    /// Postings requested with more information than what was indexed
+    /// only contain the information available.
+    pub fn record_option(&self) -> IndexRecordOption {
+        self.record_option
+    }
+
     /// Returns the term info associated with the term.
     pub fn get_term_info(&self, term: &Term) -> io::Result<Option<TermInfo>> {
         self.termdict.get(term.value_bytes())
@@ -143,7 +151,8 @@ impl InvertedIndexReader {
     ) -> io::Result<SegmentPostings> {
         let block_postings = self.read_block_postings_from_terminfo(term_info, option)?;
         let position_stream = {
-            if option.has_positions() {
+            // Positions may be requested for a field that does not index them.
+            if option.has_positions() && self.record_option.has_positions() {
                 let position_reader = self.positions_file_slice.clone();
                 let skip_reader = self.positions_idx_file_slice.clone();
                 let position_reader =
@@ -195,6 +204,34 @@ impl InvertedIndexReader {
             .transpose()
     }
 
+    /// Returns the total number of occurrences of the term in the segment,
+    /// i.e. the sum of its term frequencies over all documents
+    /// (including deleted documents).
+    ///
+    /// If the field was indexed without term frequencies, every document
+    /// is considered to contain the term once.
+    pub fn total_term_freq(&self, term: &Term) -> io::Result<u64> {
+        let mut block_postings = if let Some(block_postings) =
+            self.read_block_postings(term, IndexRecordOption::WithFreqs)?
+        {
+            block_postings
+        } else {
+            return Ok(0u64);
+        };
+        let mut total_term_freq = 0u64;
+        loop {
+            let num_docs = block_postings.docs().len();
+            if num_docs == 0 {
+                break;
+            }
+            for idx in 0..num_docs {
+                total_term_freq += u64::from(block_postings.freq(idx));
+            }
+            block_postings.advance();
+        }
+        Ok(total_term_freq)
+    }
+
     /// Returns the number of documents containing the term.
     pub fn doc_freq(&self, term: &Term) -> io::Result<u32> {
         Ok(self
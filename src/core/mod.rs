@@ -1,26 +1,40 @@
 mod executor;
+mod export;
+mod field_terms;
 pub mod index;
 mod index_meta;
 mod inverted_index_reader;
+mod multi_searcher;
+mod replication;
+mod result_cache;
+mod search_profile;
 pub mod searcher;
 mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_reader;
+mod validation;
 
 pub use self::executor::Executor;
+pub use self::field_terms::{FieldTermStreamer, FieldTerms};
 pub use self::index::Index;
-pub use self::index_meta::{IndexMeta, SegmentMeta, SegmentMetaInventory};
+pub(crate) use self::index_meta::common_attributes;
+pub use self::index_meta::{IndexMeta, IndexSettings, SegmentMeta, SegmentMetaInventory};
 pub use self::inverted_index_reader::InvertedIndexReader;
+pub use self::multi_searcher::MultiSearcher;
+pub use self::replication::ReplicationSnapshot;
+pub use self::result_cache::QueryResultCache;
+pub use self::search_profile::{SearchProfile, SegmentSearchProfile};
 pub use self::searcher::Searcher;
 pub use self::segment::Segment;
 pub use self::segment::SerializableSegment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::SegmentReader;
+pub use self::validation::{SegmentError, ValidationReport};
 
 use once_cell::sync::Lazy;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// The meta file contains all the information about the list of segments and the schema
 /// of the index.
@@ -32,3 +46,19 @@ pub static META_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("meta.jso
 /// Removing this file is safe, but will prevent the garbage collection of all of the file that
 /// are currently in the directory
 pub static MANAGED_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new(".managed.json"));
+
+/// Returns the path of the file holding the `IndexMeta` of the commit point
+/// with the given generation.
+pub(crate) fn commit_point_filepath(generation: u64) -> PathBuf {
+    PathBuf::from(format!("meta.{}.json", generation))
+}
+
+/// Returns the generation of a commit point file, or `None` if the
+/// path is not a commit point file.
+pub(crate) fn commit_point_generation(path: &Path) -> Option<u64> {
+    path.to_str()?
+        .strip_prefix("meta.")?
+        .strip_suffix(".json")?
+        .parse()
+        .ok()
+}
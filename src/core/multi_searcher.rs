@@ -0,0 +1,245 @@
+use crate::collector::Collector;
+use crate::core::Searcher;
+use crate::query::Query;
+use crate::reader::LeasedItem;
+use crate::schema::{Document, Schema};
+use crate::DocAddress;
+use crate::SegmentLocalId;
+use crate::TantivyError;
+
+/// Searches several indexes (or shards) sharing the same schema
+/// as if they were a single index.
+///
+/// The segments of all of the searchers are numbered consecutively: the
+/// `DocAddress` returned by a search over a `MultiSearcher` uses these
+/// global segment ordinals. `MultiSearcher::doc` fetches the document from the
+/// right shard, and `MultiSearcher::shard_doc_address` translates the
+/// address into an address relative to the searcher of the shard.
+///
+/// The fruits of all of the segments are merged by the collector,
+/// exactly like for a regular search: `TopDocs` returns the best hits
+/// across all shards, `Count` sums the counts, `FacetCollector` sums the
+/// facet counts, etc.
+///
+/// The scoring statistics (e.g. BM25's idf) are computed independently for each
+/// shard. Scores are only comparable across shards if the terms are
+/// distributed similarly in all of them.
+///
+/// ```rust
+/// use tantivy::collector::{Count, TopDocs};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, STORED, TEXT};
+/// use tantivy::{doc, Index, MultiSearcher};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// let schema = schema_builder.build();
+///
+/// let mut searchers = Vec::new();
+/// for titles in &[&["The Old Man and the Sea"][..], &["Of Mice and Men", "The Sea Wolf"][..]] {
+///     let index = Index::create_in_ram(schema.clone());
+///     let mut index_writer = index.writer(3_000_000)?;
+///     for &text in titles.iter() {
+///         index_writer.add_document(doc!(title => text));
+///     }
+///     index_writer.commit()?;
+///     searchers.push(index.reader()?.searcher());
+/// }
+/// let multi_searcher = MultiSearcher::new(searchers)?;
+///
+/// let query = QueryParser::for_index(multi_searcher.searchers()[0].index(), vec![title])
+///     .parse_query("sea")?;
+/// let (count, top_docs) = multi_searcher.search(&query, &(Count, TopDocs::with_limit(10)))?;
+/// assert_eq!(count, 2);
+/// for (_score, doc_address) in top_docs {
+///     let doc = multi_searcher.doc(doc_address)?;
+///     assert!(doc.get_first(title).unwrap().text().unwrap().contains("Sea"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiSearcher {
+    searchers: Vec<LeasedItem<Searcher>>,
+}
+
+impl MultiSearcher {
+    /// Creates a `MultiSearcher` over the given searchers.
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if no searcher is given, and a `SchemaError`
+    /// if the searchers do not all have the same schema.
+    pub fn new(searchers: Vec<LeasedItem<Searcher>>) -> crate::Result<MultiSearcher> {
+        let first_searcher = searchers.first().ok_or_else(|| {
+            TantivyError::InvalidArgument("A MultiSearcher requires at least one searcher.".into())
+        })?;
+        if searchers
+            .iter()
+            .any(|searcher| searcher.schema() != first_searcher.schema())
+        {
+            return Err(TantivyError::SchemaError(
+                "All of the searchers of a MultiSearcher must have the same schema.".to_string(),
+            ));
+        }
+        Ok(MultiSearcher { searchers })
+    }
+
+    /// Returns the searchers, in the order they were given.
+    pub fn searchers(&self) -> &[LeasedItem<Searcher>] {
+        &self.searchers
+    }
+
+    /// Access the schema shared by all of the searchers.
+    pub fn schema(&self) -> &Schema {
+        self.searchers[0].schema()
+    }
+
+    /// Returns the overall number of documents in all of the searchers.
+    pub fn num_docs(&self) -> u64 {
+        self.searchers
+            .iter()
+            .map(|searcher| searcher.num_docs())
+            .sum()
+    }
+
+    /// Translates a `DocAddress` returned by a search over the `MultiSearcher`
+    /// into the ordinal of its searcher, and its `DocAddress` within this searcher.
+    ///
+    /// An `InvalidArgument` error is returned if the segment ordinal is out of bounds.
+    pub fn shard_doc_address(&self, doc_address: DocAddress) -> crate::Result<(usize, DocAddress)> {
+        let DocAddress(segment_ord, doc_id) = doc_address;
+        let mut local_segment_ord = segment_ord;
+        for (shard_ord, searcher) in self.searchers.iter().enumerate() {
+            let num_segments = searcher.segment_readers().len() as SegmentLocalId;
+            if local_segment_ord < num_segments {
+                return Ok((shard_ord, DocAddress(local_segment_ord, doc_id)));
+            }
+            local_segment_ord -= num_segments;
+        }
+        Err(TantivyError::InvalidArgument(format!(
+            "Segment ordinal {} is out of bounds.",
+            segment_ord
+        )))
+    }
+
+    /// Fetches a document given a `DocAddress` returned by a search
+    /// over the `MultiSearcher`.
+    pub fn doc(&self, doc_address: DocAddress) -> crate::Result<Document> {
+        let (shard_ord, shard_doc_address) = self.shard_doc_address(doc_address)?;
+        self.searchers[shard_ord].doc(shard_doc_address)
+    }
+
+    /// Runs a query on all of the segments of all of the searchers, and
+    /// merges the results with the collector.
+    ///
+    /// The search is dispatched over the search executor of the index
+    /// of the first searcher.
+    pub fn search<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let scoring_enabled = collector.requires_scoring();
+        let weights = self
+            .searchers
+            .iter()
+            .map(|searcher| query.weight(searcher, scoring_enabled))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let segments = self
+            .searchers
+            .iter()
+            .zip(&weights)
+            .flat_map(|(searcher, weight)| {
+                searcher
+                    .segment_readers()
+                    .iter()
+                    .map(move |segment_reader| (weight, segment_reader))
+            })
+            .enumerate();
+        let fruits = self.searchers[0].index().search_executor().map(
+            |(segment_ord, (weight, segment_reader))| {
+                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+            },
+            segments,
+        )?;
+        collector.merge_fruits(fruits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiSearcher;
+    use crate::collector::{Count, FacetCollector, TopDocs};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{Facet, IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_multi_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let facet = schema_builder.add_facet_field("facet");
+        let schema = schema_builder.build();
+        let shards: &[&[(&str, &str)]] = &[
+            &[("a b", "/x"), ("a", "/y")],
+            &[],
+            &[("b", "/x"), ("a a a", "/x")],
+        ];
+        let mut searchers = Vec::new();
+        for shard in shards {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer = index.writer_for_tests()?;
+            for &(text_val, facet_val) in shard.iter() {
+                index_writer.add_document(doc!(text => text_val, facet => Facet::from(facet_val)));
+            }
+            index_writer.commit()?;
+            searchers.push(index.reader()?.searcher());
+        }
+        let multi_searcher = MultiSearcher::new(searchers)?;
+        assert_eq!(multi_searcher.num_docs(), 4);
+        assert_eq!(multi_searcher.search(&AllQuery, &Count)?, 4);
+
+        let mut facet_collector = FacetCollector::for_field(facet);
+        facet_collector.add_facet("/");
+        let facet_counts = multi_searcher.search(&AllQuery, &facet_collector)?;
+        let counts: Vec<(String, u64)> = facet_counts
+            .get("/")
+            .map(|(facet, count)| (facet.to_string(), count))
+            .collect();
+        assert_eq!(counts, vec![("/x".to_string(), 3), ("/y".to_string(), 1)]);
+
+        let query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_docs = multi_searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 3);
+        let best_doc_address = top_docs[0].1;
+        assert_eq!(best_doc_address, DocAddress(1, 1));
+        assert_eq!(
+            multi_searcher.shard_doc_address(best_doc_address)?,
+            (2, DocAddress(0, 1))
+        );
+        let best_doc = multi_searcher.doc(best_doc_address)?;
+        assert_eq!(best_doc.get_first(text).unwrap().text(), Some("a a a"));
+        assert!(multi_searcher.doc(DocAddress(2, 0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_searcher_errors() -> crate::Result<()> {
+        assert!(MultiSearcher::new(Vec::new()).is_err());
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let other_index = Index::create_in_ram(schema_builder.build());
+        let searchers = vec![index.reader()?.searcher(), other_index.reader()?.searcher()];
+        assert!(matches!(
+            MultiSearcher::new(searchers),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
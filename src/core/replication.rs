@@ -0,0 +1,133 @@
+use crate::core::{IndexMeta, SegmentComponent, META_FILEPATH};
+use crate::directory::error::DeleteError;
+use crate::directory::{Directory, ManagedDirectory, OwnedBytes, TerminatingWrite};
+use crate::error::TantivyError;
+use crate::Index;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Returns the files of the segments of a commit.
+///
+/// Contrary to `SegmentMeta::list_files`, the delete file is
+/// only listed if the segment has deletes.
+fn commit_files(meta: &IndexMeta) -> BTreeSet<PathBuf> {
+    let mut files = BTreeSet::new();
+    for segment_meta in &meta.segments {
+        for &component in SegmentComponent::iterator() {
+            if let SegmentComponent::DELETE = component {
+                if !segment_meta.has_deletes() {
+                    continue;
+                }
+            }
+            files.insert(segment_meta.relative_path(component));
+        }
+    }
+    files
+}
+
+/// A `ReplicationSnapshot` describes a commit of an index, as the
+/// set of files required to open it and its serialized `IndexMeta`.
+///
+/// It is obtained on the primary with `Index::replication_snapshot`, and installed
+/// on a replica with `Index::install_replication_snapshot`.
+///
+/// Segment files are immutable, so a replica only needs to fetch
+/// the files it does not already have.
+///
+/// As long as the snapshot is alive, its files are protected from
+/// garbage collection on the primary.
+pub struct ReplicationSnapshot {
+    index: Index,
+    meta: IndexMeta,
+    files: BTreeSet<PathBuf>,
+}
+
+impl ReplicationSnapshot {
+    pub(crate) fn new(index: Index, meta: IndexMeta) -> ReplicationSnapshot {
+        let files = commit_files(&meta);
+        ReplicationSnapshot { index, meta, files }
+    }
+
+    /// Returns the `IndexMeta` of the commit.
+    pub fn meta(&self) -> &IndexMeta {
+        &self.meta
+    }
+
+    /// Returns the `IndexMeta` of the commit, serialized as it is
+    /// in the `meta.json` file.
+    pub fn meta_json(&self) -> crate::Result<Vec<u8>> {
+        let mut buffer = serde_json::to_vec_pretty(&self.meta)?;
+        writeln!(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Returns the paths of the segment files of the commit, in increasing order.
+    ///
+    /// `meta.json` is not part of this list.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(PathBuf::as_path)
+    }
+
+    /// Reads the content of one of the files of the snapshot.
+    ///
+    /// The footer of the file is not part of the returned bytes.
+    /// It is added back when the file is installed on the replica.
+    ///
+    /// # Errors
+    /// Returns an `InvalidArgument` error if the file is not part of the snapshot.
+    pub fn read_file(&self, path: &Path) -> crate::Result<OwnedBytes> {
+        if !self.files.contains(path) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "{:?} is not part of the snapshot",
+                path
+            )));
+        }
+        let file_slice = self.index.directory().open_read(path)?;
+        Ok(file_slice.read_bytes()?)
+    }
+}
+
+/// Returns true iff the file exists and is complete.
+///
+/// A file may have been partially written by an installation that failed,
+/// in which case its footer is missing or its checksum does not match.
+fn is_installed(directory: &ManagedDirectory, path: &Path) -> crate::Result<bool> {
+    if !directory.exists(path)? {
+        return Ok(false);
+    }
+    Ok(directory.validate_checksum(path).unwrap_or(false))
+}
+
+/// Writes the missing files of the commit described by `meta` in the directory,
+/// and then atomically replaces `meta.json`.
+///
+/// Files that were only partially written are deleted and written again.
+pub(crate) fn install_files<F>(
+    directory: &ManagedDirectory,
+    meta: &IndexMeta,
+    meta_json: &[u8],
+    mut fetch_file: F,
+) -> crate::Result<()>
+where
+    F: FnMut(&Path) -> crate::Result<Vec<u8>>,
+{
+    let files = commit_files(meta);
+    for path in &files {
+        if is_installed(directory, path)? {
+            continue;
+        }
+        match directory.delete(path) {
+            Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => {}
+            Err(DeleteError::IOError { io_error, .. }) => return Err(io_error.into()),
+        }
+        let data = fetch_file(path)?;
+        let mut wrt = directory.open_write(path)?;
+        wrt.write_all(&data)?;
+        wrt.terminate()?;
+    }
+    // `meta.json` is written last: until then, readers
+    // keep on seeing the previous commit.
+    directory.atomic_write(&META_FILEPATH, meta_json)?;
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use crate::collector::Collector;
+use crate::core::{Searcher, SegmentId, SegmentReader};
+use crate::query::Query;
+use crate::reader::Warmer;
+use lru::LruCache;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+type ResultCacheKey = (String, String);
+
+/// Identifies the documents visible to a searcher:
+/// its segments, together with their number of deleted documents.
+type SearcherFingerprint = Vec<(SegmentId, u32)>;
+
+fn searcher_fingerprint(searcher: &Searcher) -> SearcherFingerprint {
+    searcher
+        .segment_readers()
+        .iter()
+        .map(|segment_reader: &SegmentReader| {
+            (
+                segment_reader.segment_id(),
+                segment_reader.num_deleted_docs(),
+            )
+        })
+        .collect()
+}
+
+struct CachedFruit<TFruit> {
+    fingerprint: SearcherFingerprint,
+    fruit: TFruit,
+}
+
+/// Cache of the results of the queries run against an index.
+///
+/// The results are identified by a `query_key` describing the query
+/// (e.g. the query string it was parsed from), and by a `collector_key`
+/// describing the collector (e.g. `"top10"`): two queries (resp. collectors)
+/// with the same key are expected to produce the same results.
+///
+/// A cached result is only returned for searchers seeing the exact same
+/// documents as the searcher that computed it: once a commit adding or deleting
+/// documents is published, the results are computed again.
+/// `QueryResultCache` implements `Warmer`: registering it on the `IndexReader`
+/// drops the outdated results every time the reader is reloaded.
+///
+/// The cache holds at most `capacity` results, and evicts the least recently
+/// used ones. Cloning it is cheap, and all of the clones share the same entries.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, DocAddress, Index, QueryResultCache, Score};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"));
+/// index_writer.commit()?;
+///
+/// let cache: QueryResultCache<Vec<(Score, DocAddress)>> = QueryResultCache::with_capacity(1_000);
+/// let reader = index.reader_builder().warmer(cache.clone()).try_into()?;
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("sea")?;
+/// let top_docs = cache.search(
+///     &reader.searcher(),
+///     "title:sea",
+///     &query,
+///     &TopDocs::with_limit(10),
+///     "top10",
+/// )?;
+/// assert_eq!(top_docs.len(), 1);
+/// assert_eq!(cache.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct QueryResultCache<TFruit> {
+    entries: Arc<Mutex<LruCache<ResultCacheKey, CachedFruit<TFruit>>>>,
+}
+
+impl<TFruit> Clone for QueryResultCache<TFruit> {
+    fn clone(&self) -> Self {
+        QueryResultCache {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<TFruit: Clone> QueryResultCache<TFruit> {
+    /// Creates an empty cache, holding at most `capacity` results.
+    pub fn with_capacity(capacity: usize) -> QueryResultCache<TFruit> {
+        QueryResultCache {
+            entries: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns true iff the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all of the entries of the cache.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Removes the results that were not computed
+    /// on the documents visible to `searcher`.
+    pub fn retain_searcher(&self, searcher: &Searcher) {
+        let fingerprint = searcher_fingerprint(searcher);
+        let mut entries = self.entries.lock().unwrap();
+        let outdated_keys: Vec<ResultCacheKey> = entries
+            .iter()
+            .filter(|(_, cached_fruit)| cached_fruit.fingerprint != fingerprint)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in outdated_keys {
+            entries.pop(&key);
+        }
+    }
+
+    /// Returns the results of `searcher.search(query, collector)`,
+    /// from the cache if they are available.
+    pub fn search<C: Collector<Fruit = TFruit>>(
+        &self,
+        searcher: &Searcher,
+        query_key: &str,
+        query: &dyn Query,
+        collector: &C,
+        collector_key: &str,
+    ) -> crate::Result<TFruit> {
+        let key = (query_key.to_string(), collector_key.to_string());
+        let fingerprint = searcher_fingerprint(searcher);
+        if let Some(cached_fruit) = self.entries.lock().unwrap().get(&key) {
+            if cached_fruit.fingerprint == fingerprint {
+                return Ok(cached_fruit.fruit.clone());
+            }
+        }
+        let fruit = searcher.search(query, collector)?;
+        self.entries.lock().unwrap().put(
+            key,
+            CachedFruit {
+                fingerprint,
+                fruit: fruit.clone(),
+            },
+        );
+        Ok(fruit)
+    }
+}
+
+impl<TFruit: Clone> fmt::Debug for QueryResultCache<TFruit> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "QueryResultCache(len={})", self.len())
+    }
+}
+
+impl<TFruit: Clone + Send + 'static> Warmer for QueryResultCache<TFruit> {
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        self.retain_searcher(searcher);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QueryResultCache;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{DocAddress, Index, ReloadPolicy, Score, Term};
+
+    #[test]
+    fn test_query_result_cache() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea"));
+        index_writer.add_document(doc!(title => "mountain"));
+        index_writer.commit()?;
+
+        let count_cache: QueryResultCache<usize> = QueryResultCache::with_capacity(2);
+        let top_docs_cache: QueryResultCache<Vec<(Score, DocAddress)>> =
+            QueryResultCache::with_capacity(10);
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .warmer(count_cache.clone())
+            .warmer(top_docs_cache.clone())
+            .try_into()?;
+        let term_query = |text: &str| {
+            TermQuery::new(Term::from_field_text(title, text), IndexRecordOption::Basic)
+        };
+
+        let searcher = reader.searcher();
+        assert_eq!(
+            count_cache.search(&searcher, "sea", &term_query("sea"), &Count, "count")?,
+            1
+        );
+        let top_docs = top_docs_cache.search(
+            &searcher,
+            "sea",
+            &term_query("sea"),
+            &TopDocs::with_limit(1),
+            "top1",
+        )?;
+        assert_eq!(top_docs.len(), 1);
+        assert_eq!(count_cache.len(), 1);
+
+        // Served from the cache.
+        assert_eq!(
+            count_cache.search(&searcher, "sea", &term_query("sea"), &Count, "count")?,
+            1
+        );
+        count_cache.search(
+            &searcher,
+            "mountain",
+            &term_query("mountain"),
+            &Count,
+            "count",
+        )?;
+        count_cache.search(&searcher, "sky", &term_query("sky"), &Count, "count")?;
+        // The capacity is 2: the least recently used result was evicted.
+        assert_eq!(count_cache.len(), 2);
+
+        index_writer.add_document(doc!(title => "sea sea"));
+        index_writer.commit()?;
+        // The searcher still sees the same documents.
+        assert_eq!(
+            count_cache.search(&searcher, "sea", &term_query("sea"), &Count, "count")?,
+            1
+        );
+        reader.reload()?;
+        assert!(count_cache.is_empty());
+        assert!(top_docs_cache.is_empty());
+        let searcher = reader.searcher();
+        assert_eq!(
+            count_cache.search(&searcher, "sea", &term_query("sea"), &Count, "count")?,
+            2
+        );
+        // Results are identified by the query key, not by the query.
+        count_cache.search(&searcher, "title:sea", &term_query("sea"), &Count, "count")?;
+        assert_eq!(count_cache.len(), 2);
+        Ok(())
+    }
+}
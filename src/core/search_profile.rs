@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros() as u64
+}
+
+/// Time spent searching one segment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentSearchProfile {
+    /// Ordinal of the segment in the searcher.
+    pub segment_ord: u32,
+    /// Number of documents in the segment, including deleted documents.
+    pub max_doc: u32,
+    /// Time spent creating the scorer of the segment and collecting
+    /// its documents, in microseconds.
+    pub collect_micros: u64,
+}
+
+/// `SearchProfile` records the time spent in each phase of a search, in microseconds.
+///
+/// The phases executed by the `Searcher` are recorded by `Searcher::search_with_profile`.
+/// The time spent parsing the query and fetching the documents of the hits happens
+/// outside of the searcher: it can be recorded with `record_parse` and `record_doc_fetch`.
+///
+/// A `SearchProfile` serializes into JSON, which makes it suitable for a slow query log.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, STORED, TEXT};
+/// use tantivy::{doc, Index, SearchProfile};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"));
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+///
+/// let mut profile = SearchProfile::default();
+/// let query = profile.record_parse(|| query_parser.parse_query("sea"))?;
+/// let top_docs = searcher.search_with_profile(&query, &TopDocs::with_limit(10), &mut profile)?;
+/// let docs = profile.record_doc_fetch(|| {
+///     top_docs
+///         .iter()
+///         .map(|&(_score, doc_address)| searcher.doc(doc_address))
+///         .collect::<tantivy::Result<Vec<_>>>()
+/// })?;
+/// assert_eq!(docs.len(), 1);
+/// assert_eq!(profile.segments.len(), 1);
+/// if profile.total_micros() > 100_000 {
+///     eprintln!("slow query: {}", profile.to_json());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchProfile {
+    /// Time spent parsing the query, if recorded.
+    pub parse_micros: Option<u64>,
+    /// Time spent creating the `Weight` of the query.
+    /// This includes the computation of the scoring statistics.
+    pub weight_micros: u64,
+    /// Time spent searching each of the segments.
+    pub segments: Vec<SegmentSearchProfile>,
+    /// Time spent merging the fruits of the segments.
+    pub merge_micros: u64,
+    /// Time spent fetching the documents of the hits, if recorded.
+    pub doc_fetch_micros: Option<u64>,
+}
+
+impl SearchProfile {
+    /// Runs `parse`, and records its duration as the parsing time.
+    pub fn record_parse<T, F: FnOnce() -> T>(&mut self, parse: F) -> T {
+        let start = Instant::now();
+        let parsed = parse();
+        self.parse_micros = Some(micros(start.elapsed()));
+        parsed
+    }
+
+    /// Runs `doc_fetch`, and records its duration as the document fetching time.
+    pub fn record_doc_fetch<T, F: FnOnce() -> T>(&mut self, doc_fetch: F) -> T {
+        let start = Instant::now();
+        let docs = doc_fetch();
+        self.doc_fetch_micros = Some(micros(start.elapsed()));
+        docs
+    }
+
+    pub(crate) fn record_search(
+        &mut self,
+        weight_duration: Duration,
+        segments: Vec<SegmentSearchProfile>,
+        merge_duration: Duration,
+    ) {
+        self.weight_micros = micros(weight_duration);
+        self.segments = segments;
+        self.merge_micros = micros(merge_duration);
+    }
+
+    /// Returns the overall time spent searching the segments.
+    ///
+    /// When the search is multithreaded, segments are searched concurrently:
+    /// the wall-clock time of the search can be lower than this sum.
+    pub fn collect_micros(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| segment.collect_micros)
+            .sum()
+    }
+
+    /// Returns the sum of the durations of all of the recorded phases.
+    pub fn total_micros(&self) -> u64 {
+        self.parse_micros.unwrap_or(0)
+            + self.weight_micros
+            + self.collect_micros()
+            + self.merge_micros
+            + self.doc_fetch_micros.unwrap_or(0)
+    }
+
+    /// Returns a json representation of the profile.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Profile encoding failed. This is a bug")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchProfile;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+
+    #[test]
+    fn test_search_with_profile() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "sea sea"));
+        index_writer.add_document(doc!(title => "mountain"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+
+        let mut profile = SearchProfile::default();
+        let query = profile.record_parse(|| query_parser.parse_query("sea"))?;
+        let (top_docs, count) =
+            searcher.search_with_profile(&query, &(TopDocs::with_limit(1), Count), &mut profile)?;
+        assert_eq!(top_docs.len(), 1);
+        assert_eq!(count, 2);
+        assert!(profile.parse_micros.is_some());
+        assert!(profile.doc_fetch_micros.is_none());
+        let mut max_docs: Vec<(u32, u32)> = profile
+            .segments
+            .iter()
+            .map(|segment| (segment.segment_ord, segment.max_doc))
+            .collect();
+        max_docs.sort();
+        assert_eq!(max_docs.len(), 2);
+        assert_eq!(max_docs[0].0, 0);
+        assert_eq!(max_docs[1].0, 1);
+        assert_eq!(max_docs.iter().map(|&(_, max_doc)| max_doc).sum::<u32>(), 3);
+        assert!(profile.total_micros() >= profile.collect_micros());
+
+        let json = profile.to_json();
+        assert!(json.contains("\"weight_micros\""));
+        let deserialized: SearchProfile = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, profile);
+        Ok(())
+    }
+}
@@ -1,16 +1,25 @@
 use crate::collector::Collector;
+use crate::core::export::export_jsonl;
 use crate::core::Executor;
+use crate::core::FieldTerms;
+use crate::core::{SearchProfile, SegmentSearchProfile};
 
 use crate::core::SegmentReader;
 use crate::query::Query;
 use crate::schema::Document;
+use crate::schema::Field;
 use crate::schema::Schema;
 use crate::schema::Term;
 use crate::space_usage::SearcherSpaceUsage;
 use crate::store::StoreReader;
 use crate::DocAddress;
 use crate::Index;
+use crate::SegmentLocalId;
 
+use futures::channel::oneshot;
+use futures::{Future, TryFutureExt};
+use std::io::Write;
+use std::time::Instant;
 use std::{fmt, io};
 
 /// Holds a list of `SegmentReader`s ready for search.
@@ -18,6 +27,10 @@ use std::{fmt, io};
 /// It guarantees that the `Segment` will not be removed before
 /// the destruction of the `Searcher`.
 ///
+/// A `Searcher` is `Send + Sync`, and can be shared between threads.
+/// Cloning a `Searcher` is cheap: the clone shares the
+/// segment readers of the original searcher.
+#[derive(Clone)]
 pub struct Searcher {
     schema: Schema,
     index: Index,
@@ -53,12 +66,58 @@ impl Searcher {
     ///
     /// The searcher uses the segment ordinal to route the
     /// the request to the right `Segment`.
+    ///
+    /// An `InvalidArgument` error is returned if the segment ordinal
+    /// or the `DocId` do not match any document.
     pub fn doc(&self, doc_address: DocAddress) -> crate::Result<Document> {
         let DocAddress(segment_local_id, doc_id) = doc_address;
-        let store_reader = &self.store_readers[segment_local_id as usize];
+        let store_reader = self.store_reader(segment_local_id)?;
         store_reader.get(doc_id)
     }
 
+    /// Fetches the values of the given `fields` of a document from tantivy's store.
+    ///
+    /// The other stored fields are skipped without being decoded. This is useful
+    /// to avoid parsing large stored fields (e.g. the body of an article) when
+    /// only a few small fields need to be displayed.
+    ///
+    /// See `Searcher::doc`.
+    pub fn doc_with_fields(
+        &self,
+        doc_address: DocAddress,
+        fields: &[Field],
+    ) -> crate::Result<Document> {
+        let DocAddress(segment_local_id, doc_id) = doc_address;
+        let store_reader = self.store_reader(segment_local_id)?;
+        store_reader.get_fields(doc_id, fields)
+    }
+
+    /// Fetches a document asynchronously from tantivy's store given a `DocAddress`.
+    ///
+    /// See `Searcher::doc`.
+    #[cfg(feature = "async-read")]
+    pub async fn doc_async(&self, doc_address: DocAddress) -> crate::Result<Document> {
+        let DocAddress(segment_local_id, doc_id) = doc_address;
+        let store_reader = self.store_reader(segment_local_id)?;
+        store_reader.get_async(doc_id).await
+    }
+
+    /// Returns the `StoreReader` of the segment with the given ordinal.
+    ///
+    /// Contrary to `SegmentReader::get_store_reader`, the store reader
+    /// is shared by all of the calls, and so is its cache of
+    /// decompressed blocks.
+    pub fn store_reader(&self, segment_local_id: SegmentLocalId) -> crate::Result<&StoreReader> {
+        self.store_readers
+            .get(segment_local_id as usize)
+            .ok_or_else(|| {
+                crate::TantivyError::InvalidArgument(format!(
+                    "Segment ordinal {} is out of bounds.",
+                    segment_local_id
+                ))
+            })
+    }
+
     /// Access the schema associated to the index of this searcher.
     pub fn schema(&self) -> &Schema {
         &self.schema
@@ -72,6 +131,16 @@ impl Searcher {
             .sum::<u64>()
     }
 
+    /// Returns the overall number of deleted documents in the index.
+    ///
+    /// Deleted documents are eventually purged by merges.
+    pub fn num_deleted_docs(&self) -> u64 {
+        self.segment_readers
+            .iter()
+            .map(|segment_reader| u64::from(segment_reader.num_deleted_docs()))
+            .sum::<u64>()
+    }
+
     /// Return the overall number of documents containing
     /// the given term.
     pub fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
@@ -84,12 +153,61 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Returns the terms of the given field, across all of the segments.
+    ///
+    /// This makes it possible to enumerate the terms of a field
+    /// (optionally restricted to a prefix or a range) together with their
+    /// document frequency.
+    pub fn field_terms(&self, field: Field) -> crate::Result<FieldTerms> {
+        let inverted_indexes = self
+            .segment_readers
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(field))
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(FieldTerms::new(inverted_indexes))
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.segment_readers
     }
 
+    /// Returns an iterator over the addresses of all of the alive documents
+    /// of the searcher, ordered by segment ordinal and then by `DocId`.
+    pub fn doc_addresses<'a>(&'a self) -> impl Iterator<Item = DocAddress> + 'a {
+        self.segment_readers
+            .iter()
+            .enumerate()
+            .flat_map(|(segment_ord, segment_reader)| {
+                segment_reader
+                    .doc_ids_alive()
+                    .map(move |doc| DocAddress(segment_ord as u32, doc))
+            })
+    }
+
+    /// Writes all of the alive documents of the searcher to `wrt` as JSON Lines:
+    /// one JSON object per line, in the format of `Schema::to_json`.
+    ///
+    /// Documents are written one at a time, in the order of `Searcher::doc_addresses`,
+    /// and only contain their stored fields. If `with_fast_fields` is true,
+    /// the values of the fast fields that are not stored are added as well.
+    /// Note that a single-valued fast field has a value (0 by default) for all
+    /// of the documents.
+    ///
+    /// Each line can be parsed back with `Schema::parse_document`, which
+    /// makes it possible to reindex the documents into an index with a new schema,
+    /// or to recover the content of a damaged index.
+    ///
+    /// Returns the number of exported documents.
+    pub fn export_jsonl<W: Write>(&self, wrt: W, with_fast_fields: bool) -> crate::Result<u64> {
+        export_jsonl(self, wrt, with_fast_fields)
+    }
+
     /// Returns the segment_reader associated with the given segment_ordinal
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment ordinal is out of bounds.
     pub fn segment_reader(&self, segment_ord: u32) -> &SegmentReader {
         &self.segment_readers[segment_ord as usize]
     }
@@ -140,13 +258,136 @@ impl Searcher {
         let segment_readers = self.segment_readers();
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
-                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+                let collect_start = Instant::now();
+                let fruit =
+                    collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader);
+                debug!(
+                    "Searched segment {} with max_doc={} in {:?}.",
+                    segment_reader.segment_id().short_uuid_string(),
+                    segment_reader.max_doc(),
+                    collect_start.elapsed()
+                );
+                fruit
             },
             segment_readers.iter().enumerate(),
         )?;
         collector.merge_fruits(fruits)
     }
 
+    /// Same as [`search(...)`](#method.search), but the search runs in the background
+    /// and its result is returned as a future.
+    ///
+    /// The search runs on the search executor of the index, or on rayon's global
+    /// thread pool if the executor is single-threaded. Awaiting the future
+    /// therefore never blocks the thread polling it, which makes it
+    /// suitable for use within an async runtime.
+    pub fn search_async<Q, C>(
+        &self,
+        query: Q,
+        collector: C,
+    ) -> impl Future<Output = crate::Result<C::Fruit>> + Send + 'static
+    where
+        Q: Query + 'static,
+        C: Collector + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let searcher = self.clone();
+        self.index.search_executor().spawn(move || {
+            let _ = sender.send(searcher.search(&query, &collector));
+        });
+        receiver.unwrap_or_else(|_| {
+            Err(crate::TantivyError::SystemError(
+                "The search task was interrupted. It probably panicked.".to_string(),
+            ))
+        })
+    }
+
+    /// Same as [`search(...)`](#method.search), but also records the time
+    /// spent in each phase of the search in `profile`.
+    pub fn search_with_profile<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        profile: &mut SearchProfile,
+    ) -> crate::Result<C::Fruit> {
+        let weight_start = Instant::now();
+        let scoring_enabled = collector.requires_scoring();
+        let weight = query.weight(self, scoring_enabled)?;
+        let weight_duration = weight_start.elapsed();
+        let segment_readers = self.segment_readers();
+        let segment_fruits = self.index.search_executor().map(
+            |(segment_ord, segment_reader)| {
+                let collect_start = Instant::now();
+                let fruit = collector.collect_segment(
+                    weight.as_ref(),
+                    segment_ord as u32,
+                    segment_reader,
+                )?;
+                let segment_profile = SegmentSearchProfile {
+                    segment_ord: segment_ord as u32,
+                    max_doc: segment_reader.max_doc(),
+                    collect_micros: collect_start.elapsed().as_micros() as u64,
+                };
+                Ok((fruit, segment_profile))
+            },
+            segment_readers.iter().enumerate(),
+        )?;
+        let (fruits, segment_profiles): (Vec<_>, Vec<_>) = segment_fruits.into_iter().unzip();
+        let merge_start = Instant::now();
+        let fruit = collector.merge_fruits(fruits)?;
+        profile.record_search(weight_duration, segment_profiles, merge_start.elapsed());
+        Ok(fruit)
+    }
+
+    /// Runs a batch of queries with the same collector, and returns the
+    /// fruits in the order of the queries.
+    ///
+    /// The weights of all of the queries are created upfront, and segments
+    /// are then visited only once: each task of the executor runs all of the
+    /// queries on a given segment.
+    ///
+    /// This is useful to run a large number of stored queries
+    /// (alerting for instance) against a new version of the index.
+    pub fn search_batch<C: Collector>(
+        &self,
+        queries: &[&dyn Query],
+        collector: &C,
+    ) -> crate::Result<Vec<C::Fruit>> {
+        let scoring_enabled = collector.requires_scoring();
+        let weights = queries
+            .iter()
+            .map(|query| query.weight(self, scoring_enabled))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let segment_readers = self.segment_readers();
+        let segment_fruits = self.index.search_executor().map(
+            |(segment_ord, segment_reader)| {
+                weights
+                    .iter()
+                    .map(|weight| {
+                        collector.collect_segment(
+                            weight.as_ref(),
+                            segment_ord as u32,
+                            segment_reader,
+                        )
+                    })
+                    .collect::<crate::Result<Vec<_>>>()
+            },
+            segment_readers.iter().enumerate(),
+        )?;
+        let mut fruits_per_query: Vec<Vec<_>> = (0..weights.len())
+            .map(|_| Vec::with_capacity(segment_readers.len()))
+            .collect();
+        for segment_fruit in segment_fruits {
+            for (query_fruits, fruit) in fruits_per_query.iter_mut().zip(segment_fruit) {
+                query_fruits.push(fruit);
+            }
+        }
+        fruits_per_query
+            .into_iter()
+            .map(|query_fruits| collector.merge_fruits(query_fruits))
+            .collect()
+    }
+
     /// Summarize total space usage of this searcher.
     pub fn space_usage(&self) -> io::Result<SearcherSpaceUsage> {
         let mut space_usage = SearcherSpaceUsage::new();
@@ -167,3 +408,61 @@ impl fmt::Debug for Searcher {
         write!(f, "Searcher({:?})", segment_ids)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{DocAddress, Index, Term};
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_search_async() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "sea sea"));
+        index_writer.add_document(doc!(title => "mountain"));
+        index_writer.commit()?;
+        let query = TermQuery::new(
+            Term::from_field_text(title, "sea"),
+            IndexRecordOption::WithFreqs,
+        );
+        let searcher = index.reader()?.searcher();
+        let expected = searcher.search(&query, &TopDocs::with_limit(2))?;
+        let top_docs = block_on(searcher.search_async(query.clone(), TopDocs::with_limit(2)))?;
+        assert_eq!(top_docs, expected);
+
+        index.set_multithread_executor(2)?;
+        let searcher = index.reader()?.searcher();
+        let count = block_on(searcher.search_async(AllQuery, Count))?;
+        assert_eq!(count, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_doc_with_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "The Sea", body => "A long, long body."));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let doc = searcher.doc_with_fields(DocAddress(0, 0), &[title])?;
+        assert_eq!(
+            doc.get_first(title).and_then(|value| value.text()),
+            Some("The Sea")
+        );
+        assert!(doc.get_first(body).is_none());
+        assert!(searcher
+            .doc_with_fields(DocAddress(1, 0), &[title])
+            .is_err());
+        Ok(())
+    }
+}
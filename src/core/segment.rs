@@ -8,6 +8,7 @@ use crate::directory::{FileSlice, WritePtr};
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::schema::Schema;
 use crate::Opstamp;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::PathBuf;
 
@@ -56,6 +57,14 @@ impl Segment {
         }
     }
 
+    /// Replaces the user attributes of the segment.
+    pub(crate) fn with_attributes(self, attributes: BTreeMap<String, String>) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self.meta.with_attributes(attributes),
+        }
+    }
+
     #[doc(hidden)]
     pub fn with_delete_meta(self, num_deleted_docs: u32, opstamp: Opstamp) -> Segment {
         Segment {
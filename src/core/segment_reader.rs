@@ -1,12 +1,14 @@
-use crate::common::HasLen;
+use crate::common::{BitSet, HasLen};
 use crate::core::InvertedIndexReader;
 use crate::core::Segment;
 use crate::core::SegmentComponent;
 use crate::core::SegmentId;
+use crate::core::SegmentMeta;
 use crate::directory::FileSlice;
 use crate::fastfield::DeleteBitSet;
 use crate::fastfield::FacetReader;
 use crate::fastfield::FastFieldReaders;
+use crate::fastfield::StrFastFieldReader;
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
 use crate::schema::FieldType;
 use crate::schema::Schema;
@@ -14,12 +16,14 @@ use crate::schema::{Field, IndexRecordOption};
 use crate::space_usage::SegmentSpaceUsage;
 use crate::store::StoreReader;
 use crate::termdict::TermDictionary;
-use crate::DocId;
 use crate::{common::CompositeFile, error::DataCorruption};
+use crate::{DateTime, DocId};
 use fail::fail_point;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::fmt;
-use std::sync::Arc;
 use std::sync::RwLock;
+use std::sync::{Arc, Mutex};
 use std::{collections::HashMap, io};
 
 /// Entry point to access all of the datastructures of the `Segment`
@@ -53,6 +57,9 @@ pub struct SegmentReader {
     store_file: FileSlice,
     delete_bitset_opt: Option<DeleteBitSet>,
     schema: Schema,
+    // Number of fields of the schema the segment was written with.
+    segment_num_fields: Option<u32>,
+    attributes: Arc<BTreeMap<String, String>>,
 }
 
 impl SegmentReader {
@@ -78,6 +85,18 @@ impl SegmentReader {
         &self.schema
     }
 
+    /// Returns the user attributes of the segment.
+    ///
+    /// See `IndexWriter::set_segment_attributes`.
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    /// Returns the value of the user attribute `key` of the segment, if any.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+
     /// Return the number of documents that have been
     /// deleted in the segment.
     pub fn num_deleted_docs(&self) -> DocId {
@@ -128,6 +147,34 @@ impl SegmentReader {
         Ok(FacetReader::new(term_ords_reader, termdict))
     }
 
+    /// Accessor to the `StrFastFieldReader` associated to a given text `Field`.
+    ///
+    /// The field must have been declared as fast in the schema.
+    pub fn str_fast_field_reader(&self, field: Field) -> crate::Result<StrFastFieldReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        match field_entry.field_type() {
+            FieldType::Str(text_options) if text_options.is_fast() => {}
+            _ => {
+                return Err(crate::TantivyError::InvalidArgument(format!(
+                    "Field {:?} is not a fast text field.",
+                    field_entry.name()
+                )));
+            }
+        }
+        let term_ords_reader = self.fast_fields().u64s(field).ok_or_else(|| {
+            DataCorruption::comment_only(format!(
+                "Cannot find data for str fast field {:?}",
+                field_entry.name()
+            ))
+        })?;
+        let termdict = self
+            .termdict_composite
+            .open_read(field)
+            .map(TermDictionary::open)
+            .unwrap_or_else(|| Ok(TermDictionary::empty()))?;
+        Ok(StrFastFieldReader::new(term_ords_reader, termdict))
+    }
+
     /// Accessor to the segment's `Field norms`'s reader.
     ///
     /// Field norms are the length (in tokens) of the fields.
@@ -135,15 +182,26 @@ impl SegmentReader {
     ///
     /// They are simply stored as a fast field, serialized in
     /// the `.fieldnorm` file of the segment.
+    ///
+    /// Segments created before an indexed field was added to the schema
+    /// do not contain any token for this field: their field norms are all `0`.
     pub fn get_fieldnorms_reader(&self, field: Field) -> crate::Result<FieldNormReader> {
-        self.fieldnorm_readers.get_field(field)?.ok_or_else(|| {
-            let field_name = self.schema.get_field_name(field);
-            let err_msg = format!(
-                "Field norm not found for field {:?}. Was it marked as indexed during indexing?",
-                field_name
-            );
-            crate::TantivyError::SchemaError(err_msg)
-        })
+        if let Some(fieldnorm_reader) = self.fieldnorm_readers.get_field(field)? {
+            return Ok(fieldnorm_reader);
+        }
+        let added_after_segment = self
+            .segment_num_fields
+            .map(|num_fields| field.field_id() >= num_fields)
+            .unwrap_or(false);
+        if added_after_segment && self.schema.get_field_entry(field).is_indexed() {
+            return Ok(FieldNormReader::constant(self.max_doc, 0));
+        }
+        let field_name = self.schema.get_field_name(field);
+        let err_msg = format!(
+            "Field norm not found for field {:?}. Was it marked as indexed during indexing?",
+            field_name
+        );
+        Err(crate::TantivyError::SchemaError(err_msg))
     }
 
     /// Accessor to the segment's `StoreReader`.
@@ -183,24 +241,43 @@ impl SegmentReader {
 
         let fast_fields_data = segment.open_read(SegmentComponent::FASTFIELDS)?;
         let fast_fields_composite = CompositeFile::open(&fast_fields_data)?;
-        let fast_field_readers =
-            Arc::new(FastFieldReaders::load_all(&schema, &fast_fields_composite)?);
+        let fast_field_readers = Arc::new(FastFieldReaders::load_all(
+            &schema,
+            &fast_fields_composite,
+            segment.meta(),
+        )?);
 
         let fieldnorm_data = segment.open_read(SegmentComponent::FIELDNORMS)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
 
-        let delete_bitset_opt = if segment.meta().has_deletes() {
+        let mut delete_bitset_opt = if segment.meta().has_deletes() {
             let delete_data = segment.open_read(SegmentComponent::DELETE)?;
             let delete_bitset = DeleteBitSet::open(delete_data)?;
             Some(delete_bitset)
         } else {
             None
         };
+        let max_doc = segment.meta().max_doc();
+        let mut num_docs = segment.meta().num_docs();
+        let expired_docs = expired_docs(segment, &schema, &fast_field_readers);
+        if let Some(expired_docs) = expired_docs.filter(|expired| expired.expired_docs.len() > 0) {
+            // Expired documents are handled as deleted documents.
+            let mut expired_docs = expired_docs.expired_docs.clone();
+            if let Some(delete_bitset) = delete_bitset_opt.as_ref() {
+                for doc in 0..max_doc {
+                    if delete_bitset.is_deleted(doc) {
+                        expired_docs.insert(doc);
+                    }
+                }
+            }
+            num_docs = max_doc - expired_docs.len() as u32;
+            delete_bitset_opt = Some(DeleteBitSet::from_bitset(&expired_docs, max_doc));
+        }
 
         Ok(SegmentReader {
             inv_idx_reader_cache: Default::default(),
-            max_doc: segment.meta().max_doc(),
-            num_docs: segment.meta().num_docs(),
+            max_doc,
+            num_docs,
             termdict_composite,
             postings_composite,
             fast_fields_readers: fast_field_readers,
@@ -211,6 +288,8 @@ impl SegmentReader {
             positions_composite,
             positions_idx_composite,
             schema,
+            segment_num_fields: segment.meta().num_fields(),
+            attributes: Arc::new(segment.meta().attributes().clone()),
         })
     }
 
@@ -333,6 +412,74 @@ impl SegmentReader {
     }
 }
 
+/// Documents of a segment whose expiration date is past.
+pub(crate) struct ExpiredDocs {
+    expired_docs: BitSet,
+    // Earliest expiration date of the other documents, if any.
+    next_expiration: Option<DateTime>,
+}
+
+/// Expired documents of the segments of an index, shared by its clones.
+///
+/// Segments are opened again every time the `IndexReader` is reloaded. Their
+/// expiration dates are only scanned again once one of their documents expired.
+pub(crate) type ExpirationCache = Arc<Mutex<HashMap<SegmentId, Arc<ExpiredDocs>>>>;
+
+/// Returns the documents of the segment whose expiration date is past,
+/// if the index has an expiration field.
+fn expired_docs(
+    segment: &Segment,
+    schema: &Schema,
+    fast_field_readers: &FastFieldReaders,
+) -> Option<Arc<ExpiredDocs>> {
+    let index = segment.index();
+    let field_name = index.settings().expiration_field.as_ref()?;
+    let field = schema.get_field(field_name)?;
+    let now = chrono::Utc::now();
+    let segment_id = segment.id();
+    let expiration_cache = index.expiration_cache();
+    if let Some(expired_docs) = expiration_cache.lock().unwrap().get(&segment_id) {
+        let is_up_to_date = expired_docs
+            .next_expiration
+            .map(|next_expiration| now < next_expiration)
+            .unwrap_or(true);
+        if is_up_to_date {
+            return Some(expired_docs.clone());
+        }
+    }
+    let expiration_reader = fast_field_readers.date(field)?;
+    let max_doc = segment.meta().max_doc();
+    let mut expired_docs = BitSet::with_max_value(max_doc);
+    let mut next_expiration: Option<DateTime> = None;
+    for doc in 0..max_doc {
+        let expiration_date = expiration_reader.get(doc);
+        if expiration_date.timestamp() == 0 {
+            continue;
+        }
+        if expiration_date <= now {
+            expired_docs.insert(doc);
+        } else if next_expiration
+            .map(|next_expiration| expiration_date < next_expiration)
+            .unwrap_or(true)
+        {
+            next_expiration = Some(expiration_date);
+        }
+    }
+    let expired_docs = Arc::new(ExpiredDocs {
+        expired_docs,
+        next_expiration,
+    });
+    let alive_segment_ids: HashSet<SegmentId> = index
+        .list_all_segment_metas()
+        .iter()
+        .map(SegmentMeta::id)
+        .collect();
+    let mut expiration_cache = expiration_cache.lock().unwrap();
+    expiration_cache.retain(|segment_id, _| alive_segment_ids.contains(segment_id));
+    expiration_cache.insert(segment_id, expired_docs.clone());
+    Some(expired_docs)
+}
+
 impl fmt::Debug for SegmentReader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "SegmentReader({:?})", self.segment_id)
@@ -341,9 +488,16 @@ impl fmt::Debug for SegmentReader {
 
 #[cfg(test)]
 mod test {
-    use crate::core::Index;
-    use crate::schema::{Schema, Term, STORED, TEXT};
+    use crate::collector::Count;
+    use crate::core::{Index, IndexSettings};
+    use crate::directory::RAMDirectory;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, Term, FAST, STORED, STRING, TEXT};
     use crate::DocId;
+    use crate::ReloadPolicy;
+    use chrono::{DateTime, Utc};
+    use futures::executor::block_on;
+    use std::sync::Arc;
 
     #[test]
     fn test_alive_docs_iterator() -> crate::Result<()> {
@@ -376,4 +530,120 @@ mod test {
         assert_eq!(vec![0u32, 2u32], docs);
         Ok(())
     }
+
+    #[test]
+    fn test_expired_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let expires_at = schema_builder.add_date_field("expires_at", FAST);
+        let settings = IndexSettings {
+            expiration_field: Some("expires_at".to_string()),
+            ..IndexSettings::default()
+        };
+        let index =
+            Index::create_with_settings(RAMDirectory::create(), schema_builder.build(), settings)?;
+        let date = |date: &str| {
+            DateTime::parse_from_rfc3339(date)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let past = date("2000-01-01T00:00:00Z");
+        let future = date("3000-01-01T00:00:00Z");
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a", expires_at => past));
+        index_writer.add_document(doc!(id => "b", expires_at => future));
+        index_writer.add_document(doc!(id => "c"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => "d", expires_at => past));
+        index_writer.add_document(doc!(id => "e", expires_at => future));
+        index_writer.add_document(doc!(id => "f"));
+        index_writer.delete_term(Term::from_field_text(id, "e"));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 3);
+        let mut num_deleted_docs: Vec<u32> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.num_deleted_docs())
+            .collect();
+        num_deleted_docs.sort_unstable();
+        assert_eq!(num_deleted_docs, vec![1, 2]);
+        assert!(index.validate()?.is_valid());
+
+        // Expired documents are removed by merges.
+        let segment_ids = index.searchable_segment_ids()?;
+        block_on(index_writer.merge(&segment_ids))?;
+        index_writer.wait_merging_threads()?;
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(segment_metas[0].max_doc(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_docs_are_scanned_once_per_expiration() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let expires_at = schema_builder.add_date_field("expires_at", FAST);
+        let settings = IndexSettings {
+            expiration_field: Some("expires_at".to_string()),
+            ..IndexSettings::default()
+        };
+        let index =
+            Index::create_with_settings(RAMDirectory::create(), schema_builder.build(), settings)?;
+        let soon = Utc::now() + chrono::Duration::seconds(1);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(expires_at => soon));
+        index_writer.add_document(doc!());
+        index_writer.commit()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        let segment_id = index.searchable_segment_ids()?[0];
+        let cached_expired_docs = || index.expiration_cache().lock().unwrap()[&segment_id].clone();
+        let expired_docs = cached_expired_docs();
+
+        // Reloading before the next expiration date reuses the previous scan.
+        reader.reload()?;
+        assert!(Arc::ptr_eq(&expired_docs, &cached_expired_docs()));
+
+        std::thread::sleep(std::time::Duration::from_millis(1_100));
+        reader.reload()?;
+        assert!(!Arc::ptr_eq(&expired_docs, &cached_expired_docs()));
+        assert_eq!(reader.searcher().num_docs(), 1);
+
+        // Merged segments are evicted from the cache.
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.add_document(doc!());
+        index_writer.commit()?;
+        block_on(index_writer.merge(&index.searchable_segment_ids()?))?;
+        index_writer.wait_merging_threads()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        let expiration_cache = index.expiration_cache().lock().unwrap();
+        assert!(segment_ids
+            .iter()
+            .all(|segment_id| !expiration_cache.contains_key(segment_id)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expiration_field_has_to_be_a_fast_date_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        for field_name in &["title", "missing"] {
+            let settings = IndexSettings {
+                expiration_field: Some(field_name.to_string()),
+                ..IndexSettings::default()
+            };
+            assert!(matches!(
+                Index::create_with_settings(RAMDirectory::create(), schema.clone(), settings),
+                Err(crate::TantivyError::SchemaError(_))
+            ));
+        }
+    }
 }
@@ -0,0 +1,303 @@
+use crate::common::HasLen;
+use crate::core::{Segment, SegmentComponent, SegmentId, SegmentReader};
+use crate::fastfield::DeleteBitSet;
+use crate::schema::{Field, FieldType, IndexRecordOption};
+use crate::DocId;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Inconsistency found in a segment by `Index::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentError {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Description of the inconsistency.
+    pub message: String,
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Segment {}: {}",
+            self.segment_id.short_uuid_string(),
+            self.message
+        )
+    }
+}
+
+/// Report returned by `Index::validate`, listing the inconsistencies
+/// found in the searchable segments of the index.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// Inconsistencies found in the segments, in the order of the segments.
+    pub segment_errors: Vec<SegmentError>,
+}
+
+impl ValidationReport {
+    /// Returns true iff no inconsistency was found.
+    pub fn is_valid(&self) -> bool {
+        self.segment_errors.is_empty()
+    }
+
+    /// Returns the ids of the segments in which an inconsistency was found.
+    pub fn damaged_segments(&self) -> HashSet<SegmentId> {
+        self.segment_errors
+            .iter()
+            .map(|segment_error| segment_error.segment_id)
+            .collect()
+    }
+}
+
+/// Checks the files of a segment, and the consistency of its components.
+///
+/// Returns the descriptions of the inconsistencies found.
+pub(crate) fn validate_segment(segment: &Segment) -> Vec<String> {
+    let mut errors = Vec::new();
+    let directory = segment.index().directory();
+    for &component in SegmentComponent::iterator() {
+        if matches!(component, SegmentComponent::DELETE) && !segment.meta().has_deletes() {
+            continue;
+        }
+        let path = segment.meta().relative_path(component);
+        match directory.validate_checksum(&path) {
+            Ok(true) => {}
+            Ok(false) => errors.push(format!("The checksum of {:?} does not match.", path)),
+            Err(err) => errors.push(format!("Failed to read {:?}: {}", path, err)),
+        }
+    }
+    if segment.meta().has_deletes() {
+        match segment
+            .open_read(SegmentComponent::DELETE)
+            .map_err(crate::TantivyError::from)
+            .and_then(DeleteBitSet::open)
+        {
+            Ok(delete_bitset)
+                if delete_bitset.len() as u32 != segment.meta().num_deleted_docs() =>
+            {
+                errors.push(format!(
+                    "The delete bitset has {} deleted documents, {} were expected.",
+                    delete_bitset.len(),
+                    segment.meta().num_deleted_docs()
+                ));
+            }
+            Ok(_) => {}
+            Err(err) => errors.push(format!("Failed to open the delete bitset: {}", err)),
+        }
+    }
+    match SegmentReader::open(segment) {
+        Ok(segment_reader) => errors.extend(validate_segment_reader(&segment_reader)),
+        Err(err) => errors.push(format!("Failed to open the segment: {}", err)),
+    }
+    errors
+}
+
+fn validate_segment_reader(segment_reader: &SegmentReader) -> Vec<String> {
+    let mut errors = Vec::new();
+    let max_doc = segment_reader.max_doc();
+    match segment_reader.get_store_reader() {
+        Ok(store_reader) => {
+            let num_stored_docs = store_reader
+                .block_checkpoints()
+                .last()
+                .map(|checkpoint| checkpoint.end_doc)
+                .unwrap_or(0);
+            if num_stored_docs != max_doc {
+                errors.push(format!(
+                    "The doc store has {} documents, {} were expected.",
+                    num_stored_docs, max_doc
+                ));
+            }
+        }
+        Err(err) => errors.push(format!("Failed to open the doc store: {}", err)),
+    }
+    for (field, field_entry) in segment_reader.schema().fields() {
+        let field_name = field_entry.name();
+        if field_entry.is_indexed() {
+            match segment_reader.get_fieldnorms_reader(field) {
+                Ok(fieldnorm_reader) if fieldnorm_reader.num_docs() != max_doc => {
+                    errors.push(format!(
+                        "The field norms of {:?} have {} documents, {} were expected.",
+                        field_name,
+                        fieldnorm_reader.num_docs(),
+                        max_doc
+                    ));
+                }
+                Ok(_) => {}
+                Err(err) => errors.push(format!(
+                    "Failed to open the field norms of {:?}: {}",
+                    field_name, err
+                )),
+            }
+            if let Err(err) = validate_postings(segment_reader, field) {
+                errors.push(format!(
+                    "The postings of {:?} are invalid: {}",
+                    field_name, err
+                ));
+            }
+        }
+        if !holds_fast_field_values(segment_reader, field, max_doc) {
+            errors.push(format!(
+                "The fast field {:?} does not hold the values of {} documents.",
+                field_name, max_doc
+            ));
+        }
+    }
+    errors
+}
+
+/// Checks that the postings of each term have the document frequency
+/// recorded in the term dictionary, and only refer to documents of the segment.
+fn validate_postings(segment_reader: &SegmentReader, field: Field) -> crate::Result<()> {
+    let max_doc = segment_reader.max_doc();
+    let inverted_index = segment_reader.inverted_index(field)?;
+    let mut term_stream = inverted_index.terms().stream()?;
+    while term_stream.advance() {
+        let term_info = term_stream.value();
+        let mut block_postings = inverted_index
+            .read_block_postings_from_terminfo(term_info, IndexRecordOption::Basic)?;
+        let mut doc_freq = 0u32;
+        loop {
+            let docs = block_postings.docs();
+            if docs.is_empty() {
+                break;
+            }
+            doc_freq += docs.len() as u32;
+            if docs[docs.len() - 1] >= max_doc {
+                return Err(crate::TantivyError::InvalidArgument(format!(
+                    "the term {:?} refers to the document {}, but the segment has {} documents",
+                    term_stream.key(),
+                    docs[docs.len() - 1],
+                    max_doc
+                )));
+            }
+            block_postings.advance();
+        }
+        if doc_freq != term_info.doc_freq {
+            return Err(crate::TantivyError::InvalidArgument(format!(
+                "the term {:?} has {} documents, {} were expected",
+                term_stream.key(),
+                doc_freq,
+                term_info.doc_freq
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn holds_fast_field_values(segment_reader: &SegmentReader, field: Field, max_doc: DocId) -> bool {
+    let fast_fields = segment_reader.fast_fields();
+    let field_type = segment_reader.schema().get_field_entry(field).field_type();
+    if let FieldType::Bytes(_) = field_type {
+        return fast_fields
+            .bytes(field)
+            .map(|bytes_reader| bytes_reader.holds_num_docs(max_doc))
+            .unwrap_or(true);
+    }
+    if let Some(fast_field_reader) = fast_fields.u64_lenient(field) {
+        return fast_field_reader.holds_num_vals(u64::from(max_doc));
+    }
+    if let Some(multivalued_reader) = fast_fields.u64s_lenient(field) {
+        return multivalued_reader.holds_num_docs(max_doc);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::SegmentComponent;
+    use crate::directory::{Directory, RAMDirectory};
+    use crate::schema::{Cardinality, Facet, IntOptions, Schema, FAST, INDEXED, STORED, TEXT};
+    use crate::{Index, Term};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_validate_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let rank = schema_builder.add_u64_field("rank", FAST | INDEXED);
+        let scores = schema_builder.add_i64_field(
+            "scores",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let bytes = schema_builder.add_bytes_field("bytes", FAST);
+        let facet = schema_builder.add_facet_field("facet");
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..300u64 {
+            index_writer.add_document(doc!(
+                title => format!("doc{} sea", i),
+                rank => i,
+                scores => -(i as i64),
+                scores => i as i64,
+                bytes => vec![i as u8; (i % 5) as usize],
+                facet => Facet::from(&format!("/cat/{}", i % 3)),
+            ));
+        }
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "mountain"));
+        index_writer.delete_term(Term::from_field_text(title, "doc3"));
+        index_writer.commit()?;
+
+        let report = index.validate()?;
+        assert!(report.is_valid(), "{:?}", report);
+        assert!(report.damaged_segments().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_and_repair_corrupted_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let directory = RAMDirectory::create();
+        let index = Index::create(directory.clone(), schema_builder.build())?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "sea"));
+        index_writer.commit()?;
+        let healthy_segment_id = index.searchable_segment_ids()?[0];
+        index_writer.add_document(doc!(title => "sea mountain"));
+        index_writer.add_document(doc!(title => "mountain"));
+        index_writer.commit()?;
+        let corrupted_segment = index
+            .searchable_segments()?
+            .into_iter()
+            .find(|segment| segment.id() != healthy_segment_id)
+            .unwrap();
+        let store_path = corrupted_segment
+            .meta()
+            .relative_path(SegmentComponent::STORE);
+        let mut store_data = directory.open_read(&store_path)?.read_bytes()?.to_vec();
+        store_data[0] ^= 1;
+        directory.atomic_write(&store_path, &store_data)?;
+
+        let report = index.validate()?;
+        assert!(!report.is_valid());
+        assert_eq!(report.segment_errors.len(), 1);
+        assert!(report.segment_errors[0].message.contains("checksum"));
+        assert_eq!(
+            report.damaged_segments().into_iter().collect::<Vec<_>>(),
+            vec![corrupted_segment.id()]
+        );
+
+        // The index cannot be repaired while an `IndexWriter` is alive.
+        assert!(matches!(
+            index.repair(),
+            Err(crate::TantivyError::LockFailure(..))
+        ));
+        drop(index_writer);
+        let report = index.repair()?;
+        assert_eq!(report.segment_errors.len(), 1);
+        assert_eq!(index.searchable_segment_ids()?, vec![healthy_segment_id]);
+        assert!(index.validate()?.is_valid());
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        let quarantined_path = PathBuf::from(format!(".quarantine.{}", store_path.display()));
+        assert_eq!(
+            directory
+                .open_read(&quarantined_path)?
+                .read_bytes()?
+                .as_slice(),
+            &store_data[..]
+        );
+        Ok(())
+    }
+}
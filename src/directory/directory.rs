@@ -17,7 +17,7 @@ use std::time::Duration;
 /// Retry the logic of acquiring locks is pretty simple.
 /// We just retry `n` times after a given `duratio`, both
 /// depending on the type of lock.
-struct RetryPolicy {
+pub(crate) struct RetryPolicy {
     num_retries: usize,
     wait_in_ms: u64,
 }
@@ -30,7 +30,7 @@ impl RetryPolicy {
         }
     }
 
-    fn wait_and_retry(&mut self) -> bool {
+    pub(crate) fn wait_and_retry(&mut self) -> bool {
         if self.num_retries == 0 {
             false
         } else {
@@ -88,7 +88,7 @@ fn try_acquire_lock(
     })))
 }
 
-fn retry_policy(is_blocking: bool) -> RetryPolicy {
+pub(crate) fn retry_policy(is_blocking: bool) -> RetryPolicy {
     if is_blocking {
         RetryPolicy {
             num_retries: 100,
@@ -2,6 +2,8 @@ use stable_deref_trait::StableDeref;
 
 use crate::common::HasLen;
 use crate::directory::OwnedBytes;
+#[cfg(feature = "async-read")]
+use futures::future::{self, BoxFuture, FutureExt};
 use std::sync::{Arc, Weak};
 use std::{io, ops::Deref};
 
@@ -21,6 +23,21 @@ pub trait FileHandle: 'static + Send + Sync + HasLen {
     ///
     /// This method may panic if the range requested is invalid.
     fn read_bytes(&self, from: usize, to: usize) -> io::Result<OwnedBytes>;
+
+    /// Reads a slice of bytes asynchronously.
+    ///
+    /// Directories relying on a remote storage should override this method,
+    /// in order to allow their users to overlap several reads.
+    ///
+    /// The default implementation simply runs the blocking `read_bytes`.
+    ///
+    /// For the moment, only the doc store reads through this method
+    /// (see `Searcher::doc_async`). Opening segments and searching them
+    /// (term dictionary, postings, fast fields) still use the blocking `read_bytes`.
+    #[cfg(feature = "async-read")]
+    fn read_bytes_async(&self, from: usize, to: usize) -> BoxFuture<'_, io::Result<OwnedBytes>> {
+        future::ready(self.read_bytes(from, to)).boxed()
+    }
 }
 
 impl FileHandle for &'static [u8] {
@@ -116,6 +133,29 @@ impl FileSlice {
         self.data.read_bytes(self.start + from, self.start + to)
     }
 
+    /// Returns a `OwnedBytes` with all of the data in the `FileSlice`,
+    /// without blocking the current thread if the underlying `FileHandle`
+    /// supports asynchronous reads.
+    #[cfg(feature = "async-read")]
+    pub async fn read_bytes_async(&self) -> io::Result<OwnedBytes> {
+        self.data.read_bytes_async(self.start, self.stop).await
+    }
+
+    /// Reads a specific slice of data asynchronously.
+    ///
+    /// This is equivalent to running `file_slice.slice(from, to).read_bytes_async()`.
+    #[cfg(feature = "async-read")]
+    pub async fn read_bytes_slice_async(&self, from: usize, to: usize) -> io::Result<OwnedBytes> {
+        assert!(from <= to);
+        assert!(
+            self.start + to <= self.stop,
+            "`to` exceeds the fileslice length"
+        );
+        self.data
+            .read_bytes_async(self.start + from, self.start + to)
+            .await
+    }
+
     /// Splits the FileSlice at the given offset and return two file slices.
     /// `file_slice[..split_offset]` and `file_slice[split_offset..]`.
     ///
@@ -0,0 +1,478 @@
+use crate::common::{BinarySerializable, HasLen, VInt};
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, WritePtr};
+use crate::directory::{WatchCallback, WatchHandle};
+use once_cell::sync::OnceCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Length of a file and byte ranges of it that are available in the hotcache.
+struct CachedFile {
+    len: usize,
+    /// Maps the start offset of each cached slice to its bytes.
+    slices: BTreeMap<usize, OwnedBytes>,
+}
+
+impl CachedFile {
+    fn get(&self, from: usize, to: usize) -> Option<OwnedBytes> {
+        let (start, bytes) = self.slices.range(..=from).next_back()?;
+        if to <= start + bytes.len() {
+            Some(bytes.slice(from - start, to - start))
+        } else {
+            None
+        }
+    }
+}
+
+/// Byte ranges that were read so far, for each file.
+///
+/// Ranges are only recorded while warming, that is until the hotcache is written.
+/// This keeps the recorder from growing for the whole lifetime of the directory.
+struct ReadRecorder {
+    files: Mutex<Option<HashMap<PathBuf, Vec<Range<usize>>>>>,
+}
+
+impl ReadRecorder {
+    fn new(recording: bool) -> ReadRecorder {
+        ReadRecorder {
+            files: Mutex::new(if recording {
+                Some(HashMap::new())
+            } else {
+                None
+            }),
+        }
+    }
+
+    fn record(&self, path: &Path, range: Range<usize>) {
+        if range.start == range.end {
+            return;
+        }
+        let mut files = self
+            .files
+            .lock()
+            .expect("Hot directory recorder lock poisoned.");
+        if let Some(files) = files.as_mut() {
+            files.entry(path.to_path_buf()).or_default().push(range);
+        }
+    }
+
+    /// Stops recording, and returns the recorded ranges for each file, sorted and merged.
+    fn stop(&self) -> Vec<(PathBuf, Vec<Range<usize>>)> {
+        let files = self
+            .files
+            .lock()
+            .expect("Hot directory recorder lock poisoned.")
+            .take()
+            .unwrap_or_default();
+        let mut merged_files: Vec<(PathBuf, Vec<Range<usize>>)> = files
+            .into_iter()
+            .map(|(path, ranges)| (path, merge_ranges(ranges)))
+            .collect();
+        merged_files.sort_by(|left, right| left.0.cmp(&right.0));
+        merged_files
+    }
+}
+
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged_ranges: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last_range) = merged_ranges.last_mut() {
+            if range.start <= last_range.end {
+                last_range.end = last_range.end.max(range.end);
+                continue;
+            }
+        }
+        merged_ranges.push(range);
+    }
+    merged_ranges
+}
+
+/// Parses a hotcache, as written by `HotDirectory::write_hotcache`.
+///
+/// The cached slices are not copied and point directly into `hotcache`.
+fn parse_hotcache(mut hotcache: OwnedBytes) -> io::Result<HashMap<PathBuf, CachedFile>> {
+    let mut cached_files = HashMap::new();
+    let num_files = VInt::deserialize(&mut hotcache)?.val();
+    for _ in 0..num_files {
+        let path = PathBuf::from(String::deserialize(&mut hotcache)?);
+        let len = VInt::deserialize(&mut hotcache)?.val() as usize;
+        let num_slices = VInt::deserialize(&mut hotcache)?.val();
+        let mut cached_file = CachedFile {
+            len,
+            slices: BTreeMap::new(),
+        };
+        for _ in 0..num_slices {
+            let start = VInt::deserialize(&mut hotcache)?.val() as usize;
+            let len = VInt::deserialize(&mut hotcache)?.val() as usize;
+            if len > hotcache.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Hotcache is truncated.",
+                ));
+            }
+            let (bytes, rest) = hotcache.split(len);
+            hotcache = rest;
+            cached_file.slices.insert(start, bytes);
+        }
+        cached_files.insert(path, cached_file);
+    }
+    Ok(cached_files)
+}
+
+struct InnerHotDirectory {
+    underlying: Box<dyn Directory>,
+    cache: RwLock<HashMap<PathBuf, Arc<CachedFile>>>,
+    recorder: ReadRecorder,
+}
+
+/// Directory wrapper that serves the most critical byte ranges
+/// of the index files from a "hotcache".
+///
+/// Opening an index and running a first query typically requires a lot of small
+/// random reads (footers, term dictionary roots, fast field headers, ...).
+/// On slow or remote directories, these reads dominate cold start latencies.
+///
+/// A `HotDirectory` created with `wrap` records all of the byte ranges that are
+/// read through it. After warming the index (opening a reader, running a few
+/// representative queries), `write_hotcache` serializes these ranges, together
+/// with their content and the length of their files, in a single compact blob.
+/// Recording stops once the hotcache is written.
+///
+/// A `HotDirectory` opened with this hotcache then serves the reads that fall
+/// in these byte ranges from memory. The files of the underlying directory are
+/// only opened on the first read that is not in the hotcache, so that opening
+/// an index and running the warmed queries does not touch the underlying directory.
+/// Such a directory does not record the byte ranges read through it.
+///
+/// The hotcache is only valid as long as the files of the index are not modified.
+/// Since segment files are immutable, it should be regenerated after each commit.
+#[derive(Clone)]
+pub struct HotDirectory {
+    inner: Arc<InnerHotDirectory>,
+}
+
+impl HotDirectory {
+    /// Wraps a directory, with an empty hotcache.
+    ///
+    /// All reads are forwarded to the underlying directory and recorded,
+    /// until the hotcache is written.
+    pub fn wrap<D: Directory>(underlying: D) -> HotDirectory {
+        HotDirectory::with_cache(Box::new(underlying), HashMap::new(), true)
+    }
+
+    /// Wraps a directory, serving the byte ranges available in the
+    /// given hotcache from memory.
+    pub fn open<D: Directory>(underlying: D, hotcache: OwnedBytes) -> io::Result<HotDirectory> {
+        let cache = parse_hotcache(hotcache)?
+            .into_iter()
+            .map(|(path, cached_file)| (path, Arc::new(cached_file)))
+            .collect();
+        Ok(HotDirectory::with_cache(Box::new(underlying), cache, false))
+    }
+
+    fn with_cache(
+        underlying: Box<dyn Directory>,
+        cache: HashMap<PathBuf, Arc<CachedFile>>,
+        recording: bool,
+    ) -> HotDirectory {
+        HotDirectory {
+            inner: Arc::new(InnerHotDirectory {
+                underlying,
+                cache: RwLock::new(cache),
+                recorder: ReadRecorder::new(recording),
+            }),
+        }
+    }
+
+    /// Serializes the byte ranges that have been read through this directory
+    /// so far, along with their content, and stops recording.
+    ///
+    /// The resulting bytes can then be passed to `HotDirectory::open`.
+    /// The hotcache written by a directory that is not recording is empty.
+    pub fn write_hotcache<W: Write>(&self, wrt: &mut W) -> crate::Result<()> {
+        let files = self.inner.recorder.stop();
+        VInt(files.len() as u64).serialize(wrt)?;
+        for (path, ranges) in files {
+            path.to_string_lossy().to_string().serialize(wrt)?;
+            let file_slice = self.open_read(&path)?;
+            VInt(file_slice.len() as u64).serialize(wrt)?;
+            VInt(ranges.len() as u64).serialize(wrt)?;
+            for range in ranges {
+                let bytes = file_slice.read_bytes_slice(range.start, range.end)?;
+                VInt(range.start as u64).serialize(wrt)?;
+                VInt(bytes.len() as u64).serialize(wrt)?;
+                wrt.write_all(bytes.as_slice())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HotDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HotDirectory({:?})", self.inner.underlying)
+    }
+}
+
+struct HotFileHandle {
+    path: PathBuf,
+    len: usize,
+    directory: Arc<InnerHotDirectory>,
+    /// The file handle of the underlying directory, opened on the first cache miss.
+    underlying: OnceCell<Box<dyn FileHandle>>,
+    cached_file: Option<Arc<CachedFile>>,
+}
+
+impl HotFileHandle {
+    fn cached_bytes(&self, from: usize, to: usize) -> Option<OwnedBytes> {
+        self.cached_file
+            .as_ref()
+            .and_then(|cached_file| cached_file.get(from, to))
+    }
+
+    fn underlying(&self) -> io::Result<&dyn FileHandle> {
+        let underlying = self.underlying.get_or_try_init(|| {
+            self.directory
+                .underlying
+                .get_file_handle(&self.path)
+                .map_err(io::Error::other)
+        })?;
+        Ok(underlying.as_ref())
+    }
+}
+
+impl HasLen for HotFileHandle {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl FileHandle for HotFileHandle {
+    fn read_bytes(&self, from: usize, to: usize) -> io::Result<OwnedBytes> {
+        self.directory.recorder.record(&self.path, from..to);
+        if let Some(bytes) = self.cached_bytes(from, to) {
+            return Ok(bytes);
+        }
+        self.underlying()?.read_bytes(from, to)
+    }
+
+    #[cfg(feature = "async-read")]
+    fn read_bytes_async(
+        &self,
+        from: usize,
+        to: usize,
+    ) -> futures::future::BoxFuture<'_, io::Result<OwnedBytes>> {
+        use futures::future::{self, FutureExt};
+        self.directory.recorder.record(&self.path, from..to);
+        if let Some(bytes) = self.cached_bytes(from, to) {
+            return future::ready(Ok(bytes)).boxed();
+        }
+        match self.underlying() {
+            Ok(underlying) => underlying.read_bytes_async(from, to),
+            Err(err) => future::ready(Err(err)).boxed(),
+        }
+    }
+}
+
+impl Directory for HotDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Box<dyn FileHandle>, OpenReadError> {
+        let cached_file = self
+            .inner
+            .cache
+            .read()
+            .expect("Hot directory cache lock poisoned.")
+            .get(path)
+            .cloned();
+        let underlying = OnceCell::new();
+        let len = if let Some(cached_file) = cached_file.as_ref() {
+            cached_file.len
+        } else {
+            let file_handle = self.inner.underlying.get_file_handle(path)?;
+            let len = file_handle.len();
+            let _ = underlying.set(file_handle);
+            len
+        };
+        Ok(Box::new(HotFileHandle {
+            path: path.to_path_buf(),
+            len,
+            directory: self.inner.clone(),
+            underlying,
+            cached_file,
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.inner
+            .cache
+            .write()
+            .expect("Hot directory cache lock poisoned.")
+            .remove(path);
+        self.inner.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.inner.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        self.inner.underlying.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.inner.underlying.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.inner.underlying.atomic_write(path, data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.inner.underlying.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.inner.underlying.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_ranges, HotDirectory};
+    use crate::collector::Count;
+    use crate::directory::{Directory, OwnedBytes, RAMDirectory};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Index, Term};
+    use std::path::Path;
+
+    #[test]
+    fn test_merge_ranges() {
+        assert!(merge_ranges(Vec::new()).is_empty());
+        assert_eq!(merge_ranges(vec![5..6, 1..3, 2..4]), vec![1..4, 5..6]);
+        assert_eq!(merge_ranges(vec![1..3, 3..4, 2..3]), vec![1..4]);
+    }
+
+    #[test]
+    fn test_hot_directory_serves_from_cache() -> crate::Result<()> {
+        let ram_directory = RAMDirectory::create();
+        ram_directory.atomic_write(Path::new("toto"), b"hello happy tax payer")?;
+        let hot_directory = HotDirectory::wrap(ram_directory.clone());
+        let file_handle = hot_directory.get_file_handle(Path::new("toto")).unwrap();
+        assert_eq!(file_handle.read_bytes(6, 11)?.as_slice(), b"happy");
+        let mut hotcache = vec![];
+        hot_directory.write_hotcache(&mut hotcache)?;
+
+        // We replace the file with another file with the same length.
+        ram_directory.delete(Path::new("toto")).unwrap();
+        ram_directory.atomic_write(Path::new("toto"), b"HELLO HAPPY TAX PAYER")?;
+        let hot_directory = HotDirectory::open(ram_directory, OwnedBytes::new(hotcache))?;
+        let file_handle = hot_directory.get_file_handle(Path::new("toto")).unwrap();
+        assert_eq!(file_handle.read_bytes(6, 11)?.as_slice(), b"happy");
+        assert_eq!(file_handle.read_bytes(7, 9)?.as_slice(), b"ap");
+        assert_eq!(file_handle.read_bytes(0, 5)?.as_slice(), b"HELLO");
+        assert_eq!(file_handle.read_bytes(6, 12)?.as_slice(), b"HAPPY ");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hot_directory_records_only_while_warming() -> crate::Result<()> {
+        let ram_directory = RAMDirectory::create();
+        ram_directory.atomic_write(Path::new("toto"), b"hello happy tax payer")?;
+        let hot_directory = HotDirectory::wrap(ram_directory.clone());
+        let file_handle = hot_directory.get_file_handle(Path::new("toto")).unwrap();
+        file_handle.read_bytes(6, 11)?;
+        let mut hotcache = vec![];
+        hot_directory.write_hotcache(&mut hotcache)?;
+        assert!(!hotcache.is_empty());
+
+        // Recording stopped when the hotcache was written.
+        file_handle.read_bytes(0, 5)?;
+        let mut second_hotcache = vec![];
+        hot_directory.write_hotcache(&mut second_hotcache)?;
+        assert_eq!(second_hotcache, vec![128u8]);
+
+        // A directory opened with a hotcache does not record anything.
+        let hot_directory = HotDirectory::open(ram_directory, OwnedBytes::new(hotcache))?;
+        let file_handle = hot_directory.get_file_handle(Path::new("toto")).unwrap();
+        file_handle.read_bytes(0, 5)?;
+        let mut third_hotcache = vec![];
+        hot_directory.write_hotcache(&mut third_hotcache)?;
+        assert_eq!(third_hotcache, vec![128u8]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hot_directory_opens_underlying_file_lazily() -> crate::Result<()> {
+        let ram_directory = RAMDirectory::create();
+        ram_directory.atomic_write(Path::new("toto"), b"hello happy tax payer")?;
+        let hot_directory = HotDirectory::wrap(ram_directory.clone());
+        let file_handle = hot_directory.get_file_handle(Path::new("toto")).unwrap();
+        assert_eq!(file_handle.read_bytes(6, 11)?.as_slice(), b"happy");
+        let mut hotcache = vec![];
+        hot_directory.write_hotcache(&mut hotcache)?;
+
+        // The file is removed from the underlying directory:
+        // only the reads served by the hotcache succeed.
+        ram_directory.delete(Path::new("toto")).unwrap();
+        let hot_directory = HotDirectory::open(ram_directory, OwnedBytes::new(hotcache))?;
+        assert!(!hot_directory.exists(Path::new("toto"))?);
+        let file_handle = hot_directory.get_file_handle(Path::new("toto")).unwrap();
+        assert_eq!(file_handle.len(), 21);
+        assert_eq!(file_handle.read_bytes(6, 11)?.as_slice(), b"happy");
+        assert!(file_handle.read_bytes(0, 5).is_err());
+        assert!(hot_directory.get_file_handle(Path::new("titi")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hot_directory_delete_evicts_cached_file() -> crate::Result<()> {
+        let ram_directory = RAMDirectory::create();
+        ram_directory.atomic_write(Path::new("toto"), b"hello happy tax payer")?;
+        let hot_directory = HotDirectory::wrap(ram_directory.clone());
+        let file_handle = hot_directory.get_file_handle(Path::new("toto")).unwrap();
+        file_handle.read_bytes(6, 11)?;
+        let mut hotcache = vec![];
+        hot_directory.write_hotcache(&mut hotcache)?;
+
+        let hot_directory = HotDirectory::open(ram_directory, OwnedBytes::new(hotcache))?;
+        assert!(hot_directory.exists(Path::new("toto"))?);
+        hot_directory.delete(Path::new("toto")).unwrap();
+        assert!(!hot_directory.exists(Path::new("toto"))?);
+        assert!(hot_directory.get_file_handle(Path::new("toto")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hot_directory_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let ram_directory = RAMDirectory::create();
+        let index = Index::create(ram_directory.clone(), schema_builder.build())?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"hello happy tax payer"));
+        index_writer.add_document(doc!(text_field=>"hello"));
+        index_writer.commit()?;
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "hello"),
+            IndexRecordOption::Basic,
+        );
+
+        let hot_directory = HotDirectory::wrap(ram_directory.clone());
+        let warmed_index = Index::open(hot_directory.clone())?;
+        let searcher = warmed_index.reader()?.searcher();
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        let mut hotcache = vec![];
+        hot_directory.write_hotcache(&mut hotcache)?;
+
+        let hot_directory = HotDirectory::open(ram_directory, OwnedBytes::new(hotcache))?;
+        let index = Index::open(hot_directory)?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,587 @@
+use crate::directory::directory::retry_policy;
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{Directory, DirectoryLock, FileHandle, FileSlice, Lock, WritePtr};
+use crate::directory::{TerminatingWrite, WatchCallback, WatchHandle};
+use crossbeam::channel::{self, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+/// Content of a lock file.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Lease {
+    /// Identifies the `DirectoryLock` holding the lease.
+    owner: Uuid,
+    /// Last time the owner renewed its lease, in milliseconds since epoch.
+    heartbeat_ms: u64,
+}
+
+impl Lease {
+    fn new(owner: Uuid) -> Lease {
+        Lease {
+            owner,
+            heartbeat_ms: now_ms(),
+        }
+    }
+
+    fn is_expired(&self, lease_duration: Duration) -> bool {
+        self.heartbeat_ms + (lease_duration.as_millis() as u64) < now_ms()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Creates the lock file of a new lease.
+///
+/// The lock file is created with `open_write`, which fails if the file already exists,
+/// so that at most one of the processes trying to create the same lease succeeds.
+/// It returns `LockBusy` for the others.
+fn create_lease(directory: &dyn Directory, path: &Path, owner: Uuid) -> Result<(), LockError> {
+    let lease_json = serde_json::to_vec(&Lease::new(owner))
+        .map_err(|err| LockError::IOError(io::Error::from(err)))?;
+    let mut wrt = directory.open_write(path).map_err(|err| match err {
+        OpenWriteError::FileAlreadyExists(_) => LockError::LockBusy,
+        OpenWriteError::IOError { io_error, .. } => LockError::IOError(io_error),
+    })?;
+    let write_res = wrt.write_all(&lease_json).and_then(|()| wrt.terminate());
+    if let Err(io_error) = write_res {
+        // Otherwise, the empty lock file would be considered busy forever.
+        let _ = directory.delete(path);
+        return Err(LockError::IOError(io_error));
+    }
+    Ok(())
+}
+
+/// Reads the lease associated to the lock file.
+///
+/// An empty lock file is being created by `create_lease`, and is reported as `LockBusy`.
+/// A lock file that cannot be deserialized (created by another kind of lock
+/// for instance) is reported as an `InvalidData` error.
+fn read_lease(directory: &dyn Directory, path: &Path) -> Result<Option<Lease>, LockError> {
+    match directory.atomic_read(path) {
+        Ok(ref data) if data.is_empty() => Err(LockError::LockBusy),
+        Ok(data) => serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|err| LockError::IOError(io::Error::new(io::ErrorKind::InvalidData, err))),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(None),
+        Err(OpenReadError::IOError { io_error, .. }) => Err(LockError::IOError(io_error)),
+        Err(OpenReadError::IncompatibleIndex(_)) => Err(LockError::IOError(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Lock file is not a lease.",
+        ))),
+    }
+}
+
+/// Renews the lease held by `owner`.
+///
+/// Returns false if the lease was lost, i.e. it is now held by someone else
+/// or does not exist anymore. In that case, the lease is left untouched.
+fn renew_lease(directory: &dyn Directory, path: &Path, owner: Uuid) -> bool {
+    match read_lease(directory, path) {
+        Ok(Some(ref lease)) if lease.owner == owner => {}
+        _ => {
+            error!("Lease {:?} was lost.", path);
+            return false;
+        }
+    }
+    let lease_json = serde_json::to_vec(&Lease::new(owner)).map_err(io::Error::from);
+    if let Err(err) = lease_json.and_then(|lease_json| directory.atomic_write(path, &lease_json)) {
+        error!("Failed to renew lease {:?}: {:?}", path, err);
+    }
+    true
+}
+
+/// A lease held through a `LeaseLockDirectory`.
+struct HeldLease {
+    path: PathBuf,
+    /// Set when the heartbeat finds the lease held by someone else, or removed.
+    lost: bool,
+}
+
+/// Leases held through a `LeaseLockDirectory` and its clones.
+struct HeldLeases {
+    directory: Box<dyn Directory>,
+    leases: Mutex<HashMap<Uuid, HeldLease>>,
+}
+
+impl HeldLeases {
+    fn leases(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, HeldLease>> {
+        self.leases.lock().expect("Lease lock poisoned.")
+    }
+
+    /// Renews all of the leases that have not been lost, and flags the ones that were.
+    fn renew(&self) {
+        let leases_to_renew: Vec<(Uuid, PathBuf)> = self
+            .leases()
+            .iter()
+            .filter(|(_, lease)| !lease.lost)
+            .map(|(owner, lease)| (*owner, lease.path.clone()))
+            .collect();
+        for (owner, path) in leases_to_renew {
+            if !renew_lease(&*self.directory, &path, owner) {
+                if let Some(lease) = self.leases().get_mut(&owner) {
+                    lease.lost = true;
+                }
+            }
+        }
+    }
+
+    /// Returns an error if one of the leases currently held was lost.
+    fn check(&self) -> io::Result<()> {
+        if let Some(lease) = self.leases().values().find(|lease| lease.lost) {
+            return Err(io::Error::other(format!(
+                "Lease {:?} was lost.",
+                lease.path
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Renews the leases of a `LeaseLockDirectory` from a single heartbeat thread,
+/// started on the first lease acquired.
+struct LeaseRenewer {
+    held_leases: Arc<HeldLeases>,
+    heartbeat_period: Duration,
+    // Dropping the sender stops the heartbeat thread.
+    heartbeat: Mutex<Option<(Sender<()>, JoinHandle<()>)>>,
+}
+
+impl LeaseRenewer {
+    fn new(directory: Box<dyn Directory>, lease_duration: Duration) -> LeaseRenewer {
+        LeaseRenewer {
+            held_leases: Arc::new(HeldLeases {
+                directory,
+                leases: Mutex::new(HashMap::new()),
+            }),
+            heartbeat_period: lease_duration / 3,
+            heartbeat: Mutex::new(None),
+        }
+    }
+
+    fn register(&self, path: PathBuf, owner: Uuid) -> io::Result<()> {
+        let mut heartbeat = self.heartbeat.lock().expect("Lease lock poisoned.");
+        if heartbeat.is_none() {
+            let (stop_heartbeat, stop_heartbeat_receiver) = channel::bounded::<()>(0);
+            let held_leases = self.held_leases.clone();
+            let heartbeat_period = self.heartbeat_period;
+            let heartbeat_thread = thread::Builder::new()
+                .name("thread-tantivy-lease-heartbeat".to_string())
+                .spawn(move || loop {
+                    match stop_heartbeat_receiver.recv_timeout(heartbeat_period) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                            return;
+                        }
+                    }
+                    held_leases.renew();
+                })?;
+            *heartbeat = Some((stop_heartbeat, heartbeat_thread));
+        }
+        self.held_leases
+            .leases()
+            .insert(owner, HeldLease { path, lost: false });
+        Ok(())
+    }
+}
+
+impl Drop for LeaseRenewer {
+    fn drop(&mut self) {
+        let heartbeat = self
+            .heartbeat
+            .get_mut()
+            .expect("Lease lock poisoned.")
+            .take();
+        if let Some((stop_heartbeat, heartbeat_thread)) = heartbeat {
+            drop(stop_heartbeat);
+            let _ = heartbeat_thread.join();
+        }
+    }
+}
+
+/// Stops renewing the lease when the associated `DirectoryLock` is dropped.
+struct LeaseGuard {
+    renewer: Arc<LeaseRenewer>,
+    path: PathBuf,
+    owner: Uuid,
+}
+
+impl Drop for LeaseGuard {
+    fn drop(&mut self) {
+        let held_leases = &self.renewer.held_leases;
+        held_leases.leases().remove(&self.owner);
+        // The lease may have expired and been acquired by someone else in the meantime.
+        match read_lease(&*held_leases.directory, &self.path) {
+            Ok(Some(ref lease)) if lease.owner == self.owner => {}
+            _ => {
+                return;
+            }
+        }
+        if let Err(e) = held_leases.directory.delete(&self.path) {
+            error!("Failed to remove the lock file. {:?}", e);
+        }
+    }
+}
+
+/// Wrapper of a directory that implements locks as leases.
+///
+/// The default `Directory::acquire_lock` implementation relies on the existence
+/// of a lock file. If a process crashes while holding a lock, the lock file remains
+/// and needs to be removed manually.
+///
+/// With a `LeaseLockDirectory`, the lock file contains the identity of its owner
+/// and the last time it was renewed. While locks are held, a single background
+/// thread, shared by the directory and its clones, renews their leases every
+/// `lease_duration / 3`.
+/// A lock file that has not been renewed for more than `lease_duration` is considered
+/// stale, and can be acquired by someone else.
+///
+/// A lease is acquired by creating its lock file with `open_write`, which fails if the
+/// file already exists. A stale lease is removed before being acquired, provided its
+/// owner did not change since it was found stale. Two processes removing the same stale
+/// lease at the same time may still remove the lease just acquired by one of them.
+/// The heartbeat then finds the lease lost.
+///
+/// When a lease is lost, writes and deletes through the directory fail until the
+/// corresponding `DirectoryLock` is dropped, so that for instance an `IndexWriter`
+/// that lost its lock cannot commit anymore. `check_leases` reports lost leases as well.
+/// Distributed storages that offer a compare-and-swap operation should rather implement
+/// `Directory::acquire_lock` on top of it.
+pub struct LeaseLockDirectory {
+    underlying: Box<dyn Directory>,
+    lease_duration: Duration,
+    renewer: Arc<LeaseRenewer>,
+}
+
+impl LeaseLockDirectory {
+    /// Wraps a directory, using leases of the given duration for its locks.
+    pub fn wrap<D: Directory>(directory: D, lease_duration: Duration) -> LeaseLockDirectory {
+        let renewer = LeaseRenewer::new(directory.box_clone(), lease_duration);
+        LeaseLockDirectory {
+            underlying: Box::new(directory),
+            lease_duration,
+            renewer: Arc::new(renewer),
+        }
+    }
+
+    /// Returns the duration after which a lease that has not been renewed
+    /// is considered stale.
+    pub fn lease_duration(&self) -> Duration {
+        self.lease_duration
+    }
+
+    /// Returns an error if one of the leases held through this directory was lost,
+    /// that is acquired by someone else or removed while its lock was still held.
+    pub fn check_leases(&self) -> io::Result<()> {
+        self.renewer.held_leases.check()
+    }
+
+    fn try_acquire_lease(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        if let Some(stale_lease) = read_lease(&*self.underlying, &lock.filepath)? {
+            if !stale_lease.is_expired(self.lease_duration) {
+                return Err(LockError::LockBusy);
+            }
+            warn!(
+                "Lock {:?} has not been renewed since {}ms. Considering it stale.",
+                lock.filepath, stale_lease.heartbeat_ms
+            );
+            // Another process may have taken over the stale lease in the meantime.
+            match read_lease(&*self.underlying, &lock.filepath)? {
+                Some(ref lease) if lease.owner == stale_lease.owner => {}
+                _ => {
+                    return Err(LockError::LockBusy);
+                }
+            }
+            match self.underlying.delete(&lock.filepath) {
+                Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => {}
+                Err(DeleteError::IOError { io_error, .. }) => {
+                    return Err(LockError::IOError(io_error));
+                }
+            }
+        }
+        let owner = Uuid::new_v4();
+        create_lease(&*self.underlying, &lock.filepath, owner)?;
+        let lease_guard = LeaseGuard {
+            renewer: self.renewer.clone(),
+            path: lock.filepath.clone(),
+            owner,
+        };
+        self.renewer
+            .register(lock.filepath.clone(), owner)
+            .map_err(LockError::IOError)?;
+        Ok(DirectoryLock::from(Box::new(lease_guard)))
+    }
+}
+
+impl Clone for LeaseLockDirectory {
+    fn clone(&self) -> Self {
+        LeaseLockDirectory {
+            underlying: self.underlying.box_clone(),
+            lease_duration: self.lease_duration,
+            renewer: self.renewer.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for LeaseLockDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LeaseLock({:?})", self.underlying)
+    }
+}
+
+impl Directory for LeaseLockDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Box<dyn FileHandle>, OpenReadError> {
+        self.underlying.get_file_handle(path)
+    }
+
+    fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+        self.underlying.open_read(path)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.check_leases()
+            .map_err(|io_error| DeleteError::IOError {
+                io_error,
+                filepath: path.to_path_buf(),
+            })?;
+        self.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        self.check_leases()
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        self.underlying.open_write(path)
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.underlying.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.check_leases()?;
+        self.underlying.atomic_write(path, data)
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        let mut retry_policy = retry_policy(lock.is_blocking);
+        loop {
+            match self.try_acquire_lease(lock) {
+                Err(LockError::LockBusy) => {
+                    if !retry_policy.wait_and_retry() {
+                        return Err(LockError::LockBusy);
+                    }
+                }
+                result => {
+                    return result;
+                }
+            }
+        }
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.underlying.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_lease, renew_lease, Lease, LeaseLockDirectory};
+    use crate::directory::error::LockError;
+    use crate::directory::{Directory, Lock, RAMDirectory, INDEX_WRITER_LOCK};
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn test_lock() -> Lock {
+        Lock {
+            filepath: PathBuf::from(".test.lock"),
+            is_blocking: false,
+        }
+    }
+
+    #[test]
+    fn test_lease_lock_is_exclusive() {
+        let ram_directory = RAMDirectory::create();
+        let directory = LeaseLockDirectory::wrap(ram_directory.clone(), Duration::from_secs(10));
+        let lock = test_lock();
+        {
+            let _directory_lock = directory.acquire_lock(&lock).unwrap();
+            assert!(ram_directory.exists(&lock.filepath).unwrap());
+            assert!(matches!(
+                directory.acquire_lock(&lock),
+                Err(LockError::LockBusy)
+            ));
+        }
+        assert!(!ram_directory.exists(&lock.filepath).unwrap());
+        assert!(directory.acquire_lock(&lock).is_ok());
+    }
+
+    #[test]
+    fn test_lease_lock_stale_lease() {
+        let ram_directory = RAMDirectory::create();
+        let directory = LeaseLockDirectory::wrap(ram_directory.clone(), Duration::from_secs(10));
+        let lock = test_lock();
+        let stale_lease = Lease {
+            owner: Uuid::new_v4(),
+            heartbeat_ms: 0,
+        };
+        ram_directory
+            .atomic_write(&lock.filepath, &serde_json::to_vec(&stale_lease).unwrap())
+            .unwrap();
+        assert!(directory.acquire_lock(&lock).is_ok());
+    }
+
+    #[test]
+    fn test_lease_lock_renew_lease() {
+        let ram_directory = RAMDirectory::create();
+        let lease_duration = Duration::from_secs(10);
+        let directory = LeaseLockDirectory::wrap(ram_directory.clone(), lease_duration);
+        let lock = test_lock();
+        let owner = Uuid::new_v4();
+        let stale_lease = Lease {
+            owner,
+            heartbeat_ms: 0,
+        };
+        ram_directory
+            .atomic_write(&lock.filepath, &serde_json::to_vec(&stale_lease).unwrap())
+            .unwrap();
+        assert!(read_lease(&ram_directory, &lock.filepath)
+            .unwrap()
+            .unwrap()
+            .is_expired(lease_duration));
+
+        // Renewing the lease makes it valid again.
+        assert!(renew_lease(&ram_directory, &lock.filepath, owner));
+        let lease = read_lease(&ram_directory, &lock.filepath).unwrap().unwrap();
+        assert_eq!(lease.owner, owner);
+        assert!(!lease.is_expired(lease_duration));
+        assert!(matches!(
+            directory.acquire_lock(&lock),
+            Err(LockError::LockBusy)
+        ));
+
+        // A lease held by someone else is not renewed.
+        let other_owner = Uuid::new_v4();
+        assert!(!renew_lease(&ram_directory, &lock.filepath, other_owner));
+        assert_eq!(
+            read_lease(&ram_directory, &lock.filepath)
+                .unwrap()
+                .unwrap()
+                .owner,
+            owner
+        );
+        ram_directory.delete(&lock.filepath).unwrap();
+        assert!(!renew_lease(&ram_directory, &lock.filepath, owner));
+    }
+
+    #[test]
+    fn test_lease_lock_does_not_acquire_a_lease_being_created() {
+        let ram_directory = RAMDirectory::create();
+        let directory = LeaseLockDirectory::wrap(ram_directory.clone(), Duration::from_secs(10));
+        let lock = test_lock();
+        ram_directory.atomic_write(&lock.filepath, b"").unwrap();
+        assert!(matches!(
+            directory.acquire_lock(&lock),
+            Err(LockError::LockBusy)
+        ));
+        assert!(ram_directory
+            .atomic_read(&lock.filepath)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_lease_lock_renews_all_leases() {
+        let ram_directory = RAMDirectory::create();
+        let lease_duration = Duration::from_secs(10);
+        let directory = LeaseLockDirectory::wrap(ram_directory.clone(), lease_duration);
+        let first_lock = test_lock();
+        let second_lock = Lock {
+            filepath: PathBuf::from(".other.lock"),
+            is_blocking: false,
+        };
+        let _first_directory_lock = directory.acquire_lock(&first_lock).unwrap();
+        let _second_directory_lock = directory.clone().acquire_lock(&second_lock).unwrap();
+        // Both leases are renewed by the renewer shared by the directory and its clones.
+        assert_eq!(directory.renewer.held_leases.leases().len(), 2);
+        for lock in &[&first_lock, &second_lock] {
+            let mut lease = read_lease(&ram_directory, &lock.filepath).unwrap().unwrap();
+            lease.heartbeat_ms = 0;
+            ram_directory
+                .atomic_write(&lock.filepath, &serde_json::to_vec(&lease).unwrap())
+                .unwrap();
+        }
+        directory.renewer.held_leases.renew();
+        for lock in &[&first_lock, &second_lock] {
+            assert!(!read_lease(&ram_directory, &lock.filepath)
+                .unwrap()
+                .unwrap()
+                .is_expired(lease_duration));
+        }
+        assert!(directory.check_leases().is_ok());
+    }
+
+    #[test]
+    fn test_lease_lock_lost_lease() {
+        let ram_directory = RAMDirectory::create();
+        let directory = LeaseLockDirectory::wrap(ram_directory.clone(), Duration::from_secs(10));
+        let lock = test_lock();
+        let directory_lock = directory.acquire_lock(&lock).unwrap();
+
+        // Someone else takes over the lease.
+        let other_lease = Lease {
+            owner: Uuid::new_v4(),
+            heartbeat_ms: 0,
+        };
+        ram_directory
+            .atomic_write(&lock.filepath, &serde_json::to_vec(&other_lease).unwrap())
+            .unwrap();
+        directory.renewer.held_leases.renew();
+        assert!(directory.check_leases().is_err());
+        assert!(directory.atomic_write(Path::new("toto"), b"toto").is_err());
+        assert!(directory.open_write(Path::new("toto")).is_err());
+        assert!(!ram_directory.exists(Path::new("toto")).unwrap());
+
+        // The lease of the new owner is left untouched.
+        drop(directory_lock);
+        assert_eq!(
+            read_lease(&ram_directory, &lock.filepath).unwrap(),
+            Some(other_lease)
+        );
+        assert!(directory.check_leases().is_ok());
+        assert!(directory.atomic_write(Path::new("toto"), b"toto").is_ok());
+    }
+
+    #[test]
+    fn test_lease_lock_index_writer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let ram_directory = RAMDirectory::create();
+        let directory = LeaseLockDirectory::wrap(ram_directory.clone(), Duration::from_secs(10));
+        let index = Index::create(directory, schema_builder.build())?;
+        {
+            let _index_writer = index.writer_for_tests()?;
+            assert!(ram_directory.exists(&INDEX_WRITER_LOCK.filepath)?);
+            assert!(index.writer_for_tests().is_err());
+        }
+        assert!(index.writer_for_tests().is_ok());
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@ use crate::directory::{FileSlice, WritePtr};
 use crate::directory::{WatchCallback, WatchHandle};
 use crate::error::DataCorruption;
 use crate::Directory;
+use crate::Version;
 
 use crc32fast::Hasher;
 use std::collections::HashSet;
@@ -48,6 +49,8 @@ pub struct ManagedDirectory {
 #[derive(Debug, Default)]
 struct MetaInformation {
     managed_paths: HashSet<PathBuf>,
+    /// Files that were scheduled for deletion, but could not be deleted.
+    pending_deletes: HashSet<PathBuf>,
 }
 
 /// Saves the file containing the list of existing files
@@ -79,6 +82,7 @@ impl ManagedDirectory {
                     directory: Box::new(directory),
                     meta_informations: Arc::new(RwLock::new(MetaInformation {
                         managed_paths: managed_files,
+                        pending_deletes: HashSet::new(),
                     })),
                 })
             }
@@ -108,7 +112,8 @@ impl ManagedDirectory {
     /// This method does not panick nor returns errors.
     /// If a file cannot be deleted (for permission reasons for instance)
     /// an error is simply logged, and the file remains in the list of managed
-    /// files.
+    /// files. It is also added to the queue of pending deletes, which can be
+    /// retried at any time with `delete_pending_files`.
     pub fn garbage_collect<L: FnOnce() -> HashSet<PathBuf>>(
         &mut self,
         get_living_files: L,
@@ -154,6 +159,50 @@ impl ManagedDirectory {
             }
         }
 
+        self.delete_files(files_to_delete)
+    }
+
+    /// Retries deleting the files that could not be deleted during
+    /// a previous garbage collection.
+    ///
+    /// On Windows, a file cannot be deleted as long as it is memory mapped
+    /// or open. Files belonging to merged segments can therefore typically only
+    /// be deleted once the searchers using them have been dropped.
+    /// These files are known to be dead, so they can be deleted without
+    /// computing the list of living files again.
+    pub fn delete_pending_files(&self) -> crate::Result<GarbageCollectionResult> {
+        let pending_deletes: Vec<PathBuf> = self
+            .meta_informations
+            .read()
+            .expect("Managed directory rlock poisoned in delete pending files.")
+            .pending_deletes
+            .iter()
+            .cloned()
+            .collect();
+        if pending_deletes.is_empty() {
+            return Ok(GarbageCollectionResult {
+                deleted_files: Vec::new(),
+                failed_to_delete_files: Vec::new(),
+            });
+        }
+        self.delete_files(pending_deletes)
+    }
+
+    /// Returns the files that are waiting to be deleted.
+    ///
+    /// See `delete_pending_files`.
+    pub fn pending_deletes(&self) -> HashSet<PathBuf> {
+        self.meta_informations
+            .read()
+            .expect("Managed directory rlock poisoned in pending deletes.")
+            .pending_deletes
+            .clone()
+    }
+
+    fn delete_files(
+        &self,
+        files_to_delete: Vec<PathBuf>,
+    ) -> crate::Result<GarbageCollectionResult> {
         let mut failed_to_delete_files = vec![];
         let mut deleted_files = vec![];
 
@@ -181,18 +230,25 @@ impl ManagedDirectory {
             }
         }
 
-        if !deleted_files.is_empty() {
-            // update the list of managed files by removing
-            // the file that were removed.
+        {
             let mut meta_informations_wlock = self
                 .meta_informations
                 .write()
                 .expect("Managed directory wlock poisoned (2).");
-            let managed_paths_write = &mut meta_informations_wlock.managed_paths;
-            for delete_file in &deleted_files {
-                managed_paths_write.remove(delete_file);
+            for failed_file in &failed_to_delete_files {
+                meta_informations_wlock
+                    .pending_deletes
+                    .insert(failed_file.clone());
+            }
+            if !deleted_files.is_empty() {
+                // update the list of managed files by removing
+                // the file that were removed.
+                for delete_file in &deleted_files {
+                    meta_informations_wlock.managed_paths.remove(delete_file);
+                    meta_informations_wlock.pending_deletes.remove(delete_file);
+                }
+                save_managed_paths(self.directory.as_ref(), &meta_informations_wlock)?;
             }
-            save_managed_paths(self.directory.as_mut(), &meta_informations_wlock)?;
         }
 
         Ok(GarbageCollectionResult {
@@ -228,6 +284,30 @@ impl ManagedDirectory {
         Ok(())
     }
 
+    /// Returns the list of the files that were created by tantivy
+    /// and have not been garbage collected yet.
+    ///
+    /// This includes the files of segments that are not referenced
+    /// by any commit anymore, but are still protected by a living
+    /// `SegmentMeta` (held by an `IndexReader` opened on a commit point
+    /// for instance).
+    pub fn list_managed_files(&self) -> HashSet<PathBuf> {
+        self.meta_informations
+            .read()
+            .expect("Managed directory rlock poisoned in list managed files.")
+            .managed_paths
+            .clone()
+    }
+
+    /// Returns the version of tantivy that wrote a managed file,
+    /// as recorded in its footer.
+    pub(crate) fn file_version(&self, path: &Path) -> result::Result<Version, OpenReadError> {
+        let reader = self.directory.open_read(path)?;
+        let (footer, _) = Footer::extract_footer(reader)
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(footer.version)
+    }
+
     /// Verify checksum of a managed file
     pub fn validate_checksum(&self, path: &Path) -> result::Result<bool, OpenReadError> {
         let reader = self.directory.open_read(path)?;
@@ -334,6 +414,98 @@ impl Clone for ManagedDirectory {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::ManagedDirectory;
+    use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+    use crate::directory::{Directory, DirectoryLock, FileHandle, Lock, RAMDirectory, WritePtr};
+    use crate::directory::{WatchCallback, WatchHandle};
+    use std::collections::HashSet;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    /// Directory refusing to delete files as long as `deletes_forbidden` is set,
+    /// the way Windows refuses to delete memory mapped files.
+    #[derive(Clone, Debug)]
+    struct StickyFilesDirectory {
+        underlying: RAMDirectory,
+        deletes_forbidden: Arc<AtomicBool>,
+    }
+
+    impl Directory for StickyFilesDirectory {
+        fn get_file_handle(&self, path: &Path) -> Result<Box<dyn FileHandle>, OpenReadError> {
+            self.underlying.get_file_handle(path)
+        }
+
+        fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+            if self.deletes_forbidden.load(Ordering::SeqCst) {
+                return Err(DeleteError::IOError {
+                    io_error: io::Error::from(io::ErrorKind::PermissionDenied),
+                    filepath: path.to_path_buf(),
+                });
+            }
+            self.underlying.delete(path)
+        }
+
+        fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+            self.underlying.exists(path)
+        }
+
+        fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+            self.underlying.open_write(path)
+        }
+
+        fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+            self.underlying.atomic_read(path)
+        }
+
+        fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+            self.underlying.atomic_write(path, data)
+        }
+
+        fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+            self.underlying.acquire_lock(lock)
+        }
+
+        fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+            self.underlying.watch(watch_callback)
+        }
+    }
+
+    #[test]
+    fn test_managed_directory_pending_deletes() -> crate::Result<()> {
+        let deletes_forbidden = Arc::new(AtomicBool::new(true));
+        let directory = StickyFilesDirectory {
+            underlying: RAMDirectory::create(),
+            deletes_forbidden: deletes_forbidden.clone(),
+        };
+        let mut managed_directory = ManagedDirectory::wrap(directory.clone())?;
+        let path = Path::new("dead_file");
+        managed_directory.atomic_write(path, b"dead")?;
+
+        let gc_result = managed_directory.garbage_collect(HashSet::new)?;
+        assert!(gc_result.deleted_files.is_empty());
+        assert_eq!(gc_result.failed_to_delete_files, vec![path.to_path_buf()]);
+        let expected_pending: HashSet<PathBuf> = vec![path.to_path_buf()].into_iter().collect();
+        assert_eq!(managed_directory.pending_deletes(), expected_pending);
+        assert!(managed_directory.list_managed_files().contains(path));
+
+        let retry_result = managed_directory.delete_pending_files()?;
+        assert_eq!(retry_result.failed_to_delete_files.len(), 1);
+        assert!(directory.exists(path)?);
+
+        deletes_forbidden.store(false, Ordering::SeqCst);
+        let retry_result = managed_directory.delete_pending_files()?;
+        assert_eq!(retry_result.deleted_files, vec![path.to_path_buf()]);
+        assert!(!directory.exists(path)?);
+        assert!(managed_directory.pending_deletes().is_empty());
+        assert!(!managed_directory.list_managed_files().contains(path));
+        Ok(())
+    }
+}
+
 #[cfg(feature = "mmap")]
 #[cfg(test)]
 mod tests_mmap_specific {
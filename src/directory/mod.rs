@@ -12,9 +12,12 @@ mod directory_lock;
 mod file_slice;
 mod file_watcher;
 mod footer;
+mod hot_directory;
+mod lease_lock_directory;
 mod managed_directory;
 mod owned_bytes;
 mod ram_directory;
+mod read_only_directory;
 mod watch_event_router;
 
 /// Errors specific to the directory module.
@@ -25,8 +28,11 @@ pub use self::directory::{Directory, DirectoryClone};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
 pub(crate) use self::file_slice::{ArcBytes, WeakArcBytes};
 pub use self::file_slice::{FileHandle, FileSlice};
+pub use self::hot_directory::HotDirectory;
+pub use self::lease_lock_directory::LeaseLockDirectory;
 pub use self::owned_bytes::OwnedBytes;
 pub use self::ram_directory::RAMDirectory;
+pub use self::read_only_directory::ReadOnlyDirectory;
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 use std::io::{self, BufWriter, Write};
 use std::path::PathBuf;
@@ -57,7 +63,7 @@ pub use self::managed_directory::ManagedDirectory;
 pub struct AntiCallToken(());
 
 /// Trait used to indicate when no more write need to be done on a writer
-pub trait TerminatingWrite: Write {
+pub trait TerminatingWrite: Write + Send {
     /// Indicate that the writer will no longer be used. Internally call terminate_ref.
     fn terminate(mut self) -> io::Result<()>
     where
@@ -0,0 +1,145 @@
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{Directory, DirectoryLock, FileHandle, FileSlice, Lock, WritePtr};
+use crate::directory::{WatchCallback, WatchHandle, INDEX_WRITER_LOCK};
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+fn read_only_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "The directory was opened in read-only mode.",
+    )
+}
+
+/// Wrapper of a directory that forbids any write.
+///
+/// Reads are forwarded to the underlying directory, while any attempt to
+/// write, delete, or acquire the `INDEX_WRITER_LOCK` returns an error.
+///
+/// Other locks are "acquired" without touching the underlying directory.
+/// In particular, the `META_LOCK` is not written when reloading searchers.
+/// This makes it possible to serve an index from a read-only filesystem,
+/// but it also means that no `IndexWriter` should be working on
+/// the underlying directory at the same time.
+pub struct ReadOnlyDirectory {
+    underlying: Box<dyn Directory>,
+}
+
+impl ReadOnlyDirectory {
+    /// Wraps a directory as a read-only directory.
+    pub fn wrap<D: Directory>(directory: D) -> ReadOnlyDirectory {
+        ReadOnlyDirectory {
+            underlying: Box::new(directory),
+        }
+    }
+}
+
+impl Clone for ReadOnlyDirectory {
+    fn clone(&self) -> Self {
+        ReadOnlyDirectory {
+            underlying: self.underlying.box_clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ReadOnlyDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ReadOnly({:?})", self.underlying)
+    }
+}
+
+impl Directory for ReadOnlyDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Box<dyn FileHandle>, OpenReadError> {
+        self.underlying.get_file_handle(path)
+    }
+
+    fn open_read(&self, path: &Path) -> Result<FileSlice, OpenReadError> {
+        self.underlying.open_read(path)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        Err(DeleteError::IOError {
+            io_error: read_only_error(),
+            filepath: path.to_path_buf(),
+        })
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        Err(OpenWriteError::wrap_io_error(
+            read_only_error(),
+            path.to_path_buf(),
+        ))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        self.underlying.atomic_read(path)
+    }
+
+    fn atomic_write(&self, _path: &Path, _data: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        if lock.filepath == INDEX_WRITER_LOCK.filepath {
+            return Err(LockError::IOError(read_only_error()));
+        }
+        Ok(DirectoryLock::from(Box::new(())))
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.underlying.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadOnlyDirectory;
+    use crate::directory::{Directory, RAMDirectory, META_LOCK};
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, Term};
+    use std::path::Path;
+
+    #[test]
+    fn test_read_only_directory_forbids_writes() {
+        let ram_directory = RAMDirectory::create();
+        ram_directory
+            .atomic_write(Path::new("toto"), b"titi")
+            .unwrap();
+        let directory = ReadOnlyDirectory::wrap(ram_directory.clone());
+        assert_eq!(directory.atomic_read(Path::new("toto")).unwrap(), b"titi");
+        assert!(directory.atomic_write(Path::new("toto"), b"tata").is_err());
+        assert!(directory.open_write(Path::new("tutu")).is_err());
+        assert!(directory.delete(Path::new("toto")).is_err());
+        assert!(ram_directory.exists(Path::new("toto")).unwrap());
+        {
+            let _meta_lock = directory.acquire_lock(&META_LOCK).unwrap();
+            assert!(!ram_directory.exists(&META_LOCK.filepath).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_open_readonly() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let directory = RAMDirectory::create();
+        let index = Index::create(directory.clone(), schema_builder.build())?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"hello"));
+        index_writer.commit()?;
+        drop(index_writer);
+
+        let read_only_index = Index::open_readonly(directory)?;
+        assert!(read_only_index.writer_for_tests().is_err());
+        let searcher = read_only_index.reader()?.searcher();
+        assert_eq!(
+            searcher.doc_freq(&Term::from_field_text(text_field, "hello"))?,
+            1
+        );
+        Ok(())
+    }
+}
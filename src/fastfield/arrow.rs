@@ -0,0 +1,721 @@
+//! Export of fast field columns to Apache Arrow.
+//!
+//! The columns are exported through the
+//! [Arrow C data interface](https://arrow.apache.org/docs/format/CDataInterface.html),
+//! so that they can be imported without copy by any Arrow implementation
+//! (`arrow-rs`, `pyarrow`, DataFusion, Polars, ...) without tantivy depending on one.
+//!
+//! The values are decoded from the fast fields into buffers that are then handed over
+//! to the consumer, which frees them through the `release` callback of the interface.
+
+use crate::fastfield::{FastFieldReaders, FastValue, OptionalFastFieldReader};
+use crate::schema::{Cardinality, Field, FieldType, Type};
+use crate::{DocId, SegmentReader, TantivyError};
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+const ARROW_FLAG_NULLABLE: i64 = 2;
+
+/// Schema of an Arrow array, laid out as the `ArrowSchema` struct
+/// of the Arrow C data interface.
+///
+/// A pointer to an `ArrowSchema` can be passed to any consumer of the interface,
+/// e.g. `arrow::ffi::FFI_ArrowSchema::from_raw`. The consumer takes ownership
+/// of the schema, and releases it once it is done with it.
+/// An `ArrowSchema` dropped on the Rust side is released as well.
+#[repr(C)]
+pub struct ArrowSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut ArrowSchema,
+    dictionary: *mut ArrowSchema,
+    release: Option<unsafe extern "C" fn(*mut ArrowSchema)>,
+    private_data: *mut c_void,
+}
+
+// The strings and the children of a schema are owned by its private data.
+struct SchemaPrivateData {
+    format: CString,
+    name: CString,
+    children: Vec<*mut ArrowSchema>,
+}
+
+unsafe extern "C" fn release_schema(schema: *mut ArrowSchema) {
+    if schema.is_null() {
+        return;
+    }
+    let schema = &mut *schema;
+    let private_data = Box::from_raw(schema.private_data as *mut SchemaPrivateData);
+    for &child in &private_data.children {
+        // Children moved out by the consumer have no `release` callback anymore,
+        // and only their struct is freed.
+        drop(Box::from_raw(child));
+    }
+    schema.release = None;
+}
+
+impl ArrowSchema {
+    fn new(
+        format: &str,
+        name: &str,
+        flags: i64,
+        children: Vec<ArrowSchema>,
+    ) -> crate::Result<ArrowSchema> {
+        let name = CString::new(name).map_err(|_| {
+            TantivyError::InvalidArgument(format!("Field name {:?} contains a nul byte.", name))
+        })?;
+        let mut private_data = Box::new(SchemaPrivateData {
+            format: CString::new(format).expect("Arrow formats do not contain nul bytes"),
+            name,
+            children: children
+                .into_iter()
+                .map(|child| Box::into_raw(Box::new(child)))
+                .collect(),
+        });
+        Ok(ArrowSchema {
+            format: private_data.format.as_ptr(),
+            name: private_data.name.as_ptr(),
+            metadata: ptr::null(),
+            flags,
+            n_children: private_data.children.len() as i64,
+            children: private_data.children.as_mut_ptr(),
+            dictionary: ptr::null_mut(),
+            release: Some(release_schema),
+            private_data: Box::into_raw(private_data) as *mut c_void,
+        })
+    }
+
+    /// Returns the Arrow format string of the schema, e.g. `l` for an `Int64` column.
+    pub fn format(&self) -> &str {
+        unsafe { CStr::from_ptr(self.format) }
+            .to_str()
+            .unwrap_or("")
+    }
+
+    /// Returns the name of the schema, i.e. the name of the field for a column.
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr(self.name) }.to_str().unwrap_or("")
+    }
+
+    /// Returns the schemas of the children, i.e. the columns of a record batch.
+    pub fn children(&self) -> Vec<&ArrowSchema> {
+        (0..self.n_children as usize)
+            .map(|ord| unsafe { &**self.children.add(ord) })
+            .collect()
+    }
+}
+
+impl Drop for ArrowSchema {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self) };
+        }
+    }
+}
+
+// The private data of the schema is only accessed through `&mut self` or by the
+// consumer that took ownership of the schema.
+unsafe impl Send for ArrowSchema {}
+
+/// Arrow array, laid out as the `ArrowArray` struct of the Arrow C data interface.
+///
+/// Like the `ArrowSchema`, its ownership can be transferred to any consumer
+/// of the interface, which releases it once it is done with it.
+#[repr(C)]
+pub struct ArrowArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut ArrowArray,
+    dictionary: *mut ArrowArray,
+    release: Option<unsafe extern "C" fn(*mut ArrowArray)>,
+    private_data: *mut c_void,
+}
+
+/// Buffer of an `ArrowArray`.
+///
+/// Arrow values and offsets are 64 bits wide, and they are stored as `u64` words
+/// holding their in-memory representation.
+enum ArrowBuffer {
+    Bytes(Vec<u8>),
+    Words(Vec<u64>),
+}
+
+impl ArrowBuffer {
+    fn as_ptr(&self) -> *const c_void {
+        match self {
+            ArrowBuffer::Bytes(bytes) => bytes.as_ptr() as *const c_void,
+            ArrowBuffer::Words(words) => words.as_ptr() as *const c_void,
+        }
+    }
+}
+
+// The buffers and the children of an array are owned by its private data.
+struct ArrayPrivateData {
+    _buffers: Vec<Option<ArrowBuffer>>,
+    buffer_ptrs: Vec<*const c_void>,
+    children: Vec<*mut ArrowArray>,
+}
+
+unsafe extern "C" fn release_array(array: *mut ArrowArray) {
+    if array.is_null() {
+        return;
+    }
+    let array = &mut *array;
+    let private_data = Box::from_raw(array.private_data as *mut ArrayPrivateData);
+    for &child in &private_data.children {
+        drop(Box::from_raw(child));
+    }
+    array.release = None;
+}
+
+impl ArrowArray {
+    fn new(
+        length: usize,
+        null_count: usize,
+        buffers: Vec<Option<ArrowBuffer>>,
+        children: Vec<ArrowArray>,
+    ) -> ArrowArray {
+        let buffer_ptrs = buffers
+            .iter()
+            .map(|buffer_opt| {
+                buffer_opt
+                    .as_ref()
+                    .map(ArrowBuffer::as_ptr)
+                    .unwrap_or_else(ptr::null)
+            })
+            .collect();
+        let mut private_data = Box::new(ArrayPrivateData {
+            _buffers: buffers,
+            buffer_ptrs,
+            children: children
+                .into_iter()
+                .map(|child| Box::into_raw(Box::new(child)))
+                .collect(),
+        });
+        ArrowArray {
+            length: length as i64,
+            null_count: null_count as i64,
+            offset: 0,
+            n_buffers: private_data.buffer_ptrs.len() as i64,
+            n_children: private_data.children.len() as i64,
+            buffers: private_data.buffer_ptrs.as_mut_ptr(),
+            children: private_data.children.as_mut_ptr(),
+            dictionary: ptr::null_mut(),
+            release: Some(release_array),
+            private_data: Box::into_raw(private_data) as *mut c_void,
+        }
+    }
+
+    /// Returns the number of elements of the array.
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    /// Returns true iff the array does not contain any element.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Returns the number of null elements of the array.
+    pub fn null_count(&self) -> usize {
+        self.null_count as usize
+    }
+
+    /// Returns the children of the array, i.e. the columns of a record batch.
+    pub fn children(&self) -> Vec<&ArrowArray> {
+        (0..self.n_children as usize)
+            .map(|ord| unsafe { &**self.children.add(ord) })
+            .collect()
+    }
+
+    /// Returns the `ord`-th buffer of the array, of `num_bytes` bytes,
+    /// or `None` if the buffer is absent, like the validity bitmap of an array
+    /// without nulls.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ord` is not lower than the number of buffers of the array.
+    ///
+    /// # Safety
+    ///
+    /// `num_bytes` must not exceed the length of the buffer, which depends on
+    /// the type and the length of the array.
+    pub unsafe fn buffer(&self, ord: usize, num_bytes: usize) -> Option<&[u8]> {
+        assert!(ord < self.n_buffers as usize);
+        let buffer_ptr = *self.buffers.add(ord);
+        if buffer_ptr.is_null() {
+            None
+        } else {
+            Some(std::slice::from_raw_parts(
+                buffer_ptr as *const u8,
+                num_bytes,
+            ))
+        }
+    }
+}
+
+impl Drop for ArrowArray {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self) };
+        }
+    }
+}
+
+// See `ArrowSchema`.
+unsafe impl Send for ArrowArray {}
+
+/// Arrow record batch holding fast field columns,
+/// exported as a struct array and its schema, as defined by the C data interface.
+///
+/// The `schema` and the `array` can be handed over separately, e.g. to
+/// `arrow::ffi::from_ffi` which returns the `StructArray` of the record batch.
+pub struct ArrowRecordBatch {
+    /// Schema of the record batch. Its children describe the columns.
+    pub schema: ArrowSchema,
+    /// Struct array of the record batch. Its children are the columns.
+    pub array: ArrowArray,
+}
+
+impl ArrowRecordBatch {
+    /// Exports the fast field columns `fields` of the documents `doc_ids` of a segment.
+    ///
+    /// The rows of the record batch follow the order of `doc_ids`.
+    /// The columns are named after the fields, and their type depends on the
+    /// cardinality of the fast field:
+    /// - single-valued fast fields are exported as non-nullable `UInt64`, `Int64`,
+    ///   `Float64` or `Timestamp(Second, "UTC")` columns.
+    /// - optional fast fields are exported as nullable columns of the same types.
+    /// - multivalued fast fields are exported as `LargeList` columns of these types.
+    /// - bytes fast fields are exported as `LargeBinary` columns.
+    ///
+    /// Single-valued columns of contiguous doc ids are decoded in bulk.
+    ///
+    /// Returns a `SchemaError` if one of the fields is not a fast field of one of
+    /// these types.
+    pub fn from_fast_fields(
+        segment_reader: &SegmentReader,
+        fields: &[Field],
+        doc_ids: &[DocId],
+    ) -> crate::Result<ArrowRecordBatch> {
+        let schema = segment_reader.schema();
+        let fast_fields = segment_reader.fast_fields();
+        let mut column_schemas = Vec::with_capacity(fields.len());
+        let mut columns = Vec::with_capacity(fields.len());
+        for &field in fields {
+            let field_entry = schema.get_field_entry(field);
+            let not_exportable = || {
+                TantivyError::SchemaError(format!(
+                    "Field {:?} is not a numerical, date or bytes fast field.",
+                    field_entry.name()
+                ))
+            };
+            let (column_schema, column) = match field_entry.field_type() {
+                FieldType::U64(options) => {
+                    let cardinality = options
+                        .get_fastfield_cardinality()
+                        .ok_or_else(not_exportable)?;
+                    export_column::<u64>(fast_fields, field, cardinality, doc_ids)
+                }
+                FieldType::I64(options) => {
+                    let cardinality = options
+                        .get_fastfield_cardinality()
+                        .ok_or_else(not_exportable)?;
+                    export_column::<i64>(fast_fields, field, cardinality, doc_ids)
+                }
+                FieldType::F64(options) => {
+                    let cardinality = options
+                        .get_fastfield_cardinality()
+                        .ok_or_else(not_exportable)?;
+                    export_column::<f64>(fast_fields, field, cardinality, doc_ids)
+                }
+                FieldType::Date(options) => {
+                    let cardinality = options
+                        .get_fastfield_cardinality()
+                        .ok_or_else(not_exportable)?;
+                    export_column::<crate::DateTime>(fast_fields, field, cardinality, doc_ids)
+                }
+                FieldType::Bytes(options) if options.is_fast() => {
+                    export_bytes_column(fast_fields, field, doc_ids)
+                }
+                _ => return Err(not_exportable()),
+            }
+            .ok_or_else(|| {
+                TantivyError::SchemaError(format!(
+                    "Fast field {:?} is not available.",
+                    field_entry.name()
+                ))
+            })?;
+            column_schemas.push(ArrowSchema::new(
+                column_schema.format,
+                field_entry.name(),
+                column_schema.flags,
+                column_schema.children,
+            )?);
+            columns.push(column);
+        }
+        Ok(ArrowRecordBatch {
+            schema: ArrowSchema::new("+s", "", 0, column_schemas)?,
+            array: ArrowArray::new(doc_ids.len(), 0, vec![None], columns),
+        })
+    }
+}
+
+/// Schema of a column, before it is named after its field.
+struct ColumnSchema {
+    format: &'static str,
+    flags: i64,
+    children: Vec<ArrowSchema>,
+}
+
+fn arrow_format(value_type: Type) -> &'static str {
+    match value_type {
+        Type::U64 => "L",
+        Type::I64 => "l",
+        Type::F64 => "g",
+        Type::Date => "tss:UTC",
+        _ => unreachable!("Only numerical and date fast fields are exported."),
+    }
+}
+
+fn export_column<Item: FastValue>(
+    fast_fields: &FastFieldReaders,
+    field: Field,
+    cardinality: Cardinality,
+    doc_ids: &[DocId],
+) -> Option<(ColumnSchema, ArrowArray)> {
+    let format = arrow_format(Item::to_type());
+    match cardinality {
+        Cardinality::SingleValue => {
+            let reader = fast_fields.typed::<Item>(field)?;
+            let is_contiguous = doc_ids.windows(2).all(|pair| pair[1] == pair[0] + 1);
+            let values: Vec<u64> = match doc_ids.first() {
+                Some(&first_doc) if is_contiguous => {
+                    let mut items = vec![Item::make_zero(); doc_ids.len()];
+                    reader.get_range(first_doc, &mut items);
+                    items.iter().map(FastValue::as_u64).collect()
+                }
+                _ => doc_ids
+                    .iter()
+                    .map(|&doc| reader.get(doc).as_u64())
+                    .collect(),
+            };
+            let column_schema = ColumnSchema {
+                format,
+                flags: 0,
+                children: Vec::new(),
+            };
+            let column = ArrowArray::new(
+                doc_ids.len(),
+                0,
+                vec![None, Some(ArrowBuffer::Words(values))],
+                Vec::new(),
+            );
+            Some((column_schema, column))
+        }
+        Cardinality::Optional => {
+            let reader = OptionalFastFieldReader::from(fast_fields.typed_multi::<Item>(field)?);
+            let mut validity = Vec::with_capacity(doc_ids.len() / 8 + 1);
+            let mut null_count = 0;
+            let values: Vec<u64> = doc_ids
+                .iter()
+                .enumerate()
+                .map(|(row, &doc)| {
+                    if row % 8 == 0 {
+                        validity.push(0u8);
+                    }
+                    match reader.get(doc) {
+                        Some(value) => {
+                            validity[row / 8] |= 1u8 << (row % 8);
+                            value.as_u64()
+                        }
+                        None => {
+                            null_count += 1;
+                            0u64
+                        }
+                    }
+                })
+                .collect();
+            let column_schema = ColumnSchema {
+                format,
+                flags: ARROW_FLAG_NULLABLE,
+                children: Vec::new(),
+            };
+            let column = ArrowArray::new(
+                doc_ids.len(),
+                null_count,
+                vec![
+                    Some(ArrowBuffer::Bytes(validity)),
+                    Some(ArrowBuffer::Words(values)),
+                ],
+                Vec::new(),
+            );
+            Some((column_schema, column))
+        }
+        Cardinality::MultiValues => {
+            let reader = fast_fields.typed_multi::<Item>(field)?;
+            let mut offsets = Vec::with_capacity(doc_ids.len() + 1);
+            offsets.push(0u64);
+            let mut values = Vec::new();
+            let mut doc_values = Vec::new();
+            for &doc in doc_ids {
+                reader.get_vals(doc, &mut doc_values);
+                values.extend(doc_values.iter().map(FastValue::as_u64));
+                offsets.push(values.len() as u64);
+            }
+            let item_schema = ArrowSchema::new(format, "item", 0, Vec::new())
+                .expect("Item names do not contain nul bytes");
+            let column_schema = ColumnSchema {
+                format: "+L",
+                flags: ARROW_FLAG_NULLABLE,
+                children: vec![item_schema],
+            };
+            let items = ArrowArray::new(
+                values.len(),
+                0,
+                vec![None, Some(ArrowBuffer::Words(values))],
+                Vec::new(),
+            );
+            let column = ArrowArray::new(
+                doc_ids.len(),
+                0,
+                vec![None, Some(ArrowBuffer::Words(offsets))],
+                vec![items],
+            );
+            Some((column_schema, column))
+        }
+    }
+}
+
+fn export_bytes_column(
+    fast_fields: &FastFieldReaders,
+    field: Field,
+    doc_ids: &[DocId],
+) -> Option<(ColumnSchema, ArrowArray)> {
+    let reader = fast_fields.bytes(field)?;
+    let mut offsets = Vec::with_capacity(doc_ids.len() + 1);
+    offsets.push(0u64);
+    let mut data = Vec::new();
+    for &doc in doc_ids {
+        data.extend_from_slice(reader.get_bytes(doc));
+        offsets.push(data.len() as u64);
+    }
+    let column_schema = ColumnSchema {
+        format: "Z",
+        flags: ARROW_FLAG_NULLABLE,
+        children: Vec::new(),
+    };
+    let column = ArrowArray::new(
+        doc_ids.len(),
+        0,
+        vec![
+            None,
+            Some(ArrowBuffer::Words(offsets)),
+            Some(ArrowBuffer::Bytes(data)),
+        ],
+        Vec::new(),
+    );
+    Some((column_schema, column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrowArray, ArrowRecordBatch};
+    use crate::schema::{Cardinality, IntOptions, Schema, FAST, INDEXED, STRING};
+    use crate::{DocId, Index, TantivyError};
+    use chrono::{TimeZone, Utc};
+
+    fn datetime(timestamp: i64) -> crate::DateTime {
+        Utc.timestamp_opt(timestamp, 0).unwrap()
+    }
+
+    fn words(array: &ArrowArray, ord: usize, num_words: usize) -> Vec<u64> {
+        let bytes = unsafe { array.buffer(ord, num_words * 8) }.unwrap();
+        bytes
+            .chunks(8)
+            .map(|chunk| {
+                let mut word = [0u8; 8];
+                word.copy_from_slice(chunk);
+                u64::from_le_bytes(word)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_arrow_record_batch() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let score = schema_builder.add_f64_field("score", FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let rank = schema_builder.add_u64_field(
+            "rank",
+            IntOptions::default().set_fast(Cardinality::Optional),
+        );
+        let tags = schema_builder.add_i64_field(
+            "tags",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let payload = schema_builder.add_bytes_field("payload", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            id => 0u64, score => 0.5f64, delta => -3i64,
+            date => datetime(1_600_000_000),
+            rank => 7u64, tags => 1i64, tags => -2i64, payload => vec![1u8, 2u8]
+        ));
+        index_writer.add_document(doc!(
+            id => 1u64, score => 1.5f64, delta => 4i64,
+            date => datetime(1_700_000_000)
+        ));
+        index_writer.add_document(doc!(
+            id => 2u64, score => 2.5f64, delta => 0i64,
+            date => datetime(0),
+            rank => 0u64, tags => 5i64, payload => vec![3u8]
+        ));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let fields = [id, score, delta, date, rank, tags, payload];
+        let doc_ids: Vec<DocId> = vec![2, 0, 1];
+        let record_batch = ArrowRecordBatch::from_fast_fields(segment_reader, &fields, &doc_ids)?;
+
+        assert_eq!(record_batch.schema.format(), "+s");
+        let column_schemas: Vec<(&str, &str)> = record_batch
+            .schema
+            .children()
+            .iter()
+            .map(|schema| (schema.name(), schema.format()))
+            .collect();
+        assert_eq!(
+            column_schemas,
+            vec![
+                ("id", "L"),
+                ("score", "g"),
+                ("delta", "l"),
+                ("date", "tss:UTC"),
+                ("rank", "L"),
+                ("tags", "+L"),
+                ("payload", "Z")
+            ]
+        );
+        assert_eq!(
+            record_batch.schema.children()[5].children()[0].format(),
+            "l"
+        );
+
+        assert_eq!(record_batch.array.len(), 3);
+        let columns = record_batch.array.children();
+        assert_eq!(columns.len(), 7);
+        assert_eq!(words(columns[0], 1, 3), vec![2, 0, 1]);
+        assert_eq!(
+            words(columns[1], 1, 3),
+            vec![2.5f64.to_bits(), 0.5f64.to_bits(), 1.5f64.to_bits()]
+        );
+        assert_eq!(words(columns[2], 1, 3), vec![0, -3i64 as u64, 4]);
+        assert_eq!(
+            words(columns[3], 1, 3),
+            vec![0, 1_600_000_000, 1_700_000_000]
+        );
+
+        // Optional: the document 1 has no rank, the document 2 has the rank `0`.
+        assert_eq!(columns[4].null_count(), 1);
+        let validity = unsafe { columns[4].buffer(0, 1) }.unwrap();
+        assert_eq!(validity[0] & 0b111, 0b011);
+        assert_eq!(words(columns[4], 1, 3), vec![0, 7, 0]);
+
+        // Multivalued
+        assert_eq!(words(columns[5], 1, 4), vec![0, 1, 3, 3]);
+        let tag_values = columns[5].children()[0];
+        assert_eq!(tag_values.len(), 3);
+        assert_eq!(words(tag_values, 1, 3), vec![5, 1, -2i64 as u64]);
+
+        // Bytes
+        assert_eq!(words(columns[6], 1, 4), vec![0, 1, 3, 3]);
+        assert_eq!(
+            unsafe { columns[6].buffer(2, 3) }.unwrap(),
+            &[3u8, 1u8, 2u8]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrow_record_batch_contiguous_doc_ids() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..100u64 {
+            index_writer.add_document(doc!(id => i * 3));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let doc_ids: Vec<DocId> = (10..60).collect();
+        let record_batch =
+            ArrowRecordBatch::from_fast_fields(searcher.segment_reader(0), &[id], &doc_ids)?;
+        let column = record_batch.array.children()[0];
+        assert_eq!(column.len(), 50);
+        assert!(unsafe { column.buffer(0, 0) }.is_none());
+        assert_eq!(
+            words(column, 1, 50),
+            (10..60u64).map(|i| i * 3).collect::<Vec<_>>()
+        );
+        let empty_batch =
+            ArrowRecordBatch::from_fast_fields(searcher.segment_reader(0), &[id], &[])?;
+        assert!(empty_batch.array.is_empty());
+        assert!(empty_batch.array.children()[0].is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrow_record_batch_not_a_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STRING);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a", count => 1u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        for &field in &[title, count] {
+            assert!(matches!(
+                ArrowRecordBatch::from_fast_fields(searcher.segment_reader(0), &[field], &[0]),
+                Err(TantivyError::SchemaError(_))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrow_release_moved_child() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => 1u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let record_batch =
+            ArrowRecordBatch::from_fast_fields(searcher.segment_reader(0), &[id], &[0])?;
+        // A consumer moving a column out of the record batch takes over its release,
+        // and marks the moved struct as released.
+        let column: ArrowArray = unsafe {
+            let child_ptr = *record_batch.array.children;
+            let column = std::ptr::read(child_ptr);
+            (*child_ptr).release = None;
+            column
+        };
+        drop(record_batch);
+        assert_eq!(words(&column, 1, 1), vec![1]);
+        drop(column);
+        Ok(())
+    }
+}
@@ -33,6 +33,10 @@ mod tests {
         assert_eq!(bytes_reader.get_bytes(3), &[1u8, 3, 5, 7, 9]);
         let long = vec![0u8; 1000];
         assert_eq!(bytes_reader.get_bytes(4), long.as_slice());
+        assert_eq!(bytes_reader.num_bytes(0), 4);
+        assert_eq!(bytes_reader.num_bytes(1), 0);
+        assert_eq!(bytes_reader.num_bytes(4), 1000);
+        assert_eq!(bytes_reader.total_num_bytes(), 1010);
         Ok(())
     }
 
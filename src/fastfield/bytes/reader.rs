@@ -28,6 +28,12 @@ impl BytesFastFieldReader {
         Ok(BytesFastFieldReader { idx_reader, values })
     }
 
+    /// Returns true iff the fast field data holds the values of `num_docs` documents.
+    pub(crate) fn holds_num_docs(&self, num_docs: DocId) -> bool {
+        self.idx_reader.holds_num_vals(u64::from(num_docs) + 1)
+            && self.idx_reader.get(num_docs) as usize <= self.values.len()
+    }
+
     fn range(&self, doc: DocId) -> (usize, usize) {
         let start = self.idx_reader.get(doc) as usize;
         let stop = self.idx_reader.get(doc + 1) as usize;
@@ -40,6 +46,13 @@ impl BytesFastFieldReader {
         &self.values.as_slice()[start..stop]
     }
 
+    /// Returns the number of bytes associated to the given `doc`,
+    /// without accessing the bytes themselves.
+    pub fn num_bytes(&self, doc: DocId) -> usize {
+        let (start, stop) = self.range(doc);
+        stop - start
+    }
+
     /// Returns the overall number of bytes in this bytes fast field.
     pub fn total_num_bytes(&self) -> usize {
         self.values.len()
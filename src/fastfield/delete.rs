@@ -63,6 +63,20 @@ impl DeleteBitSet {
         Self::open(file).unwrap()
     }
 
+    /// Builds a delete bitset in memory, from the set of deleted `DocId`s.
+    pub(crate) fn from_bitset(bitset: &BitSet, max_doc: u32) -> DeleteBitSet {
+        let mut data = vec![0u8; (max_doc as usize).div_ceil(8)];
+        for doc in 0..max_doc {
+            if bitset.contains(doc) {
+                data[(doc / 8) as usize] |= 1 << (doc % 8);
+            }
+        }
+        DeleteBitSet {
+            data: OwnedBytes::new(data),
+            len: bitset.len(),
+        }
+    }
+
     /// Opens a delete bitset given its file.
     pub fn open(file: FileSlice) -> crate::Result<DeleteBitSet> {
         let bytes = file.read_bytes()?;
@@ -23,18 +23,22 @@ values stored.
 Read access performance is comparable to that of an array lookup.
 */
 
+pub use self::arrow::{ArrowArray, ArrowRecordBatch, ArrowSchema};
 pub use self::bytes::{BytesFastFieldReader, BytesFastFieldWriter};
 pub use self::delete::write_delete_bitset;
 pub use self::delete::DeleteBitSet;
 pub use self::error::{FastFieldNotAvailableError, Result};
 pub use self::facet_reader::FacetReader;
 pub use self::multivalued::{MultiValueIntFastFieldReader, MultiValueIntFastFieldWriter};
+pub use self::optional::OptionalFastFieldReader;
 pub use self::reader::FastFieldReader;
 pub use self::readers::FastFieldReaders;
 pub use self::serializer::FastFieldSerializer;
+pub use self::str_reader::StrFastFieldReader;
 pub use self::writer::{FastFieldsWriter, IntFastFieldWriter};
 use crate::common;
 use crate::schema::Cardinality;
+use crate::schema::DatePrecision;
 use crate::schema::FieldType;
 use crate::schema::Value;
 use crate::{
@@ -42,14 +46,17 @@ use crate::{
     schema::Type,
 };
 
+mod arrow;
 mod bytes;
 mod delete;
 mod error;
 mod facet_reader;
 mod multivalued;
+mod optional;
 mod reader;
 mod readers;
 mod serializer;
+mod str_reader;
 mod writer;
 
 /// Trait for types that are allowed for fast fields: (u64, i64 and f64).
@@ -186,16 +193,28 @@ impl FastValue for crate::DateTime {
     }
 }
 
-fn value_to_u64(value: &Value) -> u64 {
+fn value_to_u64(value: &Value, date_precision: DatePrecision) -> u64 {
     match *value {
         Value::U64(ref val) => *val,
         Value::I64(ref val) => common::i64_to_u64(*val),
         Value::F64(ref val) => common::f64_to_u64(*val),
-        Value::Date(ref datetime) => common::i64_to_u64(datetime.timestamp()),
+        Value::Date(ref datetime) => {
+            common::i64_to_u64(date_precision.truncate(datetime.timestamp()))
+        }
         _ => panic!("Expected a u64/i64/f64 field, got {:?} ", value),
     }
 }
 
+/// Converts a fast field `u64` back into the `Value` of a field of the given type.
+pub(crate) fn value_from_u64(value_type: Type, val: u64) -> Value {
+    match value_type {
+        Type::I64 => Value::I64(i64::from_u64(val)),
+        Type::F64 => Value::F64(f64::from_u64(val)),
+        Type::Date => Value::Date(crate::DateTime::from_u64(val)),
+        _ => Value::U64(val),
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -206,8 +225,8 @@ mod tests {
     use crate::merge_policy::NoMergePolicy;
     use crate::schema::Field;
     use crate::schema::Schema;
-    use crate::schema::FAST;
     use crate::schema::{Document, IntOptions};
+    use crate::schema::{FAST, STORED};
     use crate::{Index, SegmentId, SegmentReader};
     use common::HasLen;
     use once_cell::sync::Lazy;
@@ -260,6 +279,7 @@ mod tests {
         let composite_file = CompositeFile::open(&file)?;
         let file = composite_file.open_read(*FIELD).unwrap();
         let fast_field_reader = FastFieldReader::<u64>::open(file)?;
+        assert_eq!(fast_field_reader.num_bits(), 4u8);
         assert_eq!(fast_field_reader.get(0), 13u64);
         assert_eq!(fast_field_reader.get(1), 14u64);
         assert_eq!(fast_field_reader.get(2), 2u64);
@@ -292,6 +312,7 @@ mod tests {
             let fast_fields_composite = CompositeFile::open(&file)?;
             let data = fast_fields_composite.open_read(*FIELD).unwrap();
             let fast_field_reader = FastFieldReader::<u64>::open(data)?;
+            assert_eq!(fast_field_reader.num_bits(), 24u8);
             assert_eq!(fast_field_reader.get(0), 4u64);
             assert_eq!(fast_field_reader.get(1), 14_082_001u64);
             assert_eq!(fast_field_reader.get(2), 3_052u64);
@@ -410,6 +431,51 @@ mod tests {
             for i in 0..100 {
                 assert_eq!(buffer[i], -100i64 + 53i64 + i as i64);
             }
+            // Spans several of the chunks decoded by `get_range`.
+            let mut buffer = vec![0i64; 1_000];
+            fast_field_reader.get_range(5_000, &mut buffer[..]);
+            for (i, &val) in buffer.iter().enumerate() {
+                assert_eq!(val, -100i64 + 5_000i64 + i as i64);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fastfield_full_range() -> crate::Result<()> {
+        let path = Path::new("test");
+        let directory: RAMDirectory = RAMDirectory::create();
+        let mut schema_builder = Schema::builder();
+        let u64_field = schema_builder.add_u64_field("u64", FAST);
+        let i64_field = schema_builder.add_i64_field("i64", FAST);
+        let schema = schema_builder.build();
+        // Timestamps in milliseconds do not fit in 32 bits.
+        let timestamp_ms = 1_600_000_000_000i64;
+        let u64_vals = [u64::MAX, 0u64, 1u64, u64::MAX - 1];
+        let i64_vals = [i64::MIN, i64::MAX, timestamp_ms, -timestamp_ms];
+        {
+            let write: WritePtr = directory.open_write(path)?;
+            let mut serializer = FastFieldSerializer::from_write(write)?;
+            let mut fast_field_writers = FastFieldsWriter::from_schema(&schema);
+            for (&u64_val, &i64_val) in u64_vals.iter().zip(i64_vals.iter()) {
+                fast_field_writers.add_document(&doc!(u64_field=>u64_val, i64_field=>i64_val));
+            }
+            fast_field_writers.serialize(&mut serializer, &HashMap::new())?;
+            serializer.close()?;
+        }
+        let file = directory.open_read(path)?;
+        let fast_fields_composite = CompositeFile::open(&file)?;
+        let u64_reader =
+            FastFieldReader::<u64>::open(fast_fields_composite.open_read(u64_field).unwrap())?;
+        let i64_reader =
+            FastFieldReader::<i64>::open(fast_fields_composite.open_read(i64_field).unwrap())?;
+        assert_eq!(u64_reader.min_value(), 0u64);
+        assert_eq!(u64_reader.max_value(), u64::MAX);
+        assert_eq!(i64_reader.min_value(), i64::MIN);
+        assert_eq!(i64_reader.max_value(), i64::MAX);
+        for doc in 0..4 {
+            assert_eq!(u64_reader.get(doc as u32), u64_vals[doc]);
+            assert_eq!(i64_reader.get(doc as u32), i64_vals[doc]);
         }
         Ok(())
     }
@@ -568,6 +634,80 @@ mod tests {
             assert_eq!(dates[1].timestamp(), 6i64);
         }
     }
+
+    #[test]
+    fn test_datefastfield_precision() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let day_field = schema_builder.add_date_field(
+            "day",
+            IntOptions::from(FAST | STORED).set_date_precision(DatePrecision::Days),
+        );
+        let minutes_field = schema_builder.add_date_field(
+            "minutes",
+            IntOptions::default()
+                .set_fast(Cardinality::MultiValues)
+                .set_date_precision(DatePrecision::Minutes),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_for_tests()?;
+        let date = crate::DateTime::from_u64(1_589_723_112i64.to_u64());
+        let before_epoch = crate::DateTime::from_u64((-30i64).to_u64());
+        index_writer.add_document(doc!(
+            day_field => date,
+            minutes_field => date,
+            minutes_field => before_epoch
+        ));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        assert_eq!(
+            fast_fields.date(day_field).unwrap().get(0).timestamp(),
+            1_589_673_600i64
+        );
+        let mut dates = vec![];
+        fast_fields
+            .dates(minutes_field)
+            .unwrap()
+            .get_vals(0, &mut dates);
+        let timestamps: Vec<i64> = dates.iter().map(|date| date.timestamp()).collect();
+        assert_eq!(timestamps, vec![1_589_723_100i64, -60i64]);
+        // Stored values are not truncated.
+        let stored_doc = searcher.doc(crate::DocAddress(0, 0))?;
+        assert_eq!(
+            stored_doc.get_first(day_field).unwrap().date_value(),
+            Some(&date)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_fast_field_readers() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let ratings = schema_builder.add_i64_field(
+            "ratings",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(price=>2.5f64, ratings=>-1i64, ratings=>4i64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+
+        assert_eq!(fast_fields.typed::<f64>(price).unwrap().get(0), 2.5f64);
+        assert!(fast_fields.typed::<u64>(price).is_none());
+        assert!(fast_fields.typed::<i64>(ratings).is_none());
+        let mut vals = Vec::new();
+        fast_fields
+            .typed_multi::<i64>(ratings)
+            .unwrap()
+            .get_vals(0, &mut vals);
+        assert_eq!(vals, vec![-1i64, 4i64]);
+        assert!(fast_fields.typed_multi::<f64>(ratings).is_none());
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
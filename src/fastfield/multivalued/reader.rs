@@ -26,6 +26,14 @@ impl<Item: FastValue> MultiValueIntFastFieldReader<Item> {
         }
     }
 
+    /// Returns true iff the fast field data holds the values of `num_docs` documents.
+    pub(crate) fn holds_num_docs(&self, num_docs: DocId) -> bool {
+        self.idx_reader.holds_num_vals(u64::from(num_docs) + 1)
+            && self
+                .vals_reader
+                .holds_num_vals(self.idx_reader.get(num_docs))
+    }
+
     pub(crate) fn into_u64s_reader(self) -> MultiValueIntFastFieldReader<u64> {
         MultiValueIntFastFieldReader {
             idx_reader: self.idx_reader,
@@ -33,6 +41,13 @@ impl<Item: FastValue> MultiValueIntFastFieldReader<Item> {
         }
     }
 
+    pub(crate) fn cast<TFastValue: FastValue>(self) -> MultiValueIntFastFieldReader<TFastValue> {
+        MultiValueIntFastFieldReader {
+            idx_reader: self.idx_reader,
+            vals_reader: self.vals_reader.cast(),
+        }
+    }
+
     /// Returns `(start, stop)`, such that the values associated
     /// to the given document are `start..stop`.
     fn range(&self, doc: DocId) -> (u64, u64) {
@@ -49,12 +64,37 @@ impl<Item: FastValue> MultiValueIntFastFieldReader<Item> {
         self.vals_reader.get_range_u64(start, &mut vals[..]);
     }
 
+    /// Returns the first value associated to the given `doc`, if any.
+    pub(crate) fn get_first_val(&self, doc: DocId) -> Option<Item> {
+        let (start, stop) = self.range(doc);
+        if start == stop {
+            return None;
+        }
+        Some(self.vals_reader.get_u64(start))
+    }
+
     /// Returns the number of values associated with the document `DocId`.
     pub fn num_vals(&self, doc: DocId) -> usize {
         let (start, stop) = self.range(doc);
         (stop - start) as usize
     }
 
+    /// Returns a lower bound of the values of this field in the segment.
+    ///
+    /// Just like for `FastFieldReader::min_value`, deleted documents are
+    /// not taken in account.
+    pub fn min_value(&self) -> Item {
+        self.vals_reader.min_value()
+    }
+
+    /// Returns an upper bound of the values of this field in the segment.
+    ///
+    /// Just like for `FastFieldReader::max_value`, deleted documents are
+    /// not taken in account.
+    pub fn max_value(&self) -> Item {
+        self.vals_reader.max_value()
+    }
+
     /// Returns the overall number of values in this field  .
     pub fn total_num_vals(&self) -> u64 {
         self.idx_reader.max_value()
@@ -65,7 +105,7 @@ impl<Item: FastValue> MultiValueIntFastFieldReader<Item> {
 mod tests {
 
     use crate::core::Index;
-    use crate::schema::{Facet, Schema};
+    use crate::schema::{Cardinality, Facet, IntOptions, Schema};
 
     #[test]
     fn test_multifastfield_reader() {
@@ -120,4 +160,28 @@ mod tests {
             assert_eq!(&vals[..], &[4]);
         }
     }
+
+    #[test]
+    fn test_multifastfield_reader_min_max() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_i64_field(
+            "field",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(field=>7i64, field=>-2i64));
+        index_writer.add_document(doc!());
+        index_writer.add_document(doc!(field=>3i64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let reader = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .i64s(field)
+            .unwrap();
+        assert_eq!(reader.min_value(), -2i64);
+        assert_eq!(reader.max_value(), 7i64);
+        Ok(())
+    }
 }
@@ -2,7 +2,7 @@ use crate::fastfield::serializer::FastSingleFieldSerializer;
 use crate::fastfield::value_to_u64;
 use crate::fastfield::FastFieldSerializer;
 use crate::postings::UnorderedTermId;
-use crate::schema::{Document, Field};
+use crate::schema::{DatePrecision, Document, Field};
 use crate::termdict::TermOrdinal;
 use crate::DocId;
 use fnv::FnvHashMap;
@@ -34,6 +34,9 @@ pub struct MultiValueIntFastFieldWriter {
     vals: Vec<UnorderedTermId>,
     doc_index: Vec<u64>,
     is_facet: bool,
+    // If true, at most one value is kept per document.
+    is_optional: bool,
+    date_precision: DatePrecision,
 }
 
 impl MultiValueIntFastFieldWriter {
@@ -44,9 +47,26 @@ impl MultiValueIntFastFieldWriter {
             vals: Vec::new(),
             doc_index: Vec::new(),
             is_facet,
+            is_optional: false,
+            date_precision: DatePrecision::Seconds,
         }
     }
 
+    /// Creates a new writer for a fast field with `Cardinality::Optional`.
+    ///
+    /// Only the first value of each document is kept.
+    pub(crate) fn optional(field: Field) -> Self {
+        MultiValueIntFastFieldWriter {
+            is_optional: true,
+            ..MultiValueIntFastFieldWriter::new(field, false)
+        }
+    }
+
+    /// Sets the precision to which date values are truncated.
+    pub(crate) fn set_date_precision(&mut self, date_precision: DatePrecision) {
+        self.date_precision = date_precision;
+    }
+
     /// Access the field associated to the `MultiValueIntFastFieldWriter`
     pub fn field(&self) -> Field {
         self.field
@@ -70,7 +90,10 @@ impl MultiValueIntFastFieldWriter {
         if !self.is_facet {
             for field_value in doc.field_values() {
                 if field_value.field() == self.field {
-                    self.add_val(value_to_u64(field_value.value()));
+                    self.add_val(value_to_u64(field_value.value(), self.date_precision));
+                    if self.is_optional {
+                        break;
+                    }
                 }
             }
         }
@@ -0,0 +1,86 @@
+use crate::fastfield::{FastValue, MultiValueIntFastFieldReader};
+use crate::DocId;
+
+/// Reader for an optional fast field,
+/// i.e. a fast field declared with `Cardinality::Optional`.
+///
+/// Contrary to the `FastFieldReader`, it distinguishes documents
+/// without any value from documents with a value equal to `0`.
+#[derive(Clone)]
+pub struct OptionalFastFieldReader<Item: FastValue> {
+    reader: MultiValueIntFastFieldReader<Item>,
+}
+
+impl<Item: FastValue> From<MultiValueIntFastFieldReader<Item>> for OptionalFastFieldReader<Item> {
+    fn from(reader: MultiValueIntFastFieldReader<Item>) -> OptionalFastFieldReader<Item> {
+        OptionalFastFieldReader { reader }
+    }
+}
+
+impl<Item: FastValue> OptionalFastFieldReader<Item> {
+    /// Returns the value associated to the given document,
+    /// or `None` if the document does not have any value.
+    pub fn get(&self, doc: DocId) -> Option<Item> {
+        self.reader.get_first_val(doc)
+    }
+
+    /// Returns true iff the document has a value.
+    pub fn has_value(&self, doc: DocId) -> bool {
+        self.reader.num_vals(doc) > 0
+    }
+
+    /// Returns the number of documents with a value.
+    pub fn num_vals(&self) -> u64 {
+        self.reader.total_num_vals()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Cardinality, IntOptions, Schema};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_optional_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let rating = schema_builder.add_i64_field(
+            "rating",
+            IntOptions::default().set_fast(Cardinality::Optional),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(rating=>0i64));
+        index_writer.add_document(doc!());
+        index_writer.add_document(doc!(rating=>-3i64, rating=>5i64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(rating=>7i64));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        assert!(fast_fields.i64(rating).is_none());
+        assert!(fast_fields.optional_u64(rating).is_none());
+        let rating_reader = fast_fields.optional_i64(rating).unwrap();
+        assert_eq!(rating_reader.get(0), Some(0i64));
+        assert_eq!(rating_reader.get(1), None);
+        assert!(!rating_reader.has_value(1));
+        assert_eq!(rating_reader.get(2), Some(-3i64));
+        assert_eq!(rating_reader.num_vals(), 2);
+
+        // Missing values survive merges.
+        let segment_ids = index.searchable_segment_ids()?;
+        futures::executor::block_on(index_writer.merge(&segment_ids))?;
+        IndexWriter::wait_merging_threads(index_writer)?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let rating_reader = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .optional_i64(rating)
+            .unwrap();
+        let ratings: Vec<Option<i64>> = (0..4).map(|doc| rating_reader.get(doc)).collect();
+        assert_eq!(ratings, vec![Some(0), None, Some(-3), Some(7)]);
+        Ok(())
+    }
+}
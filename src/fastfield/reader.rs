@@ -13,6 +13,9 @@ use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::path::Path;
 
+/// Number of values decoded at a time by `get_range`.
+const GET_RANGE_BUFFER_LEN: usize = 128;
+
 /// Trait for accessing a fastfield.
 ///
 /// Depending on the field type, a different
@@ -42,6 +45,11 @@ impl<Item: FastValue> FastFieldReader<Item> {
         })
     }
 
+    /// Returns true iff the fast field data holds at least `num_vals` values.
+    pub(crate) fn holds_num_vals(&self, num_vals: u64) -> bool {
+        self.bit_unpacker.holds_num_vals(num_vals)
+    }
+
     pub(crate) fn into_u64_reader(self) -> FastFieldReader<u64> {
         FastFieldReader {
             bit_unpacker: self.bit_unpacker,
@@ -89,8 +97,15 @@ impl<Item: FastValue> FastFieldReader<Item> {
     ///
     /// See `get_range` for an actual documentation about this method.
     pub(crate) fn get_range_u64(&self, start: u64, output: &mut [Item]) {
-        for (i, out) in output.iter_mut().enumerate() {
-            *out = self.get_u64(start + (i as u64));
+        let mut buffer = [0u64; GET_RANGE_BUFFER_LEN];
+        let mut chunk_start = start;
+        for output_chunk in output.chunks_mut(GET_RANGE_BUFFER_LEN) {
+            let buffer = &mut buffer[..output_chunk.len()];
+            self.bit_unpacker.get_range(chunk_start, buffer);
+            for (out, &val) in output_chunk.iter_mut().zip(buffer.iter()) {
+                *out = Item::from_u64(self.min_value_u64 + val);
+            }
+            chunk_start += output_chunk.len() as u64;
         }
     }
 
@@ -111,6 +126,14 @@ impl<Item: FastValue> FastFieldReader<Item> {
         self.get_range_u64(u64::from(start), output);
     }
 
+    /// Returns the number of bits used to encode each value.
+    ///
+    /// Values are stored as their offset to `min_value`, so the
+    /// number of bits only depends on the amplitude `max_value - min_value`.
+    pub fn num_bits(&self) -> u8 {
+        self.bit_unpacker.num_bits()
+    }
+
     /// Returns the minimum value for this fast field.
     ///
     /// The max value does not take in account of possible
@@ -1,10 +1,13 @@
 use crate::common::CompositeFile;
+use crate::core::SegmentMeta;
+use crate::directory::FileSlice;
 use crate::fastfield::MultiValueIntFastFieldReader;
+use crate::fastfield::OptionalFastFieldReader;
 use crate::fastfield::{BytesFastFieldReader, FastValue};
 use crate::fastfield::{FastFieldNotAvailableError, FastFieldReader};
-use crate::schema::{Cardinality, Field, FieldType, Schema};
+use crate::schema::{Cardinality, Field, FieldEntry, FieldType, Schema, Type};
 use crate::space_usage::PerFieldSpaceUsage;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Provides access to all of the FastFieldReader.
 ///
@@ -20,6 +23,8 @@ pub struct FastFieldReaders {
     fast_field_f64s: HashMap<Field, MultiValueIntFastFieldReader<f64>>,
     fast_field_dates: HashMap<Field, MultiValueIntFastFieldReader<crate::DateTime>>,
     fast_bytes: HashMap<Field, BytesFastFieldReader>,
+    // Optional fast fields are stored as multivalued fast fields.
+    optional_fields: HashSet<Field>,
     fast_fields_composite: CompositeFile,
 }
 
@@ -45,14 +50,43 @@ fn type_and_cardinality(field_type: &FieldType) -> Option<(FastType, Cardinality
             .get_fastfield_cardinality()
             .map(|cardinality| (FastType::Date, cardinality)),
         FieldType::HierarchicalFacet => Some((FastType::U64, Cardinality::MultiValues)),
+        FieldType::Str(options) if options.is_fast() => {
+            Some((FastType::U64, Cardinality::MultiValues))
+        }
         _ => None,
     }
 }
 
+/// Fast field data in which every document has the value `0`:
+/// the minimum value and the amplitude are both `0`.
+fn zero_fast_field_data() -> FileSlice {
+    FileSlice::from(vec![0u8; 16])
+}
+
+/// Returns the data replacing the missing data of a fast field.
+///
+/// Segments created before a fast field was added to the schema do not
+/// contain its data. They are read as if all of their documents had the value `0`,
+/// or no value at all for multivalued and bytes fast fields.
+///
+/// In any other segment, the data is missing because the segment is corrupted,
+/// and a `FastFieldNotAvailableError` is returned.
+fn missing_fast_field_data(
+    segment_meta: &SegmentMeta,
+    field: Field,
+    field_entry: &FieldEntry,
+) -> crate::Result<FileSlice> {
+    if segment_meta.contains_field(field) {
+        return Err(From::from(FastFieldNotAvailableError::new(field_entry)));
+    }
+    Ok(zero_fast_field_data())
+}
+
 impl FastFieldReaders {
     pub(crate) fn load_all(
         schema: &Schema,
         fast_fields_composite: &CompositeFile,
+        segment_meta: &SegmentMeta,
     ) -> crate::Result<FastFieldReaders> {
         let mut fast_field_readers = FastFieldReaders {
             fast_field_i64: Default::default(),
@@ -64,6 +98,7 @@ impl FastFieldReaders {
             fast_field_f64s: Default::default(),
             fast_field_dates: Default::default(),
             fast_bytes: Default::default(),
+            optional_fields: Default::default(),
             fast_fields_composite: fast_fields_composite.clone(),
         };
         for (field, field_entry) in schema.fields() {
@@ -72,13 +107,17 @@ impl FastFieldReaders {
                 if !bytes_option.is_fast() {
                     continue;
                 }
-                let fast_field_idx_file = fast_fields_composite
-                    .open_read_with_idx(field, 0)
-                    .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))?;
+                let idx_opt = fast_fields_composite.open_read_with_idx(field, 0);
+                let data_opt = fast_fields_composite.open_read_with_idx(field, 1);
+                let (fast_field_idx_file, data) = match (idx_opt, data_opt) {
+                    (Some(fast_field_idx_file), Some(data)) => (fast_field_idx_file, data),
+                    (None, None) => (
+                        missing_fast_field_data(segment_meta, field, field_entry)?,
+                        FileSlice::empty(),
+                    ),
+                    _ => return Err(From::from(FastFieldNotAvailableError::new(field_entry))),
+                };
                 let idx_reader = FastFieldReader::open(fast_field_idx_file)?;
-                let data = fast_fields_composite
-                    .open_read_with_idx(field, 1)
-                    .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))?;
                 let bytes_fast_field_reader = BytesFastFieldReader::open(idx_reader, data)?;
                 fast_field_readers
                     .fast_bytes
@@ -86,81 +125,94 @@ impl FastFieldReaders {
             } else if let Some((fast_type, cardinality)) = type_and_cardinality(field_type) {
                 match cardinality {
                     Cardinality::SingleValue => {
-                        if let Some(fast_field_data) = fast_fields_composite.open_read(field) {
-                            match fast_type {
-                                FastType::U64 => {
-                                    let fast_field_reader = FastFieldReader::open(fast_field_data)?;
-                                    fast_field_readers
-                                        .fast_field_u64
-                                        .insert(field, fast_field_reader);
-                                }
-                                FastType::I64 => {
-                                    let fast_field_reader =
-                                        FastFieldReader::open(fast_field_data.clone())?;
-                                    fast_field_readers
-                                        .fast_field_i64
-                                        .insert(field, fast_field_reader);
-                                }
-                                FastType::F64 => {
-                                    let fast_field_reader =
-                                        FastFieldReader::open(fast_field_data.clone())?;
-                                    fast_field_readers
-                                        .fast_field_f64
-                                        .insert(field, fast_field_reader);
-                                }
-                                FastType::Date => {
-                                    let fast_field_reader =
-                                        FastFieldReader::open(fast_field_data.clone())?;
-                                    fast_field_readers
-                                        .fast_field_date
-                                        .insert(field, fast_field_reader);
-                                }
+                        let fast_field_data = match fast_fields_composite.open_read(field) {
+                            Some(fast_field_data) => fast_field_data,
+                            None => missing_fast_field_data(segment_meta, field, field_entry)?,
+                        };
+                        match fast_type {
+                            FastType::U64 => {
+                                let fast_field_reader = FastFieldReader::open(fast_field_data)?;
+                                fast_field_readers
+                                    .fast_field_u64
+                                    .insert(field, fast_field_reader);
+                            }
+                            FastType::I64 => {
+                                let fast_field_reader =
+                                    FastFieldReader::open(fast_field_data.clone())?;
+                                fast_field_readers
+                                    .fast_field_i64
+                                    .insert(field, fast_field_reader);
+                            }
+                            FastType::F64 => {
+                                let fast_field_reader =
+                                    FastFieldReader::open(fast_field_data.clone())?;
+                                fast_field_readers
+                                    .fast_field_f64
+                                    .insert(field, fast_field_reader);
+                            }
+                            FastType::Date => {
+                                let fast_field_reader =
+                                    FastFieldReader::open(fast_field_data.clone())?;
+                                fast_field_readers
+                                    .fast_field_date
+                                    .insert(field, fast_field_reader);
                             }
-                        } else {
-                            return Err(From::from(FastFieldNotAvailableError::new(field_entry)));
                         }
                     }
-                    Cardinality::MultiValues => {
+                    Cardinality::MultiValues | Cardinality::Optional => {
+                        if cardinality == Cardinality::Optional {
+                            fast_field_readers.optional_fields.insert(field);
+                        }
                         let idx_opt = fast_fields_composite.open_read_with_idx(field, 0);
                         let data_opt = fast_fields_composite.open_read_with_idx(field, 1);
-                        if let (Some(fast_field_idx), Some(fast_field_data)) = (idx_opt, data_opt) {
-                            let idx_reader = FastFieldReader::open(fast_field_idx)?;
-                            match fast_type {
-                                FastType::I64 => {
-                                    let vals_reader = FastFieldReader::open(fast_field_data)?;
-                                    let multivalued_int_fast_field =
-                                        MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
-                                    fast_field_readers
-                                        .fast_field_i64s
-                                        .insert(field, multivalued_int_fast_field);
-                                }
-                                FastType::U64 => {
-                                    let vals_reader = FastFieldReader::open(fast_field_data)?;
-                                    let multivalued_int_fast_field =
-                                        MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
-                                    fast_field_readers
-                                        .fast_field_u64s
-                                        .insert(field, multivalued_int_fast_field);
-                                }
-                                FastType::F64 => {
-                                    let vals_reader = FastFieldReader::open(fast_field_data)?;
-                                    let multivalued_int_fast_field =
-                                        MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
-                                    fast_field_readers
-                                        .fast_field_f64s
-                                        .insert(field, multivalued_int_fast_field);
-                                }
-                                FastType::Date => {
-                                    let vals_reader = FastFieldReader::open(fast_field_data)?;
-                                    let multivalued_int_fast_field =
-                                        MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
-                                    fast_field_readers
-                                        .fast_field_dates
-                                        .insert(field, multivalued_int_fast_field);
-                                }
+                        let (fast_field_idx, fast_field_data) = match (idx_opt, data_opt) {
+                            (Some(fast_field_idx), Some(fast_field_data)) => {
+                                (fast_field_idx, fast_field_data)
+                            }
+                            (None, None) => (
+                                missing_fast_field_data(segment_meta, field, field_entry)?,
+                                zero_fast_field_data(),
+                            ),
+                            _ => {
+                                return Err(From::from(FastFieldNotAvailableError::new(
+                                    field_entry,
+                                )))
+                            }
+                        };
+                        let idx_reader = FastFieldReader::open(fast_field_idx)?;
+                        match fast_type {
+                            FastType::I64 => {
+                                let vals_reader = FastFieldReader::open(fast_field_data)?;
+                                let multivalued_int_fast_field =
+                                    MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
+                                fast_field_readers
+                                    .fast_field_i64s
+                                    .insert(field, multivalued_int_fast_field);
+                            }
+                            FastType::U64 => {
+                                let vals_reader = FastFieldReader::open(fast_field_data)?;
+                                let multivalued_int_fast_field =
+                                    MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
+                                fast_field_readers
+                                    .fast_field_u64s
+                                    .insert(field, multivalued_int_fast_field);
+                            }
+                            FastType::F64 => {
+                                let vals_reader = FastFieldReader::open(fast_field_data)?;
+                                let multivalued_int_fast_field =
+                                    MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
+                                fast_field_readers
+                                    .fast_field_f64s
+                                    .insert(field, multivalued_int_fast_field);
+                            }
+                            FastType::Date => {
+                                let vals_reader = FastFieldReader::open(fast_field_data)?;
+                                let multivalued_int_fast_field =
+                                    MultiValueIntFastFieldReader::open(idx_reader, vals_reader);
+                                fast_field_readers
+                                    .fast_field_dates
+                                    .insert(field, multivalued_int_fast_field);
                             }
-                        } else {
-                            return Err(From::from(FastFieldNotAvailableError::new(field_entry)));
                         }
                     }
                 }
@@ -209,6 +261,42 @@ impl FastFieldReaders {
             .map(|fast_field_reader| fast_field_reader.cast())
     }
 
+    /// Returns the single-valued fast field reader associated to `field`,
+    /// with the value type `Item`.
+    ///
+    /// This is useful to write collectors or scorers that are generic over
+    /// the type of the fast field.
+    ///
+    /// If `field` is not a single-valued fast field of type `Item`,
+    /// this method returns `None`.
+    pub fn typed<Item: FastValue>(&self, field: Field) -> Option<FastFieldReader<Item>> {
+        match Item::to_type() {
+            Type::U64 => self.u64(field).map(FastFieldReader::cast),
+            Type::I64 => self.i64(field).map(FastFieldReader::cast),
+            Type::F64 => self.f64(field).map(FastFieldReader::cast),
+            Type::Date => self.date(field).map(FastFieldReader::cast),
+            _ => None,
+        }
+    }
+
+    /// Returns the multivalued fast field reader associated to `field`,
+    /// with the value type `Item`.
+    ///
+    /// If `field` is not a multivalued fast field of type `Item`,
+    /// this method returns `None`.
+    pub fn typed_multi<Item: FastValue>(
+        &self,
+        field: Field,
+    ) -> Option<MultiValueIntFastFieldReader<Item>> {
+        match Item::to_type() {
+            Type::U64 => self.u64s(field).map(MultiValueIntFastFieldReader::cast),
+            Type::I64 => self.i64s(field).map(MultiValueIntFastFieldReader::cast),
+            Type::F64 => self.f64s(field).map(MultiValueIntFastFieldReader::cast),
+            Type::Date => self.dates(field).map(MultiValueIntFastFieldReader::cast),
+            _ => None,
+        }
+    }
+
     /// Returns the `i64` fast field reader reader associated to `field`.
     ///
     /// If `field` is not a i64 fast field, this method returns `None`.
@@ -252,6 +340,9 @@ impl FastFieldReaders {
         if let Some(f64s_ff_reader) = self.f64s(field) {
             return Some(f64s_ff_reader.into_u64s_reader());
         }
+        if let Some(dates_ff_reader) = self.dates(field) {
+            return Some(dates_ff_reader.into_u64s_reader());
+        }
         None
     }
 
@@ -276,6 +367,45 @@ impl FastFieldReaders {
         self.fast_field_dates.get(&field).cloned()
     }
 
+    fn optional<Item: FastValue>(
+        &self,
+        field: Field,
+        multivalued_reader: Option<MultiValueIntFastFieldReader<Item>>,
+    ) -> Option<OptionalFastFieldReader<Item>> {
+        if !self.optional_fields.contains(&field) {
+            return None;
+        }
+        multivalued_reader.map(OptionalFastFieldReader::from)
+    }
+
+    /// Returns the optional `u64` fast field reader associated to `field`.
+    ///
+    /// If `field` is not a u64 fast field with `Cardinality::Optional`, this method returns `None`.
+    pub fn optional_u64(&self, field: Field) -> Option<OptionalFastFieldReader<u64>> {
+        self.optional(field, self.u64s(field))
+    }
+
+    /// Returns the optional `i64` fast field reader associated to `field`.
+    ///
+    /// If `field` is not a i64 fast field with `Cardinality::Optional`, this method returns `None`.
+    pub fn optional_i64(&self, field: Field) -> Option<OptionalFastFieldReader<i64>> {
+        self.optional(field, self.i64s(field))
+    }
+
+    /// Returns the optional `f64` fast field reader associated to `field`.
+    ///
+    /// If `field` is not a f64 fast field with `Cardinality::Optional`, this method returns `None`.
+    pub fn optional_f64(&self, field: Field) -> Option<OptionalFastFieldReader<f64>> {
+        self.optional(field, self.f64s(field))
+    }
+
+    /// Returns the optional `crate::DateTime` fast field reader associated to `field`.
+    ///
+    /// If `field` is not a date fast field with `Cardinality::Optional`, this method returns `None`.
+    pub fn optional_date(&self, field: Field) -> Option<OptionalFastFieldReader<crate::DateTime>> {
+        self.optional(field, self.dates(field))
+    }
+
     /// Returns the `bytes` fast field reader associated to `field`.
     ///
     /// If `field` is not a bytes fast field, returns `None`.
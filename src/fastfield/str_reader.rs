@@ -0,0 +1,204 @@
+use super::MultiValueIntFastFieldReader;
+use crate::error::DataCorruption;
+use crate::termdict::TermDictionary;
+use crate::termdict::TermOrdinal;
+use crate::DocId;
+use crate::TantivyError;
+use std::str;
+
+/// The str fast field reader makes it possible to access the terms
+/// associated to a given document for a text field declared as fast.
+///
+/// Just like for the `FacetReader`, the terms are exposed in the
+/// form of term ordinals. Term ordinals are defined as their position
+/// in the sorted term dictionary of the segment: comparing two ordinals
+/// is equivalent to comparing the terms themselves.
+///
+/// These ordinals are segment local and only make sense for a given segment.
+/// They can be translated into a term via `.ord_to_str(...)`.
+pub struct StrFastFieldReader {
+    term_ords: MultiValueIntFastFieldReader<u64>,
+    term_dict: TermDictionary,
+    buffer: Vec<u8>,
+}
+
+impl StrFastFieldReader {
+    /// Creates a new `StrFastFieldReader`.
+    pub fn new(
+        term_ords: MultiValueIntFastFieldReader<u64>,
+        term_dict: TermDictionary,
+    ) -> StrFastFieldReader {
+        StrFastFieldReader {
+            term_ords,
+            term_dict,
+            buffer: vec![],
+        }
+    }
+
+    /// Returns the number of distinct terms in the segment.
+    /// This does not take in account the documents that may be marked
+    /// as deleted.
+    ///
+    /// Term ordinals range from `0` to `num_terms() - 1`.
+    pub fn num_terms(&self) -> usize {
+        self.term_dict.num_terms()
+    }
+
+    /// Accessor for the term dictionary.
+    pub fn term_dict(&self) -> &TermDictionary {
+        &self.term_dict
+    }
+
+    /// Given a term ordinal, writes the associated term into `output`.
+    ///
+    /// Returns an `InvalidArgument` error if the ordinal is not lower than `num_terms()`.
+    pub fn ord_to_str(&mut self, term_ord: TermOrdinal, output: &mut String) -> crate::Result<()> {
+        let found_term = self.term_dict.ord_to_term(term_ord, &mut self.buffer)?;
+        if !found_term {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Term ordinal {} not found.",
+                term_ord
+            )));
+        }
+        let term_str = str::from_utf8(&self.buffer[..])
+            .map_err(|utf8_err| DataCorruption::comment_only(utf8_err.to_string()))?;
+        output.clear();
+        output.push_str(term_str);
+        Ok(())
+    }
+
+    /// Return the sorted list of term ordinals associated to a document.
+    pub fn term_ords(&self, doc: DocId, output: &mut Vec<TermOrdinal>) {
+        self.term_ords.get_vals(doc, output);
+    }
+
+    /// Returns the term vector of a document: its terms, in sorted order,
+    /// together with their number of occurrences in the document.
+    ///
+    /// The terms are read from the fast field, so the text of the document
+    /// does not need to be stored or analyzed again.
+    /// Positions and offsets are not available.
+    pub fn term_vector(&mut self, doc: DocId) -> crate::Result<Vec<(String, u32)>> {
+        let mut term_ords = Vec::new();
+        self.term_ords(doc, &mut term_ords);
+        let mut term_vector: Vec<(String, u32)> = Vec::new();
+        let mut previous_term_ord = None;
+        for term_ord in term_ords {
+            if previous_term_ord == Some(term_ord) {
+                if let Some((_, term_freq)) = term_vector.last_mut() {
+                    *term_freq += 1;
+                }
+                continue;
+            }
+            let mut term = String::new();
+            self.ord_to_str(term_ord, &mut term)?;
+            term_vector.push((term, 1));
+            previous_term_ord = Some(term_ord);
+        }
+        Ok(term_vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Schema, FAST, STRING, TEXT};
+    use crate::{Index, IndexWriter, TantivyError, Term};
+
+    #[test]
+    fn test_str_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING | FAST);
+        let body = schema_builder.add_text_field("body", TEXT.set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(city=>"Tokyo", body=>"the cat"));
+        index_writer.add_document(doc!(body=>"a dog"));
+        index_writer.add_document(doc!(city=>"Paris", city=>"Berlin"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(city=>"Kyoto"));
+        index_writer.add_document(doc!(city=>"Berlin"));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let mut city_reader = segment_reader.str_fast_field_reader(city)?;
+        assert_eq!(city_reader.num_terms(), 3);
+        let mut term_ords = Vec::new();
+        city_reader.term_ords(2, &mut term_ords);
+        assert_eq!(term_ords, vec![0, 1]);
+        let mut term = String::new();
+        city_reader.ord_to_str(0, &mut term)?;
+        assert_eq!(term, "Berlin");
+        assert!(matches!(
+            city_reader.ord_to_str(3, &mut term),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        city_reader.term_ords(1, &mut term_ords);
+        assert!(term_ords.is_empty());
+        let body_reader = segment_reader.str_fast_field_reader(body)?;
+        body_reader.term_ords(0, &mut term_ords);
+        assert_eq!(term_ords.len(), 2);
+
+        // Term ordinals are remapped on merge.
+        index_writer.delete_term(Term::from_field_text(city, "Tokyo"));
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        futures::executor::block_on(index_writer.merge(&segment_ids))?;
+        IndexWriter::wait_merging_threads(index_writer)?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let mut city_reader = searcher.segment_reader(0).str_fast_field_reader(city)?;
+        assert_eq!(city_reader.num_terms(), 3);
+        let mut cities = Vec::new();
+        for doc in 0..4 {
+            city_reader.term_ords(doc, &mut term_ords);
+            let mut doc_cities = Vec::new();
+            for &term_ord in &term_ords {
+                city_reader.ord_to_str(term_ord, &mut term)?;
+                doc_cities.push(term.clone());
+            }
+            cities.push(doc_cities.join(","));
+        }
+        assert_eq!(cities, vec!["", "Berlin,Paris", "Kyoto", "Berlin"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_str_fast_field_term_vector() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT.set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body=>"the cat chased the dog"));
+        index_writer.add_document(doc!());
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let mut body_reader = searcher.segment_reader(0).str_fast_field_reader(body)?;
+        assert_eq!(
+            body_reader.term_vector(0)?,
+            vec![
+                ("cat".to_string(), 1),
+                ("chased".to_string(), 1),
+                ("dog".to_string(), 1),
+                ("the".to_string(), 2)
+            ]
+        );
+        assert!(body_reader.term_vector(1)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_str_fast_field_reader_requires_fast_field() {
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests().unwrap();
+        index_writer.add_document(doc!(city=>"Tokyo"));
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert!(searcher
+            .segment_reader(0)
+            .str_fast_field_reader(city)
+            .is_err());
+    }
+}
@@ -4,7 +4,7 @@ use crate::common::BinarySerializable;
 use crate::common::VInt;
 use crate::fastfield::{BytesFastFieldWriter, FastFieldSerializer};
 use crate::postings::UnorderedTermId;
-use crate::schema::{Cardinality, Document, Field, FieldEntry, FieldType, Schema};
+use crate::schema::{Cardinality, DatePrecision, Document, Field, FieldEntry, FieldType, Schema};
 use crate::termdict::TermOrdinal;
 use fnv::FnvHashMap;
 use std::collections::HashMap;
@@ -43,10 +43,19 @@ impl FastFieldsWriter {
                             let mut fast_field_writer = IntFastFieldWriter::new(field);
                             let default_value = fast_field_default_value(field_entry);
                             fast_field_writer.set_val_if_missing(default_value);
+                            fast_field_writer.set_date_precision(int_options.get_date_precision());
                             single_value_writers.push(fast_field_writer);
                         }
                         Some(Cardinality::MultiValues) => {
-                            let fast_field_writer = MultiValueIntFastFieldWriter::new(field, false);
+                            let mut fast_field_writer =
+                                MultiValueIntFastFieldWriter::new(field, false);
+                            fast_field_writer.set_date_precision(int_options.get_date_precision());
+                            multi_values_writers.push(fast_field_writer);
+                        }
+                        Some(Cardinality::Optional) => {
+                            let mut fast_field_writer =
+                                MultiValueIntFastFieldWriter::optional(field);
+                            fast_field_writer.set_date_precision(int_options.get_date_precision());
                             multi_values_writers.push(fast_field_writer);
                         }
                         None => {}
@@ -56,13 +65,19 @@ impl FastFieldsWriter {
                     let fast_field_writer = MultiValueIntFastFieldWriter::new(field, true);
                     multi_values_writers.push(fast_field_writer);
                 }
+                FieldType::Str(ref text_options) => {
+                    if text_options.is_fast() {
+                        // Just like facets, the term ids are pushed by the `SegmentWriter`.
+                        let fast_field_writer = MultiValueIntFastFieldWriter::new(field, true);
+                        multi_values_writers.push(fast_field_writer);
+                    }
+                }
                 FieldType::Bytes(bytes_option) => {
                     if bytes_option.is_fast() {
                         let fast_field_writer = BytesFastFieldWriter::new(field);
                         bytes_value_writers.push(fast_field_writer);
                     }
                 }
-                _ => {}
             }
         }
         FastFieldsWriter {
@@ -162,6 +177,7 @@ pub struct IntFastFieldWriter {
     val_if_missing: u64,
     val_min: u64,
     val_max: u64,
+    date_precision: DatePrecision,
 }
 
 impl IntFastFieldWriter {
@@ -174,6 +190,7 @@ impl IntFastFieldWriter {
             val_if_missing: 0u64,
             val_min: u64::max_value(),
             val_max: 0,
+            date_precision: DatePrecision::Seconds,
         }
     }
 
@@ -190,6 +207,11 @@ impl IntFastFieldWriter {
         self.val_if_missing = val_if_missing;
     }
 
+    /// Sets the precision to which date values are truncated.
+    pub(crate) fn set_date_precision(&mut self, date_precision: DatePrecision) {
+        self.date_precision = date_precision;
+    }
+
     /// Records a new value.
     ///
     /// The n-th value being recorded is implicitely
@@ -222,7 +244,7 @@ impl IntFastFieldWriter {
     /// only the first one is taken in account.
     fn extract_val(&self, doc: &Document) -> u64 {
         match doc.get_first(self.field) {
-            Some(v) => super::value_to_u64(v),
+            Some(v) => super::value_to_u64(v, self.date_precision),
             None => self.val_if_missing,
         }
     }
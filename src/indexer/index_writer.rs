@@ -2,12 +2,14 @@ use super::operation::{AddOperation, UserOperation};
 use super::segment_updater::SegmentUpdater;
 use super::PreparedCommit;
 use crate::common::BitSet;
+use crate::core::common_attributes;
 use crate::core::Index;
 use crate::core::Segment;
 use crate::core::SegmentComponent;
 use crate::core::SegmentId;
 use crate::core::SegmentMeta;
 use crate::core::SegmentReader;
+use crate::core::SerializableSegment;
 use crate::directory::TerminatingWrite;
 use crate::directory::{DirectoryLock, GarbageCollectionResult};
 use crate::docset::{DocSet, TERMINATED};
@@ -15,25 +17,32 @@ use crate::error::TantivyError;
 use crate::fastfield::write_delete_bitset;
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
+use crate::indexer::merger::IndexMerger;
 use crate::indexer::operation::DeleteOperation;
+use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::indexer::stamper::Stamper;
+use crate::indexer::write_ahead_log::{LoggedOperation, WriteAheadLog};
 use crate::indexer::MergePolicy;
 use crate::indexer::SegmentEntry;
 use crate::indexer::SegmentWriter;
 use crate::schema::Document;
 use crate::schema::IndexRecordOption;
 use crate::schema::Term;
+use crate::schema::{Field, FieldType, Value};
 use crate::Opstamp;
 use crossbeam::channel;
 use futures::executor::block_on;
 use futures::future::Future;
 use smallvec::smallvec;
 use smallvec::SmallVec;
+use std::collections::BTreeMap;
+use std::iter::Peekable;
 use std::mem;
 use std::ops::Range;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Instant;
 
 // Size of the margin for the heap. A segment is closed when the remaining memory
 // in the heap goes below MARGIN_IN_BYTES.
@@ -53,7 +62,13 @@ const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
 // - all docs in the operation will happen on the same segment and continuous docids.
 // - all operations in the group are committed at the same time, making the group
 // atomic.
-type OperationGroup = SmallVec<[AddOperation; 4]>;
+//
+// The segment attributes set when the operations were sent travel with them:
+// a segment only holds documents with the same attributes.
+struct OperationGroup {
+    operations: SmallVec<[AddOperation; 4]>,
+    segment_attributes: Arc<BTreeMap<String, String>>,
+}
 type OperationSender = channel::Sender<OperationGroup>;
 type OperationReceiver = channel::Receiver<OperationGroup>;
 
@@ -87,6 +102,12 @@ pub struct IndexWriter {
 
     stamper: Stamper,
     committed_opstamp: Opstamp,
+
+    unique_key_field: Option<Field>,
+
+    write_ahead_log: Option<Mutex<WriteAheadLog>>,
+
+    segment_attributes: Arc<RwLock<Arc<BTreeMap<String, String>>>>,
 }
 
 fn compute_deleted_bitset(
@@ -189,18 +210,25 @@ pub(crate) fn advance_deletes(
     Ok(())
 }
 
-fn index_documents(
+fn index_documents<I: Iterator<Item = OperationGroup>>(
     memory_budget: usize,
     segment: Segment,
-    grouped_document_iterator: &mut dyn Iterator<Item = OperationGroup>,
+    grouped_document_iterator: &mut Peekable<I>,
     segment_updater: &mut SegmentUpdater,
     mut delete_cursor: DeleteCursor,
+    segment_attributes: Arc<BTreeMap<String, String>>,
 ) -> crate::Result<bool> {
     let schema = segment.schema();
 
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone(), &schema)?;
-    for document_group in grouped_document_iterator {
-        for doc in document_group {
+    loop {
+        // Documents with different attributes go to the next segment.
+        match grouped_document_iterator.peek() {
+            Some(document_group) if document_group.segment_attributes == segment_attributes => {}
+            _ => break,
+        }
+        let document_group = grouped_document_iterator.next().unwrap();
+        for doc in document_group.operations {
             segment_writer.add_document(doc, &schema)?;
         }
         let mem_usage = segment_writer.mem_usage();
@@ -223,9 +251,12 @@ fn index_documents(
     // the worker thread.
     assert!(max_doc > 0);
 
+    let flush_start = Instant::now();
     let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
 
-    let segment_with_max_doc = segment.with_max_doc(max_doc);
+    let segment_with_max_doc = segment
+        .with_max_doc(max_doc)
+        .with_attributes(segment_attributes.as_ref().clone());
 
     let last_docstamp: Opstamp = *(doc_opstamps.last().unwrap());
 
@@ -241,6 +272,12 @@ fn index_documents(
         delete_cursor,
         delete_bitset_opt,
     );
+    debug!(
+        "Flushed segment {} with max_doc={} in {:?}.",
+        segment_with_max_doc.id().short_uuid_string(),
+        max_doc,
+        flush_start.elapsed()
+    );
     block_on(segment_updater.schedule_add_segment(segment_entry))?;
     Ok(true)
 }
@@ -339,6 +376,12 @@ impl IndexWriter {
             stamper,
 
             worker_id: 0,
+
+            unique_key_field: None,
+
+            write_ahead_log: None,
+
+            segment_attributes: Default::default(),
         };
         index_writer.start_workers()?;
         Ok(index_writer)
@@ -375,6 +418,12 @@ impl IndexWriter {
             error!("Some merging thread failed {:?}", e);
         }
 
+        // Files that could not be deleted during garbage collection (typically
+        // on Windows, because they were still mmapped) may be deletable now.
+        if let Err(err) = self.index.directory().delete_pending_files() {
+            error!("Failed to delete pending files {:?}", err);
+        }
+
         result
     }
 
@@ -385,6 +434,61 @@ impl IndexWriter {
         block_on(self.segment_updater.schedule_add_segment(segment_entry))
     }
 
+    /// Imports the documents of other indexes into this index.
+    ///
+    /// The indexes are required to have the same schema as this index.
+    /// The segments of each of them are merged into a single new segment
+    /// of this index, with new doc ids. The documents deleted in the
+    /// imported indexes are not imported.
+    ///
+    /// This makes it possible to build several indexes in parallel, and to
+    /// combine them afterwards.
+    ///
+    /// The imported documents are only visible after the next commit, and are
+    /// not affected by the delete operations issued before the call.
+    ///
+    /// The merge happens in the calling thread.
+    pub fn add_indexes(&mut self, indexes: &[Index]) -> crate::Result<()> {
+        let schema = self.index.schema();
+        for index in indexes {
+            if index.schema() != schema {
+                return Err(TantivyError::SchemaError(
+                    "Cannot add an index with a different schema.".to_string(),
+                ));
+            }
+        }
+        // Delete operations issued before this call do not apply to the imported documents.
+        let opstamp = self.stamper.stamp();
+        for index in indexes {
+            let segments = index.searchable_segments()?;
+            // Writing a segment without any document would leave its files orphaned.
+            let num_docs: u32 = segments
+                .iter()
+                .map(|segment| segment.meta().num_docs())
+                .sum();
+            if num_docs == 0 {
+                continue;
+            }
+            let merger = IndexMerger::open(schema.clone(), &segments[..])?;
+            let segment = self.index.new_segment();
+            let segment_serializer = SegmentSerializer::for_segment(segment.clone())?;
+            let num_docs = merger.write(segment_serializer)?;
+            let segment_metas: Vec<SegmentMeta> = segments
+                .iter()
+                .map(|segment| segment.meta().clone())
+                .collect();
+            let segment_meta = self
+                .index
+                .new_segment_meta(segment.id(), num_docs)
+                .with_attributes(common_attributes(&segment_metas));
+            let mut delete_cursor = self.delete_queue.cursor();
+            delete_cursor.skip_to(opstamp);
+            let segment_entry = SegmentEntry::new(segment_meta, delete_cursor, None);
+            block_on(self.segment_updater.schedule_add_segment(segment_entry))?;
+        }
+        Ok(())
+    }
+
     /// Creates a new segment.
     ///
     /// This method is useful only for users trying to do complex
@@ -410,10 +514,10 @@ impl IndexWriter {
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
             .spawn(move || {
+                // The iterator is kept across segments: a segment stops
+                // before the operations with different attributes it peeked.
+                let mut document_iterator = document_receiver_clone.into_iter().peekable();
                 loop {
-                    let mut document_iterator =
-                        document_receiver_clone.clone().into_iter().peekable();
-
                     // the peeking here is to avoid
                     // creating a new segment's files
                     // if no document are available.
@@ -421,18 +525,19 @@ impl IndexWriter {
                     // this is a valid guarantee as the
                     // peeked document now belongs to
                     // our local iterator.
-                    if let Some(operations) = document_iterator.peek() {
-                        if let Some(first) = operations.first() {
+                    let segment_attributes = if let Some(operations) = document_iterator.peek() {
+                        if let Some(first) = operations.operations.first() {
                             delete_cursor.skip_to(first.opstamp);
                         } else {
                             return Ok(());
                         }
+                        operations.segment_attributes.clone()
                     } else {
                         // No more documents.
                         // Happens when there is a commit, or if the `IndexWriter`
                         // was dropped.
                         return Ok(());
-                    }
+                    };
                     let segment = index.new_segment();
                     index_documents(
                         mem_budget,
@@ -440,6 +545,7 @@ impl IndexWriter {
                         &mut document_iterator,
                         &mut segment_updater,
                         delete_cursor.clone(),
+                        segment_attributes,
                     )?;
                 }
             })?;
@@ -458,6 +564,196 @@ impl IndexWriter {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Returns the number of commit points kept in the index.
+    pub fn get_num_commit_points_to_keep(&self) -> usize {
+        self.segment_updater.get_num_commit_points_to_keep()
+    }
+
+    /// Sets the number of commit points kept in the index. Defaults to 0.
+    ///
+    /// The setting is persisted in the `meta.json` file on the next commit,
+    /// and new `IndexWriter`s opened on the index start with it.
+    ///
+    /// When set, the `IndexMeta` of each commit is also saved in
+    /// a `meta.<generation>.json` file, and the last
+    /// `num_commit_points_to_keep` commit points are protected from
+    /// garbage collection.
+    /// A reader can then open one of these commit points via
+    /// `IndexReaderBuilder::commit_point`.
+    pub fn set_num_commit_points_to_keep(&self, num_commit_points_to_keep: usize) {
+        self.segment_updater
+            .set_num_commit_points_to_keep(num_commit_points_to_keep);
+    }
+
+    /// Sets the user attributes of the segments holding the documents added from now on,
+    /// e.g. the shard the documents come from, or the id of an ingestion batch.
+    ///
+    /// A segment only holds documents added with the same attributes: documents added
+    /// after the attributes changed go to a new segment.
+    /// The attributes are persisted in the segment metas, and can be read
+    /// with `SegmentMeta::attributes` or `SegmentReader::attributes`.
+    /// A segment resulting from a merge keeps the attributes shared, with the same
+    /// value, by all of the merged segments.
+    pub fn set_segment_attributes(&self, attributes: BTreeMap<String, String>) {
+        *self
+            .segment_attributes
+            .write()
+            .expect("Segment attributes lock poisoned.") = Arc::new(attributes);
+    }
+
+    fn operation_group(&self, operations: SmallVec<[AddOperation; 4]>) -> OperationGroup {
+        let segment_attributes = self
+            .segment_attributes
+            .read()
+            .expect("Segment attributes lock poisoned.")
+            .clone();
+        OperationGroup {
+            operations,
+            segment_attributes,
+        }
+    }
+
+    /// Returns the unique key field, if any.
+    pub fn unique_key_field(&self) -> Option<Field> {
+        self.unique_key_field
+    }
+
+    /// Designates `field` as the unique key of the documents.
+    ///
+    /// From then on, adding a document (with `add_document` or `run`) first
+    /// deletes the documents having the same key, whether they were added
+    /// in a previous commit or earlier in the same commit. After the commit,
+    /// only the last version of a document is left.
+    ///
+    /// Documents without a value for `field` are added as is.
+    /// Segments imported with `add_indexes` or `add_segment` are not deduplicated.
+    ///
+    /// # Errors
+    /// Returns a `SchemaError` if `field` is not indexed, or if it is a
+    /// text field that is not indexed as a single token with the `raw` tokenizer
+    /// (e.g. `STRING`).
+    pub fn set_unique_key_field(&mut self, field: Field) -> crate::Result<()> {
+        let field_entry = self.index.schema().get_field_entry(field).clone();
+        let is_valid_key = match field_entry.field_type() {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .map(|indexing_options| indexing_options.tokenizer() == "raw")
+                .unwrap_or(false),
+            FieldType::HierarchicalFacet => false,
+            _ => field_entry.is_indexed(),
+        };
+        if !is_valid_key {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} cannot be used as a unique key: it must be indexed as a single token.",
+                field_entry.name()
+            )));
+        }
+        self.unique_key_field = Some(field);
+        Ok(())
+    }
+
+    /// Enables the write-ahead log, and replays the operations that were logged
+    /// but not committed by a previous `IndexWriter`, typically before a crash.
+    ///
+    /// From then on, `add_document`, `delete_term`, `run` and `delete_all_documents`
+    /// write their operations to the log, and flush it, before returning.
+    /// The operations that were acknowledged are therefore not lost if the process
+    /// crashes before they are committed. The log is emptied on every commit and
+    /// on rollback.
+    ///
+    /// These methods panic if the operation cannot be written to the log, while
+    /// `try_add_document`, `try_delete_term` and `try_run` return an error, and do not
+    /// apply the operation. After such an error, the log refuses any operation
+    /// until the next commit or rollback.
+    ///
+    /// This method should be called right after the creation of the `IndexWriter`,
+    /// and after `set_unique_key_field` if a unique key is used. The write-ahead log
+    /// stays enabled after a rollback.
+    ///
+    /// Returns the number of operations that were replayed. Like any other operation,
+    /// they are only persisted in the index by the next commit.
+    pub fn enable_write_ahead_log(&mut self) -> crate::Result<usize> {
+        let committed_opstamp = self.index.load_metas()?.opstamp;
+        let (write_ahead_log, logged_operations) =
+            WriteAheadLog::open(self.index.directory(), committed_opstamp)?;
+        let mut num_replayed_operations = 0;
+        for logged_operation in logged_operations {
+            match logged_operation {
+                LoggedOperation::Group(mut user_operations) => {
+                    num_replayed_operations += user_operations.len();
+                    if user_operations.len() == 1 {
+                        match user_operations.pop() {
+                            Some(UserOperation::Add(document)) => {
+                                self.add_document(document);
+                            }
+                            Some(UserOperation::Delete(term)) => {
+                                self.delete_term(term);
+                            }
+                            None => {}
+                        }
+                    } else {
+                        self.run(user_operations);
+                    }
+                }
+                LoggedOperation::DeleteAll => {
+                    num_replayed_operations += 1;
+                    self.delete_all_documents()?;
+                }
+            }
+        }
+        info!(
+            "Replayed {} operations from the write-ahead log",
+            num_replayed_operations
+        );
+        self.write_ahead_log = Some(Mutex::new(write_ahead_log));
+        Ok(num_replayed_operations)
+    }
+
+    /// Locks the write-ahead log, if it is enabled.
+    ///
+    /// The lock is held while the operations are stamped and sent, so that
+    /// the order of the log is the order of the opstamps.
+    fn lock_write_ahead_log(&self) -> Option<MutexGuard<'_, WriteAheadLog>> {
+        self.write_ahead_log.as_ref().map(|write_ahead_log| {
+            write_ahead_log
+                .lock()
+                .expect("Write-ahead log lock poisoned.")
+        })
+    }
+
+    /// Empties the write-ahead log, if it is enabled, once the index is
+    /// in the state of the commit with the given `opstamp`.
+    pub(crate) fn truncate_write_ahead_log(&mut self, opstamp: Opstamp) -> crate::Result<()> {
+        if let Some(write_ahead_log) = self.write_ahead_log.as_mut() {
+            write_ahead_log
+                .get_mut()
+                .expect("Write-ahead log lock poisoned.")
+                .truncate(opstamp)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the terms identifying the previous versions of a document
+    /// according to the unique key field.
+    fn unique_key_terms(&self, document: &Document) -> Vec<Term> {
+        let field = match self.unique_key_field {
+            Some(field) => field,
+            None => return Vec::new(),
+        };
+        document
+            .get_all(field)
+            .flat_map(|value| match value {
+                Value::Str(text) => Some(Term::from_field_text(field, text)),
+                Value::U64(val) => Some(Term::from_field_u64(field, *val)),
+                Value::I64(val) => Some(Term::from_field_i64(field, *val)),
+                Value::F64(val) => Some(Term::from_field_f64(field, *val)),
+                Value::Date(date) => Some(Term::from_field_date(field, date)),
+                Value::Bytes(bytes) => Some(Term::from_field_bytes(field, bytes)),
+                _ => None,
+            })
+            .collect()
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
         for _ in 0..self.num_threads {
             self.add_indexing_worker()?;
@@ -509,6 +805,10 @@ impl IndexWriter {
     /// }
     /// ```
     pub fn delete_all_documents(&self) -> crate::Result<Opstamp> {
+        let mut write_ahead_log = self.lock_write_ahead_log();
+        if let Some(write_ahead_log) = write_ahead_log.as_mut() {
+            write_ahead_log.log_delete_all()?;
+        }
         // Delete segments
         self.segment_updater.remove_all_segments();
         // Return new stamp - reverted stamp
@@ -528,6 +828,46 @@ impl IndexWriter {
         async move { segment_updater.start_merge(merge_operation)?.await }
     }
 
+    /// Rewrites the committed segments written with an older index format
+    /// using the current index format.
+    ///
+    /// The segments to upgrade are merged into a single new segment.
+    /// This is useful after upgrading tantivy, to get rid of segments
+    /// written with an older (but still supported) index format.
+    /// Segments already written with the current index format are left untouched.
+    ///
+    /// Resolves to `None` if no segment needs to be upgraded.
+    ///
+    /// This fails if some of the segments to upgrade are currently being merged.
+    pub fn upgrade_segments(&mut self) -> impl Future<Output = crate::Result<Option<SegmentMeta>>> {
+        let segment_ids_res = self.outdated_segment_ids();
+        let segment_updater = self.segment_updater.clone();
+        async move {
+            let segment_ids = segment_ids_res?;
+            if segment_ids.is_empty() {
+                return Ok(None);
+            }
+            let merge_operation = segment_updater.make_merge_operation(&segment_ids);
+            let segment_meta = segment_updater.start_merge(merge_operation)?.await?;
+            Ok(Some(segment_meta))
+        }
+    }
+
+    /// Returns the ids of the committed segments written with an older index format.
+    ///
+    /// The version of a segment is read from the footer of its store file.
+    fn outdated_segment_ids(&self) -> crate::Result<Vec<SegmentId>> {
+        let mut segment_ids = Vec::new();
+        for segment_meta in self.index.searchable_segment_metas()? {
+            let store_path = segment_meta.relative_path(SegmentComponent::STORE);
+            let version = self.index.directory().file_version(&store_path)?;
+            if version.index_format_version() < crate::INDEX_FORMAT_VERSION {
+                segment_ids.push(segment_meta.id());
+            }
+        }
+        Ok(segment_ids)
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -551,6 +891,9 @@ impl IndexWriter {
     /// After calling rollback, the index is in the same
     /// state as it was after the last commit.
     ///
+    /// The files of the segments that were discarded are
+    /// garbage collected.
+    ///
     /// The opstamp at the last commit is returned.
     pub fn rollback(&mut self) -> crate::Result<Opstamp> {
         info!("Rolling back to opstamp {}", self.committed_opstamp);
@@ -565,12 +908,16 @@ impl IndexWriter {
             .take()
             .expect("The IndexWriter does not have any lock. This is a bug, please report.");
 
-        let new_index_writer: IndexWriter = IndexWriter::new(
+        let mut new_index_writer: IndexWriter = IndexWriter::new(
             &self.index,
             self.num_threads,
             self.heap_size_in_bytes_per_thread,
             directory_lock,
         )?;
+        new_index_writer.unique_key_field = self.unique_key_field;
+        new_index_writer.write_ahead_log = self.write_ahead_log.take();
+        new_index_writer.segment_attributes = self.segment_attributes.clone();
+        new_index_writer.set_num_commit_points_to_keep(self.get_num_commit_points_to_keep());
 
         // the current `self` is dropped right away because of this call.
         //
@@ -585,6 +932,16 @@ impl IndexWriter {
         // was dropped with the index_writer.
         for _ in document_receiver {}
 
+        // The segments that were aborted are not referenced by any commit.
+        // We garbage collect them right away rather than waiting for the next commit.
+        // Failing to do so does not fail the rollback: the files will be collected later.
+        if let Err(err) = block_on(self.garbage_collect_files()) {
+            error!("Failed to garbage collect files on rollback: {:?}", err);
+        }
+
+        let committed_opstamp = self.index.load_metas()?.opstamp;
+        self.truncate_write_ahead_log(committed_opstamp)?;
+
         Ok(self.committed_opstamp)
     }
 
@@ -657,7 +1014,14 @@ impl IndexWriter {
     /// that made it in the commit.
     ///
     pub fn commit(&mut self) -> crate::Result<Opstamp> {
-        self.prepare_commit()?.commit()
+        let commit_start = Instant::now();
+        let opstamp = self.prepare_commit()?.commit()?;
+        debug!(
+            "Committed opstamp {} in {:?}.",
+            opstamp,
+            commit_start.elapsed()
+        );
+        Ok(opstamp)
     }
 
     pub(crate) fn segment_updater(&self) -> &SegmentUpdater {
@@ -672,11 +1036,29 @@ impl IndexWriter {
     ///
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write-ahead log is enabled and the deletion
+    /// cannot be written to it. See `try_delete_term` for a fallible version.
     pub fn delete_term(&self, term: Term) -> Opstamp {
+        self.try_delete_term(term)
+            .expect("Failed to write the deletion to the write-ahead log.")
+    }
+
+    /// Same as `delete_term`, but returns an error instead of panicking
+    /// if the write-ahead log is enabled and the deletion cannot be written to it.
+    ///
+    /// The deletion is not applied if an error is returned.
+    pub fn try_delete_term(&self, term: Term) -> crate::Result<Opstamp> {
+        let mut write_ahead_log = self.lock_write_ahead_log();
+        if let Some(write_ahead_log) = write_ahead_log.as_mut() {
+            write_ahead_log.log_delete(&term)?;
+        }
         let opstamp = self.stamper.stamp();
         let delete_operation = DeleteOperation { opstamp, term };
         self.delete_queue.push(delete_operation);
-        opstamp
+        Ok(opstamp)
     }
 
     /// Returns the opstamp of the last successful commit.
@@ -697,14 +1079,60 @@ impl IndexWriter {
     /// The opstamp is an increasing `u64` that can
     /// be used by the client to align commits with its own
     /// document queue.
+    ///
+    /// If a unique key field is set (see `set_unique_key_field`), the documents
+    /// with the same key are deleted first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write-ahead log is enabled and the document
+    /// cannot be written to it. See `try_add_document` for a fallible version.
     pub fn add_document(&self, document: Document) -> Opstamp {
+        self.try_add_document(document)
+            .expect("Failed to write the document to the write-ahead log.")
+    }
+
+    /// Same as `add_document`, but returns an error instead of panicking
+    /// if the write-ahead log is enabled and the document cannot be written to it.
+    ///
+    /// The document is not added if an error is returned.
+    pub fn try_add_document(&self, document: Document) -> crate::Result<Opstamp> {
+        if self.unique_key_field.is_some() {
+            return self.try_run(vec![UserOperation::Add(document)]);
+        }
+        let mut write_ahead_log = self.lock_write_ahead_log();
+        if let Some(write_ahead_log) = write_ahead_log.as_mut() {
+            write_ahead_log.log_add(&document)?;
+        }
         let opstamp = self.stamper.stamp();
         let add_operation = AddOperation { opstamp, document };
-        let send_result = self.operation_sender.send(smallvec![add_operation]);
+        let send_result = self
+            .operation_sender
+            .send(self.operation_group(smallvec![add_operation]));
         if let Err(e) = send_result {
             panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
         }
-        opstamp
+        Ok(opstamp)
+    }
+
+    /// Replaces the documents containing `term` by `document`.
+    ///
+    /// The deletion and the addition are run as a single group of operations
+    /// (see `IndexWriter.run`): they receive contiguous opstamps, and `document`
+    /// is not affected by the deletion, even if it contains `term`.
+    ///
+    /// Like adds and deletes, the update will be visible to readers only
+    /// after calling `commit()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write-ahead log is enabled and the operations
+    /// cannot be written to it. See `try_run` for a fallible version.
+    pub fn update_document(&self, term: Term, document: Document) -> Opstamp {
+        self.run(vec![
+            UserOperation::Delete(term),
+            UserOperation::Add(document),
+        ])
     }
 
     /// Gets a range of stamps from the stamper and "pops" the last stamp
@@ -737,17 +1165,55 @@ impl IndexWriter {
     /// `user_operations`, an empty `Vec<UserOperation>`, still receives
     /// a valid opstamp even though no changes were _actually_ made to the index.
     ///
+    /// If a unique key field is set (see `set_unique_key_field`), each add
+    /// operation is preceded by the deletion of the documents with the same key.
+    ///
     /// Like adds and deletes (see `IndexWriter.add_document` and
     /// `IndexWriter.delete_term`), the changes made by calling `run` will be
     /// visible to readers only after calling `commit()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write-ahead log is enabled and the operations
+    /// cannot be written to it. See `try_run` for a fallible version.
     pub fn run(&self, user_operations: Vec<UserOperation>) -> Opstamp {
+        self.try_run(user_operations)
+            .expect("Failed to write the operations to the write-ahead log.")
+    }
+
+    /// Same as `run`, but returns an error instead of panicking if the
+    /// write-ahead log is enabled and the operations cannot be written to it.
+    ///
+    /// None of the operations are applied if an error is returned.
+    pub fn try_run(&self, mut user_operations: Vec<UserOperation>) -> crate::Result<Opstamp> {
+        let mut write_ahead_log = self.lock_write_ahead_log();
+        if let Some(write_ahead_log) = write_ahead_log.as_mut() {
+            write_ahead_log.log_operations(&user_operations)?;
+        }
+        if self.unique_key_field.is_some() {
+            user_operations = user_operations
+                .into_iter()
+                .flat_map(|user_op| {
+                    let mut user_ops = match &user_op {
+                        UserOperation::Add(document) => self
+                            .unique_key_terms(document)
+                            .into_iter()
+                            .map(UserOperation::Delete)
+                            .collect(),
+                        UserOperation::Delete(_) => Vec::new(),
+                    };
+                    user_ops.push(user_op);
+                    user_ops
+                })
+                .collect();
+        }
         let count = user_operations.len() as u64;
         if count == 0 {
-            return self.stamper.stamp();
+            return Ok(self.stamper.stamp());
         }
         let (batch_opstamp, stamps) = self.get_batch_opstamps(count);
 
-        let mut adds = OperationGroup::default();
+        let mut adds = SmallVec::new();
 
         for (user_op, opstamp) in user_operations.into_iter().zip(stamps) {
             match user_op {
@@ -761,12 +1227,12 @@ impl IndexWriter {
                 }
             }
         }
-        let send_result = self.operation_sender.send(adds);
+        let send_result = self.operation_sender.send(self.operation_group(adds));
         if let Err(e) = send_result {
             panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
         };
 
-        batch_opstamp
+        Ok(batch_opstamp)
     }
 }
 
@@ -793,6 +1259,7 @@ mod tests {
     use crate::Index;
     use crate::ReloadPolicy;
     use crate::Term;
+    use futures::executor::block_on;
 
     #[test]
     fn test_operations_group() {
@@ -1074,6 +1541,358 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rollback_garbage_collects_aborted_segments() {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests().unwrap();
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.commit().unwrap();
+        let managed_files_after_commit = index.directory().list_managed_files();
+        index_writer.add_document(doc!(text_field => "b"));
+        {
+            let prepared_commit = index_writer.prepare_commit().unwrap();
+            prepared_commit.abort().unwrap();
+        }
+        assert_eq!(
+            index.directory().list_managed_files(),
+            managed_files_after_commit
+        );
+    }
+
+    #[test]
+    fn test_upgrade_segments() -> crate::Result<()> {
+        use crate::core::SegmentComponent;
+        use crate::Directory;
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        assert!(block_on(index_writer.upgrade_segments())?.is_none());
+        for text in &["a", "b", "c"] {
+            index_writer.add_document(doc!(text_field => *text));
+            index_writer.commit()?;
+        }
+        // Segments written with the current index format are left untouched.
+        assert!(block_on(index_writer.upgrade_segments())?.is_none());
+
+        // Makes two of the segments look like they were written with
+        // the previous index format.
+        let segment_metas = index.searchable_segment_metas()?;
+        let current_format = format!("\"index_format_version\":{}", crate::INDEX_FORMAT_VERSION);
+        let older_format = format!(
+            "\"index_format_version\":{}",
+            crate::INDEX_FORMAT_VERSION - 1
+        );
+        for segment_meta in &segment_metas[..2] {
+            let store_path = segment_meta.relative_path(SegmentComponent::STORE);
+            let mut data = index.directory().atomic_read(&store_path)?;
+            let pos = data
+                .windows(current_format.len())
+                .rposition(|window| window == current_format.as_bytes())
+                .unwrap();
+            data[pos..pos + older_format.len()].copy_from_slice(older_format.as_bytes());
+            index.directory().atomic_write(&store_path, &data)?;
+        }
+
+        let segment_meta = block_on(index_writer.upgrade_segments())?.unwrap();
+        assert_eq!(segment_meta.num_docs(), 2);
+        let mut expected_segment_ids = vec![segment_metas[2].id(), segment_meta.id()];
+        expected_segment_ids.sort();
+        let mut upgraded_segment_ids = index.searchable_segment_ids()?;
+        upgraded_segment_ids.sort();
+        assert_eq!(upgraded_segment_ids, expected_segment_ids);
+        assert!(block_on(index_writer.upgrade_segments())?.is_none());
+        assert_eq!(
+            index.load_metas()?.index_format_version(),
+            Some(crate::INDEX_FORMAT_VERSION)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_points() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.commit()?;
+        assert!(index.list_commit_points().is_empty());
+        assert_eq!(index.load_metas()?.generation, 1);
+
+        index_writer.set_num_commit_points_to_keep(2);
+        index_writer.add_document(doc!(text_field => "b"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "c"));
+        index_writer.commit()?;
+        assert_eq!(index.list_commit_points(), vec![2, 3]);
+        assert_eq!(index.load_commit_point(2)?.segments.len(), 2);
+
+        // The merge publishes a new generation, and the segments of
+        // the commit point 3 are not garbage collected.
+        let segment_ids = index.searchable_segment_ids()?;
+        block_on(index_writer.merge(&segment_ids))?;
+        assert_eq!(index.load_metas()?.generation, 4);
+        assert_eq!(index.list_commit_points(), vec![3, 4]);
+        let reader = index
+            .reader_builder()
+            .commit_point(3)
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        assert_eq!(searcher.num_docs(), 3);
+        assert!(index.reader_builder().commit_point(1).try_into().is_err());
+
+        // The number of commit points to keep is persisted.
+        assert_eq!(index.load_metas()?.num_commit_points_to_keep, 2);
+        drop(index_writer);
+        let index_writer = index.writer_for_tests()?;
+        assert_eq!(index_writer.get_num_commit_points_to_keep(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_indexes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT | schema::STORED);
+        let schema = schema_builder.build();
+        let mut indexes = Vec::new();
+        for texts in &[&["a", "b", "c"][..], &["d", "e"][..]] {
+            let index = Index::create_in_ram(schema.clone());
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for text in texts.iter() {
+                index_writer.add_document(doc!(text_field => *text));
+                // Several segments per index.
+                index_writer.commit()?;
+            }
+            index_writer.delete_term(Term::from_field_text(text_field, "b"));
+            index_writer.commit()?;
+            indexes.push(index);
+        }
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "f"));
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(text_field, "d"));
+        index_writer.add_indexes(&indexes)?;
+        index_writer.delete_term(Term::from_field_text(text_field, "e"));
+        let reader = index.reader()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        assert_eq!(searcher.num_docs(), 4);
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "d"),
+            IndexRecordOption::Basic,
+        );
+        let top_docs = searcher.search(&term_query, &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs.len(), 1);
+        let doc = searcher.doc(top_docs[0].1)?;
+        assert_eq!(doc.get_first(text_field).unwrap().text(), Some("d"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_indexes_without_documents() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let schema = schema_builder.build();
+        let deleted_index = Index::create_in_ram(schema.clone());
+        let mut deleted_index_writer = deleted_index.writer_for_tests()?;
+        deleted_index_writer.add_document(doc!(text_field => "a"));
+        deleted_index_writer.commit()?;
+        deleted_index_writer.delete_term(Term::from_field_text(text_field, "a"));
+        deleted_index_writer.commit()?;
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        let num_managed_files = index.directory().list_managed_files().len();
+        index_writer.add_indexes(&[deleted_index])?;
+        index_writer.commit()?;
+        assert!(index.searchable_segment_ids()?.is_empty());
+        assert_eq!(
+            index.directory().list_managed_files().len(),
+            num_managed_files
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_indexes_schema_mismatch() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut schema_builder = schema::Schema::builder();
+        schema_builder.add_text_field("title", schema::TEXT);
+        let other_index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        assert!(matches!(
+            index_writer.add_indexes(&[other_index]),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_key_field() -> crate::Result<()> {
+        use crate::collector::Count;
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING | schema::STORED);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT | schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        assert!(matches!(
+            index_writer.set_unique_key_field(text_field),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert_eq!(index_writer.unique_key_field(), None);
+        index_writer.set_unique_key_field(id_field)?;
+        assert_eq!(index_writer.unique_key_field(), Some(id_field));
+
+        index_writer.add_document(doc!(id_field => "a", text_field => "first"));
+        index_writer.add_document(doc!(id_field => "b", text_field => "first"));
+        index_writer.add_document(doc!(id_field => "a", text_field => "second"));
+        index_writer.commit()?;
+        index_writer.run(vec![
+            UserOperation::Add(doc!(id_field => "b", text_field => "second")),
+            UserOperation::Add(doc!(id_field => "c", text_field => "first")),
+        ]);
+        index_writer.add_document(doc!(text_field => "no key"));
+        index_writer.add_document(doc!(text_field => "no key"));
+        index_writer.commit()?;
+
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 5);
+        for &(id, expected_text) in &[("a", "second"), ("b", "second"), ("c", "first")] {
+            let query = TermQuery::new(
+                Term::from_field_text(id_field, id),
+                IndexRecordOption::Basic,
+            );
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+            assert_eq!(top_docs.len(), 1);
+            let doc = searcher.doc(top_docs[0].1)?;
+            assert_eq!(
+                doc.get_first(text_field).unwrap().text(),
+                Some(expected_text)
+            );
+        }
+        let no_key_query = TermQuery::new(
+            Term::from_field_text(text_field, "key"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&no_key_query, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_document() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT | schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id_field => "a", text_field => "first"));
+        index_writer.add_document(doc!(id_field => "a", text_field => "duplicate"));
+        index_writer.add_document(doc!(id_field => "b", text_field => "first"));
+        index_writer.commit()?;
+        let a_term = Term::from_field_text(id_field, "a");
+        index_writer.update_document(
+            a_term.clone(),
+            doc!(id_field => "a", text_field => "second"),
+        );
+        index_writer.update_document(
+            Term::from_field_text(id_field, "c"),
+            doc!(id_field => "c", text_field => "first"),
+        );
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let query = TermQuery::new(a_term, IndexRecordOption::Basic);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs.len(), 1);
+        let doc = searcher.doc(top_docs[0].1)?;
+        assert_eq!(doc.get_first(text_field).unwrap().text(), Some("second"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_key_field_u64_survives_rollback() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_unique_key_field(id_field)?;
+        index_writer.add_document(doc!(id_field => 1u64));
+        index_writer.rollback()?;
+        assert_eq!(index_writer.unique_key_field(), Some(id_field));
+        index_writer.add_document(doc!(id_field => 1u64));
+        index_writer.add_document(doc!(id_field => 2u64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id_field => 1u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_point_searcher_protects_files() -> crate::Result<()> {
+        use crate::core::SegmentComponent;
+        use crate::Directory;
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.set_num_commit_points_to_keep(1);
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "b"));
+        index_writer.commit()?;
+        assert_eq!(index.list_commit_points(), vec![2]);
+        let postings_files: Vec<_> = index
+            .load_commit_point(2)?
+            .segments
+            .iter()
+            .map(|segment_meta| segment_meta.relative_path(SegmentComponent::POSTINGS))
+            .collect();
+        let reader = index
+            .reader_builder()
+            .commit_point(2)
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+
+        // The commit point 2 becomes obsolete, but its segments
+        // are still used by the searcher.
+        let segment_ids = index.searchable_segment_ids()?;
+        block_on(index_writer.merge(&segment_ids))?;
+        assert_eq!(index.list_commit_points(), vec![3]);
+        block_on(index_writer.garbage_collect_files())?;
+        for postings_file in &postings_files {
+            assert!(index.directory().exists(postings_file)?);
+        }
+        assert_eq!(searcher.num_docs(), 2);
+
+        drop(searcher);
+        drop(reader);
+        block_on(index_writer.garbage_collect_files())?;
+        for postings_file in &postings_files {
+            assert!(!index.directory().exists(postings_file)?);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_prepare_but_rollback() {
         let mut schema_builder = schema::Schema::builder();
@@ -1291,4 +2110,60 @@ mod tests {
         let commit = index_writer.commit();
         assert!(commit.is_ok());
     }
+
+    #[test]
+    fn test_segment_attributes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let directory = crate::directory::RAMDirectory::create();
+        let index = Index::create(directory.clone(), schema_builder.build())?;
+        let mut index_writer = index.writer_for_tests()?;
+        let attributes = |batch: &str| {
+            let mut attributes = std::collections::BTreeMap::new();
+            attributes.insert("shard".to_string(), "a".to_string());
+            attributes.insert("batch".to_string(), batch.to_string());
+            attributes
+        };
+        index_writer.set_segment_attributes(attributes("1"));
+        index_writer.add_document(doc!(text_field => "a"));
+        // Documents added before the attributes change keep the previous attributes.
+        index_writer.set_segment_attributes(attributes("2"));
+        index_writer.add_document(doc!(text_field => "b"));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let mut batches: Vec<&str> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| {
+                assert_eq!(segment_reader.attribute("shard"), Some("a"));
+                segment_reader.attribute("batch").unwrap()
+            })
+            .collect();
+        batches.sort_unstable();
+        assert_eq!(batches, vec!["1", "2"]);
+        let segment_attributes: Vec<_> = Index::open(directory)?
+            .searchable_segment_metas()?
+            .iter()
+            .map(|segment_meta| segment_meta.attributes().clone())
+            .collect();
+        assert!(segment_attributes.contains(&attributes("1")));
+        assert!(segment_attributes.contains(&attributes("2")));
+
+        // The merged segment keeps the attributes shared by the merged segments.
+        let segment_ids = index.searchable_segment_ids()?;
+        block_on(index_writer.merge(&segment_ids))?;
+        index_writer.wait_merging_threads()?;
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(segment_metas[0].attributes().len(), 1);
+        assert_eq!(
+            segment_metas[0]
+                .attributes()
+                .get("shard")
+                .map(String::as_str),
+            Some("a")
+        );
+        Ok(())
+    }
 }
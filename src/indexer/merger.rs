@@ -200,7 +200,7 @@ impl IndexMerger {
                         .remove(&field)
                         .expect("Logic Error in Tantivy (Please report). HierarchicalFact field should have required a\
                         `term_ordinal_mapping`.");
-                    self.write_hierarchical_facet_field(
+                    self.write_term_ordinal_fast_field(
                         field,
                         &term_ordinal_mapping,
                         fast_field_serializer,
@@ -213,15 +213,24 @@ impl IndexMerger {
                     Some(Cardinality::SingleValue) => {
                         self.write_single_fast_field(field, fast_field_serializer)?;
                     }
-                    Some(Cardinality::MultiValues) => {
+                    Some(Cardinality::MultiValues) | Some(Cardinality::Optional) => {
                         self.write_multi_fast_field(field, fast_field_serializer)?;
                     }
                     None => {}
                 },
-                FieldType::Str(_) => {
-                    // We don't handle str fast field for the moment
-                    // They can be implemented using what is done
-                    // for facets in the future.
+                FieldType::Str(ref text_options) => {
+                    if text_options.is_fast() {
+                        // Just like facets, str fast fields contain term ordinals.
+                        // A non-indexed field has no term and no ordinal to remap.
+                        match term_ord_mappings.remove(&field) {
+                            Some(term_ordinal_mapping) => self.write_term_ordinal_fast_field(
+                                field,
+                                &term_ordinal_mapping,
+                                fast_field_serializer,
+                            )?,
+                            None => self.write_multi_fast_field(field, fast_field_serializer)?,
+                        }
+                    }
                 }
                 FieldType::Bytes(byte_options) => {
                     if byte_options.is_fast() {
@@ -330,7 +339,7 @@ impl IndexMerger {
         Ok(())
     }
 
-    fn write_hierarchical_facet_field(
+    fn write_term_ordinal_fast_field(
         &self,
         field: Field,
         term_ordinal_mappings: &TermOrdinalMapping,
@@ -344,7 +353,7 @@ impl IndexMerger {
         self.write_fast_field_idx(field, fast_field_serializer)?;
 
         // We can now write the actual fast field values.
-        // In the case of hierarchical facets, they are actually term ordinals.
+        // In the case of hierarchical facets and str fields, they are actually term ordinals.
         let max_term_ord = term_ordinal_mappings.max_term_ord();
         {
             let mut serialize_vals =
@@ -518,7 +527,12 @@ impl IndexMerger {
             max_term_ords.push(terms.num_terms() as u64);
         }
 
-        let mut term_ord_mapping_opt = if *field_type == FieldType::HierarchicalFacet {
+        let requires_term_ord_mapping = match field_type {
+            FieldType::HierarchicalFacet => true,
+            FieldType::Str(text_options) => text_options.is_fast(),
+            _ => false,
+        };
+        let mut term_ord_mapping_opt = if requires_term_ord_mapping {
             Some(TermOrdinalMapping::new(max_term_ords))
         } else {
             None
@@ -32,11 +32,13 @@ impl<'a> PreparedCommit<'a> {
 
     pub fn commit(self) -> crate::Result<Opstamp> {
         info!("committing {}", self.opstamp);
-        let _ = block_on(
+        block_on(
             self.index_writer
                 .segment_updater()
                 .schedule_commit(self.opstamp, self.payload),
-        );
+        )?;
+        // The logged operations can only be discarded once they are committed.
+        self.index_writer.truncate_write_ahead_log(self.opstamp)?;
         Ok(self.opstamp)
     }
 }
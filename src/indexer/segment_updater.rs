@@ -1,6 +1,9 @@
 use super::segment_manager::{get_mergeable_segments, SegmentManager};
+use crate::core::commit_point_filepath;
+use crate::core::common_attributes;
 use crate::core::Index;
 use crate::core::IndexMeta;
+use crate::core::IndexSettings;
 use crate::core::Segment;
 use crate::core::SegmentId;
 use crate::core::SegmentMeta;
@@ -19,6 +22,7 @@ use crate::indexer::{DefaultMergePolicy, MergePolicy};
 use crate::indexer::{MergeCandidate, MergeOperation};
 use crate::schema::Schema;
 use crate::Opstamp;
+use crate::TantivyError;
 use futures::channel::oneshot;
 use futures::executor::{ThreadPool, ThreadPoolBuilder};
 use futures::future::Future;
@@ -28,9 +32,10 @@ use std::collections::HashSet;
 use std::io::Write;
 use std::ops::Deref;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::time::Instant;
 
 const NUM_MERGE_THREADS: usize = 4;
 
@@ -43,14 +48,13 @@ const NUM_MERGE_THREADS: usize = 4;
 /// and flushed.
 ///
 /// This method is not part of tantivy's public API
-pub fn save_new_metas(schema: Schema, directory: &dyn Directory) -> crate::Result<()> {
+pub fn save_new_metas(
+    schema: Schema,
+    index_settings: IndexSettings,
+    directory: &dyn Directory,
+) -> crate::Result<()> {
     save_metas(
-        &IndexMeta {
-            segments: Vec::new(),
-            schema,
-            opstamp: 0u64,
-            payload: None,
-        },
+        &IndexMeta::with_schema_and_settings(schema, index_settings),
         directory,
     )
 }
@@ -64,7 +68,7 @@ pub fn save_new_metas(schema: Schema, directory: &dyn Directory) -> crate::Resul
 /// and flushed.
 ///
 /// This method is not part of tantivy's public API
-fn save_metas(metas: &IndexMeta, directory: &dyn Directory) -> crate::Result<()> {
+pub(crate) fn save_metas(metas: &IndexMeta, directory: &dyn Directory) -> crate::Result<()> {
     info!("save metas");
     let mut buffer = serde_json::to_vec_pretty(metas)?;
     // Just adding a new line at the end of the buffer.
@@ -137,7 +141,13 @@ fn merge(
 
     let merged_segment_id = merged_segment.id();
 
-    let segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
+    let segment_metas: Vec<SegmentMeta> = segments
+        .iter()
+        .map(|segment| segment.meta().clone())
+        .collect();
+    let segment_meta = index
+        .new_segment_meta(merged_segment_id, num_docs)
+        .with_attributes(common_attributes(&segment_metas));
     Ok(SegmentEntry::new(segment_meta, delete_cursor, None))
 }
 
@@ -155,6 +165,7 @@ pub(crate) struct InnerSegmentUpdater {
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Arc<dyn MergePolicy>>,
+    num_commit_points_to_keep: AtomicUsize,
     killed: AtomicBool,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
@@ -187,6 +198,7 @@ impl SegmentUpdater {
                 )
             })?;
         let index_meta = index.load_metas()?;
+        let num_commit_points_to_keep = index_meta.num_commit_points_to_keep;
         Ok(SegmentUpdater(Arc::new(InnerSegmentUpdater {
             active_metas: RwLock::new(Arc::new(index_meta)),
             pool,
@@ -194,6 +206,7 @@ impl SegmentUpdater {
             index,
             segment_manager,
             merge_policy: RwLock::new(Arc::new(DefaultMergePolicy::default())),
+            num_commit_points_to_keep: AtomicUsize::new(num_commit_points_to_keep),
             killed: AtomicBool::new(false),
             stamper,
             merge_operations: Default::default(),
@@ -209,6 +222,15 @@ impl SegmentUpdater {
         *self.merge_policy.write().unwrap() = arc_merge_policy;
     }
 
+    pub fn get_num_commit_points_to_keep(&self) -> usize {
+        self.num_commit_points_to_keep.load(Ordering::SeqCst)
+    }
+
+    pub fn set_num_commit_points_to_keep(&self, num_commit_points_to_keep: usize) {
+        self.num_commit_points_to_keep
+            .store(num_commit_points_to_keep, Ordering::SeqCst);
+    }
+
     fn schedule_future<T: 'static + Send, F: Future<Output = crate::Result<T>> + 'static + Send>(
         &self,
         f: F,
@@ -292,12 +314,33 @@ impl SegmentUpdater {
             //
             // Segment 1 from disk 1, Segment 1 from disk 2, etc.
             commited_segment_metas.sort_by_key(|segment_meta| -(segment_meta.max_doc() as i32));
+            // The schema may have been updated by `Index::update_schema` on another
+            // `Index` since this one was opened: writing our schema would revert it.
+            let schema = index.schema();
+            if index.load_metas()?.schema != schema {
+                return Err(TantivyError::SchemaError(
+                    "The schema of the index was updated since the index was opened. \
+                     The index needs to be opened again."
+                        .to_string(),
+                ));
+            }
             let index_meta = IndexMeta {
                 segments: commited_segment_metas,
-                schema: index.schema(),
+                schema,
+                index_settings: index.settings().clone(),
                 opstamp,
+                generation: self.load_metas().generation + 1,
+                num_commit_points_to_keep: self.get_num_commit_points_to_keep(),
                 payload: commit_message,
+                version: Some(crate::version().clone()),
             };
+            // The commit point is written before `meta.json`, so that
+            // `meta.json` never refers to a missing commit point.
+            if self.get_num_commit_points_to_keep() > 0 {
+                let mut buffer = serde_json::to_vec_pretty(&index_meta)?;
+                writeln!(&mut buffer)?;
+                directory.atomic_write(&commit_point_filepath(index_meta.generation), &buffer)?;
+            }
             // TODO add context to the error.
             save_metas(&index_meta, directory.box_clone().borrow_mut())?;
             self.store_meta(&index_meta);
@@ -324,6 +367,27 @@ impl SegmentUpdater {
             .flat_map(|segment_meta| segment_meta.list_files())
             .collect();
         files.insert(META_FILEPATH.to_path_buf());
+        let num_commit_points_to_keep = self.get_num_commit_points_to_keep();
+        let commit_points = self.index.list_commit_points();
+        let num_obsolete_commit_points = commit_points
+            .len()
+            .saturating_sub(num_commit_points_to_keep);
+        for &generation in &commit_points[num_obsolete_commit_points..] {
+            match self.index.load_commit_point(generation) {
+                Ok(index_meta) => {
+                    files.extend(
+                        index_meta
+                            .segments
+                            .iter()
+                            .flat_map(|segment_meta| segment_meta.list_files()),
+                    );
+                    files.insert(commit_point_filepath(generation));
+                }
+                Err(err) => {
+                    error!("Failed to load commit point {}: {:?}", generation, err);
+                }
+            }
+        }
         files
     }
 
@@ -397,12 +461,18 @@ impl SegmentUpdater {
             // Its lifetime is used to track how many merging thread are currently running,
             // as well as which segment is currently in merge and therefore should not be
             // candidate for another merge.
+            let merge_start = Instant::now();
             match merge(
                 &segment_updater.index,
                 segment_entries,
                 merge_operation.target_opstamp(),
             ) {
                 Ok(after_merge_segment_entry) => {
+                    debug!(
+                        "Merged {:?} in {:?}.",
+                        merge_operation.segment_ids(),
+                        merge_start.elapsed()
+                    );
                     let segment_meta = segment_updater
                         .end_merge(merge_operation, after_merge_segment_entry)
                         .await;
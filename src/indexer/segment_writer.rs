@@ -10,12 +10,13 @@ use crate::schema::FieldType;
 use crate::schema::Schema;
 use crate::schema::Term;
 use crate::schema::Value;
-use crate::schema::{Field, FieldEntry};
+use crate::schema::{Field, FieldEntry, FieldValue};
 use crate::tokenizer::{BoxTokenStream, PreTokenizedStream};
 use crate::tokenizer::{FacetTokenizer, TextAnalyzer};
 use crate::tokenizer::{TokenStreamChain, Tokenizer};
 use crate::Opstamp;
 use crate::{DocId, SegmentComponent};
+use std::mem;
 
 /// Computes the initial size of the hash table.
 ///
@@ -33,6 +34,27 @@ fn initial_table_size(per_thread_memory_budget: usize) -> crate::Result<usize> {
     }
 }
 
+/// Groups the field values of a document by field.
+///
+/// `field_value_ords` are the ordinals of the field values,
+/// sorted by field.
+fn group_by_field<'a>(
+    field_value_ords: &'a [usize],
+    field_values: &'a [FieldValue],
+) -> impl Iterator<Item = (Field, &'a [usize])> + 'a {
+    let mut remaining = field_value_ords;
+    std::iter::from_fn(move || {
+        let field = field_values[*remaining.first()?].field();
+        let group_len = remaining
+            .iter()
+            .take_while(|&&ord| field_values[ord].field() == field)
+            .count();
+        let (group, rest) = remaining.split_at(group_len);
+        remaining = rest;
+        Some((field, group))
+    })
+}
+
 /// A `SegmentWriter` is in charge of creating segment index from a
 /// set of documents.
 ///
@@ -46,7 +68,9 @@ pub struct SegmentWriter {
     fieldnorms_writer: FieldNormsWriter,
     doc_opstamps: Vec<Opstamp>,
     tokenizers: Vec<Option<TextAnalyzer>>,
+    // Buffers reused from one document to the next.
     term_buffer: Term,
+    field_value_ords: Vec<usize>,
 }
 
 impl SegmentWriter {
@@ -91,6 +115,7 @@ impl SegmentWriter {
             doc_opstamps: Vec::with_capacity(1_000),
             tokenizers,
             term_buffer: Term::new(),
+            field_value_ords: Vec::new(),
         })
     }
 
@@ -127,7 +152,13 @@ impl SegmentWriter {
 
         self.fast_field_writers.add_document(&doc);
 
-        for (field, field_values) in doc.get_sorted_field_values() {
+        let doc_field_values = doc.field_values();
+        let mut field_value_ords = mem::take(&mut self.field_value_ords);
+        field_value_ords.clear();
+        field_value_ords.extend(0..doc_field_values.len());
+        field_value_ords.sort_by_key(|&ord| doc_field_values[ord].field());
+        for (field, group) in group_by_field(&field_value_ords, doc_field_values) {
+            let field_values = group.iter().map(|&ord| &doc_field_values[ord]);
             let field_entry = schema.get_field_entry(field);
             let make_schema_error = || {
                 crate::TantivyError::SchemaError(format!(
@@ -144,15 +175,12 @@ impl SegmentWriter {
             match *field_entry.field_type() {
                 FieldType::HierarchicalFacet => {
                     term_buffer.set_field(field);
-                    let facets =
-                        field_values
-                            .iter()
-                            .flat_map(|field_value| match *field_value.value() {
-                                Value::Facet(ref facet) => Some(facet.encoded_str()),
-                                _ => {
-                                    panic!("Expected hierarchical facet");
-                                }
-                            });
+                    let facets = field_values.flat_map(|field_value| match *field_value.value() {
+                        Value::Facet(ref facet) => Some(facet.encoded_str()),
+                        _ => {
+                            panic!("Expected hierarchical facet");
+                        }
+                    });
                     for facet_str in facets {
                         let mut unordered_term_id_opt = None;
                         FacetTokenizer
@@ -171,7 +199,7 @@ impl SegmentWriter {
                         }
                     }
                 }
-                FieldType::Str(_) => {
+                FieldType::Str(ref text_options) => {
                     let mut token_streams: Vec<BoxTokenStream> = vec![];
                     let mut offsets = vec![];
                     let mut total_offset = 0;
@@ -203,11 +231,17 @@ impl SegmentWriter {
                         0
                     } else {
                         let mut token_stream = TokenStreamChain::new(offsets, token_streams);
+                        let term_id_fast_field_writer_opt = if text_options.is_fast() {
+                            self.fast_field_writers.get_multivalue_writer(field)
+                        } else {
+                            None
+                        };
                         multifield_postings.index_text(
                             doc_id,
                             field,
                             &mut token_stream,
                             term_buffer,
+                            term_id_fast_field_writer_opt,
                         )
                     };
 
@@ -280,6 +314,7 @@ impl SegmentWriter {
                 }
             }
         }
+        self.field_value_ords = field_value_ords;
         doc.filter_fields(|field| schema.get_field_entry(field).is_stored());
         doc.prepare_for_store();
         let doc_writer = self.segment_serializer.get_store_writer();
@@ -345,7 +380,8 @@ impl SerializableSegment for SegmentWriter {
 
 #[cfg(test)]
 mod tests {
-    use super::initial_table_size;
+    use super::{group_by_field, initial_table_size};
+    use crate::schema::{Field, FieldValue, Value};
 
     #[test]
     fn test_hashmap_size() {
@@ -354,4 +390,26 @@ mod tests {
         assert_eq!(initial_table_size(10_000_000).unwrap(), 17);
         assert_eq!(initial_table_size(1_000_000_000).unwrap(), 19);
     }
+
+    #[test]
+    fn test_group_by_field() {
+        let field_values: Vec<FieldValue> = [2u32, 0, 2, 1]
+            .iter()
+            .map(|&field_id| FieldValue::new(Field::from_field_id(field_id), Value::U64(1)))
+            .collect();
+        let mut ords: Vec<usize> = (0..field_values.len()).collect();
+        ords.sort_by_key(|&ord| field_values[ord].field());
+        let groups: Vec<(Field, Vec<usize>)> = group_by_field(&ords, &field_values)
+            .map(|(field, group)| (field, group.to_vec()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Field::from_field_id(0), vec![1]),
+                (Field::from_field_id(1), vec![3]),
+                (Field::from_field_id(2), vec![0, 2]),
+            ]
+        );
+        assert_eq!(group_by_field(&[], &field_values).count(), 0);
+    }
 }
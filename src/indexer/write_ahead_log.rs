@@ -0,0 +1,498 @@
+use crate::common::{BinarySerializable, VInt};
+use crate::directory::error::{DeleteError, OpenReadError};
+use crate::directory::{Directory, ManagedDirectory, WritePtr};
+use crate::error::DataCorruption;
+use crate::indexer::operation::UserOperation;
+use crate::schema::{Document, Term};
+use crate::{Opstamp, TantivyError};
+use crc32fast::Hasher;
+use once_cell::sync::Lazy;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Points to the current log file.
+///
+/// The files of the write-ahead log start with a `.`: they are not
+/// managed, and therefore not subject to garbage collection.
+static WAL_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new(".tantivy-wal.json"));
+
+fn log_filepath(generation: u64) -> PathBuf {
+    PathBuf::from(format!(".tantivy-wal.{}.log", generation))
+}
+
+// Kinds of records.
+const GROUP: u8 = 0;
+const DELETE_ALL: u8 = 1;
+
+// Kinds of the operations of a group.
+const ADD: u8 = 0;
+const DELETE: u8 = 1;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct WalPointer {
+    generation: u64,
+    // Opstamp of the commit the log was started after.
+    opstamp: Opstamp,
+}
+
+/// Operation recorded in the write-ahead log.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum LoggedOperation {
+    /// Operations sent together, either by `add_document` and `delete_term`,
+    /// or as a group by `run`.
+    Group(Vec<UserOperation>),
+    /// Call to `delete_all_documents`.
+    DeleteAll,
+}
+
+fn serialize_add(document: &Document, buffer: &mut Vec<u8>) -> io::Result<()> {
+    ADD.serialize(buffer)?;
+    document.serialize(buffer)
+}
+
+fn serialize_delete(term: &Term, buffer: &mut Vec<u8>) -> io::Result<()> {
+    DELETE.serialize(buffer)?;
+    VInt(term.as_slice().len() as u64).serialize(buffer)?;
+    buffer.write_all(term.as_slice())
+}
+
+fn group_payload(num_operations: usize) -> io::Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    GROUP.serialize(&mut payload)?;
+    VInt(num_operations as u64).serialize(&mut payload)?;
+    Ok(payload)
+}
+
+fn deserialize_user_operation(data: &mut &[u8]) -> io::Result<UserOperation> {
+    match u8::deserialize(data)? {
+        ADD => Ok(UserOperation::Add(Document::deserialize(data)?)),
+        DELETE => {
+            let len = VInt::deserialize(data)?.val() as usize;
+            if len > data.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Truncated term.",
+                ));
+            }
+            let term = Term::wrap(data[..len].to_vec());
+            *data = &data[len..];
+            Ok(UserOperation::Delete(term))
+        }
+        code => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unknown operation code {}.", code),
+        )),
+    }
+}
+
+fn deserialize_logged_operation(mut data: &[u8]) -> io::Result<LoggedOperation> {
+    if u8::deserialize(&mut data)? == DELETE_ALL {
+        return Ok(LoggedOperation::DeleteAll);
+    }
+    let num_operations = VInt::deserialize(&mut data)?.val() as usize;
+    let user_operations = (0..num_operations)
+        .map(|_| deserialize_user_operation(&mut data))
+        .collect::<io::Result<Vec<UserOperation>>>()?;
+    Ok(LoggedOperation::Group(user_operations))
+}
+
+/// Parses the records of a log file.
+///
+/// A record is made of the length of its payload, the checksum of its payload, and
+/// the payload itself. Parsing stops at the first incomplete record: it was being
+/// written when the process crashed, and the operation was never acknowledged.
+///
+/// Returns the operations, and the number of bytes of the complete records.
+fn parse_log(data: &[u8]) -> io::Result<(Vec<LoggedOperation>, usize)> {
+    let mut operations = Vec::new();
+    let mut cursor = data;
+    while cursor.len() >= 8 {
+        let mut header = &cursor[..8];
+        let len = u32::deserialize(&mut header)? as usize;
+        let checksum = u32::deserialize(&mut header)?;
+        if cursor.len() < 8 + len {
+            break;
+        }
+        let payload = &cursor[8..8 + len];
+        let mut hasher = Hasher::new();
+        hasher.update(payload);
+        if hasher.finalize() != checksum {
+            break;
+        }
+        operations.push(deserialize_logged_operation(payload)?);
+        cursor = &cursor[8 + len..];
+    }
+    Ok((operations, data.len() - cursor.len()))
+}
+
+fn read_pointer(directory: &dyn Directory) -> crate::Result<Option<WalPointer>> {
+    match directory.atomic_read(&WAL_FILEPATH) {
+        Ok(data) => serde_json::from_slice(&data).map(Some).map_err(|e| {
+            DataCorruption::new(
+                WAL_FILEPATH.to_path_buf(),
+                format!("Write-ahead log file cannot be deserialized: {:?}.", e),
+            )
+            .into()
+        }),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn delete_if_exists(directory: &dyn Directory, path: &Path) -> crate::Result<()> {
+    match directory.delete(path) {
+        Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => Ok(()),
+        Err(DeleteError::IOError { io_error, .. }) => Err(io_error.into()),
+    }
+}
+
+/// Journal of the operations of an `IndexWriter` that have not been committed yet.
+///
+/// The `Directory` API does not allow appending to an existing file: the log is
+/// written to a new file every time it is restarted, and `WAL_FILEPATH` is atomically
+/// updated to point to it.
+pub(crate) struct WriteAheadLog {
+    directory: ManagedDirectory,
+    generation: u64,
+    write: WritePtr,
+    // Set when a record could not be written. The record may have been partially
+    // written, or may still be written by a later flush, so the log is not
+    // written anymore until it is truncated.
+    failed: bool,
+}
+
+impl WriteAheadLog {
+    /// Opens the write-ahead log of the directory, and returns the operations
+    /// logged after the commit with the given `opstamp`.
+    ///
+    /// If the index was committed after the log was started, all of the operations of
+    /// the log were committed and none is returned.
+    /// The returned operations are kept in the new log file.
+    pub fn open(
+        directory: &ManagedDirectory,
+        opstamp: Opstamp,
+    ) -> crate::Result<(WriteAheadLog, Vec<LoggedOperation>)> {
+        let pointer_opt = read_pointer(directory)?;
+        let generation = pointer_opt
+            .as_ref()
+            .map(|pointer| pointer.generation)
+            .unwrap_or(0);
+        let (operations, log_data) = match pointer_opt {
+            Some(pointer) if pointer.opstamp == opstamp => {
+                let data = directory.atomic_read(&log_filepath(pointer.generation))?;
+                let (operations, num_bytes) = parse_log(&data)?;
+                (operations, data[..num_bytes].to_vec())
+            }
+            _ => (Vec::new(), Vec::new()),
+        };
+        let mut write_ahead_log = WriteAheadLog {
+            directory: directory.clone(),
+            generation,
+            write: WriteAheadLog::create_log_file(directory, generation + 1)?,
+            failed: false,
+        };
+        write_ahead_log.restart(opstamp, &log_data)?;
+        Ok((write_ahead_log, operations))
+    }
+
+    fn create_log_file(directory: &ManagedDirectory, generation: u64) -> crate::Result<WritePtr> {
+        let path = log_filepath(generation);
+        // A file may have been left by a crash before `WAL_FILEPATH` was updated.
+        delete_if_exists(directory, &path)?;
+        Ok(directory.open_write(&path)?)
+    }
+
+    /// Starts a new log file after the commit with the given `opstamp`,
+    /// beginning with `log_data`.
+    fn restart(&mut self, opstamp: Opstamp, log_data: &[u8]) -> crate::Result<()> {
+        let previous_generation = self.generation;
+        self.write.write_all(log_data)?;
+        self.write.flush()?;
+        self.generation += 1;
+        let pointer = WalPointer {
+            generation: self.generation,
+            opstamp,
+        };
+        let mut pointer_json = serde_json::to_vec(&pointer)?;
+        writeln!(&mut pointer_json)?;
+        self.directory.atomic_write(&WAL_FILEPATH, &pointer_json)?;
+        delete_if_exists(&self.directory, &log_filepath(previous_generation))?;
+        Ok(())
+    }
+
+    /// Discards the logged operations, after the commit with the given `opstamp`
+    /// or after a rollback to it.
+    pub fn truncate(&mut self, opstamp: Opstamp) -> crate::Result<()> {
+        self.write = WriteAheadLog::create_log_file(&self.directory, self.generation + 1)?;
+        self.failed = false;
+        self.restart(opstamp, &[])
+    }
+
+    fn append(&mut self, payload: &[u8]) -> crate::Result<()> {
+        if self.failed {
+            return Err(TantivyError::SystemError(
+                "A previous write to the write-ahead log failed. It is only written again after \
+                 the next commit or rollback."
+                    .to_string(),
+            ));
+        }
+        let result = self.write_record(payload);
+        self.failed = result.is_err();
+        Ok(result?)
+    }
+
+    fn write_record(&mut self, payload: &[u8]) -> io::Result<()> {
+        let mut hasher = Hasher::new();
+        hasher.update(payload);
+        (payload.len() as u32).serialize(&mut self.write)?;
+        hasher.finalize().serialize(&mut self.write)?;
+        self.write.write_all(payload)?;
+        self.write.flush()
+    }
+
+    /// Logs a group of operations, and flushes the log.
+    pub fn log_operations(&mut self, user_operations: &[UserOperation]) -> crate::Result<()> {
+        let mut payload = group_payload(user_operations.len())?;
+        for user_operation in user_operations {
+            match user_operation {
+                UserOperation::Add(document) => serialize_add(document, &mut payload)?,
+                UserOperation::Delete(term) => serialize_delete(term, &mut payload)?,
+            }
+        }
+        self.append(&payload)
+    }
+
+    /// Logs the addition of a document, and flushes the log.
+    pub fn log_add(&mut self, document: &Document) -> crate::Result<()> {
+        let mut payload = group_payload(1)?;
+        serialize_add(document, &mut payload)?;
+        self.append(&payload)
+    }
+
+    /// Logs the deletion of a term, and flushes the log.
+    pub fn log_delete(&mut self, term: &Term) -> crate::Result<()> {
+        let mut payload = group_payload(1)?;
+        serialize_delete(term, &mut payload)?;
+        self.append(&payload)
+    }
+
+    /// Logs the deletion of all of the documents, and flushes the log.
+    pub fn log_delete_all(&mut self) -> crate::Result<()> {
+        self.append(&[DELETE_ALL])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_log, LoggedOperation, WriteAheadLog};
+    use crate::directory::error::{DeleteError, OpenReadError, OpenWriteError};
+    use crate::directory::{
+        AntiCallToken, Directory, FileHandle, RAMDirectory, TerminatingWrite, WatchCallback,
+        WatchHandle, WritePtr,
+    };
+    use crate::indexer::operation::UserOperation;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{Index, Term};
+    use std::io::{self, BufWriter, Write};
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_write_ahead_log_replay() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create(RAMDirectory::create(), schema_builder.build())?;
+        let num_docs = |text: &str| -> crate::Result<usize> {
+            let query =
+                TermQuery::new(Term::from_field_text(title, text), IndexRecordOption::Basic);
+            index
+                .reader()?
+                .searcher()
+                .search(&query, &crate::collector::Count)
+        };
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            assert_eq!(index_writer.enable_write_ahead_log()?, 0);
+            index_writer.add_document(doc!(title => "sea"));
+            index_writer.commit()?;
+            index_writer.add_document(doc!(title => "mountain"));
+            index_writer.run(vec![
+                UserOperation::Add(doc!(title => "sky")),
+                UserOperation::Add(doc!(title => "sea sky")),
+            ]);
+            index_writer.delete_term(Term::from_field_text(title, "sea"));
+            // The writer is dropped without committing.
+        }
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            assert_eq!(index_writer.enable_write_ahead_log()?, 4);
+            index_writer.commit()?;
+        }
+        assert_eq!(num_docs("sea")?, 0);
+        assert_eq!(num_docs("mountain")?, 1);
+        assert_eq!(num_docs("sky")?, 1);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            // The operations were committed.
+            assert_eq!(index_writer.enable_write_ahead_log()?, 0);
+            index_writer.add_document(doc!(title => "sea"));
+            index_writer.rollback()?;
+            index_writer.delete_all_documents()?;
+        }
+        let mut index_writer = index.writer_for_tests()?;
+        // The operations before the rollback were discarded.
+        assert_eq!(index_writer.enable_write_ahead_log()?, 1);
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 0);
+        Ok(())
+    }
+
+    /// Directory whose files cannot be written while `fail` is set.
+    #[derive(Clone, Debug)]
+    struct FailingDirectory {
+        underlying: RAMDirectory,
+        fail: Arc<AtomicBool>,
+    }
+
+    /// Once a write failed, the file stays unwritable.
+    struct FailingWrite {
+        underlying: WritePtr,
+        fail: Arc<AtomicBool>,
+        failed: bool,
+    }
+
+    impl FailingWrite {
+        fn check(&mut self) -> io::Result<()> {
+            self.failed |= self.fail.load(Ordering::SeqCst);
+            if self.failed {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "The file cannot be written anymore.",
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    impl Write for FailingWrite {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.check()?;
+            self.underlying.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.check()?;
+            self.underlying.flush()
+        }
+    }
+
+    impl TerminatingWrite for FailingWrite {
+        fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+            self.check()?;
+            self.underlying.terminate_ref(token)
+        }
+    }
+
+    impl Directory for FailingDirectory {
+        fn get_file_handle(&self, path: &Path) -> Result<Box<dyn FileHandle>, OpenReadError> {
+            self.underlying.get_file_handle(path)
+        }
+
+        fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+            self.underlying.delete(path)
+        }
+
+        fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+            self.underlying.exists(path)
+        }
+
+        fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+            let underlying = self.underlying.open_write(path)?;
+            Ok(BufWriter::new(Box::new(FailingWrite {
+                underlying,
+                fail: self.fail.clone(),
+                failed: false,
+            })))
+        }
+
+        fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+            self.underlying.atomic_read(path)
+        }
+
+        fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+            self.underlying.atomic_write(path, data)
+        }
+
+        fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+            self.underlying.watch(watch_callback)
+        }
+    }
+
+    #[test]
+    fn test_write_ahead_log_write_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let fail = Arc::new(AtomicBool::new(false));
+        let directory = FailingDirectory {
+            underlying: RAMDirectory::create(),
+            fail: fail.clone(),
+        };
+        let index = Index::create(directory, schema_builder.build())?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.enable_write_ahead_log()?;
+        index_writer.try_add_document(doc!(id => "a"))?;
+        fail.store(true, Ordering::SeqCst);
+        assert!(index_writer.try_add_document(doc!(id => "b")).is_err());
+        assert!(index_writer
+            .try_delete_term(Term::from_field_text(id, "a"))
+            .is_err());
+        assert!(index_writer
+            .try_run(vec![UserOperation::Add(doc!(id => "c"))])
+            .is_err());
+        fail.store(false, Ordering::SeqCst);
+        // The log is not written again until the next commit.
+        assert!(index_writer.try_add_document(doc!(id => "d")).is_err());
+        index_writer.commit()?;
+        // The operations that could not be logged were not applied.
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        index_writer.try_add_document(doc!(id => "e"))?;
+        index_writer.commit()?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_ahead_log_ignores_incomplete_record() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let (mut write_ahead_log, operations) = WriteAheadLog::open(index.directory(), 0)?;
+        assert!(operations.is_empty());
+        write_ahead_log.log_add(&doc!(id => "a"))?;
+        write_ahead_log.log_delete(&Term::from_field_text(id, "b"))?;
+        write_ahead_log.log_delete_all()?;
+        let data = index
+            .directory()
+            .atomic_read(&super::log_filepath(write_ahead_log.generation))?;
+
+        let (operations, num_bytes) = parse_log(&data)?;
+        assert_eq!(num_bytes, data.len());
+        assert_eq!(
+            operations,
+            vec![
+                LoggedOperation::Group(vec![UserOperation::Add(doc!(id => "a"))]),
+                LoggedOperation::Group(vec![UserOperation::Delete(Term::from_field_text(id, "b"))]),
+                LoggedOperation::DeleteAll,
+            ]
+        );
+        let (operations, num_bytes) = parse_log(&data[..data.len() - 1])?;
+        assert_eq!(operations.len(), 2);
+        assert!(num_bytes < data.len() - 1);
+        let mut corrupted_data = data.clone();
+        corrupted_data[data.len() - 1] ^= 1;
+        assert_eq!(parse_log(&corrupted_data)?.0.len(), 2);
+        Ok(())
+    }
+}
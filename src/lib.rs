@@ -141,6 +141,7 @@ pub mod collector;
 pub mod directory;
 pub mod fastfield;
 pub mod fieldnorm;
+pub mod percolator;
 pub(crate) mod positions;
 pub mod postings;
 pub mod query;
@@ -151,17 +152,23 @@ pub mod termdict;
 
 mod reader;
 
-pub use self::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy};
+pub use self::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy, Warmer};
 mod snippet;
 pub use self::snippet::{Snippet, SnippetGenerator};
+mod suggest;
+pub use self::suggest::{CompletionSuggester, SpellingSuggester, Suggestion};
 
 mod docset;
 pub use self::docset::{DocSet, TERMINATED};
 pub use crate::common::HasLen;
 pub use crate::common::{f64_to_u64, i64_to_u64, u64_to_f64, u64_to_i64};
-pub use crate::core::{Executor, SegmentComponent};
-pub use crate::core::{Index, IndexMeta, Searcher, Segment, SegmentId, SegmentMeta};
-pub use crate::core::{InvertedIndexReader, SegmentReader};
+pub use crate::core::{Executor, FieldTermStreamer, FieldTerms, SegmentComponent};
+pub use crate::core::{
+    Index, IndexMeta, IndexSettings, ReplicationSnapshot, Searcher, Segment, SegmentId, SegmentMeta,
+};
+pub use crate::core::{InvertedIndexReader, MultiSearcher, SegmentReader};
+pub use crate::core::{QueryResultCache, SearchProfile, SegmentSearchProfile};
+pub use crate::core::{SegmentError, ValidationReport};
 pub use crate::directory::Directory;
 pub use crate::indexer::operation::UserOperation;
 pub use crate::indexer::IndexWriter;
@@ -174,7 +181,7 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
 /// Index format version.
-const INDEX_FORMAT_VERSION: u32 = 3;
+pub(crate) const INDEX_FORMAT_VERSION: u32 = 3;
 
 /// Structure version for the index.
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -186,6 +193,13 @@ pub struct Version {
     store_compression: String,
 }
 
+impl Version {
+    /// Returns the version of the index format.
+    pub fn index_format_version(&self) -> u32 {
+        self.index_format_version
+    }
+}
+
 impl fmt::Debug for Version {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.to_string())
@@ -339,6 +353,28 @@ mod tests {
         sample_with_seed(n, ratio, 4)
     }
 
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    // Servers share those objects across their worker threads:
+    // this test breaks the build if one of them stops being `Send + Sync`.
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<Index>();
+        assert_send_sync::<crate::IndexReader>();
+        assert_send_sync::<crate::IndexWriter>();
+        assert_send_sync::<crate::Searcher>();
+        assert_send_sync::<crate::LeasedItem<crate::Searcher>>();
+        assert_send_sync::<SegmentReader>();
+        assert_send_sync::<Schema>();
+        assert_send_sync::<Document>();
+        assert_send_sync::<Term>();
+        assert_send_sync::<Box<dyn crate::query::Query>>();
+        assert_send_sync::<crate::query::QueryParser>();
+        assert_send_sync::<crate::query::BooleanQuery>();
+        assert_send_sync::<crate::tokenizer::TokenizerManager>();
+        assert_send_sync::<crate::TantivyError>();
+    }
+
     #[test]
     #[cfg(not(feature = "lz4"))]
     fn test_version_string() {
@@ -404,6 +440,113 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_searcher_doc_addresses() -> crate::Result<()> {
+        use crate::indexer::NoMergePolicy;
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.add_document(doc!(text_field=>"b"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"c"));
+        index_writer.delete_term(Term::from_field_text(text_field, "a"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let mut texts: Vec<String> = searcher
+            .doc_addresses()
+            .map(|doc_address| {
+                let doc = searcher.doc(doc_address).unwrap();
+                doc.get_first(text_field)
+                    .unwrap()
+                    .text()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        texts.sort();
+        assert_eq!(texts, vec!["b".to_string(), "c".to_string()]);
+        assert_eq!(searcher.doc_addresses().count() as u64, searcher.num_docs());
+        Ok(())
+    }
+
+    #[test]
+    fn test_searcher_doc_invalid_address() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.doc(DocAddress(0, 0)).is_ok());
+        assert!(matches!(
+            searcher.doc(DocAddress(1, 0)),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            searcher.doc(DocAddress(0, 1)),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_searcher_segment_access() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a b"));
+        index_writer.add_document(doc!(text_field=>"b"));
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(text_field, "a"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert!(segment_reader.delete_bitset().unwrap().is_deleted(0));
+        let inverted_index = segment_reader.inverted_index(text_field)?;
+        let term_b = Term::from_field_text(text_field, "b");
+        assert_eq!(inverted_index.doc_freq(&term_b)?, 2);
+        let store_reader = searcher.store_reader(0)?;
+        assert_eq!(store_reader.get(1)?, searcher.doc(DocAddress(0, 1))?);
+        assert!(matches!(
+            searcher.store_reader(1),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_searcher_search_batch() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::query::{AllQuery, Query, TermQuery};
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"a b"));
+        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"a c"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let term_query = |text: &str| {
+            TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            )
+        };
+        let (query_a, query_b, query_d) = (term_query("a"), term_query("b"), term_query("d"));
+        let queries: Vec<&dyn Query> = vec![&query_a, &query_b, &AllQuery, &query_d];
+        assert_eq!(searcher.search_batch(&queries, &Count)?, vec![3, 1, 3, 0]);
+        assert!(searcher.search_batch(&[], &Count)?.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_fieldnorm_no_docs_with_field() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
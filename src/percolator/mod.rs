@@ -0,0 +1,232 @@
+/*!
+Reverse search: matching documents against a set of registered queries.
+*/
+
+use crate::indexer::index_writer::HEAP_SIZE_MIN;
+use crate::indexer::NoMergePolicy;
+use crate::query::{PhraseQuery, Query, TermQuery};
+use crate::schema::{Document, Schema, Term};
+use crate::{Index, IndexReader, IndexWriter, ReloadPolicy};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+/// Returns a term that is required for the query to match, if it is
+/// easy to identify.
+fn required_term(query: &dyn Query) -> Option<Term> {
+    if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+        return Some(term_query.term().clone());
+    }
+    if let Some(phrase_query) = query.downcast_ref::<PhraseQuery>() {
+        return phrase_query.phrase_terms().into_iter().next();
+    }
+    None
+}
+
+/// A `Percolator` stores queries, and returns the queries that
+/// match a given document.
+///
+/// This is the reverse of a regular search, and is typically used for alerting
+/// or classification.
+///
+/// Each document is indexed in an in-ram index owned by the percolator, using
+/// the schema and the tokenizers of the index the percolator was created for.
+/// The in-ram index only ever contains the document being percolated, and
+/// documents are percolated one at a time.
+/// To avoid running all of the queries against every document, the term queries
+/// and phrase queries are only run if the document contains their (first) term.
+/// All of the other queries are run for every document.
+///
+/// ```rust
+/// use tantivy::percolator::Percolator;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let query_parser = QueryParser::for_index(&index, vec![title]);
+///
+/// let mut percolator = Percolator::for_index(&index)?;
+/// let rust_query_id = percolator.register(query_parser.parse_query("rust")?);
+/// let python_query_id = percolator.register(query_parser.parse_query("python")?);
+///
+/// let matching_queries = percolator.percolate(doc!(title => "Rust 1.48 is out"))?;
+/// assert_eq!(matching_queries, vec![rust_query_id]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Percolator {
+    schema: Schema,
+    index_writer: Mutex<IndexWriter>,
+    reader: IndexReader,
+    queries: Vec<Box<dyn Query>>,
+    queries_by_required_term: HashMap<Term, Vec<usize>>,
+    unfiltered_queries: Vec<usize>,
+}
+
+impl Percolator {
+    /// Creates an empty percolator, for documents following the schema
+    /// of the given index.
+    ///
+    /// This creates the in-ram index and the index writer documents are percolated with.
+    pub fn for_index(index: &Index) -> crate::Result<Percolator> {
+        let schema = index.schema();
+        let mut percolator_index = Index::create_in_ram(schema.clone());
+        // The tokenizer manager is shared, so that tokenizers registered later
+        // on the index are used as well.
+        percolator_index.set_tokenizers(index.tokenizers().clone());
+        let index_writer = percolator_index.writer_with_num_threads(1, HEAP_SIZE_MIN)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let reader = percolator_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(Percolator {
+            schema,
+            index_writer: Mutex::new(index_writer),
+            reader,
+            queries: Vec::new(),
+            queries_by_required_term: HashMap::new(),
+            unfiltered_queries: Vec::new(),
+        })
+    }
+
+    /// Registers a query, and returns its id.
+    ///
+    /// Ids are attributed sequentially, starting from 0.
+    pub fn register(&mut self, query: Box<dyn Query>) -> usize {
+        let query_id = self.queries.len();
+        if let Some(term) = required_term(query.as_ref()) {
+            self.queries_by_required_term
+                .entry(term)
+                .or_default()
+                .push(query_id);
+        } else {
+            self.unfiltered_queries.push(query_id);
+        }
+        self.queries.push(query);
+        query_id
+    }
+
+    /// Returns the query with the given id.
+    pub fn query(&self, query_id: usize) -> Option<&dyn Query> {
+        self.queries.get(query_id).map(Box::as_ref)
+    }
+
+    /// Returns the number of registered queries.
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// Returns true iff no query was registered.
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Returns the sorted ids of the queries matching the given document.
+    pub fn percolate(&self, doc: Document) -> crate::Result<Vec<usize>> {
+        // The lock is held until the end, as the in-ram index is reused for every document.
+        let mut index_writer = self
+            .index_writer
+            .lock()
+            .expect("Percolator index writer lock poisoned.");
+        index_writer.delete_all_documents()?;
+        index_writer.add_document(doc);
+        index_writer.commit()?;
+        self.reader.reload()?;
+        let searcher = self.reader.searcher();
+        if searcher.num_docs() == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates: BTreeSet<usize> = self.unfiltered_queries.iter().cloned().collect();
+        for (field, field_entry) in self.schema.fields() {
+            if !field_entry.is_indexed() {
+                continue;
+            }
+            let field_terms = searcher.field_terms(field)?;
+            let mut term_stream = field_terms.stream()?;
+            while term_stream.advance() {
+                let term = Term::from_field_bytes(field, term_stream.key());
+                if let Some(query_ids) = self.queries_by_required_term.get(&term) {
+                    candidates.extend(query_ids.iter().cloned());
+                }
+            }
+        }
+
+        let segment_reader = searcher.segment_reader(0);
+        let mut matching_queries = Vec::new();
+        for query_id in candidates {
+            let weight = self.queries[query_id].weight(&searcher, false)?;
+            if weight.count(segment_reader)? > 0 {
+                matching_queries.push(query_id);
+            }
+        }
+        Ok(matching_queries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Percolator;
+    use crate::query::{AllQuery, BooleanQuery, Occur, Query, QueryParser, RangeQuery};
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions, INDEXED, TEXT};
+    use crate::tokenizer::NgramTokenizer;
+    use crate::Index;
+
+    #[test]
+    fn test_percolator() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let price = schema_builder.add_u64_field("price", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let mut percolator = Percolator::for_index(&index)?;
+        assert!(percolator.is_empty());
+        let term_query = percolator.register(query_parser.parse_query("bike")?);
+        let phrase_query = percolator.register(query_parser.parse_query("\"red bike\"")?);
+        let range_query = percolator.register(Box::new(RangeQuery::new_u64(price, 0..100)));
+        let exclude_query = percolator.register(Box::new(BooleanQuery::from(vec![
+            (Occur::Must, Box::new(AllQuery) as Box<dyn Query>),
+            (Occur::MustNot, query_parser.parse_query("bike")?),
+        ])));
+        assert_eq!(percolator.len(), 4);
+        assert!(percolator.query(exclude_query).is_some());
+        assert!(percolator.query(4).is_none());
+
+        assert_eq!(
+            percolator.percolate(doc!(title => "A red bike", price => 50u64))?,
+            vec![term_query, phrase_query, range_query]
+        );
+        assert_eq!(
+            percolator.percolate(doc!(title => "A bike painted in red", price => 500u64))?,
+            vec![term_query]
+        );
+        assert_eq!(
+            percolator.percolate(doc!(title => "A red car", price => 500u64))?,
+            vec![exclude_query]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_percolator_uses_index_tokenizers() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(
+            "title",
+            TextOptions::default()
+                .set_indexing_options(TextFieldIndexing::default().set_tokenizer("ngram")),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        index
+            .tokenizers()
+            .register("ngram", NgramTokenizer::new(3, 3, false));
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let mut percolator = Percolator::for_index(&index)?;
+        let query_id = percolator.register(query_parser.parse_query("ike")?);
+        assert_eq!(percolator.percolate(doc!(title => "bike"))?, vec![query_id]);
+        Ok(())
+    }
+}
@@ -258,6 +258,16 @@ impl BlockSegmentPostings {
         self.load_block();
     }
 
+    /// Returns the last doc of the current block, as recorded in the skip data.
+    ///
+    /// The block does not need to be loaded, which makes it possible to decide
+    /// whether a block should be skipped without decoding it.
+    /// For the last block, which is not bitpacked and has no skip entry,
+    /// `TERMINATED` is returned.
+    pub fn last_doc_in_block(&self) -> DocId {
+        self.skip_reader.last_doc_in_block()
+    }
+
     pub(crate) fn position_offset(&self) -> u64 {
         self.skip_reader.position_offset()
     }
@@ -492,6 +502,18 @@ mod tests {
         assert_eq!(block_postings.doc(COMPRESSION_BLOCK_SIZE - 1), TERMINATED);
     }
 
+    #[test]
+    fn test_block_segment_postings_last_doc_in_block() {
+        let docs: Vec<DocId> = (0..300).map(|i| i * 2).collect();
+        let mut block_postings = build_block_postings(&docs[..]);
+        assert_eq!(block_postings.last_doc_in_block(), 254);
+        block_postings.advance();
+        assert_eq!(block_postings.last_doc_in_block(), 510);
+        block_postings.seek(512);
+        assert_eq!(block_postings.last_doc_in_block(), TERMINATED);
+        assert_eq!(block_postings.docs()[0], 512);
+    }
+
     #[test]
     fn test_reset_block_segment_postings() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
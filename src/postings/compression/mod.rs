@@ -11,6 +11,11 @@ pub fn compressed_block_size(num_bits: u8) -> usize {
     (num_bits as usize) * COMPRESSION_BLOCK_SIZE / 8
 }
 
+/// Bitpacks blocks of `COMPRESSION_BLOCK_SIZE` integers.
+///
+/// `BitPacker4x` picks a SIMD implementation at runtime when the CPU
+/// supports it, and falls back to a scalar implementation otherwise.
+/// Both produce the same format.
 pub struct BlockEncoder {
     bitpacker: BitPacker4x,
     pub output: [u8; COMPRESSED_BLOCK_MAX_SIZE],
@@ -55,6 +60,7 @@ impl BlockEncoder {
 #[derive(Clone)]
 pub(crate) struct AlignedBuffer(pub [u32; COMPRESSION_BLOCK_SIZE]);
 
+/// Decodes blocks bitpacked by the `BlockEncoder`.
 #[derive(Clone)]
 pub struct BlockDecoder {
     bitpacker: BitPacker4x,
@@ -285,6 +291,24 @@ pub mod tests {
         let block = BlockDecoder::with_val(TERMINATED);
         assert_eq!(block.output(0), TERMINATED);
     }
+    #[test]
+    fn test_all_docs_compression_numbits() {
+        for expected_num_bits in 0u8..=32u8 {
+            let mut data = [0u32; COMPRESSION_BLOCK_SIZE];
+            if expected_num_bits > 0 {
+                data[0] = (1u64 << (expected_num_bits as usize - 1)) as u32;
+            }
+            let mut encoder = BlockEncoder::new();
+            let (num_bits, compressed) = encoder.compress_block_unsorted(&data);
+            assert_eq!(num_bits, expected_num_bits);
+            assert_eq!(compressed.len(), compressed_block_size(num_bits));
+            let mut decoder = BlockDecoder::default();
+            let consumed_num_bytes = decoder.uncompress_block_unsorted(compressed, num_bits);
+            assert_eq!(consumed_num_bytes, compressed.len());
+            assert_eq!(decoder.output_array(), &data[..]);
+        }
+    }
+
     #[test]
     fn test_encode_vint() {
         const PADDING_VALUE: u32 = 234_234_345u32;
@@ -347,19 +371,6 @@ mod bench {
         });
     }
 
-    #[test]
-    fn test_all_docs_compression_numbits() {
-        for expected_num_bits in 0u8.. {
-            let mut data = [0u32; 128];
-            if expected_num_bits > 0 {
-                data[0] = (1u64 << (expected_num_bits as usize) - 1) as u32;
-            }
-            let mut encoder = BlockEncoder::new();
-            let (num_bits, compressed) = encoder.compress_block_unsorted(&data);
-            assert_eq!(compressed.len(), compressed_block_size(num_bits));
-        }
-    }
-
     const NUM_INTS_BENCH_VINT: usize = 10;
 
     #[bench]
@@ -49,7 +49,7 @@ pub mod tests {
     use crate::query::Scorer;
     use crate::schema::{Field, TextOptions};
     use crate::schema::{IndexRecordOption, TextFieldIndexing};
-    use crate::schema::{Schema, Term, INDEXED, TEXT};
+    use crate::schema::{Schema, Term, INDEXED, STRING, TEXT};
     use crate::tokenizer::{SimpleTokenizer, MAX_TOKEN_LEN};
     use crate::DocId;
     use crate::HasLen;
@@ -331,6 +331,95 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_boxed_postings() -> crate::Result<()> {
+        let mut positions: Vec<u32> = Vec::new();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "b c"));
+        index_writer.add_document(doc!(text_field => "a b a"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_postings = searcher
+            .segment_reader(0)
+            .inverted_index(text_field)?
+            .read_postings(
+                &Term::from_field_text(text_field, "a"),
+                IndexRecordOption::WithFreqsAndPositions,
+            )?
+            .unwrap();
+        let mut postings: Box<dyn Postings> = Box::new(segment_postings);
+        assert_eq!(postings.doc(), 1u32);
+        assert_eq!(postings.term_freq(), 2u32);
+        postings.positions_with_offset(3u32, &mut positions);
+        assert_eq!(&positions[..], &[3u32, 5u32]);
+        assert_eq!(postings.advance(), TERMINATED);
+        Ok(())
+    }
+
+    #[test]
+    fn test_total_term_freq() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let string_field = schema_builder.add_text_field("string", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a b a", string_field => "a"));
+        index_writer.add_document(doc!(text_field => "a a a c", string_field => "a"));
+        for _ in 0..200 {
+            index_writer.add_document(doc!(text_field => "b c", string_field => "b"));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let text_index = segment_reader.inverted_index(text_field)?;
+        assert_eq!(
+            text_index.total_term_freq(&Term::from_field_text(text_field, "a"))?,
+            5
+        );
+        assert_eq!(
+            text_index.total_term_freq(&Term::from_field_text(text_field, "c"))?,
+            201
+        );
+        assert_eq!(
+            text_index.total_term_freq(&Term::from_field_text(text_field, "z"))?,
+            0
+        );
+        let string_index = segment_reader.inverted_index(string_field)?;
+        assert_eq!(
+            string_index.total_term_freq(&Term::from_field_text(string_field, "b"))?,
+            200
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_positions_not_indexed() -> crate::Result<()> {
+        let mut positions: Vec<u32> = Vec::new();
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.add_document(doc!(text_field => "a"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let inverted_index = searcher.segment_reader(0).inverted_index(text_field)?;
+        assert!(!inverted_index.record_option().has_positions());
+        let mut postings = inverted_index
+            .read_postings(
+                &Term::from_field_text(text_field, "a"),
+                IndexRecordOption::WithFreqsAndPositions,
+            )?
+            .unwrap();
+        assert_eq!(postings.doc(), 0u32);
+        postings.positions(&mut positions);
+        assert!(positions.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_skip_next() -> crate::Result<()> {
         let term_0 = Term::from_field_u64(Field::from_field_id(0), 0);
@@ -1,4 +1,5 @@
 use crate::docset::DocSet;
+use std::borrow::{Borrow, BorrowMut};
 
 /// Postings (also called inverted list)
 ///
@@ -24,3 +25,15 @@ pub trait Postings: DocSet + 'static {
         self.positions_with_offset(0u32, output);
     }
 }
+
+impl<TPostings: Postings + ?Sized> Postings for Box<TPostings> {
+    fn term_freq(&self) -> u32 {
+        let unboxed: &TPostings = self.borrow();
+        unboxed.term_freq()
+    }
+
+    fn positions_with_offset(&mut self, offset: u32, output: &mut Vec<u32>) {
+        let unboxed: &mut TPostings = self.borrow_mut();
+        unboxed.positions_with_offset(offset, output)
+    }
+}
@@ -1,5 +1,6 @@
 use super::stacker::{Addr, MemoryArena, TermHashMap};
 
+use crate::fastfield::MultiValueIntFastFieldWriter;
 use crate::fieldnorm::FieldNormReaders;
 use crate::postings::recorder::{
     BufferLender, NothingRecorder, Recorder, TFAndPositionRecorder, TermFrequencyRecorder,
@@ -102,6 +103,7 @@ impl MultiFieldPostingsWriter {
         field: Field,
         token_stream: &mut dyn TokenStream,
         term_buffer: &mut Term,
+        term_id_fast_field_writer_opt: Option<&mut MultiValueIntFastFieldWriter>,
     ) -> u32 {
         let postings_writer =
             self.per_field_postings_writers[field.field_id() as usize].deref_mut();
@@ -112,6 +114,7 @@ impl MultiFieldPostingsWriter {
             token_stream,
             &mut self.heap,
             term_buffer,
+            term_id_fast_field_writer_opt,
         )
     }
 
@@ -212,6 +215,11 @@ pub trait PostingsWriter {
     ) -> io::Result<()>;
 
     /// Tokenize a text and subscribe all of its token.
+    ///
+    /// If a fast field writer is given, the `UnorderedTermId` of each token
+    /// is pushed to it, so that it can be remapped to a term ordinal upon
+    /// serialization.
+    #[allow(clippy::too_many_arguments)]
     fn index_text(
         &mut self,
         term_index: &mut TermHashMap,
@@ -220,19 +228,23 @@ pub trait PostingsWriter {
         token_stream: &mut dyn TokenStream,
         heap: &mut MemoryArena,
         term_buffer: &mut Term,
+        mut term_id_fast_field_writer_opt: Option<&mut MultiValueIntFastFieldWriter>,
     ) -> u32 {
         term_buffer.set_field(field);
         let mut sink = |token: &Token| {
             // We skip all tokens with a len greater than u16.
             if token.text.len() <= MAX_TOKEN_LEN {
                 term_buffer.set_text(token.text.as_str());
-                self.subscribe(
+                let unordered_term_id = self.subscribe(
                     term_index,
                     doc_id,
                     token.position as u32,
                     &term_buffer,
                     heap,
                 );
+                if let Some(term_id_fast_field_writer) = term_id_fast_field_writer_opt.as_mut() {
+                    term_id_fast_field_writer.add_val(unordered_term_id);
+                }
             } else {
                 info!(
                     "A token exceeding MAX_TOKEN_LEN ({}>{}) was dropped. Search for \
@@ -10,7 +10,7 @@ use crate::postings::skip::SkipSerializer;
 use crate::query::BM25Weight;
 use crate::schema::{Field, FieldEntry, FieldType};
 use crate::schema::{IndexRecordOption, Schema};
-use crate::termdict::{TermDictionaryBuilder, TermOrdinal};
+use crate::termdict::{TermDictionaryBuilder, TermDictionaryType, TermOrdinal};
 use crate::{DocId, Score};
 use std::cmp::Ordering;
 use std::io::{self, Write};
@@ -52,6 +52,7 @@ pub struct InvertedIndexSerializer {
     positions_write: CompositeWrite<WritePtr>,
     positionsidx_write: CompositeWrite<WritePtr>,
     schema: Schema,
+    term_dictionary_type: TermDictionaryType,
 }
 
 impl InvertedIndexSerializer {
@@ -62,6 +63,7 @@ impl InvertedIndexSerializer {
         positions_write: CompositeWrite<WritePtr>,
         positionsidx_write: CompositeWrite<WritePtr>,
         schema: Schema,
+        term_dictionary_type: TermDictionaryType,
     ) -> crate::Result<InvertedIndexSerializer> {
         Ok(InvertedIndexSerializer {
             terms_write,
@@ -69,6 +71,7 @@ impl InvertedIndexSerializer {
             positions_write,
             positionsidx_write,
             schema,
+            term_dictionary_type,
         })
     }
 
@@ -81,6 +84,7 @@ impl InvertedIndexSerializer {
             CompositeWrite::wrap(segment.open_write(POSITIONS)?),
             CompositeWrite::wrap(segment.open_write(POSITIONSSKIP)?),
             segment.schema(),
+            segment.index().settings().term_dictionary_type,
         )
     }
 
@@ -100,10 +104,14 @@ impl InvertedIndexSerializer {
         let positions_write = self.positions_write.for_field(field);
         let positionsidx_write = self.positionsidx_write.for_field(field);
         let field_type: FieldType = (*field_entry.field_type()).clone();
+        let term_dictionary_builder = TermDictionaryBuilder::create_with_type(
+            term_dictionary_write,
+            self.term_dictionary_type,
+        )?;
         FieldSerializer::create(
             &field_type,
             total_num_tokens,
-            term_dictionary_write,
+            term_dictionary_builder,
             postings_write,
             positions_write,
             positionsidx_write,
@@ -136,7 +144,7 @@ impl<'a> FieldSerializer<'a> {
     fn create(
         field_type: &FieldType,
         total_num_tokens: u64,
-        term_dictionary_write: &'a mut CountingWriter<WritePtr>,
+        term_dictionary_builder: TermDictionaryBuilder<&'a mut CountingWriter<WritePtr>>,
         postings_write: &'a mut CountingWriter<WritePtr>,
         positions_write: &'a mut CountingWriter<WritePtr>,
         positionsidx_write: &'a mut CountingWriter<WritePtr>,
@@ -153,7 +161,6 @@ impl<'a> FieldSerializer<'a> {
             }
             _ => IndexRecordOption::Basic,
         };
-        let term_dictionary_builder = TermDictionaryBuilder::create(term_dictionary_write)?;
         let average_fieldnorm = fieldnorm_reader
             .as_ref()
             .map(|ff_reader| (total_num_tokens as Score / ff_reader.num_docs() as Score))
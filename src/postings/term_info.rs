@@ -17,7 +17,9 @@ pub struct TermInfo {
 }
 
 impl TermInfo {
-    pub(crate) fn posting_num_bytes(&self) -> u32 {
+    /// Returns the size in bytes of the posting list
+    /// within the postings (`.idx`) file.
+    pub fn posting_num_bytes(&self) -> u32 {
         let num_bytes = self.postings_stop_offset - self.postings_start_offset;
         assert!(num_bytes <= std::u32::MAX as u64);
         num_bytes as u32
@@ -61,9 +63,26 @@ mod tests {
 
     use super::TermInfo;
     use crate::common::test::fixed_size_test;
+    use crate::common::BinarySerializable;
 
     #[test]
     fn test_fixed_size() {
         fixed_size_test::<TermInfo>();
     }
+
+    #[test]
+    fn test_term_info_serialization() {
+        let term_info = TermInfo {
+            doc_freq: 3,
+            postings_start_offset: 1_000,
+            postings_stop_offset: 1_024,
+            positions_idx: 17,
+        };
+        assert_eq!(term_info.posting_num_bytes(), 24);
+        let mut buffer = Vec::new();
+        term_info.serialize(&mut buffer).unwrap();
+        let deserialized = TermInfo::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(deserialized, term_info);
+        assert_eq!(deserialized.posting_num_bytes(), 24);
+    }
 }
@@ -135,6 +135,11 @@ pub fn block_wand(
     mut threshold: Score,
     callback: &mut dyn FnMut(u32, Score) -> Score,
 ) {
+    if scorers.len() == 1 {
+        let scorer = scorers.pop().unwrap(); //< we checked the size beforehands
+        block_wand_single_scorer(scorer, threshold, callback);
+        return;
+    }
     let mut scorers: Vec<TermScorerWithMaxScore> = scorers
         .iter_mut()
         .map(TermScorerWithMaxScore::from)
@@ -195,6 +200,51 @@ pub fn block_wand(
     }
 }
 
+/// Specialized version of `block_wand` for a single term.
+///
+/// There is no pivot to compute: blocks whose block max score does not
+/// exceed the threshold are skipped without being decoded, and the
+/// documents of the other blocks are scored one by one.
+fn block_wand_single_scorer(
+    mut scorer: TermScorer,
+    mut threshold: Score,
+    callback: &mut dyn FnMut(u32, Score) -> Score,
+) {
+    let mut doc = scorer.doc();
+    loop {
+        // We position the scorer on a block that can exceed the threshold.
+        while scorer.block_max_score() <= threshold {
+            let last_doc_in_block = scorer.last_doc_in_block();
+            if last_doc_in_block == TERMINATED {
+                return;
+            }
+            doc = last_doc_in_block + 1;
+            scorer.shallow_seek(doc);
+        }
+        // Seeking loads the block.
+        doc = scorer.seek(doc);
+        if doc == TERMINATED {
+            return;
+        }
+        loop {
+            let score = scorer.score();
+            if score > threshold {
+                threshold = callback(doc, score);
+            }
+            debug_assert!(doc <= scorer.last_doc_in_block());
+            if doc == scorer.last_doc_in_block() {
+                break;
+            }
+            doc = scorer.advance();
+            if doc == TERMINATED {
+                return;
+            }
+        }
+        doc += 1;
+        scorer.shallow_seek(doc);
+    }
+}
+
 struct TermScorerWithMaxScore<'a> {
     scorer: &'a mut TermScorer,
     max_score: Score,
@@ -416,6 +466,14 @@ mod tests {
         }
     }
 
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(500))]
+        #[test]
+        fn test_block_wand_single_term_scorer((posting_lists, fieldnorms) in gen_term_scorers(1)) {
+            test_block_wand_aux(&posting_lists[..], &fieldnorms[..]);
+        }
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(500))]
         #[test]
@@ -0,0 +1,281 @@
+use crate::common::BitSet;
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, EmptyScorer, Explanation, Query, Scorer, Weight};
+use crate::schema::geo_point::{decode, encode, quantize_lat, quantize_lon};
+use crate::schema::{Field, GeoPoint, Type};
+use crate::{DocId, Score, Searcher, SegmentReader, TantivyError};
+
+/// `BoundingBoxQuery` matches the documents having a geo point
+/// within a rectangle, given by its top left and bottom right corners.
+///
+/// The field has to be a `u64` fast field, in which the points are indexed
+/// as described in [`GeoPoint`](../schema/struct.GeoPoint.html).
+/// For multivalued fast fields, a document matches if any of its points
+/// is within the box. The documents without a point never match.
+///
+/// If the longitude of the top left corner is greater than the one of the
+/// bottom right corner, the box crosses the antimeridian.
+///
+/// Matched documents all get a constant `Score` of one.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::BoundingBoxQuery;
+/// use tantivy::schema::{GeoPoint, Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_u64_field("location", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(location => GeoPoint::new(48.8566, 2.3522)?));
+/// index_writer.add_document(doc!(location => GeoPoint::new(51.5074, -0.1278)?));
+/// index_writer.add_document(doc!(location => GeoPoint::new(40.7128, -74.0060)?));
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let europe = BoundingBoxQuery::new(
+///     location,
+///     GeoPoint::new(71.0, -25.0)?,
+///     GeoPoint::new(35.0, 45.0)?,
+/// );
+/// assert_eq!(searcher.search(&europe, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoundingBoxQuery {
+    field: Field,
+    top_left: GeoPoint,
+    bottom_right: GeoPoint,
+}
+
+impl BoundingBoxQuery {
+    /// Creates a query matching the points within the box going from `top_left`
+    /// to `bottom_right`.
+    pub fn new(field: Field, top_left: GeoPoint, bottom_right: GeoPoint) -> BoundingBoxQuery {
+        BoundingBoxQuery {
+            field,
+            top_left,
+            bottom_right,
+        }
+    }
+}
+
+impl Query for BoundingBoxQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        if field_entry.field_type().value_type() != Type::U64 || !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "The field {:?} is not a u64 fast field.",
+                field_entry.name()
+            )));
+        }
+        if self.top_left.lat() < self.bottom_right.lat() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The top left corner of the box is below its bottom right corner: {} < {}.",
+                self.top_left.lat(),
+                self.bottom_right.lat()
+            )));
+        }
+        Ok(Box::new(BoundingBoxWeight {
+            field: self.field,
+            min_lat_cell: quantize_lat(self.bottom_right.lat()),
+            max_lat_cell: quantize_lat(self.top_left.lat()),
+            min_lon_cell: quantize_lon(self.top_left.lon()),
+            max_lon_cell: quantize_lon(self.bottom_right.lon()),
+        }))
+    }
+}
+
+/// The box, expressed in the cells of the `GeoPoint` encoding.
+struct BoundingBoxWeight {
+    field: Field,
+    min_lat_cell: u32,
+    max_lat_cell: u32,
+    min_lon_cell: u32,
+    max_lon_cell: u32,
+}
+
+impl BoundingBoxWeight {
+    fn crosses_antimeridian(&self) -> bool {
+        self.min_lon_cell > self.max_lon_cell
+    }
+
+    fn contains(&self, val: u64) -> bool {
+        let (lat_cell, lon_cell) = match decode(val) {
+            Some(cells) => cells,
+            // The document does not have a point.
+            None => return false,
+        };
+        if lat_cell < self.min_lat_cell || lat_cell > self.max_lat_cell {
+            return false;
+        }
+        if self.crosses_antimeridian() {
+            lon_cell >= self.min_lon_cell || lon_cell <= self.max_lon_cell
+        } else {
+            lon_cell >= self.min_lon_cell && lon_cell <= self.max_lon_cell
+        }
+    }
+
+    /// Returns false if no encoded point within `[min_val, max_val]` can be in the box.
+    ///
+    /// The encoding is increasing with both coordinates, so that all of the points
+    /// of a box that does not cross the antimeridian are encoded between
+    /// the encodings of its corners.
+    fn may_intersect(&self, min_val: u64, max_val: u64) -> bool {
+        if self.crosses_antimeridian() {
+            return true;
+        }
+        let min_box_val = encode(self.min_lat_cell, self.min_lon_cell);
+        let max_box_val = encode(self.max_lat_cell, self.max_lon_cell);
+        max_val >= min_box_val && min_val <= max_box_val
+    }
+}
+
+impl Weight for BoundingBoxWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        let fast_fields = reader.fast_fields();
+        if let Some(fast_field_reader) = fast_fields.u64(self.field) {
+            if !self.may_intersect(fast_field_reader.min_value(), fast_field_reader.max_value()) {
+                return Ok(Box::new(EmptyScorer));
+            }
+            for doc in 0..max_doc {
+                let val = fast_field_reader.get(doc);
+                if self.contains(val) {
+                    doc_bitset.insert(doc);
+                }
+            }
+        } else if let Some(fast_field_reader) = fast_fields.u64s(self.field) {
+            if !self.may_intersect(fast_field_reader.min_value(), fast_field_reader.max_value()) {
+                return Ok(Box::new(EmptyScorer));
+            }
+            let mut vals = Vec::new();
+            for doc in 0..max_doc {
+                fast_field_reader.get_vals(doc, &mut vals);
+                if vals.iter().any(|&val| self.contains(val)) {
+                    doc_bitset.insert(doc);
+                }
+            }
+        } else {
+            let field_name = reader.schema().get_field_entry(self.field).name();
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field.",
+                field_name
+            )));
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("BoundingBoxQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundingBoxQuery;
+    use crate::collector::Count;
+    use crate::schema::{Cardinality, GeoPoint, IntOptions, Schema, FAST, TEXT};
+    use crate::{Document, Index};
+
+    fn point(lat: f64, lon: f64) -> GeoPoint {
+        GeoPoint::new(lat, lon).unwrap()
+    }
+
+    #[test]
+    fn test_bounding_box_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field("location", FAST);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(location => point(35.6762, 139.6503)));
+        index_writer.add_document(doc!(location => point(34.6937, 135.5023)));
+        index_writer.add_document(doc!(location => point(-33.8688, 151.2093)));
+        index_writer.add_document(doc!(location => point(21.3069, -157.8583)));
+        index_writer.add_document(doc!(title => "no location"));
+        index_writer.add_document(doc!(location => point(-90.0, -180.0)));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |top_left: GeoPoint, bottom_right: GeoPoint| {
+            let query = BoundingBoxQuery::new(location, top_left, bottom_right);
+            searcher.search(&query, &Count).unwrap()
+        };
+        // Japan
+        assert_eq!(count(point(46.0, 128.0), point(30.0, 146.0)), 2);
+        assert_eq!(count(point(36.0, 139.0), point(35.0, 140.0)), 1);
+        // The Pacific, crossing the antimeridian.
+        assert_eq!(count(point(40.0, 130.0), point(-40.0, -150.0)), 4);
+        assert_eq!(count(point(40.0, 140.0), point(-40.0, -150.0)), 2);
+        assert_eq!(count(point(10.0, -10.0), point(-10.0, 10.0)), 0);
+        // The document without a location is not matched, unlike (-90, -180).
+        assert_eq!(count(point(-89.0, -180.0), point(-90.0, -179.0)), 1);
+        assert_eq!(count(point(90.0, -180.0), point(-90.0, 180.0)), 5);
+
+        let query = BoundingBoxQuery::new(location, point(30.0, 128.0), point(46.0, 146.0));
+        assert!(searcher.search(&query, &Count).is_err());
+        let query = BoundingBoxQuery::new(title, point(46.0, 128.0), point(30.0, 146.0));
+        assert!(searcher.search(&query, &Count).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box_query_multivalued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field(
+            "location",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let mut doc = Document::new();
+        doc.add_geo_point(location, point(48.8566, 2.3522));
+        doc.add_geo_point(location, point(40.7128, -74.0060));
+        index_writer.add_document(doc);
+        index_writer.add_document(doc!(location => point(51.5074, -0.1278)));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = BoundingBoxQuery::new(location, point(45.0, -80.0), point(35.0, -70.0));
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        let query = BoundingBoxQuery::new(location, point(60.0, -10.0), point(40.0, 10.0));
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box_query_missing_points() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field("location", FAST);
+        let optional_location = schema_builder.add_u64_field(
+            "optional_location",
+            IntOptions::default().set_fast(Cardinality::Optional),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            location => point(-89.0, -179.0),
+            optional_location => point(-89.0, -179.0)
+        ));
+        index_writer.add_document(doc!());
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        for &field in &[location, optional_location] {
+            let world = BoundingBoxQuery::new(field, point(90.0, -180.0), point(-90.0, 180.0));
+            assert_eq!(searcher.search(&world, &Count)?, 1);
+        }
+        Ok(())
+    }
+}
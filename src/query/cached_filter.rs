@@ -0,0 +1,290 @@
+use crate::common::BitSet;
+use crate::core::{Searcher, SegmentId, SegmentReader};
+use crate::docset::{DocSet, TERMINATED};
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, Explanation, Query, Scorer, Weight};
+use crate::reader::Warmer;
+use crate::{DocId, Score, Term};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+type FilterCacheKey = (SegmentId, String);
+
+/// Cache of the documents matched by filters, for each segment.
+///
+/// A `FilterCache` is meant to be shared by the different `CachedFilterQuery`
+/// run against a given `IndexReader`. Cloning it is cheap, and
+/// all of the clones share the same entries.
+///
+/// Segments are immutable, so the entries of a segment never need to be
+/// recomputed. The entries of the segments that are not searchable anymore
+/// (after a merge for instance) are however never removed automatically.
+/// `FilterCache` implements `Warmer`: registering it on the `IndexReader`
+/// removes these stale entries every time the reader is reloaded.
+#[derive(Clone, Default)]
+pub struct FilterCache {
+    entries: Arc<RwLock<HashMap<FilterCacheKey, Arc<BitSet>>>>,
+}
+
+impl FilterCache {
+    /// Creates an empty `FilterCache`.
+    pub fn new() -> FilterCache {
+        FilterCache::default()
+    }
+
+    /// Returns the number of cached `(segment, filter)` entries.
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Returns true iff the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all of the entries of the cache.
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+
+    /// Removes the entries associated to the segments that
+    /// are not part of the given searcher.
+    pub fn retain_segments(&self, searcher: &Searcher) {
+        let segment_ids: HashSet<SegmentId> = searcher
+            .segment_readers()
+            .iter()
+            .map(SegmentReader::segment_id)
+            .collect();
+        self.entries
+            .write()
+            .unwrap()
+            .retain(|(segment_id, _), _| segment_ids.contains(segment_id));
+    }
+
+    fn get_or_compute(
+        &self,
+        key: FilterCacheKey,
+        compute: impl FnOnce() -> crate::Result<BitSet>,
+    ) -> crate::Result<Arc<BitSet>> {
+        if let Some(doc_bitset) = self.entries.read().unwrap().get(&key) {
+            return Ok(doc_bitset.clone());
+        }
+        let doc_bitset = Arc::new(compute()?);
+        self.entries
+            .write()
+            .unwrap()
+            .insert(key, doc_bitset.clone());
+        Ok(doc_bitset)
+    }
+}
+
+impl fmt::Debug for FilterCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FilterCache(len={})", self.len())
+    }
+}
+
+impl Warmer for FilterCache {
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        self.retain_segments(searcher);
+        Ok(())
+    }
+}
+
+/// `CachedFilterQuery` wraps a query and caches the set of documents
+/// it matches, for each segment, in a `FilterCache`.
+///
+/// It is meant to be used for filters that are frequently reused
+/// (a tenant id, a status flag, ...), typically as a `Occur::Must` clause
+/// of a `BooleanQuery`.
+///
+/// The score of the underlying query is ignored: all of the matched
+/// documents get the score 1.0.
+///
+/// Filters are identified in the cache by a key given by the caller.
+/// Two filters sharing a cache must have the same key if and only if they
+/// match the same documents.
+pub struct CachedFilterQuery {
+    cache_key: String,
+    query: Box<dyn Query>,
+    cache: FilterCache,
+}
+
+impl CachedFilterQuery {
+    /// Builds a cached filter query, identified in the cache by `cache_key`.
+    pub fn new(
+        cache_key: impl Into<String>,
+        query: Box<dyn Query>,
+        cache: FilterCache,
+    ) -> CachedFilterQuery {
+        CachedFilterQuery {
+            cache_key: cache_key.into(),
+            query,
+            cache,
+        }
+    }
+
+    /// Returns the key identifying the filter in the cache.
+    pub fn cache_key(&self) -> &str {
+        &self.cache_key
+    }
+}
+
+impl Clone for CachedFilterQuery {
+    fn clone(&self) -> Self {
+        CachedFilterQuery {
+            cache_key: self.cache_key.clone(),
+            query: self.query.box_clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for CachedFilterQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "CachedFilter(key={:?}, query={:?})",
+            self.cache_key, self.query
+        )
+    }
+}
+
+impl Query for CachedFilterQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(searcher, false)?;
+        Ok(Box::new(CachedFilterWeight {
+            weight,
+            key: self.cache_key.clone(),
+            cache: self.cache.clone(),
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set)
+    }
+}
+
+struct CachedFilterWeight {
+    weight: Box<dyn Weight>,
+    key: String,
+    cache: FilterCache,
+}
+
+impl CachedFilterWeight {
+    fn doc_bitset(&self, reader: &SegmentReader) -> crate::Result<Arc<BitSet>> {
+        let key = (reader.segment_id(), self.key.clone());
+        self.cache.get_or_compute(key, || {
+            let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+            let mut scorer = self.weight.scorer(reader, 1.0)?;
+            let mut doc = scorer.doc();
+            while doc != TERMINATED {
+                doc_bitset.insert(doc);
+                doc = scorer.advance();
+            }
+            Ok(doc_bitset)
+        })
+    }
+}
+
+impl Weight for CachedFilterWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        // The cached bitset is shared by the scorers, rather than copied.
+        let doc_bitset = BitSetDocSet::from(self.doc_bitset(reader)?);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("CachedFilterQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CachedFilterQuery, FilterCache};
+    use crate::collector::Count;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{Index, ReloadPolicy, Term};
+
+    #[test]
+    fn test_cached_filter_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant_field = schema_builder.add_text_field("tenant", STRING);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let filter_cache = FilterCache::new();
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .warmer(filter_cache.clone())
+            .try_into()?;
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant_field=>"a", text_field=>"hello"));
+        index_writer.add_document(doc!(tenant_field=>"b", text_field=>"hello"));
+        index_writer.add_document(doc!(tenant_field=>"a", text_field=>"happy"));
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+
+        let tenant_filter = CachedFilterQuery::new(
+            "tenant:a",
+            Box::new(TermQuery::new(
+                Term::from_field_text(tenant_field, "a"),
+                IndexRecordOption::Basic,
+            )),
+            filter_cache.clone(),
+        );
+        let hello_query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(text_field, "hello"),
+            IndexRecordOption::Basic,
+        ));
+        let query = BooleanQuery::from(vec![
+            (Occur::Must, hello_query),
+            (Occur::Must, Box::new(tenant_filter.clone())),
+        ]);
+        assert!(filter_cache.is_empty());
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        assert_eq!(filter_cache.len(), 1);
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        assert_eq!(searcher.search(&tenant_filter, &Count)?, 2);
+        assert_eq!(filter_cache.len(), 1);
+
+        // Filters are identified by their key.
+        let other_tenant_filter = CachedFilterQuery::new(
+            "tenant:b",
+            Box::new(TermQuery::new(
+                Term::from_field_text(tenant_field, "b"),
+                IndexRecordOption::Basic,
+            )),
+            filter_cache.clone(),
+        );
+        assert_eq!(searcher.search(&other_tenant_filter, &Count)?, 1);
+        assert_eq!(filter_cache.len(), 2);
+        let explanation = tenant_filter.explain(&searcher, crate::DocAddress(0, 0))?;
+        assert_eq!(explanation.value(), 1.0);
+        assert!(tenant_filter
+            .explain(&searcher, crate::DocAddress(0, 1))
+            .is_err());
+
+        // Reloading the reader purges the entries of the segments
+        // that are gone.
+        index_writer.delete_all_documents()?;
+        index_writer.add_document(doc!(tenant_field=>"a", text_field=>"hello"));
+        index_writer.commit()?;
+        reader.reload()?;
+        assert!(filter_cache.is_empty());
+        assert_eq!(reader.searcher().search(&tenant_filter, &Count)?, 1);
+        assert_eq!(filter_cache.len(), 1);
+        Ok(())
+    }
+}
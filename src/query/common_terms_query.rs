@@ -0,0 +1,172 @@
+use crate::core::Searcher;
+use crate::query::{BooleanQuery, EmptyQuery, Occur, Query, TermQuery, Weight};
+use crate::schema::IndexRecordOption;
+use crate::{TantivyError, Term};
+use std::collections::BTreeSet;
+
+/// `CommonTermsQuery` gives good relevance to queries containing very common terms,
+/// like `"to be or not to be"`, without removing them with a stop word filter.
+///
+/// The terms are split in two groups, according to their document frequency:
+/// - the low-frequency terms are required: a document has to contain all of them
+///   to match.
+/// - the high-frequency terms, appearing in more than `cutoff_frequency` of the documents,
+///   are optional: they only increase the score of the matching documents.
+///
+/// If all of the terms are high-frequency terms, they are all required.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::CommonTermsQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"));
+/// index_writer.add_document(doc!(title => "The Sea, The Sea"));
+/// index_writer.add_document(doc!(title => "The Name of the Rose"));
+/// index_writer.add_document(doc!(title => "The Little Prince"));
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let terms = vec![
+///     Term::from_field_text(title, "the"),
+///     Term::from_field_text(title, "sea"),
+/// ];
+/// // "the" is in more than half of the documents: it is optional.
+/// let query = CommonTermsQuery::new(terms, 0.5);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CommonTermsQuery {
+    terms: Vec<Term>,
+    cutoff_frequency: f64,
+}
+
+impl CommonTermsQuery {
+    /// Creates a new `CommonTermsQuery`.
+    ///
+    /// `cutoff_frequency` is the fraction of the documents of the index, between 0 and 1,
+    /// above which a term is considered as a high-frequency term.
+    pub fn new(terms: Vec<Term>, cutoff_frequency: f64) -> CommonTermsQuery {
+        CommonTermsQuery {
+            terms,
+            cutoff_frequency,
+        }
+    }
+
+    /// Splits the terms into the low-frequency terms and the high-frequency terms.
+    fn split_terms(&self, searcher: &Searcher) -> crate::Result<(Vec<Term>, Vec<Term>)> {
+        let max_doc_freq = self.cutoff_frequency * searcher.num_docs() as f64;
+        let mut low_freq_terms = Vec::new();
+        let mut high_freq_terms = Vec::new();
+        for term in &self.terms {
+            if searcher.doc_freq(term)? as f64 > max_doc_freq {
+                high_freq_terms.push(term.clone());
+            } else {
+                low_freq_terms.push(term.clone());
+            }
+        }
+        Ok((low_freq_terms, high_freq_terms))
+    }
+}
+
+fn term_queries(terms: Vec<Term>, occur: Occur) -> Vec<(Occur, Box<dyn Query>)> {
+    terms
+        .into_iter()
+        .map(|term| {
+            let term_query: Box<dyn Query> =
+                Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+            (occur, term_query)
+        })
+        .collect()
+}
+
+impl Query for CommonTermsQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        if !(self.cutoff_frequency >= 0.0 && self.cutoff_frequency <= 1.0) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The cutoff frequency has to be between 0 and 1, got {}.",
+                self.cutoff_frequency
+            )));
+        }
+        if self.terms.is_empty() {
+            return EmptyQuery.weight(searcher, scoring_enabled);
+        }
+        let (low_freq_terms, high_freq_terms) = self.split_terms(searcher)?;
+        let query = if low_freq_terms.is_empty() {
+            BooleanQuery::new(term_queries(high_freq_terms, Occur::Must))
+        } else {
+            let mut subqueries = term_queries(low_freq_terms, Occur::Must);
+            subqueries.extend(term_queries(high_freq_terms, Occur::Should));
+            BooleanQuery::new(subqueries)
+        };
+        query.weight(searcher, scoring_enabled)
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        term_set.extend(self.terms.iter().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommonTermsQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{DocAddress, Index, Term};
+
+    #[test]
+    fn test_common_terms_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "to be or not to be"));
+        index_writer.add_document(doc!(title => "to be continued"));
+        index_writer.add_document(doc!(title => "not to be confused"));
+        index_writer.add_document(doc!(title => "to the lighthouse"));
+        index_writer.add_document(doc!(title => "or else"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term = |text: &str| Term::from_field_text(title, text);
+
+        // "to" and "be" are high-frequency terms, "or" and "not" are low-frequency terms.
+        let query =
+            CommonTermsQuery::new(vec![term("to"), term("be"), term("or"), term("not")], 0.5);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        let doc_addresses: Vec<DocAddress> = top_docs
+            .iter()
+            .map(|&(_, doc_address)| doc_address)
+            .collect();
+        assert_eq!(doc_addresses, vec![DocAddress(0, 0)]);
+
+        // The high-frequency terms contribute to the score.
+        let query = CommonTermsQuery::new(vec![term("to"), term("confused")], 0.5);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        assert_eq!(top_docs.len(), 1);
+        let confused_query = TermQuery::new(term("confused"), IndexRecordOption::WithFreqs);
+        let confused_top_docs = searcher.search(&confused_query, &TopDocs::with_limit(5))?;
+        assert!(top_docs[0].0 > confused_top_docs[0].0);
+
+        // All of the terms are high-frequency terms: they are all required.
+        let query = CommonTermsQuery::new(vec![term("to"), term("be")], 0.5);
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+
+        assert_eq!(
+            searcher.search(&CommonTermsQuery::new(vec![], 0.5), &Count)?,
+            0
+        );
+        assert!(CommonTermsQuery::new(vec![term("to")], 2.0)
+            .weight(&searcher, true)
+            .is_err());
+        Ok(())
+    }
+}
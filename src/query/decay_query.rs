@@ -0,0 +1,438 @@
+use crate::common;
+use crate::fastfield::{DeleteBitSet, FastFieldReader};
+use crate::query::explanation::does_not_match;
+use crate::query::{Explanation, Query, Scorer, Weight};
+use crate::schema::{Cardinality, Field, FieldType};
+use crate::{DocId, DocSet, Score, Searcher, SegmentReader, TantivyError, Term};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Shape of the decay applied by a `DecayQuery`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecayFunction {
+    /// The factor follows a normal curve: it decreases slowly close to the origin,
+    /// then fast, and never reaches 0.
+    Gaussian,
+    /// The factor is divided by the same amount for every `scale` of distance.
+    Exponential,
+    /// The factor decreases linearly, and reaches 0 at a distance of `scale / (1 - decay)`.
+    Linear,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Decay {
+    function: DecayFunction,
+    origin: f64,
+    scale: f64,
+    offset: f64,
+    decay: f64,
+}
+
+impl Decay {
+    fn check(&self) -> crate::Result<()> {
+        if self.scale.is_nan() || self.scale <= 0.0 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The scale of a decay has to be positive, got {}.",
+                self.scale
+            )));
+        }
+        if self.offset.is_nan() || self.offset < 0.0 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The offset of a decay cannot be negative, got {}.",
+                self.offset
+            )));
+        }
+        if !(self.decay > 0.0 && self.decay < 1.0) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The decay has to be strictly between 0 and 1, got {}.",
+                self.decay
+            )));
+        }
+        Ok(())
+    }
+
+    fn factor(&self, value: f64) -> f64 {
+        let distance = ((value - self.origin).abs() - self.offset).max(0.0);
+        let num_scales = distance / self.scale;
+        match self.function {
+            DecayFunction::Gaussian => self.decay.powf(num_scales * num_scales),
+            DecayFunction::Exponential => self.decay.powf(num_scales),
+            DecayFunction::Linear => (1.0 - (1.0 - self.decay) * num_scales).max(0.0),
+        }
+    }
+}
+
+/// `DecayQuery` is a wrapper over a query, multiplying its score by a factor
+/// decreasing with the distance between the value of a fast field and an origin.
+///
+/// The factor is 1 as long as the distance is below `offset`, and `decay`
+/// at a distance of `offset + scale`. How it evolves in between and beyond
+/// depends on the `DecayFunction`.
+///
+/// The field has to be a single-valued `u64`, `i64`, `f64` or date fast field.
+/// The values of date fields are timestamps, in seconds.
+///
+/// The document set matched by the `DecayQuery` is strictly the same as the underlying query.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{DecayFunction, DecayQuery, QueryParser};
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let published = schema_builder.add_date_field("published", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// let now = chrono::Utc::now();
+/// index_writer.add_document(doc!(title => "sea", published => now - chrono::Duration::days(30)));
+/// index_writer.add_document(doc!(title => "sea", published => now - chrono::Duration::days(1)));
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("sea")?;
+/// // Halves the score of the documents published a week ago.
+/// let recency_query = DecayQuery::recency(
+///     query,
+///     published,
+///     DecayFunction::Gaussian,
+///     chrono::Duration::weeks(1),
+/// );
+/// let top_docs = searcher.search(&recency_query, &TopDocs::with_limit(2))?;
+/// assert_eq!(top_docs[0].1, DocAddress(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct DecayQuery {
+    query: Box<dyn Query>,
+    field: Field,
+    decay: Decay,
+}
+
+impl DecayQuery {
+    /// Builds a decay query, with a factor of 0.5 at a distance of `scale`
+    /// from `origin`.
+    pub fn new(
+        query: Box<dyn Query>,
+        field: Field,
+        function: DecayFunction,
+        origin: f64,
+        scale: f64,
+    ) -> DecayQuery {
+        DecayQuery {
+            query,
+            field,
+            decay: Decay {
+                function,
+                origin,
+                scale,
+                offset: 0.0,
+                decay: 0.5,
+            },
+        }
+    }
+
+    /// Builds a decay query over a date field, favoring the most recent documents:
+    /// the origin is the current time, and the factor is 0.5 for the documents
+    /// dated `scale` ago.
+    pub fn recency(
+        query: Box<dyn Query>,
+        field: Field,
+        function: DecayFunction,
+        scale: chrono::Duration,
+    ) -> DecayQuery {
+        let now = chrono::Utc::now().timestamp() as f64;
+        DecayQuery::new(query, field, function, now, scale.num_seconds() as f64)
+    }
+
+    /// Sets the distance to the origin under which the score is left unchanged.
+    ///
+    /// The offset is 0 by default.
+    pub fn with_offset(mut self, offset: f64) -> DecayQuery {
+        self.decay.offset = offset;
+        self
+    }
+
+    /// Sets the factor applied at a distance of `offset + scale` from the origin.
+    ///
+    /// It has to be strictly between 0 and 1, and is 0.5 by default.
+    pub fn with_decay(mut self, decay: f64) -> DecayQuery {
+        self.decay.decay = decay;
+        self
+    }
+}
+
+impl Clone for DecayQuery {
+    fn clone(&self) -> Self {
+        DecayQuery {
+            query: self.query.box_clone(),
+            field: self.field,
+            decay: self.decay,
+        }
+    }
+}
+
+impl fmt::Debug for DecayQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Decay(query={:?}, field={:?}, function={:?}, origin={}, scale={}, offset={}, decay={})",
+            self.query,
+            self.field,
+            self.decay.function,
+            self.decay.origin,
+            self.decay.scale,
+            self.decay.offset,
+            self.decay.decay
+        )
+    }
+}
+
+/// Returns the function converting the values of the fast field, as returned
+/// by `FastFieldReaders::u64_lenient`, into `f64`.
+fn value_mapping(field_type: &FieldType) -> Option<fn(u64) -> f64> {
+    let (mapping, options): (fn(u64) -> f64, _) = match field_type {
+        FieldType::U64(options) => (|val| val as f64, options),
+        FieldType::I64(options) | FieldType::Date(options) => {
+            (|val| common::u64_to_i64(val) as f64, options)
+        }
+        FieldType::F64(options) => (common::u64_to_f64, options),
+        _ => return None,
+    };
+    if options.get_fastfield_cardinality() == Some(Cardinality::SingleValue) {
+        Some(mapping)
+    } else {
+        None
+    }
+}
+
+impl Query for DecayQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        if !scoring_enabled {
+            return Ok(weight);
+        }
+        self.decay.check()?;
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        let value_mapping = value_mapping(field_entry.field_type()).ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "The field {:?} is not a single-valued numeric or date fast field.",
+                field_entry.name()
+            ))
+        })?;
+        Ok(Box::new(DecayWeight {
+            weight,
+            field: self.field,
+            value_mapping,
+            decay: self.decay,
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set)
+    }
+}
+
+struct DecayWeight {
+    weight: Box<dyn Weight>,
+    field: Field,
+    value_mapping: fn(u64) -> f64,
+    decay: Decay,
+}
+
+impl DecayWeight {
+    fn fast_field_reader(&self, reader: &SegmentReader) -> crate::Result<FastFieldReader<u64>> {
+        reader.fast_fields().u64_lenient(self.field).ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "The field {:?} is not a fast field.",
+                reader.schema().get_field_name(self.field)
+            ))
+        })
+    }
+}
+
+impl Weight for DecayWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let underlying = self.weight.scorer(reader, boost)?;
+        let fast_field_reader = self.fast_field_reader(reader)?;
+        Ok(Box::new(DecayScorer {
+            underlying,
+            fast_field_reader,
+            value_mapping: self.value_mapping,
+            decay: self.decay,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: u32) -> crate::Result<Explanation> {
+        let mut scorer = self.weight.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let value = (self.value_mapping)(self.fast_field_reader(reader)?.get(doc));
+        let factor = self.decay.factor(value) as Score;
+        let mut factor_explanation = Explanation::new(
+            format!(
+                "{:?} decay of the value of {:?}",
+                self.decay.function,
+                reader.schema().get_field_name(self.field)
+            ),
+            factor,
+        );
+        factor_explanation.add_const("value", value as Score);
+        factor_explanation.add_const("origin", self.decay.origin as Score);
+        factor_explanation.add_const("scale", self.decay.scale as Score);
+        factor_explanation.add_const("offset", self.decay.offset as Score);
+        factor_explanation.add_const("decay", self.decay.decay as Score);
+        let mut explanation = Explanation::new(
+            "Decayed score, product of:",
+            underlying_explanation.value() * factor,
+        );
+        explanation.add_detail(underlying_explanation);
+        explanation.add_detail(factor_explanation);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+}
+
+struct DecayScorer {
+    underlying: Box<dyn Scorer>,
+    fast_field_reader: FastFieldReader<u64>,
+    value_mapping: fn(u64) -> f64,
+    decay: Decay,
+}
+
+impl DocSet for DecayScorer {
+    fn advance(&mut self) -> DocId {
+        self.underlying.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.underlying.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId]) -> usize {
+        self.underlying.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> u32 {
+        self.underlying.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.underlying.size_hint()
+    }
+
+    fn count(&mut self, delete_bitset: &DeleteBitSet) -> u32 {
+        self.underlying.count(delete_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.underlying.count_including_deleted()
+    }
+}
+
+impl Scorer for DecayScorer {
+    fn score(&mut self) -> Score {
+        let value = (self.value_mapping)(self.fast_field_reader.get(self.doc()));
+        self.underlying.score() * self.decay.factor(value) as Score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decay, DecayFunction, DecayQuery};
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, Query};
+    use crate::schema::{Schema, FAST, TEXT};
+    use crate::{DocAddress, Index, Score};
+
+    #[test]
+    fn test_decay_functions() {
+        let decay = |function: DecayFunction| Decay {
+            function,
+            origin: 100.0,
+            scale: 10.0,
+            offset: 5.0,
+            decay: 0.5,
+        };
+        for &function in &[
+            DecayFunction::Gaussian,
+            DecayFunction::Exponential,
+            DecayFunction::Linear,
+        ] {
+            let decay = decay(function);
+            assert!(decay.check().is_ok());
+            assert_eq!(decay.factor(100.0), 1.0);
+            assert_eq!(decay.factor(95.0), 1.0);
+            assert!((decay.factor(115.0) - 0.5).abs() < 1e-9);
+            assert!((decay.factor(85.0) - 0.5).abs() < 1e-9);
+            assert!(decay.factor(110.0) > decay.factor(120.0));
+        }
+        assert!((decay(DecayFunction::Gaussian).factor(125.0) - 0.0625).abs() < 1e-9);
+        assert!((decay(DecayFunction::Exponential).factor(125.0) - 0.25).abs() < 1e-9);
+        assert_eq!(decay(DecayFunction::Linear).factor(125.0), 0.0);
+        assert!(Decay {
+            decay: 1.0,
+            ..decay(DecayFunction::Linear)
+        }
+        .check()
+        .is_err());
+        assert!(Decay {
+            scale: 0.0,
+            ..decay(DecayFunction::Linear)
+        }
+        .check()
+        .is_err());
+    }
+
+    #[test]
+    fn test_decay_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "a", price => 10.0));
+        index_writer.add_document(doc!(title => "b", price => 30.0));
+        index_writer.add_document(doc!(title => "c", price => 20.0));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = DecayQuery::new(
+            Box::new(AllQuery),
+            price,
+            DecayFunction::Exponential,
+            25.0,
+            5.0,
+        );
+        let top_docs: Vec<(Score, DocAddress)> =
+            searcher.search(&query, &TopDocs::with_limit(3))?;
+        assert_eq!(
+            top_docs,
+            vec![
+                (0.5, DocAddress(0, 1)),
+                (0.5, DocAddress(0, 2)),
+                (0.125, DocAddress(0, 0)),
+            ]
+        );
+        let explanation = query.explain(&searcher, DocAddress(0, 0))?;
+        assert_eq!(explanation.value(), 0.125);
+        assert_eq!(
+            explanation.to_pretty_json(),
+            "{\n  \"value\": 0.125,\n  \"description\": \"Decayed score, product of:\",\n  \"details\": [\n    {\n      \"value\": 1.0,\n      \"description\": \"AllQuery\",\n      \"context\": []\n    },\n    {\n      \"value\": 0.125,\n      \"description\": \"Exponential decay of the value of \\\"price\\\"\",\n      \"details\": [\n        {\n          \"value\": 10.0,\n          \"description\": \"value\",\n          \"context\": []\n        },\n        {\n          \"value\": 25.0,\n          \"description\": \"origin\",\n          \"context\": []\n        },\n        {\n          \"value\": 5.0,\n          \"description\": \"scale\",\n          \"context\": []\n        },\n        {\n          \"value\": 0.0,\n          \"description\": \"offset\",\n          \"context\": []\n        },\n        {\n          \"value\": 0.5,\n          \"description\": \"decay\",\n          \"context\": []\n        }\n      ],\n      \"context\": []\n    }\n  ],\n  \"context\": []\n}"
+        );
+
+        let query = DecayQuery::new(Box::new(AllQuery), title, DecayFunction::Linear, 0.0, 1.0);
+        assert!(matches!(
+            searcher.search(&query, &TopDocs::with_limit(3)),
+            Err(crate::TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use crate::collector::{Collector, FacetCollector, FacetCounts};
+use crate::core::Searcher;
+use crate::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery, Weight};
+use crate::schema::{Facet, Field, IndexRecordOption};
+use crate::Term;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// `DrillDownQuery` restricts the documents matching a base query
+/// to some selected facet values.
+///
+/// Facets selected within the same facet field are alternatives: a document
+/// only needs to match one of them. Facets of different fields all have to match.
+/// Selecting a facet also matches its descendants: selecting `/category/books`
+/// matches the documents in `/category/books/scifi`.
+///
+/// The facet constraints do not impact the score: documents are scored by the base query.
+///
+/// # Drill sideways
+///
+/// Once a facet value is selected, counting the facets of the documents matching
+/// the `DrillDownQuery` only shows the selected value for this field. In order to
+/// still display the counts of its unselected siblings (e.g. the other colors, with
+/// the other filters applied), `DrillDownQuery::drill_sideways` counts the facets of
+/// each field while ignoring the selection made on this very field.
+///
+/// ```rust
+/// use tantivy::collector::{Count, FacetCollector};
+/// use tantivy::query::{AllQuery, DrillDownQuery};
+/// use tantivy::schema::{Facet, Schema};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let color = schema_builder.add_facet_field("color");
+/// let size = schema_builder.add_facet_field("size");
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(color => Facet::from("/red"), size => Facet::from("/s")));
+/// index_writer.add_document(doc!(color => Facet::from("/red"), size => Facet::from("/m")));
+/// index_writer.add_document(doc!(color => Facet::from("/blue"), size => Facet::from("/s")));
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let mut drill_down = DrillDownQuery::new(Box::new(AllQuery));
+/// drill_down.add(color, Facet::from("/red"));
+///
+/// let mut color_collector = FacetCollector::for_field(color);
+/// color_collector.add_facet("/");
+/// let (count, facet_counts) = drill_down.drill_sideways(&searcher, &Count, vec![color_collector])?;
+/// assert_eq!(count, 2);
+/// // The unselected colors are still counted.
+/// let color_counts: Vec<(String, u64)> = facet_counts[0]
+///     .get("/")
+///     .map(|(facet, count)| (facet.to_string(), count))
+///     .collect();
+/// assert_eq!(color_counts, vec![("/blue".to_string(), 1), ("/red".to_string(), 2)]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct DrillDownQuery {
+    base_query: Box<dyn Query>,
+    dims: BTreeMap<Field, BTreeSet<Facet>>,
+}
+
+impl Clone for DrillDownQuery {
+    fn clone(&self) -> Self {
+        DrillDownQuery {
+            base_query: self.base_query.box_clone(),
+            dims: self.dims.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for DrillDownQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DrillDown(base_query={:?}, dims={:?})",
+            self.base_query, self.dims
+        )
+    }
+}
+
+impl DrillDownQuery {
+    /// Creates a `DrillDownQuery` matching the same documents as `base_query`,
+    /// until facets are selected.
+    pub fn new(base_query: Box<dyn Query>) -> DrillDownQuery {
+        DrillDownQuery {
+            base_query,
+            dims: BTreeMap::new(),
+        }
+    }
+
+    /// Restricts the results to the documents having the facet `facet`
+    /// (or one of its descendants) in the facet field `field`.
+    ///
+    /// If other facets were already selected for `field`, a document
+    /// only needs to match one of them.
+    pub fn add(&mut self, field: Field, facet: Facet) {
+        self.dims.entry(field).or_default().insert(facet);
+    }
+
+    /// Returns the facet fields on which facets have been selected.
+    pub fn fields(&self) -> impl Iterator<Item = Field> + '_ {
+        self.dims.keys().cloned()
+    }
+
+    /// Returns the `DrillDownQuery` obtained by removing the facets
+    /// selected for `field`.
+    pub fn sideways(&self, field: Field) -> DrillDownQuery {
+        let mut sideways_query = self.clone();
+        sideways_query.dims.remove(&field);
+        sideways_query
+    }
+
+    /// Runs the `DrillDownQuery` with `collector`, and counts the facets of the
+    /// facet collectors with drill sideways.
+    ///
+    /// The facet counts are returned in the same order as `facet_collectors`.
+    /// The counts of a facet collector on a field with selected facets
+    /// are computed over the documents matching all of the other constraints.
+    /// Those of the other facet collectors are computed over the documents
+    /// matching the `DrillDownQuery`.
+    pub fn drill_sideways<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        collector: &C,
+        facet_collectors: Vec<FacetCollector>,
+    ) -> crate::Result<(C::Fruit, Vec<FacetCounts>)> {
+        let fruit = searcher.search(self, collector)?;
+        let facet_counts = facet_collectors
+            .iter()
+            .map(|facet_collector| {
+                let field = facet_collector.field();
+                if self.dims.contains_key(&field) {
+                    searcher.search(&self.sideways(field), facet_collector)
+                } else {
+                    searcher.search(self, facet_collector)
+                }
+            })
+            .collect::<crate::Result<Vec<FacetCounts>>>()?;
+        Ok((fruit, facet_counts))
+    }
+
+    fn boolean_query(&self) -> BooleanQuery {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> =
+            vec![(Occur::Must, self.base_query.box_clone())];
+        for (&field, facets) in &self.dims {
+            let facet_queries: Vec<Box<dyn Query>> = facets
+                .iter()
+                .map(|facet| {
+                    let term = Term::from_facet(field, facet);
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>
+                })
+                .collect();
+            // The facet filters are given a boost of zero,
+            // so that they do not alter the score of the base query.
+            let facet_filter = BoostQuery::new(Box::new(BooleanQuery::union(facet_queries)), 0.0);
+            clauses.push((Occur::Must, Box::new(facet_filter)));
+        }
+        BooleanQuery::new(clauses)
+    }
+}
+
+impl Query for DrillDownQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        self.boolean_query().weight(searcher, scoring_enabled)
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.base_query.query_terms(term_set);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrillDownQuery;
+    use crate::collector::{Count, FacetCollector, FacetCounts, TopDocs};
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{Facet, IndexRecordOption, Schema, TEXT};
+    use crate::{Index, Term};
+
+    fn counts(facet_counts: &FacetCounts, facet: &str) -> Vec<(String, u64)> {
+        facet_counts
+            .get(facet)
+            .map(|(facet, count)| (facet.to_string(), count))
+            .collect()
+    }
+
+    #[test]
+    fn test_drill_down_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let category = schema_builder.add_facet_field("category");
+        let color = schema_builder.add_facet_field("color");
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for &(title_val, category_val, color_val) in &[
+            ("shirt", "/clothes/shirts", "/red"),
+            ("shirt shirt", "/clothes/shirts", "/blue"),
+            ("shirt", "/clothes/shirts", "/green"),
+            ("shirt", "/toys", "/red"),
+            ("trousers", "/clothes/trousers", "/red"),
+        ] {
+            index_writer.add_document(doc!(
+                title => title_val,
+                category => Facet::from(category_val),
+                color => Facet::from(color_val),
+            ));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let base_query = TermQuery::new(
+            Term::from_field_text(title, "shirt"),
+            IndexRecordOption::WithFreqs,
+        );
+        let mut drill_down = DrillDownQuery::new(Box::new(base_query.clone()));
+        assert_eq!(drill_down.count(&searcher)?, 4);
+        drill_down.add(category, Facet::from("/clothes"));
+        assert_eq!(drill_down.count(&searcher)?, 3);
+        drill_down.add(color, Facet::from("/red"));
+        drill_down.add(color, Facet::from("/blue"));
+        assert_eq!(
+            drill_down.fields().collect::<Vec<_>>(),
+            vec![category, color]
+        );
+        assert_eq!(drill_down.count(&searcher)?, 2);
+
+        // The facet filters do not alter the scores.
+        let top_docs = searcher.search(&drill_down, &TopDocs::with_limit(2))?;
+        let base_top_docs = searcher.search(&base_query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs, base_top_docs);
+
+        let mut category_collector = FacetCollector::for_field(category);
+        category_collector.add_facet("/");
+        let mut color_collector = FacetCollector::for_field(color);
+        color_collector.add_facet("/");
+        let (count, facet_counts) = drill_down.drill_sideways(
+            &searcher,
+            &Count,
+            vec![category_collector, color_collector],
+        )?;
+        assert_eq!(count, 2);
+        assert_eq!(
+            counts(&facet_counts[0], "/"),
+            vec![("/clothes".to_string(), 2), ("/toys".to_string(), 1)]
+        );
+        assert_eq!(
+            counts(&facet_counts[1], "/"),
+            vec![
+                ("/blue".to_string(), 1),
+                ("/green".to_string(), 1),
+                ("/red".to_string(), 1)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_drill_sideways_unselected_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_facet_field("category");
+        let color = schema_builder.add_facet_field("color");
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer
+            .add_document(doc!(category => Facet::from("/a"), color => Facet::from("/red")));
+        index_writer
+            .add_document(doc!(category => Facet::from("/b"), color => Facet::from("/red")));
+        index_writer
+            .add_document(doc!(category => Facet::from("/a"), color => Facet::from("/blue")));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let mut drill_down = DrillDownQuery::new(Box::new(AllQuery));
+        drill_down.add(color, Facet::from("/red"));
+        let mut category_collector = FacetCollector::for_field(category);
+        category_collector.add_facet("/");
+        let (_, facet_counts) =
+            drill_down.drill_sideways(&searcher, &Count, vec![category_collector])?;
+        assert_eq!(
+            counts(&facet_counts[0], "/"),
+            vec![("/a".to_string(), 1), ("/b".to_string(), 1)]
+        );
+        Ok(())
+    }
+}
@@ -49,6 +49,15 @@ static LEV_BUILDER: Lazy<HashMap<(u8, bool), LevenshteinAutomatonBuilder>> = Laz
     lev_builder_cache
 });
 
+/// Returns the Levenshtein automaton builder for the given distance, or `None`
+/// if the distance is not in `VALID_LEVENSHTEIN_DISTANCE_RANGE`.
+pub(crate) fn lev_automaton_builder(
+    distance: u8,
+    transposition_cost_one: bool,
+) -> Option<&'static LevenshteinAutomatonBuilder> {
+    LEV_BUILDER.get(&(distance, transposition_cost_one))
+}
+
 /// A Fuzzy Query matches all of the documents
 /// containing a specific term that is within
 /// Levenshtein distance
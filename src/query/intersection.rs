@@ -68,7 +68,15 @@ fn go_to_first_doc<TDocSet: DocSet>(docsets: &mut [TDocSet]) -> DocId {
 }
 
 impl<TDocSet: DocSet> Intersection<TDocSet, TDocSet> {
-    pub(crate) fn new(mut docsets: Vec<TDocSet>) -> Intersection<TDocSet, TDocSet> {
+    /// Creates the intersection of the given `DocSet`s.
+    ///
+    /// The resulting `DocSet` is positioned on the first document
+    /// common to all of the `DocSet`s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if less than two `DocSet`s are given.
+    pub fn new(mut docsets: Vec<TDocSet>) -> Intersection<TDocSet, TDocSet> {
         let num_docsets = docsets.len();
         assert!(num_docsets >= 2);
         docsets.sort_by_key(|docset| docset.size_hint());
@@ -164,7 +172,7 @@ mod tests {
     use super::Intersection;
     use crate::docset::{DocSet, TERMINATED};
     use crate::postings::tests::test_skip_against_unoptimized;
-    use crate::query::VecDocSet;
+    use crate::query::{Exclude, VecDocSet};
 
     #[test]
     fn test_intersection() {
@@ -239,6 +247,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_intersection_exclude() {
+        let a = VecDocSet::from(vec![1, 3, 5, 9, 12]);
+        let b = VecDocSet::from(vec![1, 2, 5, 9, 12, 15]);
+        let excluded = VecDocSet::from(vec![5, 12]);
+        let mut docset = Exclude::new(Intersection::new(vec![a, b]), excluded);
+        assert_eq!(docset.doc(), 1);
+        assert_eq!(docset.advance(), 9);
+        assert_eq!(docset.advance(), TERMINATED);
+    }
+
     #[test]
     fn test_intersection_empty() {
         let a = VecDocSet::from(vec![1, 3]);
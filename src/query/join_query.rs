@@ -0,0 +1,330 @@
+use crate::common::BitSet;
+use crate::core::{Searcher, SegmentReader};
+use crate::error::TantivyError;
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, Explanation, Query, Scorer, Weight};
+use crate::schema::{Cardinality, Field, FieldType, IndexRecordOption, Schema, Term};
+use crate::{DocId, Score};
+use std::collections::BTreeSet;
+
+/// Returns the cardinality of a numerical fast field.
+fn fast_field_cardinality(schema: &Schema, field: Field) -> Option<Cardinality> {
+    match schema.get_field_entry(field).field_type() {
+        FieldType::U64(options)
+        | FieldType::I64(options)
+        | FieldType::F64(options)
+        | FieldType::Date(options) => options.get_fastfield_cardinality(),
+        _ => None,
+    }
+}
+
+/// `JoinQuery` matches the documents whose `to_field` contains one of the
+/// values of the `from_field` of the documents matching an inner query.
+///
+/// This makes it possible to run simple relational lookups within an index,
+/// without denormalizing the documents.
+/// For instance, with documents describing both books and their authors, a join from the
+/// `author_id` of the books matching "title:sea" to the `id` field returns the authors
+/// who wrote a book about the sea.
+///
+/// `from_field` has to be a numerical fast field, of any cardinality, and `to_field`
+/// an indexed field of the same type.
+/// The documents without any value in an optional or multivalued `from_field` do
+/// not contribute any value. A single-valued fast field cannot tell a missing value
+/// from `0`, so its documents without a value join to the documents having `0` as
+/// `to_field`: fields that may be missing should rather be declared with
+/// `Cardinality::Optional`.
+///
+/// Matched documents all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// The inner query is run when the `Weight` is created, and the values of the
+/// `from_field` of its documents are read from the fast field.
+/// The documents of each segment having one of these values as a term
+/// of the `to_field` are then collected in a `BitSet`.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{JoinQuery, QueryParser};
+/// use tantivy::schema::{Schema, FAST, INDEXED, STORED, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let id = schema_builder.add_u64_field("id", INDEXED);
+/// let name = schema_builder.add_text_field("name", TEXT | STORED);
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let author_id = schema_builder.add_u64_field("author_id", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(id => 1u64, name => "Ernest Hemingway"));
+/// index_writer.add_document(doc!(id => 2u64, name => "John Steinbeck"));
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea", author_id => 1u64));
+/// index_writer.add_document(doc!(title => "Of Mice and Men", author_id => 2u64));
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let books_about_the_sea = QueryParser::for_index(&index, vec![title]).parse_query("sea")?;
+/// let authors = JoinQuery::new(books_about_the_sea, author_id, id);
+/// let top_docs = searcher.search(&authors, &TopDocs::with_limit(10))?;
+/// assert_eq!(top_docs.len(), 1);
+/// let author = searcher.doc(top_docs[0].1)?;
+/// assert_eq!(author.get_first(name).unwrap().text(), Some("Ernest Hemingway"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct JoinQuery {
+    inner: Box<dyn Query>,
+    from_field: Field,
+    to_field: Field,
+}
+
+impl Clone for JoinQuery {
+    fn clone(&self) -> Self {
+        JoinQuery {
+            inner: self.inner.box_clone(),
+            from_field: self.from_field,
+            to_field: self.to_field,
+        }
+    }
+}
+
+impl JoinQuery {
+    /// Creates a new `JoinQuery`, matching the documents whose `to_field` contains
+    /// one of the values of the `from_field` of the documents matching `inner`.
+    pub fn new(inner: Box<dyn Query>, from_field: Field, to_field: Field) -> JoinQuery {
+        JoinQuery {
+            inner,
+            from_field,
+            to_field,
+        }
+    }
+
+    /// Collects the values of the `from_field` of the documents matching the inner query.
+    fn collect_values(
+        &self,
+        searcher: &Searcher,
+        cardinality: Cardinality,
+    ) -> crate::Result<BTreeSet<u64>> {
+        let missing_fast_field = || {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field.",
+                searcher.schema().get_field_name(self.from_field)
+            ))
+        };
+        let inner_weight = self.inner.weight(searcher, false)?;
+        let mut values = BTreeSet::new();
+        let mut doc_values = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let fast_fields = segment_reader.fast_fields();
+            match cardinality {
+                // Missing values cannot be told apart from 0, and are read as such.
+                Cardinality::SingleValue => {
+                    let fast_field_reader = fast_fields
+                        .u64_lenient(self.from_field)
+                        .ok_or_else(missing_fast_field)?;
+                    inner_weight.for_each(segment_reader, &mut |doc, _| {
+                        if !segment_reader.is_deleted(doc) {
+                            values.insert(fast_field_reader.get(doc));
+                        }
+                    })?;
+                }
+                // Optional fast fields are stored as multivalued fast fields.
+                Cardinality::MultiValues | Cardinality::Optional => {
+                    let fast_field_reader = fast_fields
+                        .u64s_lenient(self.from_field)
+                        .ok_or_else(missing_fast_field)?;
+                    inner_weight.for_each(segment_reader, &mut |doc, _| {
+                        if !segment_reader.is_deleted(doc) {
+                            fast_field_reader.get_vals(doc, &mut doc_values);
+                            values.extend(doc_values.iter().cloned());
+                        }
+                    })?;
+                }
+            }
+        }
+        Ok(values)
+    }
+}
+
+impl Query for JoinQuery {
+    fn weight(
+        &self,
+        searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let schema = searcher.schema();
+        let cardinality = fast_field_cardinality(schema, self.from_field).ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field {:?} is not a numerical fast field.",
+                schema.get_field_name(self.from_field)
+            ))
+        })?;
+        let from_type = schema.get_field_entry(self.from_field).field_type();
+        let to_field_entry = schema.get_field_entry(self.to_field);
+        if !to_field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not indexed.",
+                to_field_entry.name()
+            )));
+        }
+        if to_field_entry.field_type().value_type() != from_type.value_type() {
+            return Err(TantivyError::SchemaError(format!(
+                "Cannot join field {:?} of type {:?} with field {:?} of type {:?}.",
+                schema.get_field_name(self.from_field),
+                from_type.value_type(),
+                to_field_entry.name(),
+                to_field_entry.field_type().value_type()
+            )));
+        }
+        let values = self.collect_values(searcher, cardinality)?;
+        Ok(Box::new(JoinWeight {
+            field: self.to_field,
+            values,
+        }))
+    }
+}
+
+struct JoinWeight {
+    field: Field,
+    values: BTreeSet<u64>,
+}
+
+impl Weight for JoinWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        let inverted_index = reader.inverted_index(self.field)?;
+        for &value in &self.values {
+            // Numerical terms are the big endian encoding of the `u64` stored in fast fields.
+            let term = Term::from_field_u64(self.field, value);
+            if let Some(term_info) = inverted_index.get_term_info(&term)? {
+                let mut block_segment_postings = inverted_index
+                    .read_block_postings_from_terminfo(&term_info, IndexRecordOption::Basic)?;
+                loop {
+                    let docs = block_segment_postings.docs();
+                    if docs.is_empty() {
+                        break;
+                    }
+                    for &doc in docs {
+                        doc_bitset.insert(doc);
+                    }
+                    block_segment_postings.advance();
+                }
+            }
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("JoinQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JoinQuery;
+    use crate::collector::Count;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{
+        Cardinality, IndexRecordOption, IntOptions, Schema, FAST, INDEXED, STRING,
+    };
+    use crate::{Index, Term};
+
+    #[test]
+    fn test_join_query_multivalued() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_i64_field("id", INDEXED);
+        let kind = schema_builder.add_text_field("kind", STRING);
+        let tag_ids = schema_builder.add_i64_field(
+            "tag_ids",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for tag_id in -2i64..3i64 {
+            index_writer.add_document(doc!(id => tag_id, kind => "tag"));
+        }
+        index_writer.add_document(doc!(kind => "post", tag_ids => -2i64, tag_ids => 1i64));
+        index_writer.add_document(doc!(kind => "post", tag_ids => 1i64));
+        index_writer.add_document(doc!(kind => "deleted_post", tag_ids => 2i64));
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(kind, "deleted_post"));
+        index_writer.add_document(doc!(kind => "post"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let posts = TermQuery::new(
+            Term::from_field_text(kind, "post"),
+            IndexRecordOption::Basic,
+        );
+        let tags_of_posts = JoinQuery::new(Box::new(posts), tag_ids, id);
+        assert_eq!(searcher.search(&tags_of_posts, &Count)?, 2);
+        let deleted_posts = TermQuery::new(
+            Term::from_field_text(kind, "deleted_post"),
+            IndexRecordOption::Basic,
+        );
+        let tags_of_deleted_posts = JoinQuery::new(Box::new(deleted_posts), tag_ids, id);
+        assert_eq!(searcher.search(&tags_of_deleted_posts, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_query_skips_missing_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let kind = schema_builder.add_text_field("kind", STRING);
+        let author_id = schema_builder.add_u64_field(
+            "author_id",
+            IntOptions::default().set_fast(Cardinality::Optional),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => 0u64, kind => "author"));
+        index_writer.add_document(doc!(id => 1u64, kind => "author"));
+        index_writer.add_document(doc!(kind => "book", author_id => 1u64));
+        index_writer.add_document(doc!(kind => "book"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let books = TermQuery::new(
+            Term::from_field_text(kind, "book"),
+            IndexRecordOption::Basic,
+        );
+        let authors_of_books = JoinQuery::new(Box::new(books), author_id, id);
+        assert_eq!(searcher.search(&authors_of_books, &Count)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_query_schema_errors() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let u64_id = schema_builder.add_u64_field("u64_id", INDEXED | FAST);
+        let i64_id = schema_builder.add_i64_field("i64_id", INDEXED | FAST);
+        let not_fast = schema_builder.add_u64_field("not_fast", INDEXED);
+        let not_indexed = schema_builder.add_u64_field("not_indexed", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        for &(from_field, to_field) in
+            &[(not_fast, u64_id), (u64_id, not_indexed), (u64_id, i64_id)]
+        {
+            let join_query = JoinQuery::new(Box::new(AllQuery), from_field, to_field);
+            assert!(matches!(
+                searcher.search(&join_query, &Count),
+                Err(crate::TantivyError::SchemaError(_))
+            ));
+        }
+        let join_query = JoinQuery::new(Box::new(AllQuery), i64_id, i64_id);
+        assert_eq!(searcher.search(&join_query, &Count)?, 0);
+        Ok(())
+    }
+}
@@ -6,13 +6,22 @@ mod bitset;
 mod bm25;
 mod boolean_query;
 mod boost_query;
+mod bounding_box_query;
+mod cached_filter;
+mod common_terms_query;
+mod decay_query;
+mod drill_down_query;
 mod empty_query;
 mod exclude;
 mod explanation;
 mod fuzzy_query;
 mod intersection;
+mod join_query;
+mod more_like_this_query;
 mod phrase_query;
+mod prefix_query;
 mod query;
+mod query_ast;
 mod query_parser;
 mod range_query;
 mod regex_query;
@@ -38,15 +47,23 @@ pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
 pub use self::boolean_query::BooleanQuery;
 pub use self::boost_query::BoostQuery;
+pub use self::bounding_box_query::BoundingBoxQuery;
+pub use self::cached_filter::{CachedFilterQuery, FilterCache};
+pub use self::common_terms_query::CommonTermsQuery;
+pub use self::decay_query::{DecayFunction, DecayQuery};
+pub use self::drill_down_query::DrillDownQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
 pub use self::explanation::Explanation;
-#[cfg(test)]
-pub(crate) use self::fuzzy_query::DFAWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
+pub(crate) use self::fuzzy_query::{lev_automaton_builder, DFAWrapper};
 pub use self::intersection::intersect_scorers;
+pub use self::join_query::JoinQuery;
+pub use self::more_like_this_query::MoreLikeThisQuery;
 pub use self::phrase_query::PhraseQuery;
+pub use self::prefix_query::PrefixQuery;
 pub use self::query::{Query, QueryClone};
+pub use self::query_ast::{QueryAST, RangeBound};
 pub use self::query_parser::QueryParser;
 pub use self::query_parser::QueryParserError;
 pub use self::range_query::RangeQuery;
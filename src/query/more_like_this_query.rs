@@ -0,0 +1,317 @@
+use crate::query::bm25::idf;
+use crate::query::{BooleanQuery, BoostQuery, EmptyQuery, Occur, Query, TermQuery, Weight};
+use crate::schema::{Field, FieldType, IndexRecordOption, Term, Value};
+use crate::{DocAddress, Score, Searcher};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// The document the similar documents are searched for.
+#[derive(Clone, Debug)]
+enum TargetDocument {
+    DocAddress(DocAddress),
+    FieldValues(Vec<(Field, Vec<Value>)>),
+}
+
+/// `MoreLikeThisQuery` matches the documents similar to a given document.
+///
+/// The text of the document is tokenized with the tokenizer of each of its fields,
+/// and its most representative terms, according to their TF-IDF score, are searched
+/// in a disjunctive query. Only indexed text fields are taken into account.
+///
+/// The document can either be a stored document of the index, or a list of
+/// field values, typically a text that was not indexed.
+/// When searching for the documents similar to a stored document, this
+/// document itself is part of the results.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::MoreLikeThisQuery;
+/// use tantivy::schema::{Schema, STORED, TEXT};
+/// use tantivy::{doc, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT | STORED);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(body => "the quick brown fox jumps over the lazy dog"));
+/// index_writer.add_document(doc!(body => "a quick brown fox is a happy fox"));
+/// index_writer.add_document(doc!(body => "the stock market crashed"));
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = MoreLikeThisQuery::for_document(DocAddress(0, 1)).with_min_doc_frequency(1);
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0].1, DocAddress(0, 1));
+/// assert_eq!(top_docs[1].1, DocAddress(0, 0));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MoreLikeThisQuery {
+    target: TargetDocument,
+    min_doc_frequency: u64,
+    max_doc_frequency: Option<u64>,
+    min_term_frequency: usize,
+    max_query_terms: usize,
+    min_word_length: usize,
+    max_word_length: Option<usize>,
+    boost_factor: Option<Score>,
+    stop_words: HashSet<String>,
+}
+
+impl MoreLikeThisQuery {
+    /// Creates a `MoreLikeThisQuery` matching the documents similar to
+    /// the stored document at `doc_address`.
+    pub fn for_document(doc_address: DocAddress) -> MoreLikeThisQuery {
+        MoreLikeThisQuery::new(TargetDocument::DocAddress(doc_address))
+    }
+
+    /// Creates a `MoreLikeThisQuery` matching the documents similar to
+    /// a document made of the given field values.
+    pub fn for_field_values(field_values: Vec<(Field, Vec<Value>)>) -> MoreLikeThisQuery {
+        MoreLikeThisQuery::new(TargetDocument::FieldValues(field_values))
+    }
+
+    fn new(target: TargetDocument) -> MoreLikeThisQuery {
+        MoreLikeThisQuery {
+            target,
+            min_doc_frequency: 5,
+            max_doc_frequency: None,
+            min_term_frequency: 2,
+            max_query_terms: 25,
+            min_word_length: 0,
+            max_word_length: None,
+            boost_factor: None,
+            stop_words: HashSet::new(),
+        }
+    }
+
+    /// Ignores the terms appearing in less than `min_doc_frequency` documents.
+    ///
+    /// Defaults to 5.
+    pub fn with_min_doc_frequency(mut self, min_doc_frequency: u64) -> MoreLikeThisQuery {
+        self.min_doc_frequency = min_doc_frequency;
+        self
+    }
+
+    /// Ignores the terms appearing in more than `max_doc_frequency` documents.
+    pub fn with_max_doc_frequency(mut self, max_doc_frequency: u64) -> MoreLikeThisQuery {
+        self.max_doc_frequency = Some(max_doc_frequency);
+        self
+    }
+
+    /// Ignores the terms appearing less than `min_term_frequency` times
+    /// in the document.
+    ///
+    /// Defaults to 2.
+    pub fn with_min_term_frequency(mut self, min_term_frequency: usize) -> MoreLikeThisQuery {
+        self.min_term_frequency = min_term_frequency;
+        self
+    }
+
+    /// Sets the maximum number of terms searched for.
+    ///
+    /// Defaults to 25.
+    pub fn with_max_query_terms(mut self, max_query_terms: usize) -> MoreLikeThisQuery {
+        self.max_query_terms = max_query_terms;
+        self
+    }
+
+    /// Ignores the words shorter than `min_word_length` characters.
+    pub fn with_min_word_length(mut self, min_word_length: usize) -> MoreLikeThisQuery {
+        self.min_word_length = min_word_length;
+        self
+    }
+
+    /// Ignores the words longer than `max_word_length` characters.
+    pub fn with_max_word_length(mut self, max_word_length: usize) -> MoreLikeThisQuery {
+        self.max_word_length = Some(max_word_length);
+        self
+    }
+
+    /// Boosts each term by its TF-IDF score, relatively to the best term,
+    /// multiplied by `boost_factor`.
+    ///
+    /// By default, the terms are not boosted.
+    pub fn with_boost_factor(mut self, boost_factor: Score) -> MoreLikeThisQuery {
+        self.boost_factor = Some(boost_factor);
+        self
+    }
+
+    /// Ignores the given words.
+    pub fn with_stop_words(mut self, stop_words: Vec<String>) -> MoreLikeThisQuery {
+        self.stop_words = stop_words.into_iter().collect();
+        self
+    }
+
+    fn is_noise_word(&self, word: &str) -> bool {
+        let num_chars = word.chars().count();
+        if num_chars < self.min_word_length {
+            return true;
+        }
+        if let Some(max_word_length) = self.max_word_length {
+            if num_chars > max_word_length {
+                return true;
+            }
+        }
+        self.stop_words.contains(word)
+    }
+
+    fn field_values(&self, searcher: &Searcher) -> crate::Result<Vec<(Field, Vec<Value>)>> {
+        match &self.target {
+            TargetDocument::DocAddress(doc_address) => {
+                let doc = searcher.doc(*doc_address)?;
+                Ok(doc
+                    .get_sorted_field_values()
+                    .into_iter()
+                    .map(|(field, field_values)| {
+                        let values = field_values
+                            .into_iter()
+                            .map(|field_value| field_value.value().clone())
+                            .collect();
+                        (field, values)
+                    })
+                    .collect())
+            }
+            TargetDocument::FieldValues(field_values) => Ok(field_values.clone()),
+        }
+    }
+
+    /// Returns the representative terms of the document, along with their score,
+    /// best term first.
+    fn scored_terms(&self, searcher: &Searcher) -> crate::Result<Vec<(Term, Score)>> {
+        let schema = searcher.schema();
+        let mut term_frequencies: HashMap<Term, usize> = HashMap::new();
+        for (field, values) in self.field_values(searcher)? {
+            let is_indexed_text = match schema.get_field_entry(field).field_type() {
+                FieldType::Str(text_options) => text_options.get_indexing_options().is_some(),
+                _ => false,
+            };
+            if !is_indexed_text {
+                continue;
+            }
+            let tokenizer = searcher.index().tokenizer_for_field(field)?;
+            for text in values.iter().flat_map(Value::text) {
+                tokenizer.token_stream(text).process(&mut |token| {
+                    if !self.is_noise_word(&token.text) {
+                        let term = Term::from_field_text(field, &token.text);
+                        *term_frequencies.entry(term).or_insert(0) += 1;
+                    }
+                });
+            }
+        }
+        let num_docs = searcher.num_docs();
+        let mut scored_terms = Vec::new();
+        for (term, term_frequency) in term_frequencies {
+            if term_frequency < self.min_term_frequency {
+                continue;
+            }
+            // The document frequency includes the deleted documents.
+            let doc_freq = searcher.doc_freq(&term)?.min(num_docs);
+            if doc_freq == 0 || doc_freq < self.min_doc_frequency {
+                continue;
+            }
+            if let Some(max_doc_frequency) = self.max_doc_frequency {
+                if doc_freq > max_doc_frequency {
+                    continue;
+                }
+            }
+            let score = term_frequency as Score * idf(doc_freq, num_docs);
+            scored_terms.push((term, score));
+        }
+        // Ties are broken by term, for the query to be deterministic.
+        scored_terms.sort_by(|(left_term, left_score), (right_term, right_score)| {
+            right_score
+                .partial_cmp(left_score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left_term.cmp(right_term))
+        });
+        scored_terms.truncate(self.max_query_terms);
+        Ok(scored_terms)
+    }
+}
+
+impl Query for MoreLikeThisQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> crate::Result<Box<dyn Weight>> {
+        let scored_terms = self.scored_terms(searcher)?;
+        let best_score = match scored_terms.first() {
+            Some(&(_, best_score)) => best_score,
+            None => return EmptyQuery.weight(searcher, scoring_enabled),
+        };
+        let subqueries: Vec<(Occur, Box<dyn Query>)> = scored_terms
+            .into_iter()
+            .map(|(term, score)| {
+                let term_query: Box<dyn Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+                let query = match self.boost_factor {
+                    Some(boost_factor) => {
+                        let boost = score / best_score * boost_factor;
+                        Box::new(BoostQuery::new(term_query, boost))
+                    }
+                    None => term_query,
+                };
+                (Occur::Should, query)
+            })
+            .collect();
+        BooleanQuery::new(subqueries).weight(searcher, scoring_enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MoreLikeThisQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::schema::{Schema, Value, STORED, STRING, TEXT};
+    use crate::{DocAddress, Index};
+
+    #[test]
+    fn test_more_like_this_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let tag = schema_builder.add_text_field("tag", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "rust rust compiler", tag => "lang"));
+        index_writer.add_document(doc!(title => "the rust compiler is fast", tag => "lang"));
+        index_writer.add_document(doc!(title => "rust on old cars", tag => "cars"));
+        index_writer.add_document(doc!(title => "the fast cars", tag => "cars"));
+        index_writer.add_document(doc!(title => "gardening", tag => "garden"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = MoreLikeThisQuery::for_document(DocAddress(0, 0))
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        let doc_addresses: Vec<DocAddress> = top_docs.iter().map(|(_, addr)| *addr).collect();
+        assert_eq!(
+            doc_addresses,
+            vec![DocAddress(0, 0), DocAddress(0, 1), DocAddress(0, 2)]
+        );
+
+        // "rust" is the only term appearing twice in the document.
+        let query = MoreLikeThisQuery::for_document(DocAddress(0, 0)).with_min_doc_frequency(1);
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+
+        let text = vec![Value::from("fast cars, fast cars, and more cars")];
+        let query = MoreLikeThisQuery::for_field_values(vec![(title, text.clone())])
+            .with_min_doc_frequency(1)
+            .with_max_doc_frequency(1);
+        assert_eq!(searcher.search(&query, &Count)?, 0);
+        let query = MoreLikeThisQuery::for_field_values(vec![(title, text.clone())])
+            .with_min_doc_frequency(1)
+            .with_boost_factor(2.0);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        assert_eq!(top_docs.len(), 3);
+        assert_eq!(top_docs[0].1, DocAddress(0, 3));
+        let query = MoreLikeThisQuery::for_field_values(vec![(title, text)])
+            .with_min_doc_frequency(1)
+            .with_stop_words(vec!["cars".to_string()]);
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        let query = MoreLikeThisQuery::for_field_values(vec![(title, vec![Value::from("x")])]);
+        assert_eq!(searcher.search(&query, &Count)?, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use crate::query::{AutomatonWeight, Query, Weight};
+use crate::schema::Term;
+use crate::Searcher;
+use tantivy_fst::Automaton;
+
+/// Automaton matching the terms starting with a given prefix.
+///
+/// The state is the number of bytes of the prefix matched so far,
+/// or `None` once the input diverged from the prefix.
+pub(crate) struct PrefixAutomaton {
+    prefix: Vec<u8>,
+}
+
+impl Automaton for PrefixAutomaton {
+    type State = Option<usize>;
+
+    fn start(&self) -> Self::State {
+        Some(0)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        *state == Some(self.prefix.len())
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        self.is_match(state)
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let matched = (*state)?;
+        if matched == self.prefix.len() {
+            Some(matched)
+        } else if self.prefix[matched] == byte {
+            Some(matched + 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// A Prefix Query matches all of the documents
+/// containing a term starting with a given prefix.
+///
+/// As for the `RegexQuery` and the `FuzzyTermQuery`, all of the matching
+/// documents get a constant score.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::PrefixQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, Term};
+///
+/// # fn test() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(title => "Programming Rust"));
+/// index_writer.add_document(doc!(title => "Rusty Nails"));
+/// index_writer.add_document(doc!(title => "Trusting Strangers"));
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = PrefixQuery::new(Term::from_field_text(title, "rust"));
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// Ok(())
+/// # }
+/// # assert!(test().is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixQuery {
+    prefix: Term,
+}
+
+impl PrefixQuery {
+    /// Creates a new PrefixQuery matching the terms of the field of `prefix`
+    /// starting with the text of `prefix`.
+    pub fn new(prefix: Term) -> PrefixQuery {
+        PrefixQuery { prefix }
+    }
+
+    fn specialized_weight(&self) -> AutomatonWeight<PrefixAutomaton> {
+        let automaton = PrefixAutomaton {
+            prefix: self.prefix.value_bytes().to_vec(),
+        };
+        AutomatonWeight::new(self.prefix.field(), automaton)
+    }
+}
+
+impl Query for PrefixQuery {
+    fn weight(
+        &self,
+        _searcher: &Searcher,
+        _scoring_enabled: bool,
+    ) -> crate::Result<Box<dyn Weight>> {
+        Ok(Box::new(self.specialized_weight()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PrefixQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::schema::{Schema, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, Term};
+
+    #[test]
+    fn test_prefix_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let country = schema_builder.add_text_field("country", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(country => "japan"));
+        index_writer.add_document(doc!(country => "jamaica"));
+        index_writer.add_document(doc!(country => "ja"));
+        index_writer.add_document(doc!(country => "korea"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |prefix: &str| {
+            let query = PrefixQuery::new(Term::from_field_text(country, prefix));
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("ja"), 3);
+        assert_eq!(count("jap"), 1);
+        assert_eq!(count("japanese"), 0);
+        assert_eq!(count("apan"), 0);
+        assert_eq!(count(""), 4);
+
+        let query = PrefixQuery::new(Term::from_field_text(country, "ko"));
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs.len(), 1);
+        assert_nearly_equals!(top_docs[0].0, 1.0);
+        assert_eq!(top_docs[0].1, DocAddress(0, 3));
+        Ok(())
+    }
+}
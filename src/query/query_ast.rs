@@ -0,0 +1,408 @@
+use crate::query::{AllQuery, BooleanQuery, BoostQuery, Occur, PhraseQuery, Query};
+use crate::query::{RangeQuery, TermQuery};
+use crate::schema::{Field, FieldType, IndexRecordOption, Schema, Term, Value};
+use crate::{Score, TantivyError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::ops::Bound;
+
+/// A structured query, that can be built programmatically or deserialized,
+/// and converted into an executable `Query` with [`to_query`](#method.to_query).
+///
+/// Contrary to the [`QueryParser`](./struct.QueryParser.html), the values of
+/// a `QueryAST` are not tokenized: they are the exact terms searched for.
+/// They are expressed in JSON, as in the documents parsed by
+/// [`Schema::parse_document`](../schema/struct.Schema.html#method.parse_document).
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::QueryAST;
+/// use tantivy::schema::{Schema, INDEXED, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let year = schema_builder.add_u64_field("year", INDEXED);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema.clone());
+/// let mut index_writer = index.writer(3_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea", year => 1952u64));
+/// index_writer.add_document(doc!(title => "The Sea Wolf", year => 1904u64));
+/// index_writer.commit()?;
+///
+/// let query_ast: QueryAST = serde_json::from_str(r#"{
+///     "bool": {
+///         "must": [{"term": {"field": "title", "value": "sea"}}],
+///         "must_not": [{"range": {"field": "year", "upper": {"excluded": 1950}}}]
+///     }
+/// }"#).unwrap();
+/// let query = query_ast.to_query(&schema)?;
+/// let searcher = index.reader()?.searcher();
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryAST {
+    /// Matches all of the documents.
+    All,
+    /// Matches the documents containing a term.
+    Term {
+        /// Name of the field.
+        field: String,
+        /// Value of the term.
+        value: JsonValue,
+    },
+    /// Matches the documents containing a sequence of consecutive terms.
+    ///
+    /// The field has to be a text field indexed with positions.
+    Phrase {
+        /// Name of the field.
+        field: String,
+        /// The terms of the phrase, in order.
+        terms: Vec<String>,
+    },
+    /// Matches the documents having a term within a range.
+    Range {
+        /// Name of the field.
+        field: String,
+        /// Lower bound of the range.
+        #[serde(default = "unbounded")]
+        lower: RangeBound,
+        /// Upper bound of the range.
+        #[serde(default = "unbounded")]
+        upper: RangeBound,
+    },
+    /// Combines queries, as a `BooleanQuery`.
+    Bool {
+        /// Queries the documents have to match.
+        #[serde(default)]
+        must: Vec<QueryAST>,
+        /// Queries the documents may match, making their score higher.
+        #[serde(default)]
+        should: Vec<QueryAST>,
+        /// Queries the documents must not match.
+        #[serde(default)]
+        must_not: Vec<QueryAST>,
+    },
+    /// Multiplies the score of a query.
+    Boost {
+        /// The boosted query.
+        query: Box<QueryAST>,
+        /// The boost factor.
+        boost: Score,
+    },
+}
+
+/// Bound of a `QueryAST::Range`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeBound {
+    /// The bound value is part of the range.
+    Included(JsonValue),
+    /// The bound value is not part of the range.
+    Excluded(JsonValue),
+    /// The range is not bounded on this side.
+    Unbounded,
+}
+
+fn unbounded() -> RangeBound {
+    RangeBound::Unbounded
+}
+
+fn resolve_field(schema: &Schema, field_name: &str) -> crate::Result<Field> {
+    schema.get_field(field_name).ok_or_else(|| {
+        TantivyError::SchemaError(format!("The field {:?} does not exist.", field_name))
+    })
+}
+
+/// Returns the field named `field_name`, checking that it is indexed.
+fn resolve_indexed_field(schema: &Schema, field_name: &str) -> crate::Result<Field> {
+    let field = resolve_field(schema, field_name)?;
+    if !schema.get_field_entry(field).is_indexed() {
+        return Err(TantivyError::SchemaError(format!(
+            "The field {:?} is not indexed.",
+            field_name
+        )));
+    }
+    Ok(field)
+}
+
+fn term_from_json(schema: &Schema, field: Field, json: &JsonValue) -> crate::Result<Term> {
+    let field_entry = schema.get_field_entry(field);
+    let value = field_entry
+        .field_type()
+        .value_from_json(json)
+        .map_err(|err| {
+            TantivyError::InvalidArgument(format!(
+                "Invalid value for the field {:?}: {:?}",
+                field_entry.name(),
+                err
+            ))
+        })?;
+    match value {
+        Value::Str(text) => Ok(Term::from_field_text(field, &text)),
+        Value::U64(val) => Ok(Term::from_field_u64(field, val)),
+        Value::I64(val) => Ok(Term::from_field_i64(field, val)),
+        Value::F64(val) => Ok(Term::from_field_f64(field, val)),
+        Value::Date(date) => Ok(Term::from_field_date(field, &date)),
+        Value::Facet(facet) => Ok(Term::from_facet(field, &facet)),
+        Value::Bytes(bytes) => Ok(Term::from_field_bytes(field, &bytes)),
+        Value::PreTokStr(_) => Err(TantivyError::InvalidArgument(format!(
+            "Expected a single value for the field {:?}, got a pre-tokenized string.",
+            field_entry.name()
+        ))),
+    }
+}
+
+impl RangeBound {
+    fn to_term_bound(&self, schema: &Schema, field: Field) -> crate::Result<Bound<Term>> {
+        match self {
+            RangeBound::Included(json) => Ok(Bound::Included(term_from_json(schema, field, json)?)),
+            RangeBound::Excluded(json) => Ok(Bound::Excluded(term_from_json(schema, field, json)?)),
+            RangeBound::Unbounded => Ok(Bound::Unbounded),
+        }
+    }
+}
+
+impl QueryAST {
+    /// Converts the `QueryAST` into an executable `Query`.
+    ///
+    /// Returns an error if the query refers to a field that does not exist,
+    /// or if a value does not match the type of its field.
+    ///
+    /// Like with the `QueryParser`, the terms and the phrases have to target indexed
+    /// fields, the phrases of several terms text fields indexed with positions,
+    /// and a `Bool` query cannot only contain `must_not` clauses.
+    pub fn to_query(&self, schema: &Schema) -> crate::Result<Box<dyn Query>> {
+        match self {
+            QueryAST::All => Ok(Box::new(AllQuery)),
+            QueryAST::Term { field, value } => {
+                let field = resolve_indexed_field(schema, field)?;
+                let term = term_from_json(schema, field, value)?;
+                Ok(Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)))
+            }
+            QueryAST::Phrase {
+                field: field_name,
+                terms,
+            } => {
+                let field = resolve_indexed_field(schema, field_name)?;
+                let has_positions = match schema.get_field_entry(field).field_type() {
+                    FieldType::Str(text_options) => text_options
+                        .get_indexing_options()
+                        .map(|indexing_options| indexing_options.index_option().has_positions())
+                        .unwrap_or(false),
+                    _ => {
+                        return Err(TantivyError::SchemaError(format!(
+                            "The field {:?} is not a text field.",
+                            field_name
+                        )))
+                    }
+                };
+                if terms.len() > 1 && !has_positions {
+                    return Err(TantivyError::SchemaError(format!(
+                        "The field {:?} does not have positions indexed.",
+                        field_name
+                    )));
+                }
+                let mut terms: Vec<Term> = terms
+                    .iter()
+                    .map(|text| Term::from_field_text(field, text))
+                    .collect();
+                match terms.len() {
+                    0 => Err(TantivyError::InvalidArgument(
+                        "A phrase query requires at least one term.".to_string(),
+                    )),
+                    1 => Ok(Box::new(TermQuery::new(
+                        terms.pop().unwrap(),
+                        IndexRecordOption::WithFreqs,
+                    ))),
+                    _ => Ok(Box::new(PhraseQuery::new(terms))),
+                }
+            }
+            QueryAST::Range {
+                field,
+                lower,
+                upper,
+            } => {
+                let field = resolve_field(schema, field)?;
+                let value_type = schema.get_field_entry(field).field_type().value_type();
+                let lower = lower.to_term_bound(schema, field)?;
+                let upper = upper.to_term_bound(schema, field)?;
+                Ok(Box::new(RangeQuery::new_term_bounds(
+                    field, value_type, &lower, &upper,
+                )))
+            }
+            QueryAST::Bool {
+                must,
+                should,
+                must_not,
+            } => {
+                if must.is_empty() && should.is_empty() && !must_not.is_empty() {
+                    return Err(TantivyError::InvalidArgument(
+                        "A bool query cannot only contain must_not clauses.".to_string(),
+                    ));
+                }
+                let mut subqueries = Vec::new();
+                for (occur, asts) in &[
+                    (Occur::Must, must),
+                    (Occur::Should, should),
+                    (Occur::MustNot, must_not),
+                ] {
+                    for ast in asts.iter() {
+                        subqueries.push((*occur, ast.to_query(schema)?));
+                    }
+                }
+                Ok(Box::new(BooleanQuery::new(subqueries)))
+            }
+            QueryAST::Boost { query, boost } => {
+                Ok(Box::new(BoostQuery::new(query.to_query(schema)?, *boost)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QueryAST, RangeBound};
+    use crate::collector::Count;
+    use crate::schema::{Schema, FAST, INDEXED, STORED, STRING, TEXT};
+    use crate::Index;
+    use crate::TantivyError;
+    use serde_json::json;
+
+    #[test]
+    fn test_query_ast_serialization() {
+        let query_ast = QueryAST::Bool {
+            must: vec![QueryAST::Term {
+                field: "title".to_string(),
+                value: json!("sea"),
+            }],
+            should: vec![QueryAST::Boost {
+                query: Box::new(QueryAST::Phrase {
+                    field: "title".to_string(),
+                    terms: vec!["old".to_string(), "man".to_string()],
+                }),
+                boost: 2.0,
+            }],
+            must_not: vec![QueryAST::Range {
+                field: "year".to_string(),
+                lower: RangeBound::Unbounded,
+                upper: RangeBound::Excluded(json!(1950)),
+            }],
+        };
+        let json = serde_json::to_string(&query_ast).unwrap();
+        assert_eq!(
+            json,
+            r#"{"bool":{"must":[{"term":{"field":"title","value":"sea"}}],"should":[{"boost":{"query":{"phrase":{"field":"title","terms":["old","man"]}},"boost":2.0}}],"must_not":[{"range":{"field":"year","lower":"unbounded","upper":{"excluded":1950}}}]}}"#
+        );
+        let deserialized: QueryAST = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, query_ast);
+        let all: QueryAST = serde_json::from_str(r#""all""#).unwrap();
+        assert_eq!(all, QueryAST::All);
+    }
+
+    #[test]
+    fn test_query_ast_to_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let year = schema_builder.add_i64_field("year", INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "the old man and the sea", year => 1952i64));
+        index_writer.add_document(doc!(title => "the sea wolf", year => 1904i64));
+        index_writer.add_document(doc!(title => "a man for all seasons", year => 1960i64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |json: &str| {
+            let query_ast: QueryAST = serde_json::from_str(json).unwrap();
+            let query = query_ast.to_query(&schema)?;
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count(r#""all""#)?, 3);
+        assert_eq!(count(r#"{"term": {"field": "title", "value": "man"}}"#)?, 2);
+        assert_eq!(count(r#"{"term": {"field": "year", "value": 1904}}"#)?, 1);
+        assert_eq!(
+            count(r#"{"phrase": {"field": "title", "terms": ["old", "man"]}}"#)?,
+            1
+        );
+        assert_eq!(
+            count(r#"{"phrase": {"field": "title", "terms": ["man", "old"]}}"#)?,
+            0
+        );
+        assert_eq!(
+            count(r#"{"range": {"field": "year", "lower": {"included": 1952}}}"#)?,
+            2
+        );
+        assert_eq!(
+            count(
+                r#"{"bool": {
+                    "should": [
+                        {"term": {"field": "title", "value": "sea"}},
+                        {"term": {"field": "title", "value": "man"}}
+                    ],
+                    "must_not": [{"term": {"field": "title", "value": "wolf"}}]
+                }}"#
+            )?,
+            2
+        );
+        assert!(count(r#"{"term": {"field": "author", "value": "hemingway"}}"#).is_err());
+        assert!(count(r#"{"term": {"field": "year", "value": "1952"}}"#).is_err());
+        assert!(count(r#"{"phrase": {"field": "title", "terms": []}}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_ast_to_query_errors() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("tag", STRING);
+        schema_builder.add_text_field("summary", STORED);
+        schema_builder.add_u64_field("year", INDEXED);
+        schema_builder.add_u64_field("rank", FAST);
+        let schema = schema_builder.build();
+        let to_query = |json: &str| {
+            let query_ast: QueryAST = serde_json::from_str(json).unwrap();
+            query_ast.to_query(&schema).map(|_| ())
+        };
+        let is_schema_error =
+            |json: &str| matches!(to_query(json), Err(TantivyError::SchemaError(_)));
+
+        // Terms of fields that are not indexed.
+        assert!(is_schema_error(
+            r#"{"term": {"field": "summary", "value": "sea"}}"#
+        ));
+        assert!(is_schema_error(
+            r#"{"term": {"field": "rank", "value": 3}}"#
+        ));
+        assert!(to_query(r#"{"term": {"field": "year", "value": 1952}}"#).is_ok());
+
+        // Phrases of fields that are not indexed text fields with positions.
+        assert!(is_schema_error(
+            r#"{"phrase": {"field": "summary", "terms": ["old", "man"]}}"#
+        ));
+        assert!(is_schema_error(
+            r#"{"phrase": {"field": "year", "terms": ["1952"]}}"#
+        ));
+        assert!(is_schema_error(
+            r#"{"phrase": {"field": "tag", "terms": ["old", "man"]}}"#
+        ));
+        assert!(to_query(r#"{"phrase": {"field": "tag", "terms": ["old"]}}"#).is_ok());
+
+        // Bool queries only excluding documents.
+        assert!(matches!(
+            to_query(r#"{"bool": {"must_not": [{"term": {"field": "title", "value": "sea"}}]}}"#),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(to_query(
+            r#"{"bool": {
+                "should": [{"term": {"field": "title", "value": "man"}}],
+                "must_not": [{"term": {"field": "title", "value": "sea"}}]
+            }}"#
+        )
+        .is_ok());
+    }
+}
@@ -16,6 +16,11 @@ pub enum LogicalLiteral {
         lower: Bound<Term>,
         upper: Bound<Term>,
     },
+    Fuzzy {
+        term: Term,
+        distance: u8,
+    },
+    Prefix(Term),
     All,
 }
 
@@ -81,6 +86,10 @@ impl fmt::Debug for LogicalLiteral {
                 ref upper,
                 ..
             } => write!(formatter, "({:?} TO {:?})", lower, upper),
+            LogicalLiteral::Fuzzy { ref term, distance } => {
+                write!(formatter, "{:?}~{}", term, distance)
+            }
+            LogicalLiteral::Prefix(ref term) => write!(formatter, "{:?}*", term),
             LogicalLiteral::All => write!(formatter, "*"),
         }
     }
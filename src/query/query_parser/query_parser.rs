@@ -2,12 +2,14 @@ use super::logical_ast::*;
 use crate::core::Index;
 use crate::query::BooleanQuery;
 use crate::query::EmptyQuery;
+use crate::query::FuzzyTermQuery;
 use crate::query::Occur;
 use crate::query::PhraseQuery;
+use crate::query::PrefixQuery;
 use crate::query::Query;
 use crate::query::RangeQuery;
 use crate::query::TermQuery;
-use crate::query::{AllQuery, BoostQuery};
+use crate::query::{lev_automaton_builder, AllQuery, BoostQuery};
 use crate::schema::{Facet, IndexRecordOption};
 use crate::schema::{Field, Schema};
 use crate::schema::{FieldType, Term};
@@ -18,6 +20,7 @@ use std::collections::HashMap;
 use std::num::{ParseFloatError, ParseIntError};
 use std::ops::Bound;
 use std::str::FromStr;
+use std::time::Instant;
 use tantivy_query_grammar::{UserInputAST, UserInputBound, UserInputLeaf};
 
 /// Possible error that may happen when parsing a query.
@@ -68,6 +71,14 @@ pub enum QueryParserError {
     /// The format for the date field is not RFC 3339 compliant.
     #[error("The date field has an invalid format")]
     DateFormatError(chrono::ParseError),
+    /// A fuzzy or prefix query was requested on a field that is not a text field,
+    /// or on a value that is tokenized into several terms.
+    #[error("A fuzzy or prefix query on the field '{0:?}' requires a single term of a text field")]
+    ExpectedSingleTextTerm(String),
+    /// The Levenshtein distance of a fuzzy query is not supported.
+    /// Only distances of 0, 1 and 2 are allowed.
+    #[error("Unsupported Levenshtein distance: {0}")]
+    InvalidFuzzyDistance(u8),
 }
 
 impl From<ParseIntError> for QueryParserError {
@@ -123,6 +134,10 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
 ///   `["title:barack", "body:barack", "title:obama", "body:obama"]`.
 ///   By default, all tokenized and indexed fields are default fields.
 ///
+///   More precisely, the terms are tokenized with the search tokenizer of each field
+///   (see [`TextFieldIndexing::set_search_tokenizer`](../schema/struct.TextFieldIndexing.html#method.set_search_tokenizer)),
+///   which is the tokenizer used at indexing time unless specified otherwise.
+///
 ///   Multiple terms are handled as an `OR` : any document containing at least
 ///   one of the term will go through the scoring.
 ///
@@ -157,7 +172,15 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
 ///   a word lexicographically between `a` and `c` (inclusive lower bound, exclusive upper bound).
 ///   Inclusive bounds are `[]`, exclusive are `{}`.
 ///
+/// * fuzzy terms: Appending `~` and a Levenshtein distance of 0, 1 or 2 to a term matches the
+///   terms within this distance. e.g., `title:roam~1` will also find documents containing "foam".
+///   The distance defaults to 2 when it is omitted.
+///
+/// * prefix terms: Appending `*` to a term matches all of the terms starting with it.
+///   e.g., `title:rust*` will find documents containing "rust", "rusty" or "rustacean".
+///
 /// * date values: The query parser supports rfc3339 formatted dates. For example "2002-10-02T15:00:00.05Z"
+///   They can be used as terms or as range bounds, e.g. `published:[2023-01-01T00:00:00Z TO 2024-01-01T00:00:00Z}`.
 ///
 /// *  all docs query: A plain `*` will match all documents in the index.
 ///
@@ -177,6 +200,33 @@ pub struct QueryParser {
     boost: HashMap<Field, Score>,
 }
 
+/// Splits a query on the whitespaces that are neither within quotes
+/// nor within parentheses or brackets.
+fn split_query_chunks(query: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut chunk_start = 0;
+    for (offset, c) in query.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '(' | '[' | '{' if !in_quotes => depth += 1,
+            ')' | ']' | '}' if !in_quotes => depth = depth.saturating_sub(1),
+            _ if c.is_whitespace() && !in_quotes && depth == 0 => {
+                if chunk_start < offset {
+                    chunks.push(&query[chunk_start..offset]);
+                }
+                chunk_start = offset + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    if chunk_start < query.len() {
+        chunks.push(&query[chunk_start..]);
+    }
+    chunks
+}
+
 fn all_negative(ast: &LogicalAST) -> bool {
     match ast {
         LogicalAST::Leaf(_) => false,
@@ -239,14 +289,58 @@ impl QueryParser {
     /// Note that `parse_query` returns an error if the input
     /// is not a valid query.
     ///
-    /// There is currently no lenient mode for the query parser
-    /// which makes it a bad choice for a public/broad user search engine.
-    ///
-    /// Implementing a lenient mode for this query parser is tracked
-    /// in [Issue 5](https://github.com/fulmicoton/tantivy/issues/5)
+    /// For queries typed by the users of a public search engine,
+    /// see [`parse_query_lenient`](#method.parse_query_lenient).
     pub fn parse_query(&self, query: &str) -> Result<Box<dyn Query>, QueryParserError> {
+        let parse_start = Instant::now();
         let logical_ast = self.parse_query_to_logical_ast(query)?;
-        Ok(convert_to_query(logical_ast))
+        let parsed_query = convert_to_query(logical_ast);
+        debug!("Parsed query {:?} in {:?}.", query, parse_start.elapsed());
+        Ok(parsed_query)
+    }
+
+    /// Parse a query, skipping the parts of the query that are not valid
+    /// instead of failing.
+    ///
+    /// The parts of the query that cannot be parsed, such as a dangling `field:`,
+    /// are dropped, and the remaining ones are parsed again. The leaves of the query
+    /// that cannot be interpreted, typically because they refer to a field that
+    /// does not exist, are dropped as well.
+    ///
+    /// Returns a best-effort query, along with the errors that were recovered from.
+    /// If nothing in the query can be kept, the query matches no document.
+    pub fn parse_query_lenient(&self, query: &str) -> (Box<dyn Query>, Vec<QueryParserError>) {
+        let mut errors = Vec::new();
+        let user_input_ast = match tantivy_query_grammar::parse_query(query) {
+            Ok(user_input_ast) => user_input_ast,
+            Err(_) => {
+                let mut valid_chunks = Vec::new();
+                for chunk in split_query_chunks(query) {
+                    if tantivy_query_grammar::parse_query(chunk).is_ok() {
+                        valid_chunks.push(chunk);
+                    } else {
+                        errors.push(QueryParserError::SyntaxError);
+                    }
+                }
+                match tantivy_query_grammar::parse_query(&valid_chunks.join(" ")) {
+                    Ok(user_input_ast) => user_input_ast,
+                    Err(_) => {
+                        errors.push(QueryParserError::SyntaxError);
+                        return (Box::new(EmptyQuery), errors);
+                    }
+                }
+            }
+        };
+        let logical_ast = self.compute_logical_ast_lenient(user_input_ast, &mut errors);
+        let logical_ast = match trim_ast(logical_ast) {
+            Some(logical_ast) => logical_ast,
+            None => return (Box::new(EmptyQuery), errors),
+        };
+        if all_negative(&logical_ast) {
+            errors.push(QueryParserError::AllButQueryForbidden);
+            return (Box::new(EmptyQuery), errors);
+        }
+        (convert_to_query(logical_ast), errors)
     }
 
     /// Parse the user query into an AST.
@@ -278,14 +372,21 @@ impl QueryParser {
         Ok(ast)
     }
 
+    /// Computes the terms associated to `phrase` for the given field.
+    ///
+    /// Range queries on numerical fields can be answered by fast fields,
+    /// in which case `allow_fast_field` makes it possible to compute terms for
+    /// a non-indexed fast field.
     fn compute_terms_for_string(
         &self,
         field: Field,
         phrase: &str,
+        allow_fast_field: bool,
     ) -> Result<Vec<(usize, Term)>, QueryParserError> {
         let field_entry = self.schema.get_field_entry(field);
         let field_type = field_entry.field_type();
-        if !field_type.is_indexed() {
+        let is_searchable = field_type.is_indexed() || (allow_fast_field && field_entry.is_fast());
+        if !is_searchable {
             let field_name = field_entry.name().to_string();
             return Err(QueryParserError::FieldNotIndexed(field_name));
         }
@@ -314,15 +415,15 @@ impl QueryParser {
             }
             FieldType::Str(ref str_options) => {
                 if let Some(option) = str_options.get_indexing_options() {
-                    let tokenizer =
-                        self.tokenizer_manager
-                            .get(option.tokenizer())
-                            .ok_or_else(|| {
-                                QueryParserError::UnknownTokenizer(
-                                    field_entry.name().to_string(),
-                                    option.tokenizer().to_string(),
-                                )
-                            })?;
+                    let tokenizer = self
+                        .tokenizer_manager
+                        .get(option.search_tokenizer())
+                        .ok_or_else(|| {
+                            QueryParserError::UnknownTokenizer(
+                                field_entry.name().to_string(),
+                                option.search_tokenizer().to_string(),
+                            )
+                        })?;
                     let mut terms: Vec<(usize, Term)> = Vec::new();
                     let mut token_stream = tokenizer.token_stream(phrase);
                     token_stream.process(&mut |token| {
@@ -374,7 +475,7 @@ impl QueryParser {
         field: Field,
         phrase: &str,
     ) -> Result<Option<LogicalLiteral>, QueryParserError> {
-        let terms = self.compute_terms_for_string(field, phrase)?;
+        let terms = self.compute_terms_for_string(field, phrase, false)?;
         match &terms[..] {
             [] => Ok(None),
             [(_, term)] => Ok(Some(LogicalLiteral::Term(term.clone()))),
@@ -382,6 +483,56 @@ impl QueryParser {
         }
     }
 
+    /// Computes the single term of a text field a fuzzy or prefix query is applied to.
+    ///
+    /// Returns `None` if the tokenizer of the field did not emit any token.
+    fn compute_single_text_term(
+        &self,
+        field: Field,
+        text: &str,
+    ) -> Result<Option<Term>, QueryParserError> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !matches!(field_entry.field_type(), FieldType::Str(_)) {
+            return Err(QueryParserError::ExpectedSingleTextTerm(
+                field_entry.name().to_string(),
+            ));
+        }
+        let mut terms = self.compute_terms_for_string(field, text, false)?;
+        match terms.len() {
+            0 => Ok(None),
+            1 => Ok(terms.pop().map(|(_, term)| term)),
+            _ => Err(QueryParserError::ExpectedSingleTextTerm(
+                field_entry.name().to_string(),
+            )),
+        }
+    }
+
+    /// Builds the logical AST of a fuzzy or prefix query, applied to the given field,
+    /// or to all of the default fields.
+    fn compute_single_term_ast<F>(
+        &self,
+        field: &Option<String>,
+        text: &str,
+        to_literal: F,
+    ) -> Result<LogicalAST, QueryParserError>
+    where
+        F: Fn(Term) -> LogicalLiteral,
+    {
+        let mut asts: Vec<LogicalAST> = Vec::new();
+        for &field in self.resolved_fields(field)?.iter() {
+            if let Some(term) = self.compute_single_text_term(field, text)? {
+                let boost = self.field_boost(field);
+                asts.push(LogicalAST::Leaf(Box::new(to_literal(term))).boost(boost));
+            }
+        }
+        let result_ast: LogicalAST = if asts.len() == 1 {
+            asts.into_iter().next().unwrap()
+        } else {
+            LogicalAST::Clause(asts.into_iter().map(|ast| (Occur::Should, ast)).collect())
+        };
+        Ok(result_ast)
+    }
+
     fn default_occur(&self) -> Occur {
         if self.conjunction_by_default {
             Occur::Must
@@ -398,7 +549,7 @@ impl QueryParser {
         if bound.term_str() == "*" {
             return Ok(Bound::Unbounded);
         }
-        let terms = self.compute_terms_for_string(field, bound.term_str())?;
+        let terms = self.compute_terms_for_string(field, bound.term_str(), true)?;
         if terms.len() != 1 {
             return Err(QueryParserError::RangeMustNotHavePhrase);
         }
@@ -449,6 +600,40 @@ impl QueryParser {
         }
     }
 
+    /// Same as `compute_logical_ast_with_occur`, except that the leaves
+    /// that cannot be interpreted are replaced by an empty clause,
+    /// and their error is appended to `errors`.
+    fn compute_logical_ast_lenient(
+        &self,
+        user_input_ast: UserInputAST,
+        errors: &mut Vec<QueryParserError>,
+    ) -> LogicalAST {
+        match user_input_ast {
+            UserInputAST::Clause(sub_queries) => {
+                let default_occur = self.default_occur();
+                let logical_sub_queries = sub_queries
+                    .into_iter()
+                    .map(|(occur_opt, sub_ast)| {
+                        let sub_ast = self.compute_logical_ast_lenient(sub_ast, errors);
+                        (occur_opt.unwrap_or(default_occur), sub_ast)
+                    })
+                    .collect();
+                LogicalAST::Clause(logical_sub_queries)
+            }
+            UserInputAST::Boost(ast, boost) => {
+                let ast = self.compute_logical_ast_lenient(*ast, errors);
+                ast.boost(boost as Score)
+            }
+            UserInputAST::Leaf(leaf) => match self.compute_logical_ast_from_leaf(*leaf) {
+                Ok(ast) => ast,
+                Err(error) => {
+                    errors.push(error);
+                    LogicalAST::Clause(Vec::new())
+                }
+            },
+        }
+    }
+
     fn field_boost(&self, field: Field) -> Score {
         self.boost.get(&field).cloned().unwrap_or(1.0)
     }
@@ -524,6 +709,22 @@ impl QueryParser {
                 };
                 Ok(result_ast)
             }
+            UserInputLeaf::Fuzzy {
+                field,
+                term,
+                distance,
+            } => {
+                if lev_automaton_builder(distance, true).is_none() {
+                    return Err(QueryParserError::InvalidFuzzyDistance(distance));
+                }
+                self.compute_single_term_ast(&field, &term, |term| LogicalLiteral::Fuzzy {
+                    term,
+                    distance,
+                })
+            }
+            UserInputLeaf::Prefix { field, prefix } => {
+                self.compute_single_term_ast(&field, &prefix, LogicalLiteral::Prefix)
+            }
         }
     }
 }
@@ -542,6 +743,10 @@ fn convert_literal_to_query(logical_literal: LogicalLiteral) -> Box<dyn Query> {
         } => Box::new(RangeQuery::new_term_bounds(
             field, value_type, &lower, &upper,
         )),
+        LogicalLiteral::Fuzzy { term, distance } => {
+            Box::new(FuzzyTermQuery::new(term, distance, true))
+        }
+        LogicalLiteral::Prefix(term) => Box::new(PrefixQuery::new(term)),
         LogicalLiteral::All => Box::new(AllQuery),
     }
 }
@@ -574,14 +779,16 @@ fn convert_to_query(logical_ast: LogicalAST) -> Box<dyn Query> {
 #[cfg(test)]
 mod test {
     use super::super::logical_ast::*;
-    use super::QueryParser;
     use super::QueryParserError;
+    use super::{split_query_chunks, QueryParser};
+    use crate::collector::Count;
     use crate::query::Query;
     use crate::schema::Field;
     use crate::schema::{IndexRecordOption, TextFieldIndexing, TextOptions};
     use crate::schema::{Schema, Term, INDEXED, STORED, STRING, TEXT};
     use crate::tokenizer::{
-        LowerCaser, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenizerManager,
+        LowerCaser, NgramTokenizer, RawTokenizer, SimpleTokenizer, StopWordFilter, TextAnalyzer,
+        TokenizerManager,
     };
     use crate::Index;
     use matches::assert_matches;
@@ -861,6 +1068,113 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_query_fuzzy() {
+        test_parse_query_to_logical_ast_helper(
+            "title:toto~1",
+            "Term(field=0,bytes=[116, 111, 116, 111])~1",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "Toto~",
+            "(Term(field=0,bytes=[116, 111, 116, 111])~2 \
+             Term(field=1,bytes=[116, 111, 116, 111])~2)",
+            false,
+        );
+        let query_parser = make_query_parser();
+        assert_matches!(
+            query_parser.parse_query("title:toto~3"),
+            Err(QueryParserError::InvalidFuzzyDistance(3))
+        );
+        assert_matches!(
+            query_parser.parse_query("title:toto~10"),
+            Err(QueryParserError::InvalidFuzzyDistance(10))
+        );
+        assert_matches!(
+            query_parser.parse_query("title:toto~1000"),
+            Err(QueryParserError::SyntaxError)
+        );
+        assert_matches!(
+            query_parser.parse_query("signed:2~1"),
+            Err(QueryParserError::ExpectedSingleTextTerm(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("title:toto-titi~1"),
+            Err(QueryParserError::ExpectedSingleTextTerm(_))
+        );
+    }
+
+    #[test]
+    fn test_parse_query_prefix() {
+        test_parse_query_to_logical_ast_helper(
+            "title:Tot*",
+            "Term(field=0,bytes=[116, 111, 116])*",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:tot* -text:toto",
+            "(Term(field=0,bytes=[116, 111, 116])* \
+             -Term(field=1,bytes=[116, 111, 116, 111]))",
+            false,
+        );
+        let query_parser = make_query_parser();
+        assert_matches!(
+            query_parser.parse_query("unsigned:2*"),
+            Err(QueryParserError::ExpectedSingleTextTerm(_))
+        );
+    }
+
+    #[test]
+    fn test_split_query_chunks() {
+        assert_eq!(
+            split_query_chunks(" title:\"a b\"  unsigned:[1 TO 2} (c  d)^2 e"),
+            vec!["title:\"a b\"", "unsigned:[1 TO 2}", "(c  d)^2", "e"]
+        );
+        assert!(split_query_chunks("  ").is_empty());
+    }
+
+    #[test]
+    fn test_parse_query_lenient() {
+        let query_parser = make_query_parser();
+        let assert_lenient =
+            |query: &str, expected_query: &str, expected_errors: Vec<QueryParserError>| {
+                let (query, errors) = query_parser.parse_query_lenient(query);
+                let expected_query = query_parser.parse_query(expected_query).unwrap();
+                assert_eq!(format!("{:?}", query), format!("{:?}", expected_query));
+                assert_eq!(errors, expected_errors);
+            };
+        assert_lenient("title:toto -text:titi", "title:toto -text:titi", vec![]);
+        assert_lenient(
+            "title:toto nonexistingfield:a text:titi",
+            "title:toto text:titi",
+            vec![QueryParserError::FieldDoesNotExist(
+                "nonexistingfield".to_string(),
+            )],
+        );
+        assert_lenient("title: toto", "toto", vec![QueryParserError::SyntaxError]);
+        assert_lenient(
+            "+toto title: unsigned:2a text:titi",
+            "+toto text:titi",
+            vec![
+                QueryParserError::SyntaxError,
+                QueryParserError::ExpectedInt("2a".parse::<u64>().unwrap_err()),
+            ],
+        );
+
+        let (query, errors) = query_parser.parse_query_lenient("title:");
+        assert_eq!(format!("{:?}", query), "EmptyQuery");
+        assert_eq!(errors, vec![QueryParserError::SyntaxError]);
+        let (query, errors) = query_parser.parse_query_lenient("-title:toto nonexistingfield:a");
+        assert_eq!(format!("{:?}", query), "EmptyQuery");
+        assert_eq!(
+            errors,
+            vec![
+                QueryParserError::FieldDoesNotExist("nonexistingfield".to_string()),
+                QueryParserError::AllButQueryForbidden,
+            ]
+        );
+    }
+
     #[test]
     fn test_single_negative_term() {
         assert_matches!(
@@ -987,6 +1301,39 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_query_parser_search_tokenizer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field_indexing = TextFieldIndexing::default()
+            .set_tokenizer("edge_ngram")
+            .set_search_tokenizer("lowercase")
+            .set_index_option(IndexRecordOption::WithFreqs);
+        let text_options = TextOptions::default().set_indexing_options(text_field_indexing);
+        let title = schema_builder.add_text_field("title", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "edge_ngram",
+            TextAnalyzer::from(NgramTokenizer::prefix_only(2, 8)).filter(LowerCaser),
+        );
+        index.tokenizers().register(
+            "lowercase",
+            TextAnalyzer::from(RawTokenizer).filter(LowerCaser),
+        );
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "Autocomplete"));
+        index_writer.add_document(doc!(title => "Automaton"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let query = query_parser.parse_query("AutoC")?;
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("auto")?, &Count)?,
+            2
+        );
+        Ok(())
+    }
+
     #[test]
     pub fn test_query_parser_expected_int() {
         let query_parser = make_query_parser();
@@ -1018,6 +1365,48 @@ mod test {
             .is_ok());
     }
 
+    #[test]
+    fn test_query_parser_date_range() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let date = schema_builder.add_date_field("date", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for date_str in &[
+            "2022-12-31T23:59:59Z",
+            "2023-01-01T00:00:00Z",
+            "2023-06-15T12:00:00+02:00",
+            "2024-01-01T00:00:00Z",
+        ] {
+            let date_time = chrono::DateTime::parse_from_rfc3339(date_str)
+                .unwrap()
+                .with_timezone(&chrono::Utc);
+            index_writer.add_document(doc!(date => date_time));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![date]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(
+            count("date:[2023-01-01T00:00:00Z TO 2024-01-01T00:00:00Z}"),
+            2
+        );
+        assert_eq!(
+            count("date:[2023-01-01T00:00:00Z TO 2024-01-01T00:00:00Z]"),
+            3
+        );
+        assert_eq!(count("date:{2023-01-01T01:00:00+01:00 TO *]"), 2);
+        assert_eq!(count("date:<2023-01-01T00:00:00Z"), 1);
+        assert_eq!(count("date:2023-06-15T10:00:00Z"), 1);
+        assert_matches!(
+            query_parser.parse_query("date:[2023-13-01T00:00:00Z TO *]"),
+            Err(QueryParserError::DateFormatError(_))
+        );
+        Ok(())
+    }
+
     #[test]
     pub fn test_query_parser_not_empty_but_no_tokens() {
         let query_parser = make_query_parser();
@@ -1,15 +1,16 @@
-use crate::common::BitSet;
+use crate::common::{i64_to_u64, u64_to_i64, BitSet};
 use crate::core::Searcher;
 use crate::core::SegmentReader;
 use crate::error::TantivyError;
 use crate::query::explanation::does_not_match;
-use crate::query::ConstScorer;
 use crate::query::{BitSetDocSet, Explanation};
+use crate::query::{ConstScorer, EmptyScorer};
 use crate::query::{Query, Scorer, Weight};
-use crate::schema::Type;
+use crate::schema::{DatePrecision, FieldType, Type};
 use crate::schema::{Field, IndexRecordOption, Term};
 use crate::termdict::{TermDictionary, TermStreamer};
 use crate::{DocId, Score};
+use byteorder::{BigEndian, ByteOrder};
 use std::collections::Bound;
 use std::io;
 use std::ops::Range;
@@ -35,6 +36,18 @@ fn map_bound<TFrom, TTo, Transform: Fn(&TFrom) -> TTo>(
 /// The current implement will iterate over the terms within the range
 /// and append all of the document cross into a `BitSet`.
 ///
+/// If the field is a numerical fast field that is not indexed, the range query
+/// is executed by scanning the fast field of each segment instead.
+/// For multivalued fast fields, a document matches if any of its values
+/// is within the range. Documents without any value in an optional or
+/// multivalued fast field never match. A single-valued fast field cannot
+/// tell a missing value from `0`, so its documents without a value match
+/// the ranges containing `0`: fields that may be missing should rather be declared
+/// with `Cardinality::Optional`.
+///
+/// The segments whose fast field values are all out of the range are skipped,
+/// using the min and max values recorded in the header of the fast field.
+///
 /// # Example
 ///
 /// ```rust
@@ -252,7 +265,8 @@ impl Query for RangeQuery {
         _scoring_enabled: bool,
     ) -> crate::Result<Box<dyn Weight>> {
         let schema = searcher.schema();
-        let value_type = schema.get_field_entry(self.field).field_type().value_type();
+        let field_entry = schema.get_field_entry(self.field);
+        let value_type = field_entry.field_type().value_type();
         if value_type != self.value_type {
             let err_msg = format!(
                 "Create a range query of the type {:?}, when the field given was of type {:?}",
@@ -260,6 +274,28 @@ impl Query for RangeQuery {
             );
             return Err(TantivyError::SchemaError(err_msg));
         }
+        if !field_entry.is_indexed() && field_entry.is_fast() {
+            // Dates may be truncated in the fast field: included bounds are truncated
+            // to the same precision, so that a value matches if it is in the range at
+            // the precision of the field. Excluded bounds are compared as is.
+            let date_precision = match field_entry.field_type() {
+                FieldType::Date(int_options) => int_options.get_date_precision(),
+                _ => DatePrecision::Seconds,
+            };
+            // Numerical terms are the big endian encoding of the `u64` stored in fast fields.
+            let term_val_to_u64 = |term_val: &Vec<u8>| BigEndian::read_u64(term_val);
+            let truncate_included = |bound: Bound<u64>| match bound {
+                Bound::Included(val) if date_precision != DatePrecision::Seconds => {
+                    Bound::Included(i64_to_u64(date_precision.truncate(u64_to_i64(val))))
+                }
+                bound => bound,
+            };
+            return Ok(Box::new(FastFieldRangeWeight {
+                field: self.field,
+                left_bound: truncate_included(map_bound(&self.left_bound, &term_val_to_u64)),
+                right_bound: truncate_included(map_bound(&self.right_bound, &term_val_to_u64)),
+            }));
+        }
         Ok(Box::new(RangeWeight {
             field: self.field,
             left_bound: self.left_bound.clone(),
@@ -328,13 +364,96 @@ impl Weight for RangeWeight {
     }
 }
 
+/// Weight of a `RangeQuery` over a numerical fast field that is not indexed.
+///
+/// Bounds are expressed in the `u64` space of fast field values,
+/// in which the order of `i64`, `f64` and dates is preserved.
+pub struct FastFieldRangeWeight {
+    field: Field,
+    left_bound: Bound<u64>,
+    right_bound: Bound<u64>,
+}
+
+impl FastFieldRangeWeight {
+    fn contains(&self, val: u64) -> bool {
+        self.intersects(val, val)
+    }
+
+    /// Returns true iff the range intersects `[min_val, max_val]`.
+    fn intersects(&self, min_val: u64, max_val: u64) -> bool {
+        use std::collections::Bound::*;
+        let above_left = match self.left_bound {
+            Included(left) => max_val >= left,
+            Excluded(left) => max_val > left,
+            Unbounded => true,
+        };
+        let below_right = match self.right_bound {
+            Included(right) => min_val <= right,
+            Excluded(right) => min_val < right,
+            Unbounded => true,
+        };
+        above_left && below_right
+    }
+}
+
+impl Weight for FastFieldRangeWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        let fast_fields = reader.fast_fields();
+        // The min and max values of the segment are recorded in the fast field header.
+        // Segments that cannot contain any matching value are skipped without
+        // scanning their column. The segment still has to be opened to read them:
+        // they are not recorded in the segment metas.
+        if let Some(fast_field_reader) = fast_fields.u64_lenient(self.field) {
+            if !self.intersects(fast_field_reader.min_value(), fast_field_reader.max_value()) {
+                return Ok(Box::new(EmptyScorer));
+            }
+            for doc in 0..max_doc {
+                if self.contains(fast_field_reader.get(doc)) {
+                    doc_bitset.insert(doc);
+                }
+            }
+        } else if let Some(fast_field_reader) = fast_fields.u64s_lenient(self.field) {
+            if !self.intersects(fast_field_reader.min_value(), fast_field_reader.max_value()) {
+                return Ok(Box::new(EmptyScorer));
+            }
+            let mut vals = Vec::new();
+            for doc in 0..max_doc {
+                fast_field_reader.get_vals(doc, &mut vals);
+                if vals.iter().any(|&val| self.contains(val)) {
+                    doc_bitset.insert(doc);
+                }
+            }
+        } else {
+            let field_name = reader.schema().get_field_entry(self.field).name();
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field.",
+                field_name
+            )));
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("RangeQuery", 1.0))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::RangeQuery;
     use crate::collector::{Count, TopDocs};
     use crate::query::QueryParser;
-    use crate::schema::{Document, Field, Schema, INDEXED, TEXT};
+    use crate::schema::{
+        Cardinality, DatePrecision, Document, Field, IntOptions, Schema, FAST, INDEXED, TEXT,
+    };
     use crate::Index;
     use std::collections::Bound;
 
@@ -482,6 +601,147 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_range_query_on_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let year = schema_builder.add_u64_field("year", FAST);
+        let temperatures = schema_builder.add_i64_field(
+            "temperatures",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(year=>1960u64, temperatures=>-3i64, temperatures=>12i64));
+        index_writer.add_document(doc!(year=>1975u64, temperatures=>20i64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(year=>1969u64));
+        index_writer.add_document(doc!(year=>2001u64, temperatures=>-10i64));
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let count = |range_query: RangeQuery| searcher.search(&range_query, &Count).unwrap();
+
+        assert_eq!(count(RangeQuery::new_u64(year, 1960..1970)), 2);
+        assert_eq!(count(RangeQuery::new_u64(year, 1980..2000)), 0);
+        assert_eq!(
+            count(RangeQuery::new_u64_bounds(
+                year,
+                Bound::Excluded(1960),
+                Bound::Unbounded
+            )),
+            3
+        );
+        assert_eq!(count(RangeQuery::new_i64(temperatures, -5..0)), 1);
+        assert_eq!(
+            count(RangeQuery::new_i64_bounds(
+                temperatures,
+                Bound::Unbounded,
+                Bound::Included(12)
+            )),
+            2
+        );
+        assert_eq!(count(RangeQuery::new_i64(temperatures, 13..20)), 0);
+
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let query = query_parser.parse_query("year:[1969 TO 2001]")?;
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_range_query_on_fast_field_skips_missing_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let rating = schema_builder.add_i64_field(
+            "rating",
+            IntOptions::default().set_fast(Cardinality::Optional),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(rating=>0i64));
+        index_writer.add_document(doc!());
+        index_writer.add_document(doc!(rating=>5i64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |range_query: RangeQuery| searcher.search(&range_query, &Count).unwrap();
+        assert_eq!(count(RangeQuery::new_i64(rating, -1..1)), 1);
+        assert_eq!(count(RangeQuery::new_i64(rating, -1..10)), 2);
+        assert_eq!(
+            count(RangeQuery::new_i64_bounds(
+                rating,
+                Bound::Unbounded,
+                Bound::Unbounded
+            )),
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_range_query_on_fast_field_date_precision() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let published = schema_builder.add_date_field(
+            "published",
+            IntOptions::from(FAST).set_date_precision(DatePrecision::Days),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        let parse_date = |date: &str| {
+            chrono::DateTime::parse_from_rfc3339(date)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        index_writer.add_document(doc!(published => parse_date("2023-01-05T12:00:00Z")));
+        index_writer.add_document(doc!(published => parse_date("2023-01-07T08:00:00Z")));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        // The bounds are compared to the stored values at the precision of the field.
+        assert_eq!(
+            count("published:[2023-01-05T18:00:00Z TO 2023-01-06T00:00:00Z}"),
+            1
+        );
+        assert_eq!(
+            count("published:[2023-01-06T00:00:00Z TO 2023-01-07T06:00:00Z]"),
+            1
+        );
+        // Excluded bounds are compared as is to the truncated values.
+        assert_eq!(
+            count("published:{2023-01-05T18:00:00Z TO 2023-01-07T06:00:00Z}"),
+            1
+        );
+        assert_eq!(count("published:{2023-01-04T12:00:00Z TO *}"), 2);
+        assert_eq!(count("published:{2023-01-05T00:00:00Z TO *}"), 1);
+        assert_eq!(count("published:[* TO 2023-01-05T06:00:00Z}"), 1);
+        assert_eq!(count("published:[* TO 2023-01-05T00:00:00Z}"), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_range_query_on_fast_field_fractional_bounds() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        for &val in &[1.0f64, 1.5f64, 1.75f64, 2.0f64, 2.5f64] {
+            index_writer.add_document(doc!(price => val));
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |left_bound: Bound<f64>, right_bound: Bound<f64>| {
+            let range_query = RangeQuery::new_f64_bounds(price, left_bound, right_bound);
+            searcher.search(&range_query, &Count).unwrap()
+        };
+        assert_eq!(count(Bound::Excluded(1.5), Bound::Unbounded), 3);
+        assert_eq!(count(Bound::Included(1.5), Bound::Unbounded), 4);
+        assert_eq!(count(Bound::Unbounded, Bound::Excluded(2.0)), 3);
+        assert_eq!(count(Bound::Excluded(1.5), Bound::Excluded(2.5)), 2);
+        Ok(())
+    }
+
     #[test]
     fn test_bug_reproduce_range_query() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -1,12 +1,16 @@
 mod pool;
+mod warming;
 
 pub use self::pool::LeasedItem;
 use self::pool::Pool;
+pub use self::warming::Warmer;
 use crate::core::Segment;
 use crate::directory::WatchHandle;
 use crate::directory::META_LOCK;
 use crate::directory::{Directory, WatchCallback};
+use crate::Executor;
 use crate::Index;
+use crate::IndexMeta;
 use crate::Searcher;
 use crate::SegmentReader;
 use std::sync::Arc;
@@ -17,7 +21,7 @@ use std::{convert::TryInto, io};
 /// Regardless of whether you search and index in the same process, tantivy does not necessarily
 /// reflects the change that are commited to your index. `ReloadPolicy` precisely helps you define
 /// when you want your index to be reloaded.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum ReloadPolicy {
     /// The index is entirely reloaded manually.
     /// All updates of the index should be manual.
@@ -26,6 +30,7 @@ pub enum ReloadPolicy {
     Manual,
     /// The index is reloaded within milliseconds after a new commit is available.
     /// This is made possible by watching changes in the `meta.json` file.
+    #[default]
     OnCommit, // TODO add NEAR_REAL_TIME(target_ms)
 }
 
@@ -40,10 +45,23 @@ pub enum ReloadPolicy {
 /// - `reload_policy` (by default `ReloadPolicy::OnCommit`):
 ///
 ///   See [`ReloadPolicy`](./enum.ReloadPolicy.html) for more details.
+/// - `commit_point` (by default the last commit):
+///
+///   The generation of the commit point the reader should open.
+/// - `warmers` (by default none):
+///
+///   See [`Warmer`](./trait.Warmer.html) for more details.
+/// - `search_executor` (by default the search executor of the index):
+///
+///   The executor used by the searchers of this reader to search
+///   the different segments.
 #[derive(Clone)]
 pub struct IndexReaderBuilder {
     num_searchers: usize,
     reload_policy: ReloadPolicy,
+    commit_point: Option<u64>,
+    warmers: Vec<Arc<dyn Warmer>>,
+    search_executor: Option<Arc<Executor>>,
     index: Index,
 }
 
@@ -51,7 +69,10 @@ impl IndexReaderBuilder {
     pub(crate) fn new(index: Index) -> IndexReaderBuilder {
         IndexReaderBuilder {
             num_searchers: num_cpus::get(),
-            reload_policy: ReloadPolicy::OnCommit,
+            reload_policy: ReloadPolicy::default(),
+            commit_point: None,
+            warmers: Vec::new(),
+            search_executor: None,
             index,
         }
     }
@@ -62,15 +83,30 @@ impl IndexReaderBuilder {
     /// to open different segment readers. It may take hundreds of milliseconds
     /// of time and it may return an error.
     pub fn try_into(self) -> crate::Result<IndexReader> {
+        let mut index = self.index;
+        if let Some(search_executor) = self.search_executor {
+            // Searchers rely on the executor of their index.
+            index.set_shared_multithread_executor(search_executor);
+        }
+        let commit_point = self
+            .commit_point
+            .map(|generation| index.load_commit_point(generation))
+            .transpose()?;
         let inner_reader = InnerIndexReader {
-            index: self.index,
+            index,
             num_searchers: self.num_searchers,
             searcher_pool: Pool::new(),
+            commit_point,
+            warmers: self.warmers,
         };
         inner_reader.reload()?;
         let inner_reader_arc = Arc::new(inner_reader);
         let watch_handle_opt: Option<WatchHandle>;
         match self.reload_policy {
+            // A historical commit point never changes.
+            _ if self.commit_point.is_some() => {
+                watch_handle_opt = None;
+            }
             ReloadPolicy::Manual => {
                 // No need to set anything...
                 watch_handle_opt = None;
@@ -106,6 +142,37 @@ impl IndexReaderBuilder {
         self
     }
 
+    /// Opens the commit point with the given generation, rather than the last commit.
+    ///
+    /// The resulting reader always searches this commit point, regardless of
+    /// the reload policy.
+    /// As long as the reader is alive, the files of the commit point are protected
+    /// from garbage collection, even if the commit point itself becomes obsolete.
+    /// See `Index::list_commit_points`.
+    pub fn commit_point(mut self, generation: u64) -> IndexReaderBuilder {
+        self.commit_point = Some(generation);
+        self
+    }
+
+    /// Registers a warmer.
+    ///
+    /// Warmers are run, in the order of registration, every time
+    /// a new version of the index is loaded, before the new searchers
+    /// are published. They are run on each of the `num_searchers` searchers.
+    pub fn warmer<W: Warmer>(mut self, warmer: W) -> IndexReaderBuilder {
+        self.warmers.push(Arc::new(warmer));
+        self
+    }
+
+    /// Sets the executor used by the searchers of this reader,
+    /// instead of the search executor of the index.
+    ///
+    /// See `Index::set_shared_multithread_executor`.
+    pub fn search_executor(mut self, search_executor: Arc<Executor>) -> IndexReaderBuilder {
+        self.search_executor = Some(search_executor);
+        self
+    }
+
     /// Sets the number of `Searcher` in the searcher pool.
     pub fn num_searchers(mut self, num_searchers: usize) -> IndexReaderBuilder {
         self.num_searchers = num_searchers;
@@ -124,6 +191,10 @@ impl TryInto<IndexReader> for IndexReaderBuilder {
 struct InnerIndexReader {
     num_searchers: usize,
     searcher_pool: Pool<Searcher>,
+    // Holding the tracked segment metas of the commit point
+    // protects its files from garbage collection.
+    commit_point: Option<IndexMeta>,
+    warmers: Vec<Arc<dyn Warmer>>,
     index: Index,
 }
 
@@ -143,18 +214,36 @@ impl InnerIndexReader {
         })
         .take(self.num_searchers)
         .collect::<io::Result<_>>()?;
+        // Each searcher has its own doc store cache, so all of them are warmed.
+        for searcher in &searchers {
+            for warmer in &self.warmers {
+                warmer.warm(searcher)?;
+            }
+        }
         self.searcher_pool.publish_new_generation(searchers);
         Ok(())
     }
 
     /// Returns the list of segments that are searchable
     fn searchable_segments(&self) -> crate::Result<Vec<Segment>> {
+        if let Some(index_meta) = self.commit_point.as_ref() {
+            return Ok(index_meta
+                .segments
+                .iter()
+                .cloned()
+                .map(|segment_meta| self.index.segment(segment_meta))
+                .collect());
+        }
         self.index.searchable_segments()
     }
 
     fn searcher(&self) -> LeasedItem<Searcher> {
         self.searcher_pool.acquire()
     }
+
+    fn try_searcher(&self) -> Option<LeasedItem<Searcher>> {
+        self.searcher_pool.try_acquire()
+    }
 }
 
 /// `IndexReader` is your entry point to read and search the index.
@@ -202,4 +291,14 @@ impl IndexReader {
     pub fn searcher(&self) -> LeasedItem<Searcher> {
         self.inner.searcher()
     }
+
+    /// Returns a searcher, or `None` if all of the `num_searchers`
+    /// searchers of the pool are currently in use.
+    ///
+    /// Contrary to `.searcher()`, this method never blocks. It makes it
+    /// possible for an application to reject or defer a query when
+    /// too many queries are running concurrently.
+    pub fn try_searcher(&self) -> Option<LeasedItem<Searcher>> {
+        self.inner.try_searcher()
+    }
 }
@@ -1,5 +1,5 @@
 use crossbeam::channel::unbounded;
-use crossbeam::channel::{Receiver, RecvError, Sender};
+use crossbeam::channel::{Receiver, RecvError, Sender, TryRecvError};
 use std::ops::{Deref, DerefMut};
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
@@ -40,6 +40,12 @@ impl<T> Queue<T> {
     fn pop(&self) -> Result<T, RecvError> {
         self.receiver.recv()
     }
+
+    /// Same as `pop`, but returns an error instead
+    /// of blocking if the queue is empty.
+    fn try_pop(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
 }
 
 /// An object pool
@@ -140,6 +146,23 @@ impl<T> Pool<T> {
             }
         }
     }
+
+    /// Acquires a new searcher, if one is available.
+    ///
+    /// Contrary to `acquire`, this method does not block and returns
+    /// `None` if all of the searchers are currently leased.
+    pub fn try_acquire(&self) -> Option<LeasedItem<T>> {
+        let generation = self.generation();
+        while let Ok(gen_item) = self.queue.try_pop() {
+            if gen_item.generation >= generation {
+                return Some(LeasedItem {
+                    gen_item: Some(gen_item),
+                    recycle_queue: Arc::clone(&self.queue),
+                });
+            }
+        }
+        None
+    }
 }
 
 /// A LeasedItem holds an object borrowed from a Pool.
@@ -204,6 +227,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pool_try_acquire() {
+        let pool = Pool::new();
+        pool.publish_new_generation(vec![1usize, 1usize]);
+        {
+            let first = pool.try_acquire().unwrap();
+            let second = pool.try_acquire().unwrap();
+            assert_eq!(*first + *second, 2);
+            assert!(pool.try_acquire().is_none());
+        }
+        pool.publish_new_generation(vec![2usize]);
+        assert_eq!(*pool.try_acquire().unwrap(), 2);
+    }
+
     #[test]
     fn test_queue() {
         let q = Queue::new();
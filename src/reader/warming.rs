@@ -0,0 +1,29 @@
+use crate::Searcher;
+
+/// `Warmer` can be used to prepare a new `Searcher` before it is
+/// published by the `IndexReader`.
+///
+/// Warmers are typically used to load in memory the data that
+/// will be needed by the first queries (fast fields, term dictionaries, ...),
+/// so that these queries are not slowed down after a reload.
+///
+/// Closures of the form `Fn(&Searcher) -> crate::Result<()>` implement `Warmer`.
+pub trait Warmer: Send + Sync + 'static {
+    /// Warms the given searcher.
+    ///
+    /// It is called for each of the searchers of the new version of the index.
+    /// These searchers share their segment readers, but each of them has its own
+    /// doc store cache.
+    ///
+    /// The searcher is not published if any of the warmers returns an error.
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()>;
+}
+
+impl<F> Warmer for F
+where
+    F: Fn(&Searcher) -> crate::Result<()> + Send + Sync + 'static,
+{
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        self(searcher)
+    }
+}
@@ -108,6 +108,11 @@ impl Document {
         self.add(FieldValue::new(field, Value::Date(*value)));
     }
 
+    /// Add a geo point, encoded into a u64 field
+    pub fn add_geo_point(&mut self, field: Field, geo_point: GeoPoint) {
+        self.add(FieldValue::new(field, Value::from(geo_point)));
+    }
+
     /// Add a bytes field
     pub fn add_bytes<T: Into<Vec<u8>>>(&mut self, field: Field, value: T) {
         self.add(FieldValue::new(field, Value::Bytes(value.into())))
@@ -214,6 +219,41 @@ mod tests {
     use crate::schema::*;
     use crate::tokenizer::{PreTokenizedString, Token};
 
+    #[test]
+    fn test_doc_serde() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let score = schema_builder.add_i64_field("score", INDEXED);
+        let ratio = schema_builder.add_f64_field("ratio", INDEXED);
+        let date = schema_builder.add_date_field("date", INDEXED);
+        let category = schema_builder.add_facet_field("category");
+        let payload = schema_builder.add_bytes_field("payload", BytesOptions::default());
+        let doc = doc!(
+            title => "My title",
+            title => PreTokenizedString {
+                text: "pre".to_string(),
+                tokens: vec![Token {
+                    offset_from: 0,
+                    offset_to: 3,
+                    position: 0,
+                    text: "pre".to_string(),
+                    position_length: 1,
+                }],
+            },
+            count => 4u64,
+            score => 4i64,
+            ratio => 4.5f64,
+            date => "2020-09-13T12:26:40.5Z".parse::<crate::DateTime>().unwrap(),
+            category => Facet::from("/a/b"),
+            payload => vec![1u8, 2u8],
+        );
+        let doc_json = serde_json::to_string(&doc).unwrap();
+        let deserialized_doc: Document = serde_json::from_str(&doc_json).unwrap();
+        assert_eq!(deserialized_doc, doc);
+        assert_eq!(deserialized_doc.get_first(score), Some(&Value::I64(4)));
+    }
+
     #[test]
     fn test_doc() {
         let mut schema_builder = Schema::builder();
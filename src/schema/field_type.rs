@@ -221,7 +221,7 @@ mod tests {
     use crate::schema::field_type::ValueParsingError;
     use crate::schema::TextOptions;
     use crate::schema::Value;
-    use crate::schema::{Schema, INDEXED};
+    use crate::schema::{Schema, INDEXED, STORED};
     use crate::tokenizer::{PreTokenizedString, Token};
     use crate::{DateTime, Document};
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
@@ -251,6 +251,18 @@ mod tests {
         assert_eq!(doc_json, r#"{"date":["1982-09-17T13:20:00+00:00"]}"#);
     }
 
+    #[test]
+    fn test_serialize_json_bytes() {
+        let mut doc = Document::new();
+        let mut schema_builder = Schema::builder();
+        let bytes_field = schema_builder.add_bytes_field("bytes", STORED);
+        let schema = schema_builder.build();
+        doc.add_bytes(bytes_field, "this is a test".as_bytes());
+        let doc_json = schema.to_json(&doc);
+        assert_eq!(doc_json, r#"{"bytes":["dGhpcyBpcyBhIHRlc3Q="]}"#);
+        assert_eq!(schema.parse_document(&doc_json).unwrap(), doc);
+    }
+
     #[test]
     fn test_bytes_value_from_json() {
         let result = FieldType::Bytes(Default::default())
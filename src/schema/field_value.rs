@@ -4,12 +4,82 @@ use crate::schema::Value;
 use std::io::{self, Read, Write};
 
 /// `FieldValue` holds together a `Field` and its `Value`.
+///
+/// Contrary to the serialization of a `Value` alone, which produces
+/// plain JSON values, the serde serialization of a `FieldValue` keeps track
+/// of the type of the value, so that it can be deserialized losslessly.
 #[derive(Debug, Clone, Ord, PartialEq, Eq, PartialOrd, serde::Serialize, serde::Deserialize)]
 pub struct FieldValue {
     field: Field,
+    #[serde(with = "typed_value")]
     value: Value,
 }
 
+/// Serializes a `Value` tagged with its type,
+/// e.g. `{"I64": 3}` or `{"Date": "2020-10-12T10:00:00+00:00"}`.
+mod typed_value {
+    use crate::schema::{Facet, Value};
+    use crate::tokenizer::PreTokenizedString;
+    use chrono::Utc;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    enum TypedValueRef<'a> {
+        Str(&'a str),
+        PreTokStr(&'a PreTokenizedString),
+        U64(u64),
+        I64(i64),
+        F64(f64),
+        Date(String),
+        Facet(&'a Facet),
+        Bytes(&'a [u8]),
+    }
+
+    #[derive(Deserialize)]
+    enum TypedValue {
+        Str(String),
+        PreTokStr(PreTokenizedString),
+        U64(u64),
+        I64(i64),
+        F64(f64),
+        Date(String),
+        Facet(Facet),
+        Bytes(Vec<u8>),
+    }
+
+    pub fn serialize<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+        let typed_value = match value {
+            Value::Str(text) => TypedValueRef::Str(text),
+            Value::PreTokStr(tok_str) => TypedValueRef::PreTokStr(tok_str),
+            Value::U64(val) => TypedValueRef::U64(*val),
+            Value::I64(val) => TypedValueRef::I64(*val),
+            Value::F64(val) => TypedValueRef::F64(*val),
+            Value::Date(date) => TypedValueRef::Date(date.to_rfc3339()),
+            Value::Facet(facet) => TypedValueRef::Facet(facet),
+            Value::Bytes(bytes) => TypedValueRef::Bytes(bytes),
+        };
+        typed_value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        let value = match TypedValue::deserialize(deserializer)? {
+            TypedValue::Str(text) => Value::Str(text),
+            TypedValue::PreTokStr(tok_str) => Value::PreTokStr(tok_str),
+            TypedValue::U64(val) => Value::U64(val),
+            TypedValue::I64(val) => Value::I64(val),
+            TypedValue::F64(val) => Value::F64(val),
+            TypedValue::Date(date) => {
+                let date =
+                    chrono::DateTime::parse_from_rfc3339(&date).map_err(de::Error::custom)?;
+                Value::Date(date.with_timezone(&Utc))
+            }
+            TypedValue::Facet(facet) => Value::Facet(facet),
+            TypedValue::Bytes(bytes) => Value::Bytes(bytes),
+        };
+        Ok(value)
+    }
+}
+
 impl FieldValue {
     /// Constructor
     pub fn new(field: Field, value: Value) -> FieldValue {
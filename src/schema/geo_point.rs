@@ -0,0 +1,222 @@
+use crate::TantivyError;
+
+/// Mean radius of the Earth, in meters.
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Number of cells the longitude is quantized into.
+const NUM_LON_CELLS: f64 = 4_294_967_296.0;
+
+/// Number of cells the latitude is quantized into.
+///
+/// The last cell is left out, so that the encodings of the points,
+/// which are offset by one, do not overflow.
+const NUM_LAT_CELLS: f64 = NUM_LON_CELLS - 1.0;
+
+/// Value single-valued fast fields hold for the documents without a point.
+///
+/// It is not the encoding of any point.
+pub(crate) const MISSING_POINT_VAL: u64 = 0;
+
+/// A point on the Earth, given by its latitude and longitude in degrees.
+///
+/// Geo points are indexed into `u64` fields: the latitude and the longitude
+/// are quantized over 32 bits each, and their bits are interleaved along a
+/// Z-order curve. The precision of the encoding is below a centimeter.
+/// The encodings are offset by one, so that `0` is not the encoding of any point.
+///
+/// The field has to be a `u64` fast field for the points to be filtered
+/// with a [`BoundingBoxQuery`](../query/struct.BoundingBoxQuery.html), or
+/// sorted by distance with
+/// [`TopDocs::order_by_geo_distance`](../collector/struct.TopDocs.html#method.order_by_geo_distance).
+///
+/// The documents without a point hold the value `0` in a single-valued fast
+/// field: they are treated as missing by the queries and collectors.
+///
+/// ```rust
+/// use tantivy::schema::{GeoPoint, Schema, FAST, STORED};
+/// use tantivy::Document;
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let location = schema_builder.add_u64_field("location", FAST | STORED);
+/// let paris = GeoPoint::new(48.8566, 2.3522)?;
+/// let mut doc = Document::new();
+/// doc.add_geo_point(location, paris);
+/// let stored = GeoPoint::from_u64(doc.get_first(location).unwrap().u64_value().unwrap());
+/// assert!(stored.distance(&paris) < 0.01);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeoPoint {
+    lat: f64,
+    lon: f64,
+}
+
+impl GeoPoint {
+    /// Creates a geo point.
+    ///
+    /// The latitude has to be within `[-90, 90]` and the longitude
+    /// within `[-180, 180]`, otherwise an `InvalidArgument` error is returned.
+    pub fn new(lat: f64, lon: f64) -> crate::Result<GeoPoint> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The latitude has to be within [-90, 90], got {}.",
+                lat
+            )));
+        }
+        if !(-180.0..=180.0).contains(&lon) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The longitude has to be within [-180, 180], got {}.",
+                lon
+            )));
+        }
+        Ok(GeoPoint { lat, lon })
+    }
+
+    /// Returns the latitude, in degrees.
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    /// Returns the longitude, in degrees.
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// Encodes the point into the `u64` indexed for it.
+    ///
+    /// The encoding is never `0`.
+    pub fn to_u64(&self) -> u64 {
+        encode(quantize_lat(self.lat), quantize_lon(self.lon))
+    }
+
+    /// Decodes a point from its `u64` encoding.
+    ///
+    /// `0`, which is not the encoding of any point, is decoded as `(-90, -180)`.
+    pub fn from_u64(val: u64) -> GeoPoint {
+        let (lat_cell, lon_cell) = decode(val).unwrap_or((0, 0));
+        // The point is decoded at the center of its cell.
+        GeoPoint {
+            lat: (lat_cell as f64 + 0.5) / NUM_LAT_CELLS * 180.0 - 90.0,
+            lon: (lon_cell as f64 + 0.5) / NUM_LON_CELLS * 360.0 - 180.0,
+        }
+    }
+
+    /// Returns the great-circle distance to another point, in meters.
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let lat = self.lat.to_radians();
+        let other_lat = other.lat.to_radians();
+        let half_delta_lat = (other_lat - lat) / 2.0;
+        let half_delta_lon = (other.lon - self.lon).to_radians() / 2.0;
+        let a = half_delta_lat.sin().powi(2)
+            + lat.cos() * other_lat.cos() * half_delta_lon.sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+}
+
+fn quantize(ratio: f64, num_cells: f64) -> u32 {
+    (ratio * num_cells).min(num_cells - 1.0) as u32
+}
+
+pub(crate) fn quantize_lat(lat: f64) -> u32 {
+    quantize((lat + 90.0) / 180.0, NUM_LAT_CELLS)
+}
+
+pub(crate) fn quantize_lon(lon: f64) -> u32 {
+    quantize((lon + 180.0) / 360.0, NUM_LON_CELLS)
+}
+
+/// Spreads the 32 bits of `val` over the even bits of a `u64`.
+fn spread(val: u32) -> u64 {
+    let mut val = u64::from(val);
+    val = (val | (val << 16)) & 0x0000_FFFF_0000_FFFF;
+    val = (val | (val << 8)) & 0x00FF_00FF_00FF_00FF;
+    val = (val | (val << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    val = (val | (val << 2)) & 0x3333_3333_3333_3333;
+    (val | (val << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Inverse of `spread`: gathers the even bits of `val`.
+fn gather(val: u64) -> u32 {
+    let mut val = val & 0x5555_5555_5555_5555;
+    val = (val | (val >> 1)) & 0x3333_3333_3333_3333;
+    val = (val | (val >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    val = (val | (val >> 4)) & 0x00FF_00FF_00FF_00FF;
+    val = (val | (val >> 8)) & 0x0000_FFFF_0000_FFFF;
+    ((val | (val >> 16)) & 0x0000_0000_FFFF_FFFF) as u32
+}
+
+/// Interleaves the cells of the latitude (odd bits) and of the longitude (even bits).
+fn interleave(lat_cell: u32, lon_cell: u32) -> u64 {
+    (spread(lat_cell) << 1) | spread(lon_cell)
+}
+
+/// Inverse of `interleave`.
+fn deinterleave(val: u64) -> (u32, u32) {
+    (gather(val >> 1), gather(val))
+}
+
+/// Encodes the cells of a point, offsetting their interleaving by one
+/// to keep `MISSING_POINT_VAL` apart.
+///
+/// The result is increasing with both the latitude and the longitude.
+pub(crate) fn encode(lat_cell: u32, lon_cell: u32) -> u64 {
+    interleave(lat_cell, lon_cell) + 1
+}
+
+/// Returns the cells of the latitude and of the longitude of an encoded point,
+/// or `None` for `MISSING_POINT_VAL`.
+pub(crate) fn decode(val: u64) -> Option<(u32, u32)> {
+    val.checked_sub(1).map(deinterleave)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode, deinterleave, encode, interleave, quantize_lat, GeoPoint, MISSING_POINT_VAL,
+    };
+
+    #[test]
+    fn test_geo_point_encoding() {
+        assert_eq!(interleave(0b11, 0b01), 0b1011);
+        for &(lat_cell, lon_cell) in &[(0, 0), (1, 2), (u32::MAX, 0), (123_456, u32::MAX)] {
+            assert_eq!(
+                deinterleave(interleave(lat_cell, lon_cell)),
+                (lat_cell, lon_cell)
+            );
+        }
+        for &(lat, lon) in &[
+            (0.0, 0.0),
+            (-90.0, -180.0),
+            (90.0, 180.0),
+            (35.6762, 139.6503),
+        ] {
+            let point = GeoPoint::new(lat, lon).unwrap();
+            let decoded = GeoPoint::from_u64(point.to_u64());
+            assert!((decoded.lat() - lat).abs() < 1e-7);
+            assert!((decoded.lon() - lon).abs() < 1e-7);
+        }
+        assert_eq!(decode(MISSING_POINT_VAL), None);
+        let south_west = GeoPoint::new(-90.0, -180.0).unwrap();
+        assert_ne!(south_west.to_u64(), MISSING_POINT_VAL);
+        assert_eq!(decode(south_west.to_u64()), Some((0, 0)));
+        // The largest encoding does not overflow.
+        let max_val = encode(quantize_lat(90.0), u32::MAX);
+        assert_eq!(GeoPoint::new(90.0, 180.0).unwrap().to_u64(), max_val);
+        assert!(max_val < u64::MAX);
+        assert!(GeoPoint::new(90.5, 0.0).is_err());
+        assert!(GeoPoint::new(0.0, -180.5).is_err());
+    }
+
+    #[test]
+    fn test_geo_point_distance() {
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+        let london = GeoPoint::new(51.5074, -0.1278).unwrap();
+        assert!((paris.distance(&london) - 343_500.0).abs() < 1_000.0);
+        assert_eq!(paris.distance(&paris), 0.0);
+        let north_pole = GeoPoint::new(90.0, 0.0).unwrap();
+        let south_pole = GeoPoint::new(-90.0, 0.0).unwrap();
+        assert!((north_pole.distance(&south_pole) - 20_015_114.0).abs() < 1.0);
+    }
+}
@@ -12,6 +12,69 @@ pub enum Cardinality {
     /// This is more memory and CPU expensive than the SingleValue solution.
     #[serde(rename = "multi")]
     MultiValues,
+    /// The document can have at most one value associated to the document.
+    ///
+    /// Contrary to `SingleValue`, a document without any value is not
+    /// associated to a default value: the reader makes it possible to
+    /// distinguish a missing value from a `0` value.
+    ///
+    /// Internally, optional fast fields are stored like multivalued fast fields:
+    /// an index column associates each document to the position of its
+    /// value (if any) in a dense column of values.
+    /// If more than one value is associated to the document, only the first
+    /// one is kept.
+    #[serde(rename = "optional")]
+    Optional,
+}
+
+/// Precision of the values of a date fast field.
+///
+/// Dates are stored in fast fields with a precision of one second.
+/// A coarser precision truncates the timestamps stored in the fast field,
+/// which is useful for fields that are only used to group or filter
+/// documents by minute, hour or day.
+///
+/// The stored and indexed values are not affected. The included bounds of a range
+/// query answered from the fast field are truncated to the same precision, while its
+/// excluded bounds are compared as is to the truncated values.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum DatePrecision {
+    /// Timestamps are kept as is.
+    #[default]
+    #[serde(rename = "seconds")]
+    Seconds,
+    /// Timestamps are truncated to the minute.
+    #[serde(rename = "minutes")]
+    Minutes,
+    /// Timestamps are truncated to the hour.
+    #[serde(rename = "hours")]
+    Hours,
+    /// Timestamps are truncated to the day.
+    #[serde(rename = "days")]
+    Days,
+}
+
+impl DatePrecision {
+    fn is_seconds(&self) -> bool {
+        *self == DatePrecision::Seconds
+    }
+
+    fn num_seconds(self) -> i64 {
+        match self {
+            DatePrecision::Seconds => 1,
+            DatePrecision::Minutes => 60,
+            DatePrecision::Hours => 3_600,
+            DatePrecision::Days => 86_400,
+        }
+    }
+
+    /// Truncates a timestamp (expressed in seconds) to the precision.
+    ///
+    /// Timestamps before the epoch are rounded down as well.
+    pub fn truncate(self, timestamp: i64) -> i64 {
+        let num_seconds = self.num_seconds();
+        timestamp.div_euclid(num_seconds) * num_seconds
+    }
 }
 
 /// Define how an u64, i64, of f64 field should be handled by tantivy.
@@ -21,6 +84,8 @@ pub struct IntOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     fast: Option<Cardinality>,
     stored: bool,
+    #[serde(default, skip_serializing_if = "DatePrecision::is_seconds")]
+    precision: DatePrecision,
 }
 
 impl IntOptions {
@@ -75,6 +140,19 @@ impl IntOptions {
     pub fn get_fastfield_cardinality(&self) -> Option<Cardinality> {
         self.fast
     }
+
+    /// Sets the precision of the values of a date fast field.
+    ///
+    /// This option is ignored for non-date fields.
+    pub fn set_date_precision(mut self, precision: DatePrecision) -> IntOptions {
+        self.precision = precision;
+        self
+    }
+
+    /// Returns the precision of the values of a date fast field.
+    pub fn get_date_precision(&self) -> DatePrecision {
+        self.precision
+    }
 }
 
 impl Default for IntOptions {
@@ -83,6 +161,7 @@ impl Default for IntOptions {
             indexed: false,
             stored: false,
             fast: None,
+            precision: DatePrecision::Seconds,
         }
     }
 }
@@ -99,6 +178,7 @@ impl From<FastFlag> for IntOptions {
             indexed: false,
             stored: false,
             fast: Some(Cardinality::SingleValue),
+            precision: DatePrecision::Seconds,
         }
     }
 }
@@ -109,6 +189,7 @@ impl From<StoredFlag> for IntOptions {
             indexed: false,
             stored: true,
             fast: None,
+            precision: DatePrecision::Seconds,
         }
     }
 }
@@ -119,6 +200,7 @@ impl From<IndexedFlag> for IntOptions {
             indexed: true,
             stored: false,
             fast: None,
+            precision: DatePrecision::Seconds,
         }
     }
 }
@@ -132,6 +214,7 @@ impl<T: Into<IntOptions>> BitOr<T> for IntOptions {
             indexed: self.indexed | other.indexed,
             stored: self.stored | other.stored,
             fast: self.fast.or(other.fast),
+            precision: self.precision.max(other.precision),
         }
     }
 }
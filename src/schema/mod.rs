@@ -104,6 +104,7 @@ let schema = schema_builder.build();
 
 mod document;
 mod facet;
+pub(crate) mod geo_point;
 mod schema;
 mod term;
 
@@ -128,6 +129,7 @@ pub use self::value::Value;
 
 pub use self::facet::Facet;
 pub(crate) use self::facet::FACET_SEP_BYTE;
+pub use self::geo_point::GeoPoint;
 
 pub use self::document::Document;
 pub use self::field::Field;
@@ -146,6 +148,7 @@ pub use self::text_options::TEXT;
 pub use self::bytes_options::BytesOptions;
 pub use self::flags::{FAST, INDEXED, STORED};
 pub use self::int_options::Cardinality;
+pub use self::int_options::DatePrecision;
 pub use self::int_options::IntOptions;
 
 use once_cell::sync::Lazy;
@@ -1,5 +1,5 @@
 use crate::schema::Value;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 /// Internal representation of a document used for JSON
@@ -7,6 +7,13 @@ use std::collections::BTreeMap;
 ///
 /// A `NamedFieldDocument` is a simple representation of a document
 /// as a `BTreeMap<String, Vec<Value>>`.
+/// It is obtained from a `Document` with `Schema::to_named_doc`,
+/// and converted back with `Schema::convert_named_doc`.
 ///
-#[derive(Serialize)]
+/// It can be returned as is by an HTTP API, as it serializes into a JSON object
+/// keyed by field name.
+/// When deserialized, values are read as plain JSON values: dates, facets
+/// and bytes are read as strings. Use `Schema::parse_document` to get the values
+/// with the types of the fields of the schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NamedFieldDocument(pub BTreeMap<String, Vec<Value>>);
@@ -590,6 +590,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_named_doc_serde() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let schema = schema_builder.build();
+        let doc = doc!(title => "hello", title => "happy", count => 3u64);
+        let named_doc = schema.to_named_doc(&doc);
+        let named_doc_json = serde_json::to_string(&named_doc).unwrap();
+        assert_eq!(named_doc_json, r#"{"count":[3],"title":["hello","happy"]}"#);
+        let deserialized_named_doc: NamedFieldDocument =
+            serde_json::from_str(&named_doc_json).unwrap();
+        assert_eq!(deserialized_named_doc, named_doc);
+        assert_eq!(
+            schema.convert_named_doc(deserialized_named_doc).unwrap(),
+            doc
+        );
+    }
+
     #[test]
     pub fn test_parse_document() {
         let mut schema_builder = Schema::builder();
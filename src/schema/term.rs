@@ -4,6 +4,8 @@ use super::Field;
 use crate::common;
 use crate::schema::Facet;
 use crate::DateTime;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::str;
 
 /// Size (in bytes) of the buffer of a int field.
@@ -229,6 +231,56 @@ where
     }
 }
 
+impl<B> Serialize for Term<B>
+where
+    B: AsRef<[u8]>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.as_slice())
+    }
+}
+
+impl<'de> Deserialize<'de> for Term {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TermVisitor;
+
+        impl<'de> Visitor<'de> for TermVisitor {
+            type Value = Term;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("the bytes of a term, starting with a 4 bytes field id")
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Term, E> {
+                self.visit_byte_buf(bytes.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Term, E> {
+                if bytes.len() < 4 {
+                    return Err(E::invalid_length(bytes.len(), &self));
+                }
+                Ok(Term::wrap(bytes))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Term, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                self.visit_byte_buf(bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(TermVisitor)
+    }
+}
+
 impl fmt::Debug for Term {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -272,4 +324,15 @@ mod tests {
             assert_eq!(term.as_slice()[11], (983u64 % 256u64) as u8);
         }
     }
+
+    #[test]
+    fn test_term_serde() {
+        let field = Field::from_field_id(3);
+        let term = Term::from_field_text(field, "tantivy");
+        let term_json = serde_json::to_string(&term).unwrap();
+        let deserialized_term: Term = serde_json::from_str(&term_json).unwrap();
+        assert_eq!(deserialized_term, term);
+        assert_eq!(deserialized_term.text(), "tantivy");
+        assert!(serde_json::from_str::<Term>("[0, 0, 3]").is_err());
+    }
 }
@@ -1,5 +1,5 @@
 use crate::schema::flags::SchemaFlagList;
-use crate::schema::flags::StoredFlag;
+use crate::schema::flags::{FastFlag, StoredFlag};
 use crate::schema::IndexRecordOption;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
@@ -10,6 +10,12 @@ use std::ops::BitOr;
 pub struct TextOptions {
     indexing: Option<TextFieldIndexing>,
     stored: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    fast: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !*val
 }
 
 impl TextOptions {
@@ -23,12 +29,31 @@ impl TextOptions {
         self.stored
     }
 
+    /// Returns true iff the term ordinals of the text are stored in a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+
     /// Sets the field as stored
     pub fn set_stored(mut self) -> TextOptions {
         self.stored = true;
         self
     }
 
+    /// Sets the field as a fast field.
+    ///
+    /// For each document, the ordinals of its terms in the segment's term
+    /// dictionary are stored in a multivalued `u64` fast field. They can be
+    /// accessed via the [`StrFastFieldReader`](../fastfield/struct.StrFastFieldReader.html).
+    ///
+    /// The ordinals point to the term dictionary of the field, so a fast
+    /// text field should also be indexed. This is typically used with the
+    /// `STRING` flag, to sort or group documents by keyword.
+    pub fn set_fast(mut self) -> TextOptions {
+        self.fast = true;
+        self
+    }
+
     /// Sets the field as indexed, with the specific indexing options.
     pub fn set_indexing_options(mut self, indexing: TextFieldIndexing) -> TextOptions {
         self.indexing = Some(indexing);
@@ -41,6 +66,7 @@ impl Default for TextOptions {
         TextOptions {
             indexing: None,
             stored: false,
+            fast: false,
         }
     }
 }
@@ -51,10 +77,13 @@ impl Default for TextOptions {
 /// - the amount of information that should be stored about the presence of a term in a document.
 /// Essentially, should we store the term frequency and/or the positions (See [`IndexRecordOption`](./enum.IndexRecordOption.html)).
 /// - the name of the `Tokenizer` that should be used to process the field.
+/// - optionally, the name of a different `Tokenizer` used to process the queries on the field.
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     record: IndexRecordOption,
     tokenizer: Cow<'static, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    search_tokenizer: Option<Cow<'static, str>>,
 }
 
 impl Default for TextFieldIndexing {
@@ -62,6 +91,7 @@ impl Default for TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: Cow::Borrowed("default"),
             record: IndexRecordOption::Basic,
+            search_tokenizer: None,
         }
     }
 }
@@ -78,6 +108,24 @@ impl TextFieldIndexing {
         &self.tokenizer
     }
 
+    /// Sets the tokenizer to be used by the `QueryParser` for the given field,
+    /// in place of the tokenizer used at indexing time.
+    ///
+    /// For instance, a field indexed with the edge n-grams of its words,
+    /// for autocompletion, is typically searched with the plain words typed
+    /// by the user.
+    pub fn set_search_tokenizer(mut self, tokenizer_name: &str) -> TextFieldIndexing {
+        self.search_tokenizer = Some(Cow::Owned(tokenizer_name.to_string()));
+        self
+    }
+
+    /// Returns the tokenizer that will be used to process the queries on this field.
+    ///
+    /// Unless it was set with `set_search_tokenizer`, this is the same as `tokenizer()`.
+    pub fn search_tokenizer(&self) -> &str {
+        self.search_tokenizer.as_deref().unwrap_or(&self.tokenizer)
+    }
+
     /// Sets which information should be indexed with the tokens.
     ///
     /// See [IndexRecordOption](./enum.IndexRecordOption.html) for more detail.
@@ -99,8 +147,10 @@ pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("raw"),
         record: IndexRecordOption::Basic,
+        search_tokenizer: None,
     }),
     stored: false,
+    fast: false,
 };
 
 /// The field will be tokenized and indexed
@@ -108,8 +158,10 @@ pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("default"),
         record: IndexRecordOption::WithFreqsAndPositions,
+        search_tokenizer: None,
     }),
     stored: false,
+    fast: false,
 };
 
 impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
@@ -120,6 +172,7 @@ impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
         TextOptions {
             indexing: self.indexing.or(other.indexing),
             stored: self.stored | other.stored,
+            fast: self.fast | other.fast,
         }
     }
 }
@@ -135,6 +188,17 @@ impl From<StoredFlag> for TextOptions {
         TextOptions {
             indexing: None,
             stored: true,
+            fast: false,
+        }
+    }
+}
+
+impl From<FastFlag> for TextOptions {
+    fn from(_: FastFlag) -> TextOptions {
+        TextOptions {
+            indexing: None,
+            stored: false,
+            fast: true,
         }
     }
 }
@@ -169,6 +233,41 @@ mod tests {
                 if text_options.get_indexing_options().unwrap().tokenizer() == "default"));
     }
 
+    #[test]
+    fn test_fast_text_options() {
+        let field_options = STRING | FAST;
+        assert!(field_options.is_fast());
+        assert!(!field_options.is_stored());
+        assert!(field_options.get_indexing_options().is_some());
+        assert!(!(STRING | STORED).is_fast());
+        let json = serde_json::to_string(&field_options).unwrap();
+        assert!(json.ends_with(r#""stored":false,"fast":true}"#));
+        assert_eq!(
+            serde_json::from_str::<TextOptions>(&json).unwrap(),
+            field_options
+        );
+        // The flag is only serialized for fast fields.
+        let json = serde_json::to_string(&STRING).unwrap();
+        assert!(json.ends_with(r#""stored":false}"#));
+        assert!(!serde_json::from_str::<TextOptions>(&json)
+            .unwrap()
+            .is_fast());
+    }
+
+    #[test]
+    fn test_search_tokenizer() {
+        let indexing = TextFieldIndexing::default().set_tokenizer("ngram");
+        assert_eq!(indexing.search_tokenizer(), "ngram");
+        let json = serde_json::to_string(&indexing).unwrap();
+        assert!(!json.contains("search_tokenizer"));
+        let indexing = indexing.set_search_tokenizer("raw");
+        assert_eq!(indexing.tokenizer(), "ngram");
+        assert_eq!(indexing.search_tokenizer(), "raw");
+        let json = serde_json::to_string(&indexing).unwrap();
+        let deserialized: TextFieldIndexing = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, indexing);
+    }
+
     #[test]
     fn test_cmp_index_record_option() {
         assert!(IndexRecordOption::WithFreqsAndPositions > IndexRecordOption::WithFreqs);
@@ -1,4 +1,4 @@
-use crate::schema::Facet;
+use crate::schema::{Facet, GeoPoint};
 use crate::tokenizer::PreTokenizedString;
 use crate::DateTime;
 use serde::de::Visitor;
@@ -7,6 +7,11 @@ use std::{cmp::Ordering, fmt};
 
 /// Value represents the value of a any field.
 /// It is an enum over all over all of the possible field type.
+///
+/// Values are serialized as untagged JSON values: dates are serialized as
+/// RFC 3339 strings and bytes as base64 strings, so that they are deserialized
+/// back as `Value::Str`. The type of the field is required to get the original
+/// value back, as done by `Schema::parse_document`.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     /// The str type is used for any text information.
@@ -82,7 +87,7 @@ impl Serialize for Value {
             Value::F64(u) => serializer.serialize_f64(u),
             Value::Date(ref date) => serializer.serialize_str(&date.to_rfc3339()),
             Value::Facet(ref facet) => facet.serialize(serializer),
-            Value::Bytes(ref bytes) => serializer.serialize_bytes(bytes),
+            Value::Bytes(ref bytes) => serializer.serialize_str(&base64::encode(bytes)),
         }
     }
 }
@@ -234,6 +239,12 @@ impl From<crate::DateTime> for Value {
     }
 }
 
+impl From<GeoPoint> for Value {
+    fn from(geo_point: GeoPoint) -> Value {
+        Value::U64(geo_point.to_u64())
+    }
+}
+
 impl<'a> From<&'a str> for Value {
     fn from(s: &'a str) -> Value {
         Value::Str(s.to_string())
@@ -266,7 +277,7 @@ impl From<PreTokenizedString> for Value {
 
 mod binary_serialize {
     use super::Value;
-    use crate::common::{f64_to_u64, u64_to_f64, BinarySerializable};
+    use crate::common::{f64_to_u64, u64_to_f64, BinarySerializable, VInt};
     use crate::schema::Facet;
     use crate::tokenizer::PreTokenizedString;
     use chrono::{TimeZone, Utc};
@@ -285,6 +296,38 @@ mod binary_serialize {
 
     const TOK_STR_CODE: u8 = 0;
 
+    impl Value {
+        /// Advances `reader` past a serialized value, without deserializing it.
+        pub(crate) fn skip_serialized(reader: &mut &[u8]) -> io::Result<()> {
+            let mut type_code = u8::deserialize(reader)?;
+            if type_code == EXT_CODE {
+                // All of the extended types are serialized as strings.
+                u8::deserialize(reader)?;
+                type_code = TEXT_CODE;
+            }
+            let num_bytes = match type_code {
+                TEXT_CODE | HIERARCHICAL_FACET_CODE | BYTES_CODE => {
+                    VInt::deserialize(reader)?.val() as usize
+                }
+                U64_CODE | I64_CODE | F64_CODE | DATE_CODE => 8,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("No field type is associated with code {:?}", type_code),
+                    ));
+                }
+            };
+            if num_bytes > reader.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Value is truncated.",
+                ));
+            }
+            *reader = &reader[num_bytes..];
+            Ok(())
+        }
+    }
+
     impl BinarySerializable for Value {
         fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
             match *self {
@@ -400,4 +443,14 @@ mod tests {
         let serialized_value_json = serde_json::to_string_pretty(&value).unwrap();
         assert_eq!(serialized_value_json, r#""1996-12-20T00:39:57+00:00""#);
     }
+
+    #[test]
+    fn test_serialize_bytes() {
+        let value = Value::Bytes(b"tantivy".to_vec());
+        let serialized_value_json = serde_json::to_string(&value).unwrap();
+        assert_eq!(serialized_value_json, r#""dGFudGl2eQ==""#);
+        // The type of the value is not part of the JSON.
+        let deserialized_value: Value = serde_json::from_str(&serialized_value_json).unwrap();
+        assert_eq!(deserialized_value, Value::Str("dGFudGl2eQ==".to_string()));
+    }
 }
@@ -91,14 +91,23 @@ impl Snippet {
 
     /// Returns a hignlightned html from the `Snippet`.
     pub fn to_html(&self) -> String {
+        self.to_html_with_tags(HIGHLIGHTEN_PREFIX, HIGHLIGHTEN_POSTFIX)
+    }
+
+    /// Returns a hignlightned html from the `Snippet`, surrounding
+    /// highlighted sections with the given `prefix` and `postfix`
+    /// (e.g. `<em class="hit">` and `</em>`) rather than `<b>` and `</b>`.
+    ///
+    /// The text of the snippet is html-escaped, but the prefix and postfix are not.
+    pub fn to_html_with_tags(&self, prefix: &str, postfix: &str) -> String {
         let mut html = String::new();
         let mut start_from: usize = 0;
 
         for item in self.highlighted.iter() {
             html.push_str(&encode_minimal(&self.fragments[start_from..item.start]));
-            html.push_str(HIGHLIGHTEN_PREFIX);
+            html.push_str(prefix);
             html.push_str(&encode_minimal(&self.fragments[item.start..item.stop]));
-            html.push_str(HIGHLIGHTEN_POSTFIX);
+            html.push_str(postfix);
             start_from = item.stop;
         }
         html.push_str(&encode_minimal(
@@ -419,6 +428,18 @@ Survey in 2016, 2017, and 2018."#;
         let snippet = select_best_fragment_combination(&fragments[..], &text);
         assert_eq!(snippet.fragments, "c d");
         assert_eq!(snippet.to_html(), "<b>c</b> d");
+        assert_eq!(
+            snippet.to_html_with_tags("<em class=\"hit\">", "</em>"),
+            "<em class=\"hit\">c</em> d"
+        );
+        assert_eq!(
+            snippet
+                .highlighted()
+                .iter()
+                .map(|section| section.bounds())
+                .collect::<Vec<_>>(),
+            vec![(0, 1)]
+        );
     }
 
     #[test]
@@ -54,6 +54,16 @@ impl SearcherSpaceUsage {
         &self.segments[..]
     }
 
+    /// Returns the byte usage of the given field, summed over all of the segments.
+    ///
+    /// See `SegmentSpaceUsage::field_total`.
+    pub fn field_total(&self, field: Field) -> ByteCount {
+        self.segments
+            .iter()
+            .map(|segment| segment.field_total(field))
+            .sum()
+    }
+
     /// Returns total byte usage of this searcher, including all large subcomponents.
     /// Does not account for smaller things like `meta.json`.
     pub fn total(&self) -> ByteCount {
@@ -96,6 +106,7 @@ impl SegmentSpaceUsage {
         let total = termdict.total()
             + postings.total()
             + positions.total()
+            + positions_idx.total()
             + fast_fields.total()
             + fieldnorms.total()
             + store.total()
@@ -178,6 +189,24 @@ impl SegmentSpaceUsage {
         self.deletes
     }
 
+    /// Returns the byte usage of the given field in this segment.
+    ///
+    /// The store is not taken in account, as stored fields are
+    /// compressed together.
+    pub fn field_total(&self, field: Field) -> ByteCount {
+        [
+            &self.termdict,
+            &self.postings,
+            &self.positions,
+            &self.positions_idx,
+            &self.fast_fields,
+            &self.fieldnorms,
+        ]
+        .iter()
+        .map(|per_field_usage| per_field_usage.field_total(field))
+        .sum()
+    }
+
     /// Total space usage in bytes for this segment.
     pub fn total(&self) -> ByteCount {
         self.total
@@ -237,6 +266,11 @@ impl PerFieldSpaceUsage {
         self.fields.iter()
     }
 
+    /// Bytes used by the given field, or 0 if the field is absent.
+    pub fn field_total(&self, field: Field) -> ByteCount {
+        self.fields.get(&field).map(FieldUsage::total).unwrap_or(0)
+    }
+
     /// Bytes used by the represented file
     pub fn total(&self) -> ByteCount {
         self.total
@@ -489,4 +523,50 @@ mod test {
         assert!(segment_space_usage.deletes() > 0);
         Ok(())
     }
+
+    #[test]
+    fn test_field_total() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_text_field("name", TEXT);
+        let num = schema_builder.add_u64_field("num", FAST | INDEXED);
+        let absent = schema_builder.add_u64_field("absent", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(name => "hello happy tax payer", num => 1u64));
+        index_writer.add_document(doc!(name => "happy", num => 3u64));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(name => "hello", num => 2u64));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(searcher.num_deleted_docs(), 0);
+        let searcher_space_usage = searcher.space_usage()?;
+        assert_eq!(2, searcher_space_usage.segments().len());
+        for segment in searcher_space_usage.segments() {
+            assert!(segment.field_total(name) > 0);
+            assert!(segment.field_total(num) > 0);
+            assert_eq!(segment.field_total(absent), 0);
+        }
+        let field_totals: ByteCount = [name, num]
+            .iter()
+            .map(|&field| searcher_space_usage.field_total(field))
+            .sum();
+        let segments_store_and_deletes: ByteCount = searcher_space_usage
+            .segments()
+            .iter()
+            .map(|segment| segment.store().total() + segment.deletes())
+            .sum();
+        assert_eq!(
+            field_totals + segments_store_and_deletes,
+            searcher_space_usage.total()
+        );
+
+        index_writer.delete_term(Term::from_field_u64(num, 1u64));
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        assert_eq!(searcher.num_deleted_docs(), 1);
+        Ok(())
+    }
 }
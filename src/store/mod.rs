@@ -38,6 +38,7 @@ mod reader;
 mod writer;
 pub use self::reader::StoreReader;
 pub use self::writer::StoreWriter;
+pub(crate) use self::writer::DEFAULT_BLOCK_SIZE;
 
 #[cfg(all(feature = "lz4", feature = "brotli"))]
 compile_error!("feature `lz4` or `brotli` must not be enabled together.");
@@ -112,6 +113,36 @@ pub mod tests {
         schema
     }
 
+    #[test]
+    fn test_store_block_size() -> crate::Result<()> {
+        let path = Path::new("store");
+        let directory = RAMDirectory::create();
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TextOptions::default().set_stored());
+        let mut store_writer = StoreWriter::with_block_size(directory.open_write(path)?, 100);
+        for i in 0..50 {
+            let title_text = format!("{} {}", i, "a".repeat(30));
+            store_writer.store(&Document::from(vec![FieldValue::new(
+                title,
+                From::from(title_text),
+            )]))?;
+        }
+        store_writer.close()?;
+        let store = StoreReader::open(directory.open_read(path)?)?;
+        let checkpoints: Vec<_> = store.block_checkpoints().collect();
+        // A block is closed once it exceeds 100 bytes, i.e. every 3 documents.
+        assert_eq!(checkpoints.len(), 17);
+        assert!(checkpoints[..16]
+            .iter()
+            .all(|checkpoint| checkpoint.end_doc - checkpoint.start_doc == 3));
+        for i in 0..50 {
+            let doc = store.get(i)?;
+            let title_text = doc.get_first(title).unwrap().text().unwrap();
+            assert!(title_text.starts_with(&format!("{} ", i)));
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_store() -> crate::Result<()> {
         let path = Path::new("store");
@@ -3,7 +3,7 @@ use super::index::SkipIndex;
 use crate::common::VInt;
 use crate::common::{BinarySerializable, HasLen};
 use crate::directory::{FileSlice, OwnedBytes};
-use crate::schema::Document;
+use crate::schema::{Document, Field, FieldValue, Value};
 use crate::space_usage::StoreSpaceUsage;
 use crate::store::index::Checkpoint;
 use crate::DocId;
@@ -20,6 +20,9 @@ type Block = Arc<Vec<u8>>;
 type BlockCache = Arc<Mutex<LruCache<u64, Block>>>;
 
 /// Reads document off tantivy's [`Store`](./index.html)
+///
+/// Clones share the same cache of decompressed blocks.
+#[derive(Clone)]
 pub struct StoreReader {
     data: FileSlice,
     cache: BlockCache,
@@ -67,15 +70,20 @@ impl StoreReader {
             .read_bytes()
     }
 
-    fn read_block(&self, checkpoint: &Checkpoint) -> io::Result<Block> {
+    fn cached_block(&self, checkpoint: &Checkpoint) -> Option<Block> {
         if let Some(block) = self.cache.lock().unwrap().get(&checkpoint.start_offset) {
             self.cache_hits.fetch_add(1, Ordering::SeqCst);
-            return Ok(block.clone());
+            return Some(block.clone());
         }
-
         self.cache_misses.fetch_add(1, Ordering::SeqCst);
+        None
+    }
 
-        let compressed_block = self.compressed_block(checkpoint)?;
+    fn decompress_block(
+        &self,
+        checkpoint: &Checkpoint,
+        compressed_block: OwnedBytes,
+    ) -> io::Result<Block> {
         let mut decompressed_block = vec![];
         decompress(compressed_block.as_slice(), &mut decompressed_block)?;
 
@@ -88,6 +96,29 @@ impl StoreReader {
         Ok(block)
     }
 
+    fn read_block(&self, checkpoint: &Checkpoint) -> io::Result<Block> {
+        if let Some(block) = self.cached_block(checkpoint) {
+            return Ok(block);
+        }
+        let compressed_block = self.compressed_block(checkpoint)?;
+        self.decompress_block(checkpoint, compressed_block)
+    }
+
+    #[cfg(feature = "async-read")]
+    async fn read_block_async(&self, checkpoint: &Checkpoint) -> io::Result<Block> {
+        if let Some(block) = self.cached_block(checkpoint) {
+            return Ok(block);
+        }
+        let compressed_block = self
+            .data
+            .read_bytes_slice_async(
+                checkpoint.start_offset as usize,
+                checkpoint.end_offset as usize,
+            )
+            .await?;
+        self.decompress_block(checkpoint, compressed_block)
+    }
+
     /// Reads a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
@@ -96,18 +127,37 @@ impl StoreReader {
     /// It should not be called to score documents
     /// for instance.
     pub fn get(&self, doc_id: DocId) -> crate::Result<Document> {
-        let checkpoint = self.block_checkpoint(doc_id).ok_or_else(|| {
-            crate::TantivyError::InvalidArgument(format!("Failed to lookup Doc #{}.", doc_id))
-        })?;
-        let mut cursor = &self.read_block(&checkpoint)?[..];
-        for _ in checkpoint.start_doc..doc_id {
-            let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
-            cursor = &cursor[doc_length..];
-        }
+        let checkpoint = self.checkpoint_or_err(doc_id)?;
+        let block = self.read_block(&checkpoint)?;
+        doc_from_block(&block, &checkpoint, doc_id, None)
+    }
 
-        let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
-        cursor = &cursor[..doc_length];
-        Ok(Document::deserialize(&mut cursor)?)
+    /// Reads the values of the given `fields` of a document.
+    ///
+    /// The other fields are skipped without being decoded, which saves
+    /// parsing large stored fields that are not needed.
+    /// Note that the entire block containing the document still needs to be decompressed.
+    pub fn get_fields(&self, doc_id: DocId, fields: &[Field]) -> crate::Result<Document> {
+        let checkpoint = self.checkpoint_or_err(doc_id)?;
+        let block = self.read_block(&checkpoint)?;
+        doc_from_block(&block, &checkpoint, doc_id, Some(fields))
+    }
+
+    /// Reads a given document asynchronously.
+    ///
+    /// This is the asynchronous version of `.get(doc)`: the
+    /// compressed block is read via `FileSlice::read_bytes_async`.
+    #[cfg(feature = "async-read")]
+    pub async fn get_async(&self, doc_id: DocId) -> crate::Result<Document> {
+        let checkpoint = self.checkpoint_or_err(doc_id)?;
+        let block = self.read_block_async(&checkpoint).await?;
+        doc_from_block(&block, &checkpoint, doc_id, None)
+    }
+
+    fn checkpoint_or_err(&self, doc_id: DocId) -> crate::Result<Checkpoint> {
+        self.block_checkpoint(doc_id).ok_or_else(|| {
+            crate::TantivyError::InvalidArgument(format!("Failed to lookup Doc #{}.", doc_id))
+        })
     }
 
     /// Summarize total space usage of this store reader.
@@ -116,6 +166,41 @@ impl StoreReader {
     }
 }
 
+fn doc_from_block(
+    block: &[u8],
+    checkpoint: &Checkpoint,
+    doc_id: DocId,
+    fields: Option<&[Field]>,
+) -> crate::Result<Document> {
+    let mut cursor = block;
+    for _ in checkpoint.start_doc..doc_id {
+        let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
+        cursor = &cursor[doc_length..];
+    }
+
+    let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
+    cursor = &cursor[..doc_length];
+    match fields {
+        Some(fields) => Ok(deserialize_fields(&mut cursor, fields)?),
+        None => Ok(Document::deserialize(&mut cursor)?),
+    }
+}
+
+/// Deserializes the values of `fields` of a document, and skips the others.
+fn deserialize_fields(cursor: &mut &[u8], fields: &[Field]) -> io::Result<Document> {
+    let num_field_values = VInt::deserialize(cursor)?.val() as usize;
+    let mut doc = Document::default();
+    for _ in 0..num_field_values {
+        let field = Field::deserialize(cursor)?;
+        if fields.contains(&field) {
+            doc.add(FieldValue::new(field, Value::deserialize(cursor)?));
+        } else {
+            Value::skip_serialized(cursor)?;
+        }
+    }
+    Ok(doc)
+}
+
 fn split_file(data: FileSlice) -> io::Result<(FileSlice, FileSlice)> {
     let (data, footer_len_bytes) = data.split_from_end(size_of::<u64>());
     let serialized_offset: OwnedBytes = footer_len_bytes.read_bytes()?;
@@ -201,4 +286,77 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "async-read")]
+    #[test]
+    fn test_store_get_async() -> crate::Result<()> {
+        let directory = RAMDirectory::create();
+        let path = Path::new("store");
+        let writer = directory.open_write(path)?;
+        let schema = write_lorem_ipsum_store(writer, 500);
+        let title = schema.get_field("title").unwrap();
+        let store = StoreReader::open(directory.open_read(path)?)?;
+        for &doc_id in &[0, 1, 250, 499] {
+            let doc = futures::executor::block_on(store.get_async(doc_id))?;
+            assert_eq!(
+                get_text_field(&doc, &title),
+                Some(format!("Doc {}", doc_id).as_str())
+            );
+        }
+        assert!(futures::executor::block_on(store.get_async(500)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_get_fields() -> crate::Result<()> {
+        use crate::schema::Facet;
+        use crate::store::StoreWriter;
+        use crate::tokenizer::{PreTokenizedString, Token};
+
+        let field = Field::from_field_id;
+        let values = vec![
+            Value::Str("a very long body".to_string()),
+            Value::U64(1),
+            Value::I64(-2),
+            Value::F64(3.5),
+            Value::Date("2020-09-13T12:26:40Z".parse::<crate::DateTime>().unwrap()),
+            Value::Facet(Facet::from("/a/b")),
+            Value::Bytes(vec![1u8, 2u8, 3u8]),
+            Value::PreTokStr(PreTokenizedString {
+                text: "hello".to_string(),
+                tokens: vec![Token::default()],
+            }),
+        ];
+        let mut doc = Document::default();
+        for (field_id, value) in values.iter().enumerate() {
+            doc.add(FieldValue::new(field(field_id as u32), value.clone()));
+        }
+        doc.add(FieldValue::new(field(1), Value::U64(4)));
+
+        let directory = RAMDirectory::create();
+        let path = Path::new("store");
+        let mut store_writer = StoreWriter::new(directory.open_write(path)?);
+        store_writer.store(&doc)?;
+        store_writer.store(&doc)?;
+        store_writer.close()?;
+        let store = StoreReader::open(directory.open_read(path)?)?;
+
+        assert_eq!(store.get_fields(1, &[])?.len(), 0);
+        let all_fields: Vec<Field> = (0..8).map(field).collect();
+        assert_eq!(store.get_fields(1, &all_fields)?, doc);
+        for (field_id, value) in values.into_iter().enumerate() {
+            let selected_doc = store.get_fields(0, &[field(field_id as u32)])?;
+            let selected_values: Vec<&Value> = selected_doc
+                .field_values()
+                .iter()
+                .map(FieldValue::value)
+                .collect();
+            if field_id == 1 {
+                assert_eq!(selected_values, vec![&value, &Value::U64(4)]);
+            } else {
+                assert_eq!(selected_values, vec![&value]);
+            }
+        }
+        Ok(())
+    }
 }
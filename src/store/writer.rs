@@ -10,7 +10,8 @@ use crate::store::index::Checkpoint;
 use crate::DocId;
 use std::io::{self, Write};
 
-const BLOCK_SIZE: usize = 16_384;
+/// Default size of the uncompressed blocks of the store, in bytes.
+pub(crate) const DEFAULT_BLOCK_SIZE: usize = 16_384;
 
 /// Write tantivy's [`Store`](./index.html)
 ///
@@ -18,9 +19,14 @@ const BLOCK_SIZE: usize = 16_384;
 /// the store is written to disc as document as being added,
 /// as opposed to when the segment is getting finalized.
 ///
+/// Documents are buffered until they fill a block of `block_size` bytes, which
+/// is then compressed and written: the memory used by the store writer does not
+/// depend on the number of documents.
+///
 /// The skip list index on the other hand, is built in memory.
 ///
 pub struct StoreWriter {
+    block_size: usize,
     doc: DocId,
     first_doc_in_block: DocId,
     offset_index_writer: SkipIndexBuilder,
@@ -35,7 +41,17 @@ impl StoreWriter {
     /// The store writer will writes blocks on disc as
     /// document are added.
     pub fn new(writer: WritePtr) -> StoreWriter {
+        StoreWriter::with_block_size(writer, DEFAULT_BLOCK_SIZE)
+    }
+
+    /// Create a store writer with a given block size.
+    ///
+    /// Blocks are the unit of compression of the store: larger blocks compress
+    /// better, but reading a document requires to decompress its entire block.
+    /// A block is written as soon as it exceeds `block_size` bytes.
+    pub fn with_block_size(writer: WritePtr, block_size: usize) -> StoreWriter {
         StoreWriter {
+            block_size,
             doc: 0,
             first_doc_in_block: 0,
             offset_index_writer: SkipIndexBuilder::new(),
@@ -58,7 +74,7 @@ impl StoreWriter {
         self.current_block
             .write_all(&self.intermediary_buffer[..])?;
         self.doc += 1;
-        if self.current_block.len() > BLOCK_SIZE {
+        if self.current_block.len() > self.block_size {
             self.write_and_compress_block()?;
         }
         Ok(())
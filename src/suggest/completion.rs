@@ -0,0 +1,176 @@
+use crate::schema::Field;
+use crate::Searcher;
+use crate::TantivyError;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use tantivy_fst::{IntoStreamer, Map, Streamer};
+
+/// `CompletionSuggester` proposes completions for a prefix typed by a user
+/// (type-ahead), without running any query.
+///
+/// It is built from a list of weighted entries, and stored in memory as a finite
+/// state transducer. Entries starting with a given prefix are returned by
+/// decreasing weight.
+///
+/// The suggester can be built from arbitrary entries, or from the terms of a field,
+/// weighted by their document frequency. It is not built at indexing time:
+/// `for_field` streams the whole term dictionary of the field, in each segment,
+/// and should be called once per reader reload rather than for each suggestion.
+///
+/// The weights are not part of the structure used to look up the prefix:
+/// `suggest` visits all of the entries starting with the prefix before keeping
+/// the heaviest ones. Its cost is linear in the number of these entries, which is
+/// the whole suggester for an empty or one-character prefix.
+///
+/// ```rust
+/// use tantivy::CompletionSuggester;
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let completion_suggester = CompletionSuggester::build(vec![
+///     ("tantivy", 10),
+///     ("tangerine", 3),
+///     ("tango", 7),
+///     ("lucene", 8),
+/// ])?;
+/// assert_eq!(
+///     completion_suggester.suggest("tan", 2),
+///     vec![("tantivy".to_string(), 10), ("tango".to_string(), 7)]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct CompletionSuggester {
+    map: Map<Vec<u8>>,
+}
+
+impl CompletionSuggester {
+    /// Builds a completion suggester from `(input, weight)` entries.
+    ///
+    /// Entries do not need to be sorted. If an input appears more than once,
+    /// its highest weight is kept.
+    pub fn build<I, S>(entries: I) -> crate::Result<CompletionSuggester>
+    where
+        I: IntoIterator<Item = (S, u64)>,
+        S: Into<String>,
+    {
+        let mut sorted_entries: BTreeMap<String, u64> = BTreeMap::new();
+        for (input, weight) in entries {
+            let entry_weight = sorted_entries.entry(input.into()).or_insert(weight);
+            *entry_weight = (*entry_weight).max(weight);
+        }
+        let map = Map::from_iter(sorted_entries)
+            .map_err(|err| TantivyError::SystemError(format!("Failed to build FST: {}", err)))?;
+        Ok(CompletionSuggester { map })
+    }
+
+    /// Builds a completion suggester from the terms of a text field,
+    /// weighted by their document frequency.
+    pub fn for_field(searcher: &Searcher, field: Field) -> crate::Result<CompletionSuggester> {
+        let field_terms = searcher.field_terms(field)?;
+        let mut term_stream = field_terms.stream()?;
+        let mut entries = Vec::new();
+        while term_stream.advance() {
+            if let Ok(text) = std::str::from_utf8(term_stream.key()) {
+                entries.push((text.to_string(), term_stream.doc_freq()));
+            }
+        }
+        CompletionSuggester::build(entries)
+    }
+
+    /// Returns the number of entries of the suggester.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true iff the suggester has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns at most `limit` entries starting with `prefix`, with their weight.
+    ///
+    /// Entries are sorted by decreasing weight. Entries with the same weight
+    /// are sorted in lexicographical order.
+    ///
+    /// All of the entries starting with `prefix` are visited, whatever the `limit`.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<(String, u64)> {
+        let mut stream = self.map.range().ge(prefix).into_stream();
+        let mut completions = Vec::new();
+        while let Some((key, weight)) = stream.next() {
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            // Keys are valid utf-8, as they were built from `String`s.
+            completions.push((String::from_utf8_lossy(key).into_owned(), weight));
+        }
+        completions
+            .sort_by(|left, right| (Reverse(left.1), &left.0).cmp(&(Reverse(right.1), &right.0)));
+        completions.truncate(limit);
+        completions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CompletionSuggester;
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+
+    #[test]
+    fn test_completion_suggester() -> crate::Result<()> {
+        let completion_suggester = CompletionSuggester::build(vec![
+            ("car", 3),
+            ("cart", 5),
+            ("carton", 5),
+            ("card", 1),
+            ("car", 4),
+            ("bus", 10),
+        ])?;
+        assert_eq!(completion_suggester.len(), 5);
+        assert_eq!(
+            completion_suggester.suggest("car", 10),
+            vec![
+                ("cart".to_string(), 5),
+                ("carton".to_string(), 5),
+                ("car".to_string(), 4),
+                ("card".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            completion_suggester.suggest("cart", 1),
+            vec![("cart".to_string(), 5)]
+        );
+        assert_eq!(
+            completion_suggester.suggest("", 1),
+            vec![("bus".to_string(), 10)]
+        );
+        assert!(completion_suggester.suggest("d", 10).is_empty());
+        assert!(completion_suggester.suggest("car", 0).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_completion_suggester_for_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"search engine"));
+        index_writer.add_document(doc!(text_field=>"search seal"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"sea"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let completion_suggester = CompletionSuggester::for_field(&searcher, text_field)?;
+        assert_eq!(completion_suggester.len(), 4);
+        assert_eq!(
+            completion_suggester.suggest("sea", 10),
+            vec![
+                ("search".to_string(), 2),
+                ("sea".to_string(), 1),
+                ("seal".to_string(), 1),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+/*!
+Suggesters, proposing terms to the user while they type or
+after they submitted a query.
+*/
+
+mod completion;
+mod spelling;
+
+pub use self::completion::CompletionSuggester;
+pub use self::spelling::{SpellingSuggester, Suggestion};
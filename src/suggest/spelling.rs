@@ -0,0 +1,222 @@
+use crate::query::{lev_automaton_builder, DFAWrapper};
+use crate::schema::Field;
+use crate::Searcher;
+use crate::TantivyError::InvalidArgument;
+use levenshtein_automata::Distance;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// A correction proposed by the `SpellingSuggester`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    text: String,
+    distance: u8,
+    doc_freq: u64,
+}
+
+impl Suggestion {
+    /// Returns the text of the suggested term.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the Levenshtein distance between the misspelled word
+    /// and the suggested term.
+    pub fn distance(&self) -> u8 {
+        self.distance
+    }
+
+    /// Returns the number of documents containing the suggested term.
+    pub fn doc_freq(&self) -> u64 {
+        self.doc_freq
+    }
+}
+
+/// `SpellingSuggester` proposes corrections ("did you mean ...?") for a word,
+/// picked among the terms of a field of the index.
+///
+/// Candidates are the terms within a given Levenshtein distance of the word.
+/// They are ranked by increasing distance, and then by decreasing document frequency.
+///
+/// The suggester works on the terms as they are indexed: the word should
+/// be processed by the same tokenizer as the field (lowercasing, stemming, ...)
+/// beforehand.
+///
+/// A typical usage is to call it for the terms of a query that have a
+/// doc frequency of zero or a low doc frequency.
+///
+/// ```rust
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, SpellingSuggester};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer = index.writer_with_num_threads(1, 10_000_000)?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"));
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl"));
+/// index_writer.add_document(doc!(title => "A Dairy Cow"));
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let spelling_suggester = SpellingSuggester::new(title);
+/// let suggestions = spelling_suggester.suggest(&searcher, "diart", 2)?;
+/// assert_eq!(suggestions[0].text(), "diary");
+/// assert_eq!(suggestions[1].text(), "dairy");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpellingSuggester {
+    field: Field,
+    max_distance: u8,
+    transposition_cost_one: bool,
+}
+
+impl SpellingSuggester {
+    /// Creates a new spelling suggester for the given field.
+    ///
+    /// By default, the maximum distance is 2 and a transposition costs 1.
+    pub fn new(field: Field) -> SpellingSuggester {
+        SpellingSuggester {
+            field,
+            max_distance: 2,
+            transposition_cost_one: true,
+        }
+    }
+
+    /// Sets the maximum Levenshtein distance of the suggestions.
+    ///
+    /// It must be lower than 3.
+    pub fn set_max_distance(&mut self, max_distance: u8) {
+        self.max_distance = max_distance;
+    }
+
+    /// Sets whether a transposition of two letters costs 1, rather than 2.
+    pub fn set_transposition_cost_one(&mut self, transposition_cost_one: bool) {
+        self.transposition_cost_one = transposition_cost_one;
+    }
+
+    /// Returns at most `limit` suggestions for the given word, best suggestion first.
+    ///
+    /// The word itself is never suggested.
+    pub fn suggest(
+        &self,
+        searcher: &Searcher,
+        word: &str,
+        limit: usize,
+    ) -> crate::Result<Vec<Suggestion>> {
+        let automaton_builder =
+            lev_automaton_builder(self.max_distance, self.transposition_cost_one).ok_or_else(
+                || {
+                    InvalidArgument(format!(
+                        "Levenshtein distance of {} is not allowed.",
+                        self.max_distance
+                    ))
+                },
+            )?;
+        let automaton = DFAWrapper(automaton_builder.build_dfa(word));
+        let mut doc_freqs: HashMap<Vec<u8>, u64> = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.field)?;
+            let mut term_stream = inverted_index.terms().search(&automaton).into_stream()?;
+            while term_stream.advance() {
+                *doc_freqs.entry(term_stream.key().to_vec()).or_insert(0) +=
+                    u64::from(term_stream.value().doc_freq);
+            }
+        }
+        let mut suggestions: Vec<Suggestion> = doc_freqs
+            .into_iter()
+            .filter(|(term_bytes, _)| term_bytes.as_slice() != word.as_bytes())
+            .filter_map(|(term_bytes, doc_freq)| {
+                let distance = match automaton.0.eval(&term_bytes) {
+                    Distance::Exact(distance) => distance,
+                    Distance::AtLeast(_) => return None,
+                };
+                let text = String::from_utf8(term_bytes).ok()?;
+                Some(Suggestion {
+                    text,
+                    distance,
+                    doc_freq,
+                })
+            })
+            .collect();
+        suggestions.sort_by(|left, right| {
+            (left.distance, Reverse(left.doc_freq), &left.text).cmp(&(
+                right.distance,
+                Reverse(right.doc_freq),
+                &right.text,
+            ))
+        });
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpellingSuggester;
+    use crate::schema::{Schema, TEXT};
+    use crate::Index;
+
+    #[test]
+    fn test_spelling_suggester() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field=>"house"));
+        index_writer.add_document(doc!(text_field=>"house mouse"));
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field=>"horse mouse hose"));
+        index_writer.add_document(doc!(text_field=>"mouse"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let spelling_suggester = SpellingSuggester::new(text_field);
+        let suggestions: Vec<(String, u8, u64)> = spelling_suggester
+            .suggest(&searcher, "hosue", 10)?
+            .into_iter()
+            .map(|suggestion| {
+                (
+                    suggestion.text().to_string(),
+                    suggestion.distance(),
+                    suggestion.doc_freq(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            suggestions,
+            vec![
+                ("house".to_string(), 1, 2),
+                ("hose".to_string(), 1, 1),
+                ("mouse".to_string(), 2, 3),
+                ("horse".to_string(), 2, 1),
+            ]
+        );
+        let top_suggestions = spelling_suggester.suggest(&searcher, "hosue", 1)?;
+        assert_eq!(top_suggestions.len(), 1);
+        assert_eq!(top_suggestions[0].text(), "house");
+        // The word itself is not suggested.
+        let suggestions = spelling_suggester.suggest(&searcher, "house", 10)?;
+        assert!(suggestions
+            .iter()
+            .all(|suggestion| suggestion.text() != "house"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_spelling_suggester_invalid_distance() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        let mut spelling_suggester = SpellingSuggester::new(text_field);
+        spelling_suggester.set_max_distance(3);
+        assert!(matches!(
+            spelling_suggester.suggest(&searcher, "house", 10),
+            Err(crate::TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+}
@@ -20,7 +20,6 @@ as `u64`.
 A second datastructure makes it possible to access a [`TermInfo`](../postings/struct.TermInfo.html).
 */
 mod streamer;
-mod term_info_store;
 mod termdict;
 
 pub use self::streamer::{TermStreamer, TermStreamerBuilder};
@@ -1,9 +1,9 @@
-use super::term_info_store::{TermInfoStore, TermInfoStoreWriter};
-use super::{TermStreamer, TermStreamerBuilder};
+use super::TermStreamerBuilder;
 use crate::common::{BinarySerializable, CountingWriter};
 use crate::directory::{FileSlice, OwnedBytes};
 use crate::error::DataCorruption;
 use crate::postings::TermInfo;
+use crate::termdict::term_info_store::{TermInfoStore, TermInfoStoreWriter};
 use crate::termdict::TermOrdinal;
 use once_cell::sync::Lazy;
 use std::io::{self, Write};
@@ -37,23 +37,11 @@ where
         })
     }
 
-    /// Inserts a `(key, value)` pair in the term dictionary.
-    ///
-    /// *Keys have to be inserted in order.*
-    pub fn insert<K: AsRef<[u8]>>(&mut self, key_ref: K, value: &TermInfo) -> io::Result<()> {
-        let key = key_ref.as_ref();
-        self.insert_key(key)?;
-        self.insert_value(value)?;
-        Ok(())
-    }
-
     /// # Warning
     /// Horribly dangerous internal API
     ///
     /// If used, it must be used by systematically alternating calls
     /// to insert_key and insert_value.
-    ///
-    /// Prefer using `.insert(key, value)`
     pub(crate) fn insert_key(&mut self, key: &[u8]) -> io::Result<()> {
         self.fst_builder
             .insert(key, self.term_ord)
@@ -190,11 +178,6 @@ impl TermDictionary {
         TermStreamerBuilder::new(self, self.fst_index.range())
     }
 
-    /// A stream of all the sorted terms. [See also `.stream_field()`](#method.stream_field)
-    pub fn stream(&self) -> io::Result<TermStreamer<'_>> {
-        self.range().into_stream()
-    }
-
     /// Returns a search builder, to stream all of the terms
     /// within the Automaton
     pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermStreamerBuilder<'a, A> {
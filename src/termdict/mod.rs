@@ -3,9 +3,14 @@ The term dictionary main role is to associate the sorted [`Term`s](../struct.Ter
 a [`TermInfo`](../postings/struct.TermInfo.html) struct that contains some meta-information
 about the term.
 
-Internally, the term dictionary relies on the `fst` crate to store
-a sorted mapping that associate each term to its rank in the lexicographical order.
-For instance, in a dictionary containing the sorted terms "abba", "bjork", "blur" and "donovan",
+Two implementations of the term dictionary are available, selected per index through
+[`IndexSettings::term_dictionary_type`](../struct.IndexSettings.html#structfield.term_dictionary_type):
+- by default, the term dictionary relies on the `fst` crate to store
+  a sorted mapping that associate each term to its rank in the lexicographical order.
+- alternatively, the terms can be stored in an SSTable, made of sorted blocks of
+  prefix-compressed terms, which is faster to build.
+
+In both cases, the rank of a term in the lexicographical order is its `TermOrdinal`. For instance, in a dictionary containing the sorted terms "abba", "bjork", "blur" and "donovan",
 the `TermOrdinal` are respectively `0`, `1`, `2`, and `3`.
 
 For `u64`-terms, tantivy explicitely uses a `BigEndian` representation to ensure that the
@@ -20,28 +25,22 @@ as `u64`.
 A second datastructure makes it possible to access a [`TermInfo`](../postings/struct.TermInfo.html).
 */
 
-use tantivy_fst::automaton::AlwaysMatch;
-
 mod fst_termdict;
-use fst_termdict as termdict;
-
 mod merger;
+mod sstable_termdict;
+mod streamer;
+mod term_dictionary;
+mod term_info_store;
 
 #[cfg(test)]
 mod tests;
 
+pub use self::streamer::{TermStreamer, TermStreamerBuilder};
+pub use self::term_dictionary::{TermDictionary, TermDictionaryBuilder, TermDictionaryType};
+
 /// Position of the term in the sorted list of terms.
 pub type TermOrdinal = u64;
 
-/// The term dictionary contains all of the terms in
-/// `tantivy index` in a sorted manner.
-pub type TermDictionary = self::termdict::TermDictionary;
-
-/// Builder for the new term dictionary.
-///
-/// Inserting must be done in the order of the `keys`.
-pub type TermDictionaryBuilder<W> = self::termdict::TermDictionaryBuilder<W>;
-
 /// Given a list of sorted term streams,
 /// returns an iterator over sorted unique terms.
 ///
@@ -50,7 +49,3 @@ pub type TermDictionaryBuilder<W> = self::termdict::TermDictionaryBuilder<W>;
 /// - a slice with the ordinal of the segments containing
 /// the terms.
 pub type TermMerger<'a> = self::merger::TermMerger<'a>;
-
-/// `TermStreamer` acts as a cursor over a range of terms of a segment.
-/// Terms are guaranteed to be sorted.
-pub type TermStreamer<'a, A = AlwaysMatch> = self::termdict::TermStreamer<'a, A>;
@@ -0,0 +1,30 @@
+/*!
+SSTable implementation of the term dictionary.
+
+The terms are stored in sorted order, in blocks of `BLOCK_LEN` terms.
+Within a block, each term is encoded as the length of the prefix it shares with
+the previous term, followed by the remaining suffix. The first term of each block is
+stored in full, and an index of the blocks' first terms makes it possible to
+binary search the block that may contain a given term.
+
+Since all of the blocks but the last contain exactly `BLOCK_LEN` terms, the block
+containing a given `TermOrdinal` is known without any search.
+
+Compared to the FST, the SSTable is cheaper to build, but looking up a term requires
+to decode a part of its block, and automata are evaluated on every term of the
+streamed range.
+*/
+mod streamer;
+mod termdict;
+
+pub use self::streamer::{TermStreamer, TermStreamerBuilder};
+pub use self::termdict::{TermDictionary, TermDictionaryBuilder};
+
+/// Magic number starting an SSTable term dictionary file.
+///
+/// An FST term dictionary starts with the version of the `fst` format,
+/// so that both formats can be told apart.
+pub(crate) const SSTABLE_MAGIC_NUMBER: u64 = u64::from_le_bytes(*b"TSSTABLE");
+
+/// Number of terms in a block.
+const BLOCK_LEN: usize = 128;
@@ -0,0 +1,240 @@
+use std::io;
+use std::ops::Bound;
+
+use super::{TermDictionary, BLOCK_LEN};
+use crate::postings::TermInfo;
+use crate::termdict::TermOrdinal;
+use tantivy_fst::automaton::AlwaysMatch;
+use tantivy_fst::Automaton;
+
+/// Runs the automaton on `key`, reusing the states of the prefix
+/// it shares with the previously tested key.
+///
+/// `states[i]` is the state of the automaton after consuming `states_key[..i]`.
+fn automaton_matches<A: Automaton>(
+    automaton: &A,
+    states: &mut Vec<A::State>,
+    states_key: &mut Vec<u8>,
+    key: &[u8],
+) -> bool {
+    let common_prefix_len = states_key
+        .iter()
+        .zip(key.iter())
+        .take_while(|(left, right)| left == right)
+        .count();
+    states.truncate(common_prefix_len + 1);
+    states_key.truncate(common_prefix_len);
+    for &byte in &key[common_prefix_len..] {
+        let state = &states[states.len() - 1];
+        if !automaton.can_match(state) {
+            return false;
+        }
+        let next_state = automaton.accept(state, byte);
+        states.push(next_state);
+        states_key.push(byte);
+    }
+    automaton.is_match(&states[states.len() - 1])
+}
+
+/// `TermStreamerBuilder` is a helper object used to define
+/// a range of terms that should be streamed.
+pub struct TermStreamerBuilder<'a, A = AlwaysMatch>
+where
+    A: Automaton,
+{
+    term_dictionary: &'a TermDictionary,
+    automaton: A,
+    lower: Bound<Vec<u8>>,
+    upper: Bound<Vec<u8>>,
+    backward: bool,
+}
+
+impl<'a, A> TermStreamerBuilder<'a, A>
+where
+    A: Automaton,
+{
+    pub(crate) fn new(term_dictionary: &'a TermDictionary, automaton: A) -> Self {
+        TermStreamerBuilder {
+            term_dictionary,
+            automaton,
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+            backward: false,
+        }
+    }
+
+    /// Limit the range to terms greater or equal to the bound
+    pub fn ge<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.lower = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    /// Limit the range to terms strictly greater than the bound
+    pub fn gt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.lower = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    /// Limit the range to terms lesser or equal to the bound
+    pub fn le<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.upper = Bound::Included(bound.as_ref().to_vec());
+        self
+    }
+
+    /// Limit the range to terms lesser or equal to the bound
+    pub fn lt<T: AsRef<[u8]>>(mut self, bound: T) -> Self {
+        self.upper = Bound::Excluded(bound.as_ref().to_vec());
+        self
+    }
+
+    /// Iterate over the range backwards.
+    pub fn backward(mut self) -> Self {
+        self.backward = true;
+        self
+    }
+
+    /// Creates the stream corresponding to the range
+    /// of terms defined using the `TermStreamerBuilder`.
+    pub fn into_stream(self) -> io::Result<TermStreamer<'a, A>> {
+        let term_dictionary = self.term_dictionary;
+        let start_ord = match self.lower {
+            Bound::Included(ref key) => term_dictionary.lower_bound_ord(key, true),
+            Bound::Excluded(ref key) => term_dictionary.lower_bound_ord(key, false),
+            Bound::Unbounded => 0u64,
+        };
+        let stop_ord = match self.upper {
+            Bound::Included(ref key) => term_dictionary.lower_bound_ord(key, false),
+            Bound::Excluded(ref key) => term_dictionary.lower_bound_ord(key, true),
+            Bound::Unbounded => term_dictionary.num_terms() as u64,
+        };
+        let stop_ord = stop_ord.max(start_ord);
+        let next_ord = if self.backward { stop_ord } else { start_ord };
+        let start_state = self.automaton.start();
+        Ok(TermStreamer {
+            term_dictionary,
+            automaton: self.automaton,
+            states: vec![start_state],
+            states_key: Vec::new(),
+            start_ord,
+            stop_ord,
+            backward: self.backward,
+            next_ord,
+            block_id: None,
+            block_keys: Vec::new(),
+            block_key_ends: Vec::new(),
+            term_ord: 0u64,
+            current_key: Vec::with_capacity(100),
+            current_value: TermInfo::default(),
+        })
+    }
+}
+
+/// `TermStreamer` acts as a cursor over a range of terms of a segment.
+/// Terms are guaranteed to be sorted.
+pub struct TermStreamer<'a, A = AlwaysMatch>
+where
+    A: Automaton,
+{
+    term_dictionary: &'a TermDictionary,
+    automaton: A,
+    states: Vec<A::State>,
+    states_key: Vec<u8>,
+    start_ord: TermOrdinal,
+    stop_ord: TermOrdinal,
+    backward: bool,
+    next_ord: TermOrdinal,
+    block_id: Option<usize>,
+    block_keys: Vec<u8>,
+    block_key_ends: Vec<usize>,
+    term_ord: TermOrdinal,
+    current_key: Vec<u8>,
+    current_value: TermInfo,
+}
+
+impl<'a, A> TermStreamer<'a, A>
+where
+    A: Automaton,
+{
+    /// Decodes the block containing `term_ord`, unless it is already loaded.
+    fn load_block(&mut self, term_ord: TermOrdinal) {
+        let block_id = term_ord as usize / BLOCK_LEN;
+        if self.block_id == Some(block_id) {
+            return;
+        }
+        self.block_keys.clear();
+        self.block_key_ends.clear();
+        let mut block_cursor = self.term_dictionary.block_cursor(block_id);
+        while block_cursor.advance() {
+            self.block_keys.extend_from_slice(block_cursor.key());
+            self.block_key_ends.push(self.block_keys.len());
+        }
+        self.block_id = Some(block_id);
+    }
+
+    /// Advance position the stream on the next item.
+    /// Before the first call to `.advance()`, the stream
+    /// is an unitialized state.
+    pub fn advance(&mut self) -> bool {
+        loop {
+            let term_ord = if self.backward {
+                if self.next_ord <= self.start_ord {
+                    return false;
+                }
+                self.next_ord -= 1;
+                self.next_ord
+            } else {
+                if self.next_ord >= self.stop_ord {
+                    return false;
+                }
+                self.next_ord += 1;
+                self.next_ord - 1
+            };
+            self.load_block(term_ord);
+            let idx_in_block = term_ord as usize % BLOCK_LEN;
+            let key_start = if idx_in_block == 0 {
+                0
+            } else {
+                self.block_key_ends[idx_in_block - 1]
+            };
+            let key_end = self.block_key_ends[idx_in_block];
+            let key = &self.block_keys[key_start..key_end];
+            if automaton_matches(&self.automaton, &mut self.states, &mut self.states_key, key) {
+                self.current_key.clear();
+                self.current_key.extend_from_slice(key);
+                self.term_ord = term_ord;
+                self.current_value = self.term_dictionary.term_info_from_ord(term_ord);
+                return true;
+            }
+        }
+    }
+
+    /// Returns the `TermOrdinal` of the given term.
+    ///
+    /// May panic if the called as `.advance()` as never
+    /// been called before.
+    pub fn term_ord(&self) -> TermOrdinal {
+        self.term_ord
+    }
+
+    /// Accesses the current key.
+    ///
+    /// `.key()` should return the key that was returned
+    /// by the `.next()` method.
+    ///
+    /// If the end of the stream as been reached, and `.next()`
+    /// has been called and returned `None`, `.key()` remains
+    /// the value of the last key encountered.
+    ///
+    /// Before any call to `.next()`, `.key()` returns an empty array.
+    pub fn key(&self) -> &[u8] {
+        &self.current_key
+    }
+
+    /// Accesses the current value.
+    ///
+    /// Calling `.value()` after the end of the stream will return the
+    /// last `.value()` encountered.
+    pub fn value(&self) -> &TermInfo {
+        &self.current_value
+    }
+}
@@ -0,0 +1,308 @@
+use super::TermStreamerBuilder;
+use super::{BLOCK_LEN, SSTABLE_MAGIC_NUMBER};
+use crate::common::{read_u32_vint, write_u32_vint, BinarySerializable, CountingWriter, VInt};
+use crate::directory::{FileSlice, OwnedBytes};
+use crate::error::DataCorruption;
+use crate::postings::TermInfo;
+use crate::termdict::term_info_store::{TermInfoStore, TermInfoStoreWriter};
+use crate::termdict::TermOrdinal;
+use std::cmp::Ordering;
+use std::io::{self, Write};
+use tantivy_fst::Automaton;
+
+fn common_prefix_len(left: &[u8], right: &[u8]) -> usize {
+    left.iter()
+        .zip(right.iter())
+        .take_while(|(left_byte, right_byte)| left_byte == right_byte)
+        .count()
+}
+
+/// Builder for the SSTable term dictionary.
+///
+/// Inserting must be done in the order of the `keys`.
+pub struct TermDictionaryBuilder<W> {
+    write: CountingWriter<W>,
+    block_index: Vec<BlockAddr>,
+    previous_key: Vec<u8>,
+    term_info_store_writer: TermInfoStoreWriter,
+    term_ord: u64,
+}
+
+impl<W> TermDictionaryBuilder<W>
+where
+    W: Write,
+{
+    /// Creates a new `TermDictionaryBuilder`
+    pub fn create(w: W) -> io::Result<Self> {
+        let mut write = CountingWriter::wrap(w);
+        SSTABLE_MAGIC_NUMBER.serialize(&mut write)?;
+        Ok(TermDictionaryBuilder {
+            write,
+            block_index: Vec::new(),
+            previous_key: Vec::new(),
+            term_info_store_writer: TermInfoStoreWriter::new(),
+            term_ord: 0,
+        })
+    }
+
+    /// # Warning
+    /// Horribly dangerous internal API
+    ///
+    /// If used, it must be used by systematically alternating calls
+    /// to insert_key and insert_value.
+    pub(crate) fn insert_key(&mut self, key: &[u8]) -> io::Result<()> {
+        if self.term_ord > 0 && key <= &self.previous_key[..] {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Keys must be inserted in strictly increasing order.",
+            ));
+        }
+        let is_block_start = self.term_ord == (self.block_index.len() * BLOCK_LEN) as u64;
+        let common_prefix_len = if is_block_start {
+            self.block_index.push(BlockAddr {
+                first_key: key.to_vec(),
+                offset: self.write.written_bytes() - 8u64,
+            });
+            0
+        } else {
+            common_prefix_len(&self.previous_key, key)
+        };
+        let suffix = &key[common_prefix_len..];
+        write_u32_vint(common_prefix_len as u32, &mut self.write)?;
+        write_u32_vint(suffix.len() as u32, &mut self.write)?;
+        self.write.write_all(suffix)?;
+        self.previous_key.truncate(common_prefix_len);
+        self.previous_key.extend_from_slice(suffix);
+        self.term_ord += 1;
+        Ok(())
+    }
+
+    /// # Warning
+    ///
+    /// Horribly dangerous internal API. See `.insert_key(...)`.
+    pub(crate) fn insert_value(&mut self, term_info: &TermInfo) -> io::Result<()> {
+        self.term_info_store_writer.write_term_info(term_info)
+    }
+
+    /// Finalize writing the builder, and returns the underlying
+    /// `Write` object.
+    pub fn finish(mut self) -> io::Result<W> {
+        let blocks_len = self.write.written_bytes() - 8u64;
+        VInt(self.block_index.len() as u64).serialize(&mut self.write)?;
+        for block_addr in &self.block_index {
+            block_addr.serialize(&mut self.write)?;
+        }
+        let block_index_len = self.write.written_bytes() - 8u64 - blocks_len;
+        self.term_info_store_writer.serialize(&mut self.write)?;
+        blocks_len.serialize(&mut self.write)?;
+        block_index_len.serialize(&mut self.write)?;
+        Ok(self.write.finish())
+    }
+}
+
+/// First key and offset of a block of terms.
+#[derive(Debug)]
+struct BlockAddr {
+    first_key: Vec<u8>,
+    offset: u64,
+}
+
+impl BinarySerializable for BlockAddr {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.offset).serialize(writer)?;
+        VInt(self.first_key.len() as u64).serialize(writer)?;
+        writer.write_all(&self.first_key)
+    }
+
+    fn deserialize<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let offset = VInt::deserialize_u64(reader)?;
+        let key_len = VInt::deserialize_u64(reader)? as usize;
+        let mut first_key = vec![0u8; key_len];
+        reader.read_exact(&mut first_key)?;
+        Ok(BlockAddr { first_key, offset })
+    }
+}
+
+/// Decodes the keys of a block, one after the other.
+pub(crate) struct BlockCursor<'a> {
+    data: &'a [u8],
+    key: Vec<u8>,
+}
+
+impl<'a> BlockCursor<'a> {
+    /// Decodes the next key of the block.
+    ///
+    /// Returns false once all of the keys of the block have been decoded.
+    pub fn advance(&mut self) -> bool {
+        if self.data.is_empty() {
+            return false;
+        }
+        let common_prefix_len = read_u32_vint(&mut self.data) as usize;
+        let suffix_len = read_u32_vint(&mut self.data) as usize;
+        self.key.truncate(common_prefix_len);
+        self.key.extend_from_slice(&self.data[..suffix_len]);
+        self.data = &self.data[suffix_len..];
+        true
+    }
+
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+/// The SSTable term dictionary.
+///
+/// Terms are stored in sorted, prefix-compressed blocks. The `TermInfoStore`
+/// makes it possible to fetch the `TermInfo` associated to a `TermOrdinal`.
+pub struct TermDictionary {
+    blocks: OwnedBytes,
+    block_index: Vec<BlockAddr>,
+    term_info_store: TermInfoStore,
+}
+
+impl TermDictionary {
+    /// Opens a `TermDictionary`.
+    pub fn open(file: FileSlice) -> crate::Result<Self> {
+        let (main_slice, footer_slice) = file.split_from_end(16);
+        let mut footer_bytes = footer_slice.read_bytes()?;
+        let blocks_len = u64::deserialize(&mut footer_bytes)? as usize;
+        let block_index_len = u64::deserialize(&mut footer_bytes)? as usize;
+        let (header_slice, main_slice) = main_slice.split(8);
+        let mut header_bytes = header_slice.read_bytes()?;
+        if u64::deserialize(&mut header_bytes)? != SSTABLE_MAGIC_NUMBER {
+            return Err(
+                DataCorruption::comment_only("SSTable term dictionary header is invalid").into(),
+            );
+        }
+        let (blocks_slice, main_slice) = main_slice.split(blocks_len);
+        let (block_index_slice, term_info_store_slice) = main_slice.split(block_index_len);
+        let block_index_bytes = block_index_slice.read_bytes()?;
+        let mut block_index_data = block_index_bytes.as_slice();
+        let num_blocks = VInt::deserialize_u64(&mut block_index_data)? as usize;
+        let block_index = (0..num_blocks)
+            .map(|_| BlockAddr::deserialize(&mut block_index_data))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(TermDictionary {
+            blocks: blocks_slice.read_bytes()?,
+            block_index,
+            term_info_store: TermInfoStore::open(term_info_store_slice)?,
+        })
+    }
+
+    /// Returns the number of terms in the dictionary.
+    /// Term ordinals range from 0 to `num_terms() - 1`.
+    pub fn num_terms(&self) -> usize {
+        self.term_info_store.num_terms()
+    }
+
+    /// Returns a cursor over the keys of the block `block_id`.
+    pub(crate) fn block_cursor(&self, block_id: usize) -> BlockCursor<'_> {
+        let start = self.block_index[block_id].offset as usize;
+        let stop = self
+            .block_index
+            .get(block_id + 1)
+            .map(|block_addr| block_addr.offset as usize)
+            .unwrap_or_else(|| self.blocks.len());
+        BlockCursor {
+            data: &self.blocks.as_slice()[start..stop],
+            key: Vec::new(),
+        }
+    }
+
+    /// Returns the last block whose first key is lower or equal to `key`,
+    /// or `None` if all of the keys of the dictionary are greater than `key`.
+    fn block_containing(&self, key: &[u8]) -> Option<usize> {
+        match self
+            .block_index
+            .binary_search_by(|block_addr| block_addr.first_key.as_slice().cmp(key))
+        {
+            Ok(block_id) => Some(block_id),
+            Err(0) => None,
+            Err(block_id) => Some(block_id - 1),
+        }
+    }
+
+    /// Returns the ordinal of the first term that is greater or equal to `key`,
+    /// and whether this term is equal to `key`.
+    ///
+    /// The ordinal is `num_terms()` if there is no such term.
+    fn seek(&self, key: &[u8]) -> (TermOrdinal, bool) {
+        let block_id = if let Some(block_id) = self.block_containing(key) {
+            block_id
+        } else {
+            return (0u64, false);
+        };
+        let mut term_ord = (block_id * BLOCK_LEN) as TermOrdinal;
+        let mut block_cursor = self.block_cursor(block_id);
+        while block_cursor.advance() {
+            match block_cursor.key().cmp(key) {
+                Ordering::Less => term_ord += 1,
+                Ordering::Equal => return (term_ord, true),
+                Ordering::Greater => return (term_ord, false),
+            }
+        }
+        (term_ord, false)
+    }
+
+    /// Returns the ordinal of the first term that is greater than `key`,
+    /// or greater or equal to `key` if `inclusive` is true.
+    ///
+    /// Returns `num_terms()` if there is no such term.
+    pub(crate) fn lower_bound_ord(&self, key: &[u8], inclusive: bool) -> TermOrdinal {
+        match self.seek(key) {
+            (term_ord, true) if !inclusive => term_ord + 1,
+            (term_ord, _) => term_ord,
+        }
+    }
+
+    /// Returns the ordinal associated to a given term.
+    pub fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermOrdinal>> {
+        match self.seek(key.as_ref()) {
+            (term_ord, true) => Ok(Some(term_ord)),
+            (_, false) => Ok(None),
+        }
+    }
+
+    /// Returns the term associated to a given term ordinal.
+    ///
+    /// Returns true iff the term has been found.
+    pub fn ord_to_term(&self, ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool> {
+        bytes.clear();
+        if ord >= self.num_terms() as u64 {
+            return Ok(false);
+        }
+        let block_id = ord as usize / BLOCK_LEN;
+        let mut block_cursor = self.block_cursor(block_id);
+        for _ in 0..=(ord as usize % BLOCK_LEN) {
+            if !block_cursor.advance() {
+                return Ok(false);
+            }
+        }
+        bytes.extend_from_slice(block_cursor.key());
+        Ok(true)
+    }
+
+    /// Returns the `TermInfo` associated to the term ordinal.
+    pub fn term_info_from_ord(&self, term_ord: TermOrdinal) -> TermInfo {
+        self.term_info_store.get(term_ord)
+    }
+
+    /// Lookups the value corresponding to the key.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermInfo>> {
+        Ok(self
+            .term_ord(key)?
+            .map(|term_ord| self.term_info_from_ord(term_ord)))
+    }
+
+    /// Returns a range builder, to stream all of the terms
+    /// within an interval.
+    pub fn range(&self) -> TermStreamerBuilder<'_> {
+        TermStreamerBuilder::new(self, tantivy_fst::automaton::AlwaysMatch)
+    }
+
+    /// Returns a search builder, to stream all of the terms
+    /// within the Automaton
+    pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermStreamerBuilder<'a, A> {
+        TermStreamerBuilder::new(self, automaton)
+    }
+}
@@ -0,0 +1,181 @@
+use std::io;
+
+use super::{fst_termdict, sstable_termdict};
+use crate::postings::TermInfo;
+use crate::termdict::TermOrdinal;
+use tantivy_fst::automaton::AlwaysMatch;
+use tantivy_fst::Automaton;
+
+enum InnerTermStreamerBuilder<'a, A>
+where
+    A: Automaton,
+{
+    Fst(fst_termdict::TermStreamerBuilder<'a, A>),
+    SSTable(sstable_termdict::TermStreamerBuilder<'a, A>),
+}
+
+/// `TermStreamerBuilder` is a helper object used to define
+/// a range of terms that should be streamed.
+pub struct TermStreamerBuilder<'a, A = AlwaysMatch>(InnerTermStreamerBuilder<'a, A>)
+where
+    A: Automaton;
+
+impl<'a, A> From<fst_termdict::TermStreamerBuilder<'a, A>> for TermStreamerBuilder<'a, A>
+where
+    A: Automaton,
+{
+    fn from(stream_builder: fst_termdict::TermStreamerBuilder<'a, A>) -> Self {
+        TermStreamerBuilder(InnerTermStreamerBuilder::Fst(stream_builder))
+    }
+}
+
+impl<'a, A> From<sstable_termdict::TermStreamerBuilder<'a, A>> for TermStreamerBuilder<'a, A>
+where
+    A: Automaton,
+{
+    fn from(stream_builder: sstable_termdict::TermStreamerBuilder<'a, A>) -> Self {
+        TermStreamerBuilder(InnerTermStreamerBuilder::SSTable(stream_builder))
+    }
+}
+
+impl<'a, A> TermStreamerBuilder<'a, A>
+where
+    A: Automaton,
+{
+    /// Limit the range to terms greater or equal to the bound
+    pub fn ge<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        match self.0 {
+            InnerTermStreamerBuilder::Fst(stream_builder) => stream_builder.ge(bound).into(),
+            InnerTermStreamerBuilder::SSTable(stream_builder) => stream_builder.ge(bound).into(),
+        }
+    }
+
+    /// Limit the range to terms strictly greater than the bound
+    pub fn gt<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        match self.0 {
+            InnerTermStreamerBuilder::Fst(stream_builder) => stream_builder.gt(bound).into(),
+            InnerTermStreamerBuilder::SSTable(stream_builder) => stream_builder.gt(bound).into(),
+        }
+    }
+
+    /// Limit the range to terms lesser or equal to the bound
+    pub fn le<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        match self.0 {
+            InnerTermStreamerBuilder::Fst(stream_builder) => stream_builder.le(bound).into(),
+            InnerTermStreamerBuilder::SSTable(stream_builder) => stream_builder.le(bound).into(),
+        }
+    }
+
+    /// Limit the range to terms lesser or equal to the bound
+    pub fn lt<T: AsRef<[u8]>>(self, bound: T) -> Self {
+        match self.0 {
+            InnerTermStreamerBuilder::Fst(stream_builder) => stream_builder.lt(bound).into(),
+            InnerTermStreamerBuilder::SSTable(stream_builder) => stream_builder.lt(bound).into(),
+        }
+    }
+
+    /// Iterate over the range backwards.
+    pub fn backward(self) -> Self {
+        match self.0 {
+            InnerTermStreamerBuilder::Fst(stream_builder) => stream_builder.backward().into(),
+            InnerTermStreamerBuilder::SSTable(stream_builder) => stream_builder.backward().into(),
+        }
+    }
+
+    /// Creates the stream corresponding to the range
+    /// of terms defined using the `TermStreamerBuilder`.
+    pub fn into_stream(self) -> io::Result<TermStreamer<'a, A>> {
+        let inner = match self.0 {
+            InnerTermStreamerBuilder::Fst(stream_builder) => {
+                InnerTermStreamer::Fst(stream_builder.into_stream()?)
+            }
+            InnerTermStreamerBuilder::SSTable(stream_builder) => {
+                InnerTermStreamer::SSTable(stream_builder.into_stream()?)
+            }
+        };
+        Ok(TermStreamer(inner))
+    }
+}
+
+enum InnerTermStreamer<'a, A>
+where
+    A: Automaton,
+{
+    Fst(fst_termdict::TermStreamer<'a, A>),
+    SSTable(sstable_termdict::TermStreamer<'a, A>),
+}
+
+/// `TermStreamer` acts as a cursor over a range of terms of a segment.
+/// Terms are guaranteed to be sorted.
+pub struct TermStreamer<'a, A = AlwaysMatch>(InnerTermStreamer<'a, A>)
+where
+    A: Automaton;
+
+impl<'a, A> TermStreamer<'a, A>
+where
+    A: Automaton,
+{
+    /// Advance position the stream on the next item.
+    /// Before the first call to `.advance()`, the stream
+    /// is an unitialized state.
+    pub fn advance(&mut self) -> bool {
+        match self.0 {
+            InnerTermStreamer::Fst(ref mut streamer) => streamer.advance(),
+            InnerTermStreamer::SSTable(ref mut streamer) => streamer.advance(),
+        }
+    }
+
+    /// Returns the `TermOrdinal` of the given term.
+    ///
+    /// May panic if the called as `.advance()` as never
+    /// been called before.
+    pub fn term_ord(&self) -> TermOrdinal {
+        match self.0 {
+            InnerTermStreamer::Fst(ref streamer) => streamer.term_ord(),
+            InnerTermStreamer::SSTable(ref streamer) => streamer.term_ord(),
+        }
+    }
+
+    /// Accesses the current key.
+    ///
+    /// `.key()` should return the key that was returned
+    /// by the `.next()` method.
+    ///
+    /// If the end of the stream as been reached, and `.next()`
+    /// has been called and returned `None`, `.key()` remains
+    /// the value of the last key encountered.
+    ///
+    /// Before any call to `.next()`, `.key()` returns an empty array.
+    pub fn key(&self) -> &[u8] {
+        match self.0 {
+            InnerTermStreamer::Fst(ref streamer) => streamer.key(),
+            InnerTermStreamer::SSTable(ref streamer) => streamer.key(),
+        }
+    }
+
+    /// Accesses the current value.
+    ///
+    /// Calling `.value()` after the end of the stream will return the
+    /// last `.value()` encountered.
+    ///
+    /// # Panics
+    ///
+    /// Calling `.value()` before the first call to `.advance()` returns
+    /// `V::default()`.
+    pub fn value(&self) -> &TermInfo {
+        match self.0 {
+            InnerTermStreamer::Fst(ref streamer) => streamer.value(),
+            InnerTermStreamer::SSTable(ref streamer) => streamer.value(),
+        }
+    }
+
+    /// Return the next `(key, value)` pair.
+    #[cfg_attr(feature = "cargo-clippy", allow(clippy::should_implement_trait))]
+    pub fn next(&mut self) -> Option<(&[u8], &TermInfo)> {
+        if self.advance() {
+            Some((self.key(), self.value()))
+        } else {
+            None
+        }
+    }
+}
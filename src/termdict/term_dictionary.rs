@@ -0,0 +1,251 @@
+use super::sstable_termdict::SSTABLE_MAGIC_NUMBER;
+use super::{fst_termdict, sstable_termdict};
+use super::{TermOrdinal, TermStreamer, TermStreamerBuilder};
+use crate::common::HasLen;
+use crate::directory::FileSlice;
+use crate::postings::TermInfo;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use tantivy_fst::Automaton;
+
+/// Returns the smallest key greater than all of the keys starting with `prefix`,
+/// or `None` if there is no such key.
+fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut upper_bound = prefix.to_vec();
+    while let Some(last_byte) = upper_bound.pop() {
+        if last_byte != u8::MAX {
+            upper_bound.push(last_byte + 1);
+            return Some(upper_bound);
+        }
+    }
+    None
+}
+
+/// Data structure used to store the term dictionaries of an index.
+///
+/// The type is only used when writing new segments. When opening a term
+/// dictionary, its type is detected from its header, so that segments written
+/// with different types can live in the same index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TermDictionaryType {
+    /// Finite state transducer, mapping terms to their ordinal.
+    ///
+    /// This is the most compact representation, and the fastest to
+    /// intersect with an automaton, as in fuzzy and regex queries.
+    Fst,
+    /// Sorted blocks of prefix-compressed terms, with an index of the
+    /// first term of each block.
+    ///
+    /// This representation is faster to build than the FST, which speeds up
+    /// indexing and merges. Automata are however evaluated on every term of
+    /// the dictionary.
+    SSTable,
+}
+
+impl Default for TermDictionaryType {
+    fn default() -> TermDictionaryType {
+        TermDictionaryType::Fst
+    }
+}
+
+enum InnerTermDictionaryBuilder<W> {
+    Fst(fst_termdict::TermDictionaryBuilder<W>),
+    SSTable(sstable_termdict::TermDictionaryBuilder<W>),
+}
+
+/// Builder for the new term dictionary.
+///
+/// Inserting must be done in the order of the `keys`.
+pub struct TermDictionaryBuilder<W>(InnerTermDictionaryBuilder<W>);
+
+impl<W> TermDictionaryBuilder<W>
+where
+    W: Write,
+{
+    /// Creates a new `TermDictionaryBuilder`, writing an FST term dictionary.
+    pub fn create(w: W) -> io::Result<Self> {
+        TermDictionaryBuilder::create_with_type(w, TermDictionaryType::default())
+    }
+
+    /// Creates a new `TermDictionaryBuilder`, writing a term dictionary
+    /// of the given type.
+    pub fn create_with_type(w: W, term_dictionary_type: TermDictionaryType) -> io::Result<Self> {
+        let inner = match term_dictionary_type {
+            TermDictionaryType::Fst => {
+                InnerTermDictionaryBuilder::Fst(fst_termdict::TermDictionaryBuilder::create(w)?)
+            }
+            TermDictionaryType::SSTable => InnerTermDictionaryBuilder::SSTable(
+                sstable_termdict::TermDictionaryBuilder::create(w)?,
+            ),
+        };
+        Ok(TermDictionaryBuilder(inner))
+    }
+
+    /// Inserts a `(key, value)` pair in the term dictionary.
+    ///
+    /// *Keys have to be inserted in order.*
+    pub fn insert<K: AsRef<[u8]>>(&mut self, key_ref: K, value: &TermInfo) -> io::Result<()> {
+        let key = key_ref.as_ref();
+        self.insert_key(key)?;
+        self.insert_value(value)?;
+        Ok(())
+    }
+
+    /// # Warning
+    /// Horribly dangerous internal API
+    ///
+    /// If used, it must be used by systematically alternating calls
+    /// to insert_key and insert_value.
+    ///
+    /// Prefer using `.insert(key, value)`
+    pub(crate) fn insert_key(&mut self, key: &[u8]) -> io::Result<()> {
+        match self.0 {
+            InnerTermDictionaryBuilder::Fst(ref mut builder) => builder.insert_key(key),
+            InnerTermDictionaryBuilder::SSTable(ref mut builder) => builder.insert_key(key),
+        }
+    }
+
+    /// # Warning
+    ///
+    /// Horribly dangerous internal API. See `.insert_key(...)`.
+    pub(crate) fn insert_value(&mut self, term_info: &TermInfo) -> io::Result<()> {
+        match self.0 {
+            InnerTermDictionaryBuilder::Fst(ref mut builder) => builder.insert_value(term_info),
+            InnerTermDictionaryBuilder::SSTable(ref mut builder) => builder.insert_value(term_info),
+        }
+    }
+
+    /// Finalize writing the builder, and returns the underlying
+    /// `Write` object.
+    pub fn finish(self) -> io::Result<W> {
+        match self.0 {
+            InnerTermDictionaryBuilder::Fst(builder) => builder.finish(),
+            InnerTermDictionaryBuilder::SSTable(builder) => builder.finish(),
+        }
+    }
+}
+
+enum InnerTermDictionary {
+    Fst(fst_termdict::TermDictionary),
+    SSTable(sstable_termdict::TermDictionary),
+}
+
+/// The term dictionary contains all of the terms in
+/// `tantivy index` in a sorted manner.
+///
+/// Depending on the `TermDictionaryType` it was written with, the terms are
+/// either stored in an FST or in an SSTable.
+pub struct TermDictionary(InnerTermDictionary);
+
+impl TermDictionary {
+    /// Opens a `TermDictionary`.
+    ///
+    /// The type of the term dictionary is detected from its header.
+    pub fn open(file: FileSlice) -> crate::Result<Self> {
+        let is_sstable = if file.len() >= 8 {
+            file.read_bytes_slice(0, 8)?.read_u64() == SSTABLE_MAGIC_NUMBER
+        } else {
+            false
+        };
+        let inner = if is_sstable {
+            InnerTermDictionary::SSTable(sstable_termdict::TermDictionary::open(file)?)
+        } else {
+            InnerTermDictionary::Fst(fst_termdict::TermDictionary::open(file)?)
+        };
+        Ok(TermDictionary(inner))
+    }
+
+    /// Creates an empty term dictionary which contains no terms.
+    pub fn empty() -> Self {
+        TermDictionary(InnerTermDictionary::Fst(
+            fst_termdict::TermDictionary::empty(),
+        ))
+    }
+
+    /// Returns the number of terms in the dictionary.
+    /// Term ordinals range from 0 to `num_terms() - 1`.
+    pub fn num_terms(&self) -> usize {
+        match self.0 {
+            InnerTermDictionary::Fst(ref termdict) => termdict.num_terms(),
+            InnerTermDictionary::SSTable(ref termdict) => termdict.num_terms(),
+        }
+    }
+
+    /// Returns the ordinal associated to a given term.
+    pub fn term_ord<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermOrdinal>> {
+        match self.0 {
+            InnerTermDictionary::Fst(ref termdict) => termdict.term_ord(key),
+            InnerTermDictionary::SSTable(ref termdict) => termdict.term_ord(key),
+        }
+    }
+
+    /// Returns the term associated to a given term ordinal.
+    ///
+    /// Term ordinals are defined as the position of the term in
+    /// the sorted list of terms.
+    ///
+    /// Returns true iff the term has been found.
+    ///
+    /// Regardless of whether the term is found or not,
+    /// the buffer may be modified.
+    pub fn ord_to_term(&self, ord: TermOrdinal, bytes: &mut Vec<u8>) -> io::Result<bool> {
+        match self.0 {
+            InnerTermDictionary::Fst(ref termdict) => termdict.ord_to_term(ord, bytes),
+            InnerTermDictionary::SSTable(ref termdict) => termdict.ord_to_term(ord, bytes),
+        }
+    }
+
+    /// Returns the number of terms in the dictionary.
+    pub fn term_info_from_ord(&self, term_ord: TermOrdinal) -> TermInfo {
+        match self.0 {
+            InnerTermDictionary::Fst(ref termdict) => termdict.term_info_from_ord(term_ord),
+            InnerTermDictionary::SSTable(ref termdict) => termdict.term_info_from_ord(term_ord),
+        }
+    }
+
+    /// Lookups the value corresponding to the key.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> io::Result<Option<TermInfo>> {
+        match self.0 {
+            InnerTermDictionary::Fst(ref termdict) => termdict.get(key),
+            InnerTermDictionary::SSTable(ref termdict) => termdict.get(key),
+        }
+    }
+
+    /// Returns a range builder, to stream all of the terms
+    /// within an interval.
+    pub fn range(&self) -> TermStreamerBuilder<'_> {
+        match self.0 {
+            InnerTermDictionary::Fst(ref termdict) => termdict.range().into(),
+            InnerTermDictionary::SSTable(ref termdict) => termdict.range().into(),
+        }
+    }
+
+    /// Returns a range builder, to stream all of the terms
+    /// starting with the given `prefix`.
+    ///
+    /// The returned builder can be further restricted or reversed,
+    /// like the one returned by `.range()`.
+    pub fn prefix_range<K: AsRef<[u8]>>(&self, prefix: K) -> TermStreamerBuilder<'_> {
+        let prefix = prefix.as_ref();
+        let range = self.range().ge(prefix);
+        match prefix_upper_bound(prefix) {
+            Some(upper_bound) => range.lt(upper_bound),
+            None => range,
+        }
+    }
+
+    /// A stream of all the sorted terms. [See also `.stream_field()`](#method.stream_field)
+    pub fn stream(&self) -> io::Result<TermStreamer<'_>> {
+        self.range().into_stream()
+    }
+
+    /// Returns a search builder, to stream all of the terms
+    /// within the Automaton
+    pub fn search<'a, A: Automaton + 'a>(&'a self, automaton: A) -> TermStreamerBuilder<'a, A> {
+        match self.0 {
+            InnerTermDictionary::Fst(ref termdict) => termdict.search(automaton).into(),
+            InnerTermDictionary::SSTable(ref termdict) => termdict.search(automaton).into(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{TermDictionary, TermDictionaryBuilder, TermStreamer};
+use super::{TermDictionary, TermDictionaryBuilder, TermDictionaryType, TermStreamer};
 
 use crate::directory::{Directory, FileSlice, RAMDirectory, TerminatingWrite};
 use crate::postings::TermInfo;
@@ -24,8 +24,7 @@ fn test_empty_term_dictionary() {
     assert!(empty.stream().unwrap().next().is_none());
 }
 
-#[test]
-fn test_term_ordinals() -> crate::Result<()> {
+fn test_term_ordinals(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
     const COUNTRIES: [&'static str; 7] = [
         "San Marino",
         "Serbia",
@@ -39,7 +38,8 @@ fn test_term_ordinals() -> crate::Result<()> {
     let path = PathBuf::from("TermDictionary");
     {
         let write = directory.open_write(&path)?;
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(write)?;
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(write, term_dictionary_type)?;
         for term in COUNTRIES.iter() {
             term_dictionary_builder.insert(term.as_bytes(), &make_term_info(0u64))?;
         }
@@ -56,13 +56,13 @@ fn test_term_ordinals() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_term_dictionary_simple() -> crate::Result<()> {
+fn test_term_dictionary_simple(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
     let directory = RAMDirectory::create();
     let path = PathBuf::from("TermDictionary");
     {
         let write = directory.open_write(&path)?;
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(write)?;
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(write, term_dictionary_type)?;
         term_dictionary_builder.insert("abc".as_bytes(), &make_term_info(34u64))?;
         term_dictionary_builder.insert("abcd".as_bytes(), &make_term_info(346u64))?;
         term_dictionary_builder.finish()?.terminate()?;
@@ -94,13 +94,13 @@ fn test_term_dictionary_simple() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_term_dictionary_stream() -> crate::Result<()> {
+fn test_term_dictionary_stream(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
     let ids: Vec<_> = (0u32..10_000u32)
         .map(|i| (format!("doc{:0>6}", i), i))
         .collect();
     let buffer: Vec<u8> = {
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(vec![]).unwrap();
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(vec![], term_dictionary_type).unwrap();
         for &(ref id, ref i) in &ids {
             term_dictionary_builder
                 .insert(id.as_bytes(), &make_term_info(*i as u64))
@@ -129,10 +129,12 @@ fn test_term_dictionary_stream() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_stream_high_range_prefix_suffix() -> crate::Result<()> {
+fn test_stream_high_range_prefix_suffix(
+    term_dictionary_type: TermDictionaryType,
+) -> crate::Result<()> {
     let buffer: Vec<u8> = {
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(vec![]).unwrap();
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(vec![], term_dictionary_type).unwrap();
         // term requires more than 16bits
         term_dictionary_builder.insert("abcdefghijklmnopqrstuvwxy", &make_term_info(1))?;
         term_dictionary_builder.insert("abcdefghijklmnopqrstuvwxyz", &make_term_info(2))?;
@@ -155,13 +157,13 @@ fn test_stream_high_range_prefix_suffix() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_stream_range() -> crate::Result<()> {
+fn test_stream_range(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
     let ids: Vec<_> = (0u32..10_000u32)
         .map(|i| (format!("doc{:0>6}", i), i))
         .collect();
     let buffer: Vec<u8> = {
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(vec![]).unwrap();
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(vec![], term_dictionary_type).unwrap();
         for &(ref id, ref i) in &ids {
             term_dictionary_builder
                 .insert(id.as_bytes(), &make_term_info(*i as u64))
@@ -226,10 +228,10 @@ fn test_stream_range() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_empty_string() -> crate::Result<()> {
+fn test_empty_string(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
     let buffer: Vec<u8> = {
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(vec![]).unwrap();
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(vec![], term_dictionary_type).unwrap();
         term_dictionary_builder
             .insert(&[], &make_term_info(1 as u64))
             .unwrap();
@@ -249,9 +251,12 @@ fn test_empty_string() -> crate::Result<()> {
     Ok(())
 }
 
-fn stream_range_test_dict() -> crate::Result<TermDictionary> {
+fn stream_range_test_dict(
+    term_dictionary_type: TermDictionaryType,
+) -> crate::Result<TermDictionary> {
     let buffer: Vec<u8> = {
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(Vec::new())?;
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(Vec::new(), term_dictionary_type)?;
         for i in 0u8..10u8 {
             let number_arr = [i; 1];
             term_dictionary_builder.insert(&number_arr, &make_term_info(i as u64))?;
@@ -262,9 +267,10 @@ fn stream_range_test_dict() -> crate::Result<TermDictionary> {
     TermDictionary::open(file)
 }
 
-#[test]
-fn test_stream_range_boundaries_forward() -> crate::Result<()> {
-    let term_dictionary = stream_range_test_dict()?;
+fn test_stream_range_boundaries_forward(
+    term_dictionary_type: TermDictionaryType,
+) -> crate::Result<()> {
+    let term_dictionary = stream_range_test_dict(term_dictionary_type)?;
     let value_list = |mut streamer: TermStreamer<'_>| {
         let mut res: Vec<u32> = vec![];
         while let Some((_, ref v)) = streamer.next() {
@@ -304,9 +310,10 @@ fn test_stream_range_boundaries_forward() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_stream_range_boundaries_backward() -> crate::Result<()> {
-    let term_dictionary = stream_range_test_dict()?;
+fn test_stream_range_boundaries_backward(
+    term_dictionary_type: TermDictionaryType,
+) -> crate::Result<()> {
+    let term_dictionary = stream_range_test_dict(term_dictionary_type)?;
     let value_list_backward = |mut streamer: TermStreamer<'_>| {
         let mut res: Vec<u32> = vec![];
         while let Some((_, ref v)) = streamer.next() {
@@ -365,9 +372,54 @@ fn test_stream_range_boundaries_backward() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_ord_to_term() -> crate::Result<()> {
-    let termdict = stream_range_test_dict()?;
+fn test_stream_prefix_range(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
+    let buffer: Vec<u8> = {
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(Vec::new(), term_dictionary_type)?;
+        for (term_ord, key) in [
+            &b"a"[..],
+            &b"ab"[..],
+            &b"ab\xff"[..],
+            &b"ab\xff\xff"[..],
+            &b"ac"[..],
+            &b"\xff"[..],
+            &b"\xff\x01"[..],
+        ]
+        .iter()
+        .enumerate()
+        {
+            term_dictionary_builder.insert(key, &make_term_info(term_ord as u64))?;
+        }
+        term_dictionary_builder.finish()?
+    };
+    let term_dictionary = TermDictionary::open(FileSlice::from(buffer))?;
+    let prefix_keys = |prefix: &[u8]| -> crate::Result<Vec<Vec<u8>>> {
+        let mut keys = Vec::new();
+        let mut stream = term_dictionary.prefix_range(prefix).into_stream()?;
+        while let Some((key, _)) = stream.next() {
+            keys.push(key.to_vec());
+        }
+        Ok(keys)
+    };
+    assert_eq!(
+        prefix_keys(b"ab")?,
+        vec![b"ab".to_vec(), b"ab\xff".to_vec(), b"ab\xff\xff".to_vec()]
+    );
+    assert_eq!(
+        prefix_keys(b"ab\xff")?,
+        vec![b"ab\xff".to_vec(), b"ab\xff\xff".to_vec()]
+    );
+    assert_eq!(
+        prefix_keys(b"\xff")?,
+        vec![b"\xff".to_vec(), b"\xff\x01".to_vec()]
+    );
+    assert_eq!(prefix_keys(b"b")?, Vec::<Vec<u8>>::new());
+    assert_eq!(prefix_keys(b"")?.len(), 7);
+    Ok(())
+}
+
+fn test_ord_to_term(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
+    let termdict = stream_range_test_dict(term_dictionary_type)?;
     let mut bytes = vec![];
     for b in 0u8..10u8 {
         termdict.ord_to_term(b as u64, &mut bytes)?;
@@ -376,9 +428,8 @@ fn test_ord_to_term() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_stream_term_ord() -> crate::Result<()> {
-    let termdict = stream_range_test_dict()?;
+fn test_stream_term_ord(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
+    let termdict = stream_range_test_dict(term_dictionary_type)?;
     let mut stream = termdict.stream()?;
     for b in 0u8..10u8 {
         assert!(stream.advance(), true);
@@ -389,8 +440,7 @@ fn test_stream_term_ord() -> crate::Result<()> {
     Ok(())
 }
 
-#[test]
-fn test_automaton_search() -> crate::Result<()> {
+fn test_automaton_search(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
     use crate::query::DFAWrapper;
     use levenshtein_automata::LevenshteinAutomatonBuilder;
 
@@ -408,7 +458,8 @@ fn test_automaton_search() -> crate::Result<()> {
     let path = PathBuf::from("TermDictionary");
     {
         let write = directory.open_write(&path)?;
-        let mut term_dictionary_builder = TermDictionaryBuilder::create(write)?;
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(write, term_dictionary_type)?;
         for term in COUNTRIES.iter() {
             term_dictionary_builder.insert(term.as_bytes(), &make_term_info(0u64))?;
         }
@@ -429,3 +480,155 @@ fn test_automaton_search() -> crate::Result<()> {
     assert!(!range.advance());
     Ok(())
 }
+
+fn test_term_ord_across_blocks(term_dictionary_type: TermDictionaryType) -> crate::Result<()> {
+    let buffer: Vec<u8> = {
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(Vec::new(), term_dictionary_type)?;
+        for i in 0u64..1_000u64 {
+            let key = format!("key{:0>4}", i * 2);
+            term_dictionary_builder.insert(key.as_bytes(), &make_term_info(i))?;
+        }
+        term_dictionary_builder.finish()?
+    };
+    let term_dictionary = TermDictionary::open(FileSlice::from(buffer))?;
+    assert_eq!(term_dictionary.num_terms(), 1_000);
+    let mut bytes = vec![];
+    for i in 0u64..1_000u64 {
+        let key = format!("key{:0>4}", i * 2);
+        assert_eq!(term_dictionary.term_ord(key.as_bytes())?, Some(i));
+        assert_eq!(
+            term_dictionary.get(key.as_bytes())?,
+            Some(make_term_info(i))
+        );
+        assert!(term_dictionary.ord_to_term(i, &mut bytes)?);
+        assert_eq!(bytes, key.as_bytes());
+        let missing_key = format!("key{:0>4}", i * 2 + 1);
+        assert_eq!(term_dictionary.term_ord(missing_key.as_bytes())?, None);
+    }
+    assert_eq!(term_dictionary.term_ord(b"a")?, None);
+    assert_eq!(term_dictionary.term_ord(b"z")?, None);
+    assert!(!term_dictionary.ord_to_term(1_000, &mut bytes)?);
+    let mut stream = term_dictionary
+        .range()
+        .gt(b"key0255")
+        .le(b"key1500")
+        .backward()
+        .into_stream()?;
+    let mut term_ords = vec![];
+    while stream.advance() {
+        term_ords.push(stream.term_ord());
+    }
+    assert_eq!(term_ords, (128u64..=750u64).rev().collect::<Vec<_>>());
+    Ok(())
+}
+
+fn test_automaton_search_across_blocks(
+    term_dictionary_type: TermDictionaryType,
+) -> crate::Result<()> {
+    use crate::query::DFAWrapper;
+    use levenshtein_automata::LevenshteinAutomatonBuilder;
+    let buffer: Vec<u8> = {
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::create_with_type(Vec::new(), term_dictionary_type)?;
+        for i in 0u64..1_000u64 {
+            let key = format!("{:0>4}", i);
+            term_dictionary_builder.insert(key.as_bytes(), &make_term_info(i))?;
+        }
+        term_dictionary_builder.finish()?
+    };
+    let term_dictionary = TermDictionary::open(FileSlice::from(buffer))?;
+    let lev_automaton_builder = LevenshteinAutomatonBuilder::new(1, false);
+    let automaton = DFAWrapper(lev_automaton_builder.build_dfa("0500"));
+    let mut stream = term_dictionary.search(automaton).into_stream()?;
+    let mut keys = vec![];
+    while let Some((key, term_info)) = stream.next() {
+        let key = str::from_utf8(key).unwrap().to_string();
+        assert_eq!(term_info, &make_term_info(key.parse::<u64>().unwrap()));
+        keys.push(key);
+    }
+    let expected: Vec<String> = (0u64..1_000u64)
+        .map(|i| format!("{:0>4}", i))
+        .filter(|key| {
+            key.bytes()
+                .zip("0500".bytes())
+                .filter(|(left, right)| left != right)
+                .count()
+                <= 1
+        })
+        .collect();
+    assert_eq!(keys, expected);
+    Ok(())
+}
+
+#[test]
+fn test_sstable_term_dictionary_rejects_unsorted_keys() -> crate::Result<()> {
+    let mut term_dictionary_builder =
+        TermDictionaryBuilder::create_with_type(Vec::new(), TermDictionaryType::SSTable)?;
+    term_dictionary_builder.insert(b"b", &make_term_info(0))?;
+    assert!(term_dictionary_builder
+        .insert(b"a", &make_term_info(1))
+        .is_err());
+    assert!(term_dictionary_builder
+        .insert(b"b", &make_term_info(1))
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_empty_sstable_term_dictionary() -> crate::Result<()> {
+    let buffer = TermDictionaryBuilder::create_with_type(Vec::new(), TermDictionaryType::SSTable)?
+        .finish()?;
+    let term_dictionary = TermDictionary::open(FileSlice::from(buffer))?;
+    assert_eq!(term_dictionary.num_terms(), 0);
+    assert_eq!(term_dictionary.term_ord(b"")?, None);
+    assert!(term_dictionary.stream()?.next().is_none());
+    assert!(term_dictionary
+        .range()
+        .backward()
+        .into_stream()?
+        .next()
+        .is_none());
+    Ok(())
+}
+
+macro_rules! term_dictionary_tests {
+    ($($test_fn:ident),*) => {
+        mod fst {
+            use crate::termdict::TermDictionaryType;
+            $(
+                #[test]
+                fn $test_fn() -> crate::Result<()> {
+                    super::$test_fn(TermDictionaryType::Fst)
+                }
+            )*
+        }
+
+        mod sstable {
+            use crate::termdict::TermDictionaryType;
+            $(
+                #[test]
+                fn $test_fn() -> crate::Result<()> {
+                    super::$test_fn(TermDictionaryType::SSTable)
+                }
+            )*
+        }
+    };
+}
+
+term_dictionary_tests!(
+    test_term_ordinals,
+    test_term_dictionary_simple,
+    test_term_dictionary_stream,
+    test_stream_high_range_prefix_suffix,
+    test_stream_range,
+    test_empty_string,
+    test_stream_range_boundaries_forward,
+    test_stream_range_boundaries_backward,
+    test_stream_prefix_range,
+    test_ord_to_term,
+    test_stream_term_ord,
+    test_automaton_search,
+    test_term_ord_across_blocks,
+    test_automaton_search_across_blocks
+);
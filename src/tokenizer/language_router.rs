@@ -0,0 +1,450 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let tokenizer = LanguageRouter::with_stemmers(LanguageDetector::default());
+//!
+//! let mut stream = tokenizer.token_stream("The dogs are running");
+//! assert_eq!(stream.next().unwrap().text, "dog");
+//! assert_eq!(stream.next().unwrap().text, "run");
+//! assert!(stream.next().is_none());
+//!
+//! let mut stream = tokenizer.token_stream("Les chats sont dans le jardin");
+//! assert_eq!(stream.next().unwrap().text, "chat");
+//! assert_eq!(stream.next().unwrap().text, "jardin");
+//! assert!(stream.next().is_none());
+//! ```
+use super::{
+    BoxTokenStream, Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    StopWordFilter, TextAnalyzer, Tokenizer,
+};
+use crate::schema::{Document, Field};
+
+/// Most frequent words of the languages written with the latin alphabet.
+///
+/// They are both used to detect the language of a text
+/// and as the stop words of the pipelines built by `LanguageRouter::with_stemmers`.
+const WORD_PROFILES: &[(Language, &[&str])] = &[
+    (
+        Language::English,
+        &[
+            "the", "and", "of", "to", "is", "in", "that", "it", "was", "for", "with", "on", "are",
+            "this", "be", "as", "at", "by", "not", "have", "you", "he", "she", "they",
+        ],
+    ),
+    (
+        Language::French,
+        &[
+            "le", "la", "les", "et", "est", "un", "une", "des", "du", "de", "que", "qui", "dans",
+            "pour", "pas", "sur", "au", "avec", "il", "elle", "ce", "sont", "nous", "vous",
+        ],
+    ),
+    (
+        Language::German,
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "den", "von", "mit",
+            "sich", "des", "auf", "für", "im", "dem", "auch", "es", "ich", "sie", "wir", "wird",
+        ],
+    ),
+    (
+        Language::Spanish,
+        &[
+            "el", "la", "los", "las", "y", "es", "un", "una", "que", "de", "en", "por", "con",
+            "para", "no", "se", "del", "al", "lo", "como", "más", "pero", "su", "está",
+        ],
+    ),
+    (
+        Language::Italian,
+        &[
+            "il", "la", "le", "gli", "e", "è", "un", "una", "che", "di", "in", "per", "con", "non",
+            "si", "del", "della", "al", "lo", "come", "sono", "ma", "anche", "questo",
+        ],
+    ),
+    (
+        Language::Portuguese,
+        &[
+            "o", "a", "os", "as", "e", "é", "um", "uma", "que", "de", "em", "por", "com", "para",
+            "não", "se", "do", "da", "no", "na", "mais", "mas", "são", "está",
+        ],
+    ),
+    (
+        Language::Dutch,
+        &[
+            "de", "het", "een", "en", "is", "van", "in", "dat", "niet", "op", "te", "zijn", "voor",
+            "met", "die", "er", "aan", "ook", "als", "maar", "wordt", "ik", "je", "hij",
+        ],
+    ),
+    (
+        Language::Swedish,
+        &[
+            "och", "är", "en", "ett", "att", "det", "som", "på", "för", "med", "inte", "av",
+            "till", "den", "har", "jag", "vi", "om", "var", "men", "så", "de", "han", "hon",
+        ],
+    ),
+];
+
+/// Returns the language written with the script `c` belongs to,
+/// if this script is specific to one of the supported languages.
+fn script_language(c: char) -> Option<Language> {
+    match c {
+        '\u{0370}'..='\u{03FF}' => Some(Language::Greek),
+        '\u{0400}'..='\u{04FF}' => Some(Language::Russian),
+        '\u{0600}'..='\u{06FF}' => Some(Language::Arabic),
+        '\u{0B80}'..='\u{0BFF}' => Some(Language::Tamil),
+        _ => None,
+    }
+}
+
+/// Returns the ISO 639-1 code of the language.
+fn language_code(language: Language) -> &'static str {
+    match language {
+        Language::Arabic => "ar",
+        Language::Danish => "da",
+        Language::Dutch => "nl",
+        Language::English => "en",
+        Language::Finnish => "fi",
+        Language::French => "fr",
+        Language::German => "de",
+        Language::Greek => "el",
+        Language::Hungarian => "hu",
+        Language::Italian => "it",
+        Language::Norwegian => "no",
+        Language::Portuguese => "pt",
+        Language::Romanian => "ro",
+        Language::Russian => "ru",
+        Language::Spanish => "es",
+        Language::Swedish => "sv",
+        Language::Tamil => "ta",
+        Language::Turkish => "tr",
+    }
+}
+
+/// `LanguageDetector` guesses the language of a text.
+///
+/// Greek, Russian, Arabic and Tamil are detected by their script.
+/// English, French, German, Spanish, Italian, Portuguese, Dutch and Swedish are detected
+/// by counting the occurrences of their most frequent words.
+///
+/// The detection is reliable on sentences, but is little more than a guess on
+/// a couple of words. A text whose frequent words are shared by several languages
+/// (e.g. `"la"`, in French, Spanish and Italian) is not attributed to any of them.
+///
+/// Queries are usually too short for their language to be detected. The recommended
+/// setup is to detect the language of the documents when indexing them, index their
+/// text into a field per language, each with a fixed analyzer, and search the fields
+/// of the languages of interest.
+#[derive(Clone, Debug)]
+pub struct LanguageDetector {
+    min_words: usize,
+}
+
+impl Default for LanguageDetector {
+    fn default() -> LanguageDetector {
+        LanguageDetector { min_words: 1 }
+    }
+}
+
+impl LanguageDetector {
+    /// Sets the minimum number of frequent words a text written with the
+    /// latin alphabet has to contain for its language to be detected.
+    pub fn with_min_words(min_words: usize) -> LanguageDetector {
+        LanguageDetector { min_words }
+    }
+
+    /// Returns the language of the text, or `None` if it could not be detected.
+    pub fn detect(&self, text: &str) -> Option<Language> {
+        let mut num_letters = 0;
+        let mut script_counts: Vec<(Language, usize)> = Vec::new();
+        for c in text.chars().filter(|c| c.is_alphabetic()) {
+            num_letters += 1;
+            if let Some(language) = script_language(c) {
+                match script_counts.iter_mut().find(|(lang, _)| *lang == language) {
+                    Some((_, count)) => *count += 1,
+                    None => script_counts.push((language, 1)),
+                }
+            }
+        }
+        if let Some(&(language, count)) = script_counts.iter().max_by_key(|(_, count)| *count) {
+            if count * 2 > num_letters {
+                return Some(language);
+            }
+        }
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        let mut best_language = None;
+        let mut best_count = self.min_words.max(1) - 1;
+        for &(language, profile) in WORD_PROFILES {
+            let count = words
+                .iter()
+                .filter(|word| profile.contains(&word.as_str()))
+                .count();
+            if count > best_count {
+                best_language = Some(language);
+                best_count = count;
+            } else if count == best_count {
+                // Ties are ambiguous.
+                best_language = None;
+            }
+        }
+        best_language
+    }
+
+    /// Detects the language of the text values of `text_field`, and records
+    /// its ISO 639-1 code (e.g. `"en"`) into `language_field`.
+    ///
+    /// `language_field` is typically a `STRING` field, making it possible
+    /// to filter the documents by language.
+    /// Returns the detected language, if any.
+    pub fn tag_document(
+        &self,
+        doc: &mut Document,
+        text_field: Field,
+        language_field: Field,
+    ) -> Option<Language> {
+        let text = doc
+            .get_all(text_field)
+            .flat_map(|value| value.text())
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let language = self.detect(&text)?;
+        doc.add_text(language_field, language_code(language));
+        Some(language)
+    }
+}
+
+/// `LanguageRouter` is a `Tokenizer` detecting the language of each text it receives,
+/// and running the analyzer registered for this language on it.
+///
+/// The texts whose language could not be detected, or has no registered analyzer,
+/// go through the default analyzer.
+///
+/// The same tokenizer analyzes the queries on the field. Queries are usually much
+/// shorter than documents: their language is often not detected, and their terms
+/// then go through the default analyzer, missing the stemmed terms of the documents.
+/// For keyword search, prefer detecting the language at indexing time with
+/// `LanguageDetector`, and indexing the text into a field per language.
+#[derive(Clone)]
+pub struct LanguageRouter {
+    detector: LanguageDetector,
+    analyzers: Vec<(Language, TextAnalyzer)>,
+    default_analyzer: TextAnalyzer,
+}
+
+impl LanguageRouter {
+    /// Creates a `LanguageRouter` sending every text to `default_analyzer`
+    /// until analyzers are registered for specific languages.
+    pub fn new(detector: LanguageDetector, default_analyzer: TextAnalyzer) -> LanguageRouter {
+        LanguageRouter {
+            detector,
+            analyzers: Vec::new(),
+            default_analyzer,
+        }
+    }
+
+    /// Creates a `LanguageRouter` lowercasing, removing the stop words,
+    /// and stemming the texts according to their language.
+    ///
+    /// All of the languages supported by the `LanguageDetector` are registered.
+    /// The default analyzer only lowercases the tokens.
+    pub fn with_stemmers(detector: LanguageDetector) -> LanguageRouter {
+        let base_analyzer = || {
+            TextAnalyzer::from(SimpleTokenizer)
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+        };
+        let mut router = LanguageRouter::new(detector, base_analyzer());
+        for language in &[
+            Language::Greek,
+            Language::Russian,
+            Language::Arabic,
+            Language::Tamil,
+        ] {
+            router = router.route(*language, base_analyzer().filter(Stemmer::new(*language)));
+        }
+        for &(language, stop_words) in WORD_PROFILES {
+            let stop_words = stop_words.iter().map(|word| word.to_string()).collect();
+            let analyzer = base_analyzer()
+                .filter(StopWordFilter::remove(stop_words))
+                .filter(Stemmer::new(language));
+            router = router.route(language, analyzer);
+        }
+        router
+    }
+
+    /// Registers the analyzer to use for the texts written in `language`,
+    /// replacing the previously registered one if any.
+    pub fn route(mut self, language: Language, analyzer: TextAnalyzer) -> LanguageRouter {
+        self.analyzers.retain(|(lang, _)| *lang != language);
+        self.analyzers.push((language, analyzer));
+        self
+    }
+
+    fn analyzer(&self, text: &str) -> &TextAnalyzer {
+        self.detector
+            .detect(text)
+            .and_then(|language| {
+                self.analyzers
+                    .iter()
+                    .find(|(lang, _)| *lang == language)
+                    .map(|(_, analyzer)| analyzer)
+            })
+            .unwrap_or(&self.default_analyzer)
+    }
+}
+
+impl Tokenizer for LanguageRouter {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        self.analyzer(text).token_stream(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LanguageDetector, LanguageRouter};
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::schema::{STRING, TEXT};
+    use crate::tokenizer::{Language, LowerCaser, RemoveLongFilter, Stemmer};
+    use crate::tokenizer::{SimpleTokenizer, TextAnalyzer, Token, Tokenizer};
+    use crate::{Document, Index};
+
+    fn token_texts(tokenizer: &LanguageRouter, text: &str) -> Vec<String> {
+        let mut tokens: Vec<Token> = vec![];
+        tokenizer
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens.into_iter().map(|token| token.text).collect()
+    }
+
+    #[test]
+    fn test_language_detector() {
+        let detector = LanguageDetector::default();
+        assert_eq!(
+            detector.detect("The quick brown fox jumps over the lazy dog"),
+            Some(Language::English)
+        );
+        assert_eq!(
+            detector.detect("Le renard saute sur le chien dans la cour"),
+            Some(Language::French)
+        );
+        assert_eq!(
+            detector.detect("Der schnelle braune Fuchs springt über den faulen Hund"),
+            Some(Language::German)
+        );
+        assert_eq!(
+            detector.detect("El rápido zorro marrón salta sobre el perro"),
+            Some(Language::Spanish)
+        );
+        assert_eq!(
+            detector.detect("Быстрая коричневая лиса"),
+            Some(Language::Russian)
+        );
+        assert_eq!(detector.detect("la"), None);
+        assert_eq!(detector.detect("42 1337"), None);
+        assert_eq!(detector.detect(""), None);
+        assert_eq!(LanguageDetector::with_min_words(2).detect("the fox"), None);
+    }
+
+    #[test]
+    fn test_language_router() {
+        let router = LanguageRouter::with_stemmers(LanguageDetector::default());
+        assert_eq!(
+            token_texts(&router, "The dogs are running"),
+            vec!["dog", "run"]
+        );
+        assert_eq!(
+            token_texts(&router, "Die Hunde laufen im Garten"),
+            vec!["hund", "lauf", "gart"]
+        );
+        assert_eq!(token_texts(&router, "Running42"), vec!["running42"]);
+
+        let router = LanguageRouter::new(
+            LanguageDetector::default(),
+            TextAnalyzer::from(SimpleTokenizer),
+        )
+        .route(Language::English, TextAnalyzer::from(SimpleTokenizer));
+        assert_eq!(token_texts(&router, "The Dogs"), vec!["The", "Dogs"]);
+    }
+
+    #[test]
+    fn test_tag_document() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let lang = schema_builder.add_text_field("lang", STRING);
+        let detector = LanguageDetector::default();
+
+        let mut doc = doc!(body => "Il gatto è sul tavolo", body => "e il cane dorme");
+        assert_eq!(
+            detector.tag_document(&mut doc, body, lang),
+            Some(Language::Italian)
+        );
+        assert_eq!(
+            doc.get_first(lang).and_then(|value| value.text()),
+            Some("it")
+        );
+
+        let mut doc = doc!(body => "1984");
+        assert_eq!(detector.tag_document(&mut doc, body, lang), None);
+        assert!(doc.get_first(lang).is_none());
+    }
+
+    #[test]
+    fn test_per_language_fields() -> crate::Result<()> {
+        let text_options = |tokenizer: &str| {
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(tokenizer)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+        };
+        let mut schema_builder = Schema::builder();
+        let body_en = schema_builder.add_text_field("body_en", text_options("en_stem"));
+        let body_fr = schema_builder.add_text_field("body_fr", text_options("fr_stem"));
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "fr_stem",
+            TextAnalyzer::from(SimpleTokenizer)
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+                .filter(Stemmer::new(Language::French)),
+        );
+        let detector = LanguageDetector::default();
+        let body_field = |language: Option<Language>| -> Option<Field> {
+            match language? {
+                Language::English => Some(body_en),
+                Language::French => Some(body_fr),
+                _ => None,
+            }
+        };
+        let mut index_writer = index.writer_for_tests()?;
+        for text in &[
+            "The cats are sleeping in the garden",
+            "Les chats dorment dans le jardin",
+        ] {
+            let mut doc = Document::new();
+            if let Some(field) = body_field(detector.detect(text)) {
+                doc.add_text(field, text);
+            }
+            index_writer.add_document(doc);
+        }
+        index_writer.commit()?;
+
+        // Single keywords, whose language cannot be detected, match the stemmed terms.
+        let searcher = index.reader()?.searcher();
+        let count = |field: Field, query: &str| -> crate::Result<usize> {
+            let query = QueryParser::for_index(&index, vec![field]).parse_query(query)?;
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(detector.detect("cat"), None);
+        assert_eq!(count(body_en, "cat")?, 1);
+        assert_eq!(count(body_en, "sleep")?, 1);
+        assert_eq!(count(body_fr, "chat")?, 1);
+        assert_eq!(count(body_fr, "jardins")?, 1);
+        assert_eq!(count(body_en, "chat")?, 0);
+        Ok(())
+    }
+}
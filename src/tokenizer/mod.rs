@@ -120,6 +120,7 @@
 mod alphanum_only;
 mod ascii_folding_filter;
 mod facet_tokenizer;
+mod language_router;
 mod lower_caser;
 mod ngram_tokenizer;
 mod raw_tokenizer;
@@ -135,6 +136,7 @@ mod tokenizer_manager;
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::ascii_folding_filter::AsciiFoldingFilter;
 pub use self::facet_tokenizer::FacetTokenizer;
+pub use self::language_router::{LanguageDetector, LanguageRouter};
 pub use self::lower_caser::LowerCaser;
 pub use self::ngram_tokenizer::NgramTokenizer;
 pub use self::raw_tokenizer::RawTokenizer;